@@ -134,13 +134,30 @@ impl Client {
         }
     }
 
+    /// Splits a script into the commands to send. A line starting with `\` is a command
+    /// of its own, while the SQL between two such lines is sent as a single request, which
+    /// the server splits into statements. Hence a transaction block of the script runs as
+    /// a whole.
     fn split_into_commands(&self, buffer_content: &str) -> Vec<String> {
-        buffer_content
-            .split(';')
-            .map(str::trim)
-            .filter(|cmd| !cmd.is_empty())
-            .map(|cmd| cmd.replace('\n', " "))
-            .collect()
+        let mut commands = Vec::new();
+        let mut sql = String::new();
+        for line in buffer_content.lines() {
+            let trimmed = line.trim();
+            if trimmed.starts_with('\\') {
+                if !sql.trim().is_empty() {
+                    commands.push(sql.trim().to_string());
+                }
+                sql.clear();
+                commands.push(trimmed.trim_end_matches(';').trim_end().to_string());
+            } else {
+                sql.push_str(line);
+                sql.push('\n');
+            }
+        }
+        if !sql.trim().is_empty() {
+            commands.push(sql.trim().to_string());
+        }
+        commands
     }
 
     /// Sends a request to the server and waits for a response.
@@ -257,6 +274,9 @@ impl Client {
                 true
             }
             Response::QueryResult(result) => self.process_query_result(result),
+            Response::QueryResults(results) => results
+                .into_iter()
+                .all(|result| self.process_query_result(result)),
             Response::QueryExecutionError(msg) => {
                 error!("Received QueryExecutionError: {}", msg);
                 true
@@ -287,6 +307,20 @@ impl Client {
                 let message = format!("Inserted {} rows to table: {}", inserted, table_name);
                 info!("Received Query Result: {}", message);
             }
+            QueryResult::Delete {
+                deleted,
+                table_name,
+            } => {
+                let message = format!("Deleted {} rows from table: {}", deleted, table_name);
+                info!("Received Query Result: {}", message);
+            }
+            QueryResult::Update {
+                updated,
+                table_name,
+            } => {
+                let message = format!("Updated {} rows in table: {}", updated, table_name);
+                info!("Received Query Result: {}", message);
+            }
         }
         true
     }
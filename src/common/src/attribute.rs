@@ -1,4 +1,4 @@
-use crate::{ids::ContainerId, DataType};
+use crate::{ids::ContainerId, DataType, Field};
 
 /// Handle attributes. Pairs the name with the dtype.
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
@@ -9,6 +9,9 @@ pub struct Attribute {
     pub dtype: DataType,
     /// Attribute constraint
     pub constraint: Constraint,
+    /// Value used when an insert does not supply one. None means NULL.
+    #[serde(default)]
+    pub default: Option<Field>,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
@@ -35,6 +38,7 @@ impl Attribute {
             name,
             dtype,
             constraint: Constraint::None,
+            default: None,
         }
     }
 
@@ -43,6 +47,7 @@ impl Attribute {
             name,
             dtype,
             constraint,
+            default: None,
         }
     }
 
@@ -51,9 +56,16 @@ impl Attribute {
             name,
             dtype,
             constraint: Constraint::PrimaryKey,
+            default: None,
         }
     }
 
+    /// Sets the value used when an insert does not supply one.
+    pub fn with_default(mut self, default: Option<Field>) -> Self {
+        self.default = default;
+        self
+    }
+
     /// Returns the name of the attribute.
     pub fn name(&self) -> &str {
         &self.name
@@ -63,4 +75,9 @@ impl Attribute {
     pub fn dtype(&self) -> &DataType {
         &self.dtype
     }
+
+    /// Returns the value used when an insert does not supply one.
+    pub fn default(&self) -> Option<&Field> {
+        self.default.as_ref()
+    }
 }
@@ -1,5 +1,5 @@
 use crate::ids::{ColumnId, ContainerId};
-use crate::table::TableInfo;
+use crate::table::{TableInfo, ViewInfo};
 use crate::{table::TableSchema, MAX_COLUMNS};
 use serde::Serialize;
 use std::collections::HashMap;
@@ -69,12 +69,20 @@ impl ContainerIdGenerator {
     fn get_table_id_if_exists(&self, table_name: &str) -> Option<ContainerId> {
         self.table_to_id.get(table_name).cloned()
     }
+
+    /// Releases the table name. ContainerIds are never reused, so a new table
+    /// with the same name will get a new ContainerId.
+    fn remove_table(&mut self, table_name: &str) -> Option<ContainerId> {
+        self.table_to_id.remove(table_name)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Catalog {
     container_id_generator: Mutex<ContainerIdGenerator>,
     tables: RwLock<HashMap<ContainerId, TableInfo>>,
+    #[serde(default)]
+    views: RwLock<HashMap<String, ViewInfo>>,
 }
 
 impl Catalog {
@@ -82,6 +90,7 @@ impl Catalog {
         Arc::new(Catalog {
             container_id_generator: Mutex::new(ContainerIdGenerator::new()),
             tables: RwLock::new(HashMap::new()),
+            views: RwLock::new(HashMap::new()),
         })
     }
 
@@ -105,6 +114,15 @@ impl Catalog {
         }
     }
 
+    /// Removes the table from the catalog and returns its info if it existed.
+    pub fn remove_table(&self, c_id: ContainerId) -> Option<TableInfo> {
+        let mut generator = self.container_id_generator.lock().unwrap();
+        let mut tables = self.tables.write().unwrap();
+        let table_info = tables.remove(&c_id)?;
+        generator.remove_table(&table_info.name);
+        Some(table_info)
+    }
+
     pub fn get_table(&self, c_id: ContainerId) -> Option<TableInfo> {
         let tables = self.tables.read().unwrap();
         tables.get(&c_id).cloned()
@@ -148,6 +166,29 @@ impl Catalog {
         }
     }
 
+    /// Adds a view to the catalog, replacing the view with the same name if any.
+    /// Returns the replaced view.
+    pub fn add_view(&self, view_info: ViewInfo) -> Option<ViewInfo> {
+        let mut views = self.views.write().unwrap();
+        views.insert(view_info.name.clone(), view_info)
+    }
+
+    /// Removes the view from the catalog and returns it if it existed.
+    pub fn remove_view(&self, view_name: &str) -> Option<ViewInfo> {
+        let mut views = self.views.write().unwrap();
+        views.remove(view_name)
+    }
+
+    pub fn get_view(&self, view_name: &str) -> Option<ViewInfo> {
+        let views = self.views.read().unwrap();
+        views.get(view_name).cloned()
+    }
+
+    pub fn get_view_names(&self) -> Vec<String> {
+        let views = self.views.read().unwrap();
+        views.keys().cloned().collect()
+    }
+
     /// Given a table name, return a vector of tuples containing the column name and column index.
     /// Note that the column index is calculated as `container_id * MAX_COLUMNS (100) + column_index`
     /// This column index is for temporary use only and is used for sequential scans.
@@ -232,6 +232,7 @@ pub enum Response {
     SystemMsg(String),
     SystemErr(String),
     QueryResult(crate::QueryResult),
+    QueryResults(Vec<crate::QueryResult>), // one result per statement of a script
     QueryExecutionError(String),
     Shutdown(bool), // true if the request for shutdown comes from the client
     QuietOk,
@@ -245,6 +246,7 @@ impl Response {
             Response::SystemMsg(_) => true,
             Response::SystemErr(_) => false,
             Response::QueryResult(_) => true,
+            Response::QueryResults(_) => true,
             Response::QueryExecutionError(_) => false,
             Response::Shutdown(_) => true,
             Response::QuietOk => true,
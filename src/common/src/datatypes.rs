@@ -2,10 +2,11 @@ use crate::attribute::Attribute;
 
 #[allow(unused_imports)]
 use crate::error::{c_err, CrustyError};
-use crate::query::bytecode_expr::{And, FromBool, Or};
+use crate::query::bytecode_expr::{like_match, And, Cast, FromBool, Like, Not, Nullable, Or};
 use crate::BinaryOp;
 use chrono::{Duration, NaiveDate};
-use std::ops::{Add, Div, Mul, Sub};
+use std::borrow::Cow;
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
 
 pub fn base_date() -> NaiveDate {
     NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()
@@ -83,11 +84,11 @@ impl From<&Field> for DataType {
             Field::SmallInt(_) => DataType::SmallInt,
             Field::Char(i, _) => DataType::Char(*i),
             Field::String(_) => DataType::String,
-
-            // FIXME: I don't think I'm using the right precision and scale here
-            // (should get the number of digits in the whole number and the number
-            // of digits after the decimal point)
-            Field::Decimal(p, s) => DataType::Decimal(*p as u32, *s),
+            // The precision is the number of digits, with at least one before the point
+            Field::Decimal(v, s) => {
+                let digits = v.unsigned_abs().checked_ilog10().map_or(1, |d| d + 1);
+                DataType::Decimal(digits.max(s + 1), *s)
+            }
             Field::Date(_) => DataType::Date,
             Field::Bool(_) => DataType::Bool,
             Field::Null => DataType::Null,
@@ -111,6 +112,90 @@ impl DataType {
             DataType::Null => Some(1),
         }
     }
+
+    /// Precision and scale of the decimals holding any value of a numeric type.
+    fn decimal_digits(&self) -> Option<(u32, u32)> {
+        match self {
+            DataType::SmallInt => Some((5, 0)),
+            DataType::Int => Some((10, 0)),
+            DataType::BigInt => Some((19, 0)),
+            DataType::Decimal(p, s) => Some((*p, *s)),
+            _ => None,
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        self.decimal_digits().is_some()
+    }
+
+    pub fn is_string(&self) -> bool {
+        matches!(self, DataType::String | DataType::Char(_))
+    }
+
+    /// The smallest type both types convert to implicitly, following the coercion
+    /// lattice:
+    ///
+    /// ```text
+    /// SmallInt < Int < BigInt < Decimal
+    /// Char <-> String
+    /// String -> Date
+    /// ```
+    ///
+    /// NULL converts to any type, and the common type of two decimals keeps the digits
+    /// of both, e.g. decimal(5,2) and decimal(4,3) give decimal(6,3). Returns None if the
+    /// types are incompatible, e.g. date and bigint.
+    pub fn common_type(&self, other: &DataType) -> Option<DataType> {
+        match (self, other) {
+            (a, b) if a == b => Some(a.clone()),
+            (DataType::Null, t) | (t, DataType::Null) => Some(t.clone()),
+            (DataType::Decimal(..), _) | (_, DataType::Decimal(..)) => {
+                let (p1, s1) = self.decimal_digits()?;
+                let (p2, s2) = other.decimal_digits()?;
+                let scale = s1.max(s2);
+                Some(DataType::Decimal((p1 - s1).max(p2 - s2) + scale, scale))
+            }
+            (a, b) if a.is_numeric() && b.is_numeric() => {
+                if a.decimal_digits() > b.decimal_digits() {
+                    Some(a.clone())
+                } else {
+                    Some(b.clone())
+                }
+            }
+            (a, b) if a.is_string() && b.is_string() => Some(DataType::String),
+            (DataType::Date, t) | (t, DataType::Date) if t.is_string() => Some(DataType::Date),
+            _ => None,
+        }
+    }
+
+    /// Whether values of this type convert implicitly to `target`.
+    pub fn coerces_to(&self, target: &DataType) -> bool {
+        (self.is_string() && target.is_string())
+            || self.common_type(target).as_ref() == Some(target)
+    }
+
+    /// Whether `CAST` converts values of this type to `target`. This is the case for
+    /// implicit coercions, between numbers, from and to strings and between bools
+    /// and integers. The conversion can still fail for some values, e.g. of 'x' to int.
+    pub fn can_cast_to(&self, target: &DataType) -> bool {
+        let is_int =
+            |t: &DataType| matches!(t, DataType::BigInt | DataType::Int | DataType::SmallInt);
+        self.coerces_to(target)
+            || (self.is_numeric() && target.is_numeric())
+            || (self.is_string() && *target != DataType::Null)
+            || target.is_string()
+            || (*self == DataType::Bool && is_int(target))
+            || (is_int(self) && *target == DataType::Bool)
+    }
+}
+
+/// `v / d` rounded half away from zero, for a positive `d`.
+fn div_round(v: i64, d: i64) -> i64 {
+    let q = v / d;
+    if 2 * (v % d).abs() >= d {
+        q + v.signum()
+    } else {
+        q
+    }
 }
 
 /// For each of the dtypes, make sure that there is a corresponding field type.
@@ -133,30 +218,173 @@ impl FromBool for Field {
     }
 }
 
+impl Nullable for Field {
+    fn null() -> Self {
+        Field::Null
+    }
+
+    fn is_null(&self) -> bool {
+        matches!(self, Field::Null)
+    }
+}
+
+/// SQL AND: false if either side is false, otherwise NULL if either side is NULL.
 impl And for Field {
     fn and(&self, other: &Self) -> Self {
         match (self, other) {
-            (Field::Bool(a), Field::Bool(b)) => Field::Bool(*a && *b),
+            (Field::Bool(false), Field::Bool(_) | Field::Null)
+            | (Field::Bool(_) | Field::Null, Field::Bool(false)) => Field::Bool(false),
+            (Field::Bool(true), Field::Bool(true)) => Field::Bool(true),
+            (Field::Bool(_) | Field::Null, Field::Bool(_) | Field::Null) => Field::Null,
+            _ => panic!("Expected bool"),
+        }
+    }
+}
+
+/// SQL NOT: NULL stays NULL.
+impl Not for Field {
+    fn not(&self) -> Self {
+        match self {
+            Field::Bool(b) => Field::Bool(!b),
+            Field::Null => Field::Null,
             _ => panic!("Expected bool"),
         }
     }
 }
 
+/// SQL OR: true if either side is true, otherwise NULL if either side is NULL.
 impl Or for Field {
     fn or(&self, other: &Self) -> Self {
         match (self, other) {
-            (Field::Bool(a), Field::Bool(b)) => Field::Bool(*a || *b),
+            (Field::Bool(true), Field::Bool(_) | Field::Null)
+            | (Field::Bool(_) | Field::Null, Field::Bool(true)) => Field::Bool(true),
+            (Field::Bool(false), Field::Bool(false)) => Field::Bool(false),
+            (Field::Bool(_) | Field::Null, Field::Bool(_) | Field::Null) => Field::Null,
             _ => panic!("Expected bool"),
         }
     }
 }
 
+impl Like for Field {
+    fn like(&self, pattern: &Self, case_insensitive: bool) -> Option<bool> {
+        let value = self.like_operand()?;
+        let pattern = pattern.like_operand()?;
+        Some(like_match(&value, &pattern, case_insensitive))
+    }
+
+    fn starts_with(&self, prefix: &Self) -> Option<bool> {
+        let value = self.like_operand()?;
+        let prefix = prefix.like_operand()?;
+        Some(value.starts_with(prefix.as_ref()))
+    }
+}
+
+impl Cast for Field {
+    /// Converts the value for `CAST` and implicit coercions. Numbers are rounded half
+    /// away from zero when digits after the point are dropped, and strings are parsed.
+    fn cast(&self, dtype: &DataType) -> Result<Self, CrustyError> {
+        let err = |reason: &str| {
+            CrustyError::ValidationError(format!("Cannot cast {} to {}{}", self, dtype, reason))
+        };
+        let out_of_range = || err(": out of range");
+        if *self == Field::Null {
+            return Ok(Field::Null);
+        }
+        match dtype {
+            DataType::BigInt | DataType::Int | DataType::SmallInt => {
+                let v = match self {
+                    Field::BigInt(v) => *v,
+                    Field::Int(v) => *v as i64,
+                    Field::SmallInt(v) => *v as i64,
+                    Field::Decimal(v, s) => {
+                        div_round(*v, 10i64.checked_pow(*s).ok_or_else(out_of_range)?)
+                    }
+                    Field::String(v) | Field::Char(_, v) => {
+                        v.trim().parse::<i64>().map_err(|_| err(""))?
+                    }
+                    Field::Bool(b) => *b as i64,
+                    _ => return Err(err("")),
+                };
+                match dtype {
+                    DataType::Int => i32::try_from(v).map(Field::Int).map_err(|_| out_of_range()),
+                    DataType::SmallInt => i16::try_from(v)
+                        .map(Field::SmallInt)
+                        .map_err(|_| out_of_range()),
+                    _ => Ok(Field::BigInt(v)),
+                }
+            }
+            DataType::Decimal(p, s) => {
+                let (v, scale) = match self {
+                    Field::Decimal(v, scale) => (*v, *scale),
+                    Field::String(v) | Field::Char(_, v) => {
+                        let v = v.trim();
+                        let scale = v.split_once('.').map_or(0, |(_, frac)| frac.len() as u32);
+                        match Field::from_str_to_decimal(v, scale + 18, scale) {
+                            Ok(Field::Decimal(v, scale)) => (v, scale),
+                            _ => return Err(err("")),
+                        }
+                    }
+                    Field::BigInt(v) => (*v, 0),
+                    Field::Int(v) => (*v as i64, 0),
+                    Field::SmallInt(v) => (*v as i64, 0),
+                    _ => return Err(err("")),
+                };
+                let v = if scale <= *s {
+                    10i64
+                        .checked_pow(s - scale)
+                        .and_then(|m| v.checked_mul(m))
+                        .ok_or_else(out_of_range)?
+                } else {
+                    div_round(v, 10i64.checked_pow(scale - s).ok_or_else(out_of_range)?)
+                };
+                // At most p digits, i.e. p - s before the point
+                match 10i64.checked_pow(*p) {
+                    Some(max) if v.abs() >= max => Err(out_of_range()),
+                    _ => Ok(Field::Decimal(v, *s)),
+                }
+            }
+            DataType::String => match self {
+                Field::String(_) => Ok(self.clone()),
+                field => Ok(Field::String(field.to_string())),
+            },
+            DataType::Char(n) => {
+                Field::from_str_to_char(&self.to_string(), *n).map_err(|_| out_of_range())
+            }
+            DataType::Date => match self {
+                Field::Date(_) => Ok(self.clone()),
+                Field::String(v) | Field::Char(_, v) => {
+                    Field::from_str_to_date(v.trim()).map_err(|_| err(""))
+                }
+                _ => Err(err("")),
+            },
+            DataType::Bool => match self {
+                Field::Bool(_) => Ok(self.clone()),
+                Field::String(v) | Field::Char(_, v) => match v.trim().to_lowercase().as_str() {
+                    "true" | "t" | "yes" | "y" | "on" | "1" => Ok(Field::Bool(true)),
+                    "false" | "f" | "no" | "n" | "off" | "0" => Ok(Field::Bool(false)),
+                    _ => Err(err("")),
+                },
+                Field::BigInt(v) => Ok(Field::Bool(*v != 0)),
+                Field::Int(v) => Ok(Field::Bool(*v != 0)),
+                Field::SmallInt(v) => Ok(Field::Bool(*v != 0)),
+                _ => Err(err("")),
+            },
+            DataType::Null => Err(err("")),
+        }
+    }
+}
+
 impl Add for Field {
     type Output = Result<Self, CrustyError>;
 
     fn add(self, other: Self) -> Self::Output {
         match (self, other) {
-            (Field::BigInt(a), Field::BigInt(b)) => Ok(Field::BigInt(a + b)),
+            // Arithmetic with NULL is NULL
+            (Field::Null, _) | (_, Field::Null) => Ok(Field::Null),
+            (Field::BigInt(a), Field::BigInt(b)) => a
+                .checked_add(b)
+                .map(Field::BigInt)
+                .ok_or_else(|| c_err("Value out of range")),
             (Field::Decimal(a, s_l), Field::Decimal(b, s_r)) => {
                 // We adjust to the larger scale
                 let res_scale = if s_l > s_r { s_l } else { s_r };
@@ -172,7 +400,11 @@ impl Add for Field {
                 let adjusted_b = b * 10i64.pow(s_l);
                 Ok(Field::Decimal(a + adjusted_b, s_l))
             }
-            _ => panic!("Expected int or decimal"),
+            // A date plus a number of days
+            (Field::Date(d), Field::BigInt(n)) | (Field::BigInt(n), Field::Date(d)) => {
+                Ok(Field::Date(d + n))
+            }
+            _ => Err(c_err("Expected int or decimal")),
         }
     }
 }
@@ -182,7 +414,12 @@ impl Sub for Field {
 
     fn sub(self, other: Self) -> Self::Output {
         match (self, other) {
-            (Field::BigInt(a), Field::BigInt(b)) => Ok(Field::BigInt(a - b)),
+            // Arithmetic with NULL is NULL
+            (Field::Null, _) | (_, Field::Null) => Ok(Field::Null),
+            (Field::BigInt(a), Field::BigInt(b)) => a
+                .checked_sub(b)
+                .map(Field::BigInt)
+                .ok_or_else(|| c_err("Value out of range")),
             (Field::Decimal(a, s_l), Field::Decimal(b, s_r)) => {
                 // We adjust to the larger scale
                 let res_scale = if s_l > s_r { s_l } else { s_r };
@@ -198,6 +435,9 @@ impl Sub for Field {
                 let adjusted_b = b * 10i64.pow(s_l);
                 Ok(Field::Decimal(a - adjusted_b, s_l))
             }
+            (Field::Date(d), Field::BigInt(n)) => Ok(Field::Date(d - n)),
+            // The number of days between two dates
+            (Field::Date(a), Field::Date(b)) => Ok(Field::BigInt(a - b)),
             _ => Err(c_err("Expected int or decimal")),
         }
     }
@@ -208,7 +448,12 @@ impl Mul for Field {
 
     fn mul(self, other: Self) -> Self::Output {
         match (self, other) {
-            (Field::BigInt(a), Field::BigInt(b)) => Ok(Field::BigInt(a * b)),
+            // Arithmetic with NULL is NULL
+            (Field::Null, _) | (_, Field::Null) => Ok(Field::Null),
+            (Field::BigInt(a), Field::BigInt(b)) => a
+                .checked_mul(b)
+                .map(Field::BigInt)
+                .ok_or_else(|| c_err("Value out of range")),
             (Field::Decimal(a, s_l), Field::Decimal(b, s_r)) => {
                 // We adjust to the larger scale
                 // e.g. 123.456 * 2.34
@@ -245,6 +490,8 @@ impl Div for Field {
 
     fn div(self, other: Self) -> Self::Output {
         match (self, other) {
+            // Arithmetic with NULL is NULL
+            (Field::Null, _) | (_, Field::Null) => Ok(Field::Null),
             (Field::BigInt(a), Field::BigInt(b)) => {
                 if b == 0 {
                     return Err(c_err("Division by zero"));
@@ -296,7 +543,76 @@ impl Div for Field {
     }
 }
 
+impl Rem for Field {
+    type Output = Result<Self, CrustyError>;
+
+    /// Remainder of the division, with the sign of the dividend.
+    fn rem(self, other: Self) -> Self::Output {
+        match (self, other) {
+            // Arithmetic with NULL is NULL
+            (Field::Null, _) | (_, Field::Null) => Ok(Field::Null),
+            (Field::BigInt(a), Field::BigInt(b)) => {
+                if b == 0 {
+                    return Err(c_err("Division by zero"));
+                }
+                // i64::MIN % -1 overflows
+                a.checked_rem(b)
+                    .map(Field::BigInt)
+                    .ok_or_else(|| c_err("Value out of range"))
+            }
+            (Field::Decimal(a, s_l), Field::Decimal(b, s_r)) => {
+                if b == 0 {
+                    return Err(c_err("Division by zero"));
+                }
+                // We adjust to the larger scale, e.g. 5.5 % 2 is 55 % 20 with a scale of 1
+                let res_scale = s_l.max(s_r);
+                let adjusted_a = a.checked_mul(10i64.pow(res_scale - s_l));
+                let adjusted_b = b.checked_mul(10i64.pow(res_scale - s_r));
+                adjusted_a
+                    .zip(adjusted_b)
+                    .and_then(|(a, b)| a.checked_rem(b))
+                    .map(|rem| Field::Decimal(rem, res_scale))
+                    .ok_or_else(|| c_err("Value out of range"))
+            }
+            (Field::BigInt(a), Field::Decimal(b, s_r)) => {
+                Field::Decimal(a, 0) % Field::Decimal(b, s_r)
+            }
+            (Field::Decimal(a, s_l), Field::BigInt(b)) => {
+                Field::Decimal(a, s_l) % Field::Decimal(b, 0)
+            }
+            _ => Err(c_err("Expected int or decimal")),
+        }
+    }
+}
+
+impl Neg for Field {
+    type Output = Result<Self, CrustyError>;
+
+    fn neg(self) -> Self::Output {
+        let negated = match self {
+            Field::Null => return Ok(Field::Null),
+            // The negation of the smallest value overflows
+            Field::BigInt(a) => a.checked_neg().map(Field::BigInt),
+            Field::Int(a) => a.checked_neg().map(Field::Int),
+            Field::SmallInt(a) => a.checked_neg().map(Field::SmallInt),
+            Field::Decimal(a, s) => a.checked_neg().map(|a| Field::Decimal(a, s)),
+            _ => return Err(c_err("Expected int or decimal")),
+        };
+        negated.ok_or_else(|| c_err("Value out of range"))
+    }
+}
+
 impl Field {
+    /// Text matched by LIKE: strings as is, other values as printed (the translator
+    /// only lets them through an explicit cast). None for NULL.
+    fn like_operand(&self) -> Option<Cow<'_, str>> {
+        match self {
+            Field::String(s) | Field::Char(_, s) => Some(Cow::Borrowed(s)),
+            Field::Null => None,
+            field => Some(Cow::Owned(field.to_string())),
+        }
+    }
+
     pub fn size(&self) -> usize {
         match self {
             Field::BigInt(_) => 8,
@@ -616,7 +932,19 @@ impl std::fmt::Display for Field {
     }
 }
 
+/// Whether `left op right` is true. Comparisons with NULL are never true, except
+/// IS [NOT] DISTINCT FROM.
 pub fn compare_fields(op: BinaryOp, left: &Field, right: &Field) -> bool {
+    match op {
+        BinaryOp::IsDistinctFrom => return left != right,
+        BinaryOp::IsNotDistinctFrom => return left == right,
+        BinaryOp::And => return left.and(right) == Field::Bool(true),
+        BinaryOp::Or => return left.or(right) == Field::Bool(true),
+        _ => {}
+    }
+    if left.is_null() || right.is_null() {
+        return false;
+    }
     match op {
         BinaryOp::Eq => left == right,
         BinaryOp::Neq => left != right,
@@ -624,8 +952,10 @@ pub fn compare_fields(op: BinaryOp, left: &Field, right: &Field) -> bool {
         BinaryOp::Ge => left >= right,
         BinaryOp::Lt => left < right,
         BinaryOp::Le => left <= right,
-        BinaryOp::And => left.and(right).unwrap_bool_field(),
-        BinaryOp::Or => left.or(right).unwrap_bool_field(),
+        BinaryOp::Like => left.like(right, false) == Some(true),
+        BinaryOp::ILike => left.like(right, true) == Some(true),
+        BinaryOp::NotLike => left.like(right, false) == Some(false),
+        BinaryOp::NotILike => left.like(right, true) == Some(false),
         _ => panic!("Unsupported comparison operation"),
     }
 }
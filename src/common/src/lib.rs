@@ -55,5 +55,5 @@ pub mod prelude {
 pub use crate::error::{ConversionError, CrustyError};
 
 pub use crate::datatypes::{DataType, Field};
-pub use crate::query::operation::{AggOp, BinaryOp};
+pub use crate::query::operation::{AggOp, BinaryOp, UnaryOp};
 pub use query::query_result::QueryResult;
@@ -1,5 +1,5 @@
-use crate::{tuple::Tuple, CrustyError, Field};
-use std::ops::{Add, Div, Mul, Sub};
+use crate::{tuple::Tuple, CrustyError, DataType, Field};
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
 
 pub trait FromBool {
     fn from_bool(b: bool) -> Self;
@@ -13,15 +13,46 @@ pub trait Or {
     fn or(&self, other: &Self) -> Self;
 }
 
+pub trait Not {
+    fn not(&self) -> Self;
+}
+
+pub trait Nullable {
+    fn null() -> Self;
+    fn is_null(&self) -> bool;
+}
+
+/// Call of a scalar function of the function registry.
+pub trait CallFn: Sized {
+    fn call_fn(id: usize, args: &[Self]) -> Result<Self, CrustyError>;
+}
+
+/// Conversion of a value to another type.
+pub trait Cast: Sized {
+    fn cast(&self, dtype: &DataType) -> Result<Self, CrustyError>;
+}
+
+/// Pattern matching of LIKE. Returns None if either operand is NULL.
+pub trait Like {
+    fn like(&self, pattern: &Self, case_insensitive: bool) -> Option<bool>;
+    fn starts_with(&self, prefix: &Self) -> Option<bool>;
+}
+
 pub enum ByteCodes {
     // CONTROL FLOW
     PushLit,
     PushField,
+    // Jumps take the position of the next bytecode to evaluate as operand
+    Jump,
+    // Pops a condition and jumps unless it is true, i.e. if it is false or NULL
+    JumpIfNotTrue,
     // MATH OPERATIONS
     Add,
     Sub,
     Mul,
     Div,
+    Mod,
+    Neg,
     // COMPARISON OPERATIONS
     Eq,
     Neq,
@@ -32,17 +63,44 @@ pub enum ByteCodes {
     // LOGICAL OPERATIONS
     And,
     Or,
+    Not,
+    // NULL OPERATIONS
+    IsNull,
+    IsNotNull,
+    IsDistinctFrom,
+    IsNotDistinctFrom,
+    // SET MEMBERSHIP OPERATIONS
+    In,
+    NotIn,
+    // PATTERN MATCHING OPERATIONS
+    Like,
+    ILike,
+    NotLike,
+    NotILike,
+    // LIKE with a pattern made of a prefix followed by `%`
+    StartsWith,
+    NotStartsWith,
+    // FUNCTION CALLS
+    // Takes the function id and the number of arguments as operands
+    CallFn,
+    // TYPE CONVERSIONS
+    // Takes the target type as operands, see `ByteCodeExpr::add_cast`
+    Cast,
 }
 
-const STATIC_DISPATCHER: [DispatchFn<Field>; 14] = [
+const STATIC_DISPATCHER: [DispatchFn<Field>; 33] = [
     // CONTROL FLOW
     PUSH_LIT_FN,
     PUSH_FIELD_FN,
+    JUMP_FN,
+    JUMP_IF_NOT_TRUE_FN,
     // MATH OPERATIONS
     ADD_FN,
     SUB_FN,
     MUL_FN,
     DIV_FN,
+    MOD_FN,
+    NEG_FN,
     // COMPARISON OPERATIONS
     EQ_FN,
     NEQ_FN,
@@ -53,6 +111,26 @@ const STATIC_DISPATCHER: [DispatchFn<Field>; 14] = [
     // LOGICAL OPERATIONS
     AND_FN,
     OR_FN,
+    NOT_FN,
+    // NULL OPERATIONS
+    IS_NULL_FN,
+    IS_NOT_NULL_FN,
+    IS_DISTINCT_FROM_FN,
+    IS_NOT_DISTINCT_FROM_FN,
+    // SET MEMBERSHIP OPERATIONS
+    IN_FN,
+    NOT_IN_FN,
+    // PATTERN MATCHING OPERATIONS
+    LIKE_FN,
+    ILIKE_FN,
+    NOT_LIKE_FN,
+    NOT_ILIKE_FN,
+    STARTS_WITH_FN,
+    NOT_STARTS_WITH_FN,
+    // FUNCTION CALLS
+    CALL_FN_FN,
+    // TYPE CONVERSIONS
+    CAST_FN,
 ];
 
 // Utility functions
@@ -87,11 +165,46 @@ impl ByteCodeExpr {
         i
     }
 
+    /// Adds a jump whose target is not known yet. Returns the position of the target,
+    /// to be set by `patch_jump`.
+    pub fn add_jump(&mut self, jump: ByteCodes) -> usize {
+        self.add_code(jump as usize);
+        self.add_code(usize::MAX);
+        self.bytecodes.len() - 1
+    }
+
+    /// Makes the jump added at `pos` jump to the next bytecode added.
+    pub fn patch_jump(&mut self, pos: usize) {
+        self.bytecodes[pos] = self.bytecodes.len();
+    }
+
+    /// Adds a conversion of the top of the stack to a type. The type is encoded as a tag
+    /// followed by its two parameters, e.g. the precision and scale of a decimal.
+    pub fn add_cast(&mut self, dtype: &DataType) {
+        let (tag, a, b) = match dtype {
+            DataType::BigInt => (0, 0, 0),
+            DataType::Int => (1, 0, 0),
+            DataType::SmallInt => (2, 0, 0),
+            DataType::Char(n) => (3, *n as usize, 0),
+            DataType::String => (4, 0, 0),
+            DataType::Decimal(p, s) => (5, *p as usize, *s as usize),
+            DataType::Date => (6, 0, 0),
+            DataType::Bool => (7, 0, 0),
+            DataType::Null => (8, 0, 0),
+        };
+        self.add_code(ByteCodes::Cast as usize);
+        self.add_code(tag);
+        self.add_code(a);
+        self.add_code(b);
+    }
+
     fn is_empty(&self) -> bool {
         self.bytecodes.is_empty()
     }
 
-    pub fn eval(&self, record: &Tuple) -> Field {
+    /// Evaluates the expression on a record. Fails if an operation fails, e.g. a
+    /// function call outside of its domain.
+    pub fn eval(&self, record: &Tuple) -> Result<Field, CrustyError> {
         if self.is_empty() {
             panic!("Cannot evaluate empty expression")
         }
@@ -103,19 +216,23 @@ impl ByteCodeExpr {
         while i < bytecodes.len() {
             let opcode = bytecodes[i];
             i += 1;
-            STATIC_DISPATCHER[opcode](bytecodes, &mut i, &mut stack, literals, record);
+            STATIC_DISPATCHER[opcode](bytecodes, &mut i, &mut stack, literals, record)?;
         }
-        stack.pop().unwrap()
+        Ok(stack.pop().unwrap())
     }
 }
 
-type DispatchFn<T> = fn(&[usize], &mut usize, &mut Vec<T>, &[T], &[T]);
+type DispatchFn<T> = fn(&[usize], &mut usize, &mut Vec<T>, &[T], &[T]) -> Result<(), CrustyError>;
 const PUSH_LIT_FN: DispatchFn<Field> = push_lit;
 const PUSH_FIELD_FN: DispatchFn<Field> = push_field;
+const JUMP_FN: DispatchFn<Field> = jump;
+const JUMP_IF_NOT_TRUE_FN: DispatchFn<Field> = jump_if_not_true;
 const ADD_FN: DispatchFn<Field> = add;
 const SUB_FN: DispatchFn<Field> = sub;
 const MUL_FN: DispatchFn<Field> = mul;
 const DIV_FN: DispatchFn<Field> = div;
+const MOD_FN: DispatchFn<Field> = modulo;
+const NEG_FN: DispatchFn<Field> = neg;
 const EQ_FN: DispatchFn<Field> = eq;
 const NEQ_FN: DispatchFn<Field> = neq;
 const LT_FN: DispatchFn<Field> = lt;
@@ -124,6 +241,21 @@ const LTE_FN: DispatchFn<Field> = lte;
 const GTE_FN: DispatchFn<Field> = gte;
 const AND_FN: DispatchFn<Field> = and;
 const OR_FN: DispatchFn<Field> = or;
+const NOT_FN: DispatchFn<Field> = not;
+const IS_NULL_FN: DispatchFn<Field> = is_null;
+const IS_NOT_NULL_FN: DispatchFn<Field> = is_not_null;
+const IS_DISTINCT_FROM_FN: DispatchFn<Field> = is_distinct_from;
+const IS_NOT_DISTINCT_FROM_FN: DispatchFn<Field> = is_not_distinct_from;
+const IN_FN: DispatchFn<Field> = in_list;
+const NOT_IN_FN: DispatchFn<Field> = not_in_list;
+const LIKE_FN: DispatchFn<Field> = like;
+const ILIKE_FN: DispatchFn<Field> = ilike;
+const NOT_LIKE_FN: DispatchFn<Field> = not_like;
+const NOT_ILIKE_FN: DispatchFn<Field> = not_ilike;
+const STARTS_WITH_FN: DispatchFn<Field> = starts_with;
+const NOT_STARTS_WITH_FN: DispatchFn<Field> = not_starts_with;
+const CALL_FN_FN: DispatchFn<Field> = call_fn;
+const CAST_FN: DispatchFn<Field> = cast;
 
 fn push_field<T>(
     bytecodes: &[usize],
@@ -131,11 +263,13 @@ fn push_field<T>(
     stack: &mut Vec<T>,
     _literals: &[T],
     record: &[T],
-) where
+) -> Result<(), CrustyError>
+where
     T: Clone,
 {
     stack.push(record[bytecodes[*i]].clone());
     *i += 1;
+    Ok(())
 }
 
 fn push_lit<T>(
@@ -144,117 +278,1036 @@ fn push_lit<T>(
     stack: &mut Vec<T>,
     literals: &[T],
     _record: &[T],
-) where
+) -> Result<(), CrustyError>
+where
     T: Clone,
 {
     stack.push(literals[bytecodes[*i]].clone());
     *i += 1;
+    Ok(())
 }
 
-fn add<T>(_bytecodes: &[usize], _i: &mut usize, stack: &mut Vec<T>, _literals: &[T], _record: &[T])
+fn jump<T>(
+    bytecodes: &[usize],
+    i: &mut usize,
+    _stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError> {
+    *i = bytecodes[*i];
+    Ok(())
+}
+
+fn jump_if_not_true<T>(
+    bytecodes: &[usize],
+    i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
+where
+    T: PartialEq + FromBool,
+{
+    let cond = stack.pop().unwrap();
+    if cond == T::from_bool(true) {
+        *i += 1;
+    } else {
+        *i = bytecodes[*i];
+    }
+    Ok(())
+}
+
+fn add<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
 where
     T: Add<Output = Result<T, CrustyError>> + Clone,
 {
     let r = stack.pop().unwrap();
     let l = stack.pop().unwrap();
-    stack.push((l + r).unwrap());
+    stack.push((l + r).map_err(execution_error)?);
+    Ok(())
 }
 
-fn sub<T>(_bytecodes: &[usize], _i: &mut usize, stack: &mut Vec<T>, _literals: &[T], _record: &[T])
+fn sub<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
 where
     T: Sub<Output = Result<T, CrustyError>> + Clone,
 {
     let r = stack.pop().unwrap();
     let l = stack.pop().unwrap();
-    stack.push((l - r).unwrap());
+    stack.push((l - r).map_err(execution_error)?);
+    Ok(())
 }
 
-fn mul<T>(_bytecodes: &[usize], _i: &mut usize, stack: &mut Vec<T>, _literals: &[T], _record: &[T])
+fn mul<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
 where
     T: Mul<Output = Result<T, CrustyError>> + Clone,
 {
     let r = stack.pop().unwrap();
     let l = stack.pop().unwrap();
-    stack.push((l * r).unwrap());
+    stack.push((l * r).map_err(execution_error)?);
+    Ok(())
 }
 
-fn div<T>(_bytecodes: &[usize], _i: &mut usize, stack: &mut Vec<T>, _literals: &[T], _record: &[T])
+fn div<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
 where
     T: Div<Output = Result<T, CrustyError>> + Clone,
 {
     let r = stack.pop().unwrap();
     let l = stack.pop().unwrap();
-    stack.push((l / r).unwrap());
+    stack.push((l / r).map_err(execution_error)?);
+    Ok(())
 }
 
-fn eq<T>(_bytecodes: &[usize], _i: &mut usize, stack: &mut Vec<T>, _literals: &[T], _record: &[T])
+fn modulo<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
 where
-    T: PartialEq + Clone + FromBool,
+    T: Rem<Output = Result<T, CrustyError>> + Clone,
 {
     let r = stack.pop().unwrap();
     let l = stack.pop().unwrap();
-    stack.push(T::from_bool(l == r));
+    stack.push((l % r).map_err(execution_error)?);
+    Ok(())
 }
 
-fn neq<T>(_bytecodes: &[usize], _i: &mut usize, stack: &mut Vec<T>, _literals: &[T], _record: &[T])
+fn neg<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
 where
-    T: PartialEq + Clone + FromBool,
+    T: Neg<Output = Result<T, CrustyError>> + Clone,
+{
+    let v = stack.pop().unwrap();
+    stack.push((-v).map_err(execution_error)?);
+    Ok(())
+}
+
+/// Reports the failure of an operation as an error of the evaluation.
+fn execution_error(e: CrustyError) -> CrustyError {
+    match e {
+        CrustyError::CrustyError(s) | CrustyError::ValidationError(s) => {
+            CrustyError::ExecutionError(s)
+        }
+        e => e,
+    }
+}
+
+/// Result of a comparison: NULL if either side is NULL.
+fn compare<T>(l: &T, r: &T, cmp: impl Fn(&T, &T) -> bool) -> T
+where
+    T: FromBool + Nullable,
+{
+    if l.is_null() || r.is_null() {
+        T::null()
+    } else {
+        T::from_bool(cmp(l, r))
+    }
+}
+
+fn eq<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
+where
+    T: PartialEq + Clone + FromBool + Nullable,
 {
     let r = stack.pop().unwrap();
     let l = stack.pop().unwrap();
-    stack.push(T::from_bool(l != r));
+    stack.push(compare(&l, &r, |l, r| l == r));
+    Ok(())
 }
 
-fn lt<T>(_bytecodes: &[usize], _i: &mut usize, stack: &mut Vec<T>, _literals: &[T], _record: &[T])
+fn neq<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
 where
-    T: PartialOrd + Clone + FromBool,
+    T: PartialEq + Clone + FromBool + Nullable,
 {
     let r = stack.pop().unwrap();
     let l = stack.pop().unwrap();
-    stack.push(T::from_bool(l < r));
+    stack.push(compare(&l, &r, |l, r| l != r));
+    Ok(())
 }
 
-fn gt<T>(_bytecodes: &[usize], _i: &mut usize, stack: &mut Vec<T>, _literals: &[T], _record: &[T])
+fn lt<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
 where
-    T: PartialOrd + Clone + FromBool,
+    T: PartialOrd + Clone + FromBool + Nullable,
 {
     let r = stack.pop().unwrap();
     let l = stack.pop().unwrap();
-    stack.push(T::from_bool(l > r));
+    stack.push(compare(&l, &r, |l, r| l < r));
+    Ok(())
 }
 
-fn lte<T>(_bytecodes: &[usize], _i: &mut usize, stack: &mut Vec<T>, _literals: &[T], _record: &[T])
+fn gt<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
 where
-    T: PartialOrd + Clone + FromBool,
+    T: PartialOrd + Clone + FromBool + Nullable,
 {
     let r = stack.pop().unwrap();
     let l = stack.pop().unwrap();
-    stack.push(T::from_bool(l <= r));
+    stack.push(compare(&l, &r, |l, r| l > r));
+    Ok(())
 }
 
-fn gte<T>(_bytecodes: &[usize], _i: &mut usize, stack: &mut Vec<T>, _literals: &[T], _record: &[T])
+fn lte<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
 where
-    T: PartialOrd + Clone + FromBool,
+    T: PartialOrd + Clone + FromBool + Nullable,
 {
     let r = stack.pop().unwrap();
     let l = stack.pop().unwrap();
-    stack.push(T::from_bool(l >= r));
+    stack.push(compare(&l, &r, |l, r| l <= r));
+    Ok(())
 }
 
-fn and<T>(_bytecodes: &[usize], _i: &mut usize, stack: &mut Vec<T>, _literals: &[T], _record: &[T])
+fn gte<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
+where
+    T: PartialOrd + Clone + FromBool + Nullable,
+{
+    let r = stack.pop().unwrap();
+    let l = stack.pop().unwrap();
+    stack.push(compare(&l, &r, |l, r| l >= r));
+    Ok(())
+}
+
+fn and<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
 where
     T: PartialEq + Clone + And,
 {
     let r = stack.pop().unwrap();
     let l = stack.pop().unwrap();
     stack.push(l.and(&r));
+    Ok(())
 }
 
-fn or<T>(_bytecodes: &[usize], _i: &mut usize, stack: &mut Vec<T>, _literals: &[T], _record: &[T])
+fn or<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
 where
     T: PartialEq + Clone + Or,
 {
     let r = stack.pop().unwrap();
     let l = stack.pop().unwrap();
     stack.push(l.or(&r));
+    Ok(())
+}
+
+fn not<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
+where
+    T: Clone + Not,
+{
+    let v = stack.pop().unwrap();
+    stack.push(v.not());
+    Ok(())
+}
+
+fn is_null<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
+where
+    T: FromBool + Nullable,
+{
+    let v = stack.pop().unwrap();
+    stack.push(T::from_bool(v.is_null()));
+    Ok(())
+}
+
+fn is_not_null<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
+where
+    T: FromBool + Nullable,
+{
+    let v = stack.pop().unwrap();
+    stack.push(T::from_bool(!v.is_null()));
+    Ok(())
+}
+
+/// Inequality where NULL equals NULL and differs from any other value.
+fn is_distinct_from<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
+where
+    T: PartialEq + FromBool,
+{
+    let r = stack.pop().unwrap();
+    let l = stack.pop().unwrap();
+    stack.push(T::from_bool(l != r));
+    Ok(())
+}
+
+fn is_not_distinct_from<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
+where
+    T: PartialEq + FromBool,
+{
+    let r = stack.pop().unwrap();
+    let l = stack.pop().unwrap();
+    stack.push(T::from_bool(l == r));
+    Ok(())
+}
+
+/// Pops the number of list values given by the operand and then the probed value.
+/// Returns whether the value is in the list, or NULL if that is unknown: the value
+/// is NULL, or it is not found but the list contains a NULL.
+fn membership<T>(bytecodes: &[usize], i: &mut usize, stack: &mut Vec<T>) -> Option<bool>
+where
+    T: PartialEq + Nullable,
+{
+    let n = bytecodes[*i];
+    *i += 1;
+    let list = stack.split_off(stack.len() - n);
+    let value = stack.pop().unwrap();
+    if value.is_null() {
+        None
+    } else if list.contains(&value) {
+        Some(true)
+    } else if list.iter().any(|v| v.is_null()) {
+        None
+    } else {
+        Some(false)
+    }
+}
+
+fn in_list<T>(
+    bytecodes: &[usize],
+    i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
+where
+    T: PartialEq + Clone + FromBool + Nullable,
+{
+    let res = match membership(bytecodes, i, stack) {
+        Some(b) => T::from_bool(b),
+        None => T::null(),
+    };
+    stack.push(res);
+    Ok(())
+}
+
+fn not_in_list<T>(
+    bytecodes: &[usize],
+    i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
+where
+    T: PartialEq + Clone + FromBool + Nullable,
+{
+    let res = match membership(bytecodes, i, stack) {
+        Some(b) => T::from_bool(!b),
+        None => T::null(),
+    };
+    stack.push(res);
+    Ok(())
+}
+
+/// Pushes the result of a pattern match, negated if needed, or NULL if it is unknown.
+fn push_match<T>(stack: &mut Vec<T>, res: Option<bool>, negated: bool)
+where
+    T: FromBool + Nullable,
+{
+    let res = match res {
+        Some(b) => T::from_bool(b != negated),
+        None => T::null(),
+    };
+    stack.push(res);
+}
+
+fn like<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
+where
+    T: Like + FromBool + Nullable,
+{
+    let r = stack.pop().unwrap();
+    let l = stack.pop().unwrap();
+    push_match(stack, l.like(&r, false), false);
+    Ok(())
+}
+
+fn ilike<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
+where
+    T: Like + FromBool + Nullable,
+{
+    let r = stack.pop().unwrap();
+    let l = stack.pop().unwrap();
+    push_match(stack, l.like(&r, true), false);
+    Ok(())
+}
+
+fn not_like<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
+where
+    T: Like + FromBool + Nullable,
+{
+    let r = stack.pop().unwrap();
+    let l = stack.pop().unwrap();
+    push_match(stack, l.like(&r, false), true);
+    Ok(())
+}
+
+fn not_ilike<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
+where
+    T: Like + FromBool + Nullable,
+{
+    let r = stack.pop().unwrap();
+    let l = stack.pop().unwrap();
+    push_match(stack, l.like(&r, true), true);
+    Ok(())
+}
+
+fn starts_with<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
+where
+    T: Like + FromBool + Nullable,
+{
+    let r = stack.pop().unwrap();
+    let l = stack.pop().unwrap();
+    push_match(stack, l.starts_with(&r), false);
+    Ok(())
+}
+
+fn not_starts_with<T>(
+    _bytecodes: &[usize],
+    _i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
+where
+    T: Like + FromBool + Nullable,
+{
+    let r = stack.pop().unwrap();
+    let l = stack.pop().unwrap();
+    push_match(stack, l.starts_with(&r), true);
+    Ok(())
+}
+
+/// Pops the number of arguments given by the second operand and pushes the result of
+/// the function given by the first.
+fn call_fn<T>(
+    bytecodes: &[usize],
+    i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
+where
+    T: CallFn,
+{
+    let id = bytecodes[*i];
+    let argc = bytecodes[*i + 1];
+    *i += 2;
+    let args = stack.split_off(stack.len() - argc);
+    stack.push(T::call_fn(id, &args).map_err(execution_error)?);
+    Ok(())
+}
+
+/// Converts the top of the stack to the type encoded by `ByteCodeExpr::add_cast`.
+fn cast<T>(
+    bytecodes: &[usize],
+    i: &mut usize,
+    stack: &mut Vec<T>,
+    _literals: &[T],
+    _record: &[T],
+) -> Result<(), CrustyError>
+where
+    T: Cast,
+{
+    let (a, b) = (bytecodes[*i + 1], bytecodes[*i + 2]);
+    let dtype = match bytecodes[*i] {
+        0 => DataType::BigInt,
+        1 => DataType::Int,
+        2 => DataType::SmallInt,
+        3 => DataType::Char(a as u8),
+        4 => DataType::String,
+        5 => DataType::Decimal(a as u32, b as u32),
+        6 => DataType::Date,
+        7 => DataType::Bool,
+        _ => DataType::Null,
+    };
+    *i += 3;
+    let value = stack.pop().unwrap();
+    stack.push(value.cast(&dtype).map_err(execution_error)?);
+    Ok(())
+}
+
+/// Element of a LIKE pattern.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PatternToken {
+    Char(char),
+    /// `_`
+    AnyChar,
+    /// `%`
+    AnyString,
+}
+
+/// Splits a LIKE pattern into tokens. `\` escapes the next character; a trailing `\`
+/// matches itself.
+fn tokenize_pattern(pattern: &str) -> Vec<PatternToken> {
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '%' => PatternToken::AnyString,
+            '_' => PatternToken::AnyChar,
+            '\\' => PatternToken::Char(chars.next().unwrap_or('\\')),
+            c => PatternToken::Char(c),
+        };
+        tokens.push(token);
+    }
+    tokens
+}
+
+/// Whether a value matches a LIKE pattern.
+pub fn like_match(value: &str, pattern: &str, case_insensitive: bool) -> bool {
+    let (value, pattern) = if case_insensitive {
+        (value.to_lowercase(), pattern.to_lowercase())
+    } else {
+        (value.to_string(), pattern.to_string())
+    };
+    let value = value.chars().collect::<Vec<_>>();
+    let tokens = tokenize_pattern(&pattern);
+    // Greedy matching, going back to the last `%` on a mismatch
+    let (mut v, mut p) = (0, 0);
+    let mut backtrack = None;
+    while v < value.len() {
+        match tokens.get(p) {
+            Some(PatternToken::AnyString) => {
+                p += 1;
+                backtrack = Some((p, v));
+            }
+            Some(PatternToken::AnyChar) => {
+                v += 1;
+                p += 1;
+            }
+            Some(PatternToken::Char(c)) if *c == value[v] => {
+                v += 1;
+                p += 1;
+            }
+            _ => match backtrack {
+                Some((bp, bv)) => {
+                    p = bp;
+                    v = bv + 1;
+                    backtrack = Some((bp, bv + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    tokens[p..].iter().all(|t| *t == PatternToken::AnyString)
+}
+
+/// If a LIKE pattern is a literal prefix followed by `%`, returns the prefix, so that
+/// the match can be done with a prefix comparison.
+pub fn like_prefix(pattern: &str) -> Option<String> {
+    let tokens = tokenize_pattern(pattern);
+    let (last, prefix) = tokens.split_last()?;
+    if *last != PatternToken::AnyString {
+        return None;
+    }
+    prefix
+        .iter()
+        .map(|t| match t {
+            PatternToken::Char(c) => Some(*c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Rewrites a LIKE pattern using `escape` as escape character to the default `\` escape.
+pub fn normalize_like_escape(pattern: &str, escape: char) -> String {
+    if escape == '\\' {
+        return pattern.to_string();
+    }
+    let mut normalized = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c == escape {
+            normalized.push('\\');
+            normalized.push(chars.next().unwrap_or(escape));
+        } else if c == '\\' {
+            normalized.push_str("\\\\");
+        } else {
+            normalized.push(c);
+        }
+    }
+    normalized
+}
+
+/// Estimated selectivity of a LIKE pattern when no sample matches it: each literal
+/// character makes the pattern more selective, each `%` but a trailing one less so.
+pub fn like_selectivity(pattern: &str) -> f64 {
+    const CHAR_SEL: f64 = 0.2;
+    const ANY_CHAR_SEL: f64 = 0.9;
+    const ANY_STRING_SEL: f64 = 5.0;
+    let tokens = tokenize_pattern(pattern);
+    let end = tokens
+        .iter()
+        .rposition(|t| *t != PatternToken::AnyString)
+        .map_or(0, |i| i + 1);
+    let sel = tokens[..end].iter().fold(1.0, |sel, t| match t {
+        PatternToken::Char(_) => sel * CHAR_SEL,
+        PatternToken::AnyChar => sel * ANY_CHAR_SEL,
+        PatternToken::AnyString => sel * ANY_STRING_SEL,
+    });
+    sel.clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Evaluates `op` on the two fields of a record.
+    fn eval_binary(op: ByteCodes, l: Field, r: Field) -> Field {
+        let mut expr = colidx_expr(0);
+        expr.add_code(ByteCodes::PushField as usize);
+        expr.add_code(1);
+        expr.add_code(op as usize);
+        expr.eval(&Tuple::new(vec![l, r])).unwrap()
+    }
+
+    #[test]
+    fn test_three_valued_logic() {
+        let t = Field::Bool(true);
+        let f = Field::Bool(false);
+        let n = Field::Null;
+        // Comparisons with NULL are NULL
+        assert_eq!(eval_binary(ByteCodes::Eq, Field::BigInt(1), n.clone()), n);
+        assert_eq!(eval_binary(ByteCodes::Neq, n.clone(), n.clone()), n);
+        assert_eq!(eval_binary(ByteCodes::Lt, n.clone(), Field::BigInt(1)), n);
+        assert_eq!(eval_binary(ByteCodes::Add, n.clone(), Field::BigInt(1)), n);
+        assert_eq!(
+            eval_binary(ByteCodes::Gte, Field::BigInt(1), Field::BigInt(1)),
+            t
+        );
+        // AND and OR truth tables
+        let and_table = [
+            (&t, &t, &t),
+            (&t, &f, &f),
+            (&t, &n, &n),
+            (&f, &f, &f),
+            (&f, &n, &f),
+            (&n, &n, &n),
+        ];
+        for (l, r, res) in and_table {
+            assert_eq!(eval_binary(ByteCodes::And, l.clone(), r.clone()), *res);
+            assert_eq!(eval_binary(ByteCodes::And, r.clone(), l.clone()), *res);
+        }
+        let or_table = [
+            (&t, &t, &t),
+            (&t, &f, &t),
+            (&t, &n, &t),
+            (&f, &f, &f),
+            (&f, &n, &n),
+            (&n, &n, &n),
+        ];
+        for (l, r, res) in or_table {
+            assert_eq!(eval_binary(ByteCodes::Or, l.clone(), r.clone()), *res);
+            assert_eq!(eval_binary(ByteCodes::Or, r.clone(), l.clone()), *res);
+        }
+        // NULL operations are never NULL
+        let mut expr = colidx_expr(0);
+        expr.add_code(ByteCodes::IsNull as usize);
+        assert_eq!(expr.eval(&Tuple::new(vec![n.clone()])).unwrap(), t);
+        assert_eq!(expr.eval(&Tuple::new(vec![Field::BigInt(1)])).unwrap(), f);
+        let mut expr = colidx_expr(0);
+        expr.add_code(ByteCodes::IsNotNull as usize);
+        assert_eq!(expr.eval(&Tuple::new(vec![n.clone()])).unwrap(), f);
+        assert_eq!(
+            eval_binary(ByteCodes::IsDistinctFrom, n.clone(), n.clone()),
+            f
+        );
+        assert_eq!(
+            eval_binary(ByteCodes::IsDistinctFrom, Field::BigInt(1), n.clone()),
+            t
+        );
+        assert_eq!(
+            eval_binary(ByteCodes::IsNotDistinctFrom, n.clone(), n.clone()),
+            t
+        );
+        assert_eq!(
+            eval_binary(
+                ByteCodes::IsNotDistinctFrom,
+                Field::BigInt(1),
+                Field::BigInt(2)
+            ),
+            f
+        );
+    }
+
+    fn eval_unary(op: ByteCodes, v: Field) -> Field {
+        let mut expr = colidx_expr(0);
+        expr.add_code(op as usize);
+        expr.eval(&Tuple::new(vec![v])).unwrap()
+    }
+
+    #[test]
+    fn test_mod_neg_not() {
+        let n = Field::Null;
+        assert_eq!(
+            eval_binary(ByteCodes::Mod, Field::BigInt(7), Field::BigInt(3)),
+            Field::BigInt(1)
+        );
+        assert_eq!(
+            eval_binary(ByteCodes::Mod, Field::BigInt(-7), Field::BigInt(3)),
+            Field::BigInt(-1)
+        );
+        assert_eq!(
+            eval_binary(ByteCodes::Mod, Field::Decimal(55, 1), Field::BigInt(2)),
+            Field::Decimal(15, 1)
+        );
+        assert_eq!(eval_binary(ByteCodes::Mod, n.clone(), Field::BigInt(3)), n);
+        assert_eq!(
+            eval_unary(ByteCodes::Neg, Field::BigInt(4)),
+            Field::BigInt(-4)
+        );
+        assert_eq!(
+            eval_unary(ByteCodes::Neg, Field::Decimal(15, 1)),
+            Field::Decimal(-15, 1)
+        );
+        assert_eq!(eval_unary(ByteCodes::Neg, n.clone()), n);
+        assert_eq!(
+            eval_unary(ByteCodes::Not, Field::Bool(true)),
+            Field::Bool(false)
+        );
+        assert_eq!(
+            eval_unary(ByteCodes::Not, Field::Bool(false)),
+            Field::Bool(true)
+        );
+        assert_eq!(eval_unary(ByteCodes::Not, n.clone()), n);
+    }
+
+    #[test]
+    fn test_arithmetic_overflow() {
+        let eval = |op: ByteCodes, l: Field, r: Field| {
+            let mut expr = colidx_expr(0);
+            expr.add_code(ByteCodes::PushField as usize);
+            expr.add_code(1);
+            expr.add_code(op as usize);
+            expr.eval(&Tuple::new(vec![l, r]))
+        };
+        let (min, max) = (Field::BigInt(i64::MIN), Field::BigInt(i64::MAX));
+        for (op, l, r) in [
+            (ByteCodes::Add, max.clone(), Field::BigInt(1)),
+            (ByteCodes::Sub, min.clone(), Field::BigInt(1)),
+            (ByteCodes::Mul, max.clone(), Field::BigInt(2)),
+            (ByteCodes::Mod, min.clone(), Field::BigInt(-1)),
+            (
+                ByteCodes::Mod,
+                Field::Decimal(i64::MIN, 0),
+                Field::Decimal(-1, 0),
+            ),
+            (ByteCodes::Div, max.clone(), Field::BigInt(0)),
+        ] {
+            assert!(matches!(
+                eval(op, l, r),
+                Err(CrustyError::ExecutionError(_))
+            ));
+        }
+        let mut expr = colidx_expr(0);
+        expr.add_code(ByteCodes::Neg as usize);
+        for v in [min, Field::Int(i32::MIN), Field::Decimal(i64::MIN, 2)] {
+            assert!(matches!(
+                expr.eval(&Tuple::new(vec![v])),
+                Err(CrustyError::ExecutionError(_))
+            ));
+        }
+        assert_eq!(
+            eval_binary(
+                ByteCodes::Add,
+                Field::BigInt(i64::MAX - 1),
+                Field::BigInt(1)
+            ),
+            Field::BigInt(i64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_conditional_jumps() {
+        // CASE WHEN @0 > 1 THEN 'big' WHEN @0 = 1 THEN 'one' ELSE 'small' END
+        let mut expr = ByteCodeExpr::new();
+        let mut end_jumps = Vec::new();
+        for (op, val, res) in [(ByteCodes::Gt, 1, "big"), (ByteCodes::Eq, 1, "one")] {
+            expr.add_code(ByteCodes::PushField as usize);
+            expr.add_code(0);
+            let i = expr.add_literal(Field::BigInt(val));
+            expr.add_code(ByteCodes::PushLit as usize);
+            expr.add_code(i);
+            expr.add_code(op as usize);
+            let next_when = expr.add_jump(ByteCodes::JumpIfNotTrue);
+            let i = expr.add_literal(Field::String(res.to_string()));
+            expr.add_code(ByteCodes::PushLit as usize);
+            expr.add_code(i);
+            end_jumps.push(expr.add_jump(ByteCodes::Jump));
+            expr.patch_jump(next_when);
+        }
+        let i = expr.add_literal(Field::String("small".to_string()));
+        expr.add_code(ByteCodes::PushLit as usize);
+        expr.add_code(i);
+        for pos in end_jumps {
+            expr.patch_jump(pos);
+        }
+
+        let eval = |v: Field| expr.eval(&Tuple::new(vec![v])).unwrap();
+        assert_eq!(eval(Field::BigInt(5)), Field::String("big".to_string()));
+        assert_eq!(eval(Field::BigInt(1)), Field::String("one".to_string()));
+        assert_eq!(eval(Field::BigInt(0)), Field::String("small".to_string()));
+        // An unknown condition is not true
+        assert_eq!(eval(Field::Null), Field::String("small".to_string()));
+    }
+
+    #[test]
+    fn test_cast() {
+        let cast = |v: Field, dtype: DataType| {
+            let mut expr = colidx_expr(0);
+            expr.add_cast(&dtype);
+            expr.eval(&Tuple::new(vec![v])).unwrap()
+        };
+        let s = |s: &str| Field::String(s.to_string());
+        assert_eq!(cast(Field::BigInt(7), DataType::Int), Field::Int(7));
+        assert_eq!(
+            cast(Field::Int(7), DataType::Decimal(5, 2)),
+            Field::Decimal(700, 2)
+        );
+        assert_eq!(
+            cast(Field::Decimal(12345, 3), DataType::Decimal(4, 2)),
+            Field::Decimal(1235, 2)
+        );
+        assert_eq!(
+            cast(Field::Decimal(-25, 1), DataType::BigInt),
+            Field::BigInt(-3)
+        );
+        assert_eq!(cast(s(" 42 "), DataType::BigInt), Field::BigInt(42));
+        assert_eq!(
+            cast(s("1.5"), DataType::Decimal(10, 2)),
+            Field::Decimal(150, 2)
+        );
+        assert_eq!(
+            cast(s("2024-01-31"), DataType::Date),
+            crate::datatypes::f_date("2024-01-31")
+        );
+        assert_eq!(
+            cast(crate::datatypes::f_date("2024-01-31"), DataType::String),
+            s("2024-01-31")
+        );
+        assert_eq!(cast(Field::Decimal(150, 2), DataType::String), s("1.50"));
+        assert_eq!(cast(s("yes"), DataType::Bool), Field::Bool(true));
+        assert_eq!(cast(Field::Bool(true), DataType::BigInt), Field::BigInt(1));
+        assert_eq!(cast(Field::Null, DataType::Date), Field::Null);
+
+        // Values that do not fit the type
+        assert!(Field::BigInt(100_000).cast(&DataType::SmallInt).is_err());
+        assert!(Field::BigInt(1000).cast(&DataType::Decimal(4, 2)).is_err());
+        assert!(s("abc").cast(&DataType::BigInt).is_err());
+        assert!(s("abcdef").cast(&DataType::Char(3)).is_err());
+        assert!(Field::Date(0).cast(&DataType::BigInt).is_err());
+
+        // A failed cast fails the evaluation
+        let mut expr = colidx_expr(0);
+        expr.add_cast(&DataType::BigInt);
+        assert!(matches!(
+            expr.eval(&Tuple::new(vec![s("abc")])),
+            Err(CrustyError::ExecutionError(_))
+        ));
+    }
+
+    #[test]
+    fn test_common_type() {
+        use DataType::*;
+        assert_eq!(Int.common_type(&BigInt), Some(BigInt));
+        assert_eq!(SmallInt.common_type(&Int), Some(Int));
+        assert_eq!(BigInt.common_type(&Decimal(5, 2)), Some(Decimal(21, 2)));
+        assert_eq!(
+            Decimal(5, 2).common_type(&Decimal(4, 3)),
+            Some(Decimal(6, 3))
+        );
+        assert_eq!(Char(3).common_type(&String), Some(String));
+        assert_eq!(String.common_type(&Date), Some(Date));
+        assert_eq!(Null.common_type(&Bool), Some(Bool));
+        assert_eq!(Date.common_type(&BigInt), None);
+        assert_eq!(Bool.common_type(&String), None);
+
+        assert!(Int.coerces_to(&Decimal(10, 0)));
+        assert!(!Decimal(10, 0).coerces_to(&BigInt));
+        assert!(String.coerces_to(&Char(3)));
+        assert!(!Date.coerces_to(&String));
+        assert!(Date.can_cast_to(&String));
+        assert!(Decimal(10, 2).can_cast_to(&Int));
+        assert!(Bool.can_cast_to(&BigInt));
+        assert!(!Date.can_cast_to(&BigInt));
+        assert!(!Bool.can_cast_to(&Date));
+    }
+
+    #[test]
+    fn test_like_match() {
+        assert!(like_match("hello", "hello", false));
+        assert!(like_match("hello", "h%", false));
+        assert!(like_match("hello", "%llo", false));
+        assert!(like_match("hello", "%l%l%", false));
+        assert!(like_match("hello", "h_l_o", false));
+        assert!(like_match("", "%", false));
+        assert!(!like_match("hello", "h_l", false));
+        assert!(!like_match("hello", "%x%", false));
+        assert!(!like_match("Hello", "h%", false));
+        assert!(like_match("Hello", "h%", true));
+        assert!(like_match("50%", "50\\%", false));
+        assert!(!like_match("500", "50\\%", false));
+        assert!(like_match("a_b", "a\\_b", false));
+        assert!(!like_match("axb", "a\\_b", false));
+        assert!(like_match("mississippi", "%iss%ppi", false));
+    }
+
+    #[test]
+    fn test_like_prefix() {
+        assert_eq!(like_prefix("abc%"), Some("abc".to_string()));
+        assert_eq!(like_prefix("%"), Some(String::new()));
+        assert_eq!(like_prefix("a\\%b%"), Some("a%b".to_string()));
+        assert_eq!(like_prefix("abc"), None);
+        assert_eq!(like_prefix("a_c%"), None);
+        assert_eq!(like_prefix("a%c%"), None);
+    }
+
+    #[test]
+    fn test_normalize_like_escape() {
+        assert_eq!(normalize_like_escape("a#%b", '#'), "a\\%b");
+        assert_eq!(normalize_like_escape("a\\b##", '#'), "a\\\\b\\#");
+        assert!(like_match(
+            "a\\b#",
+            &normalize_like_escape("a\\b##", '#'),
+            false
+        ));
+        assert_eq!(normalize_like_escape("a\\%", '\\'), "a\\%");
+    }
+
+    #[test]
+    fn test_like_selectivity() {
+        assert_eq!(like_selectivity("%"), 1.0);
+        assert!(like_selectivity("ab%") < like_selectivity("a%"));
+        assert!(like_selectivity("%ab%") > like_selectivity("ab"));
+        assert!(like_selectivity("a%b") > like_selectivity("ab"));
+    }
 }
@@ -9,11 +9,12 @@ use std::collections::{HashMap, HashSet};
 
 use crate::{
     attribute::Attribute, ids::ColumnId, table::TableSchema, traits::plan::Plan, BinaryOp,
-    DataType, Field,
+    DataType, Field, UnaryOp,
 };
 
 use super::{
-    logical_expr::prelude::LogicalRelExpr, physical_expr::physical_rel_expr::PhysicalRelExpr,
+    functions::get_function, logical_expr::prelude::LogicalRelExpr,
+    physical_expr::physical_rel_expr::PhysicalRelExpr,
 };
 
 #[derive(Debug, Clone)]
@@ -29,6 +30,10 @@ pub enum Expression<P: Plan> {
         left: Box<Expression<P>>,
         right: Box<Expression<P>>,
     },
+    Unary {
+        op: UnaryOp,
+        expr: Box<Expression<P>>,
+    },
     Case {
         expr: Box<Expression<P>>,
         whens: Vec<(Expression<P>, Expression<P>)>,
@@ -37,6 +42,25 @@ pub enum Expression<P: Plan> {
     Subquery {
         expr: Box<P>,
     },
+    InList {
+        expr: Box<Expression<P>>,
+        list: Vec<Expression<P>>,
+        negated: bool,
+    },
+    // Call of the scalar function with the given id in the function registry
+    Function {
+        id: usize,
+        args: Vec<Expression<P>>,
+    },
+    // Conversion of the value to a type, explicit (CAST) or inserted by the translator
+    Cast {
+        expr: Box<Expression<P>>,
+        dtype: DataType,
+    },
+    // Parameter of a prepared statement ($1 has index 0), bound before execution
+    Param {
+        idx: usize,
+    },
 }
 
 impl<P: Plan> Expression<P> {
@@ -58,6 +82,37 @@ impl<P: Plan> Expression<P> {
         }
     }
 
+    pub fn unary(op: UnaryOp, expr: Expression<P>) -> Expression<P> {
+        Expression::Unary {
+            op,
+            expr: Box::new(expr),
+        }
+    }
+
+    /// `CASE expr WHEN when THEN then ... ELSE else_expr END`. A searched CASE, whose
+    /// WHEN clauses are conditions, has `true` as `expr`.
+    pub fn case(
+        expr: Expression<P>,
+        whens: Vec<(Expression<P>, Expression<P>)>,
+        else_expr: Expression<P>,
+    ) -> Expression<P> {
+        Expression::Case {
+            expr: Box::new(expr),
+            whens,
+            else_expr: Box::new(else_expr),
+        }
+    }
+
+    /// Whether this is the operand of a searched CASE, whose WHEN clauses are conditions.
+    pub fn is_searched_case_operand(&self) -> bool {
+        matches!(
+            self,
+            Expression::Field {
+                val: Field::Bool(true)
+            }
+        )
+    }
+
     pub fn eq(self, other: Expression<P>) -> Expression<P> {
         Expression::Binary {
             op: BinaryOp::Eq,
@@ -81,16 +136,69 @@ impl<P: Plan> Expression<P> {
         }
     }
 
+    pub fn in_list(expr: Expression<P>, list: Vec<Expression<P>>, negated: bool) -> Expression<P> {
+        Expression::InList {
+            expr: Box::new(expr),
+            list,
+            negated,
+        }
+    }
+
+    pub fn function(id: usize, args: Vec<Expression<P>>) -> Expression<P> {
+        Expression::Function { id, args }
+    }
+
+    pub fn cast(expr: Expression<P>, dtype: DataType) -> Expression<P> {
+        Expression::Cast {
+            expr: Box::new(expr),
+            dtype,
+        }
+    }
+
     pub fn has_subquery(&self) -> bool {
         match self {
             Expression::ColRef { id: _ } => false,
             Expression::Field { val: _ } => false,
             Expression::Binary { left, right, .. } => left.has_subquery() || right.has_subquery(),
+            Expression::Unary { expr, .. } => expr.has_subquery(),
             Expression::Case { .. } => {
                 // Currently, we don't support subqueries in the case expression
                 false
             }
             Expression::Subquery { expr: _ } => true,
+            Expression::InList { expr, list, .. } => {
+                expr.has_subquery() || list.iter().any(|e| e.has_subquery())
+            }
+            Expression::Function { args, .. } => args.iter().any(|e| e.has_subquery()),
+            Expression::Cast { expr, .. } => expr.has_subquery(),
+            Expression::Param { idx: _ } => false,
+        }
+    }
+
+    /// Whether the expression references a parameter of a prepared statement.
+    pub fn has_param(&self) -> bool {
+        match self {
+            Expression::ColRef { .. } | Expression::Field { .. } | Expression::Subquery { .. } => {
+                false
+            }
+            Expression::Binary { left, right, .. } => left.has_param() || right.has_param(),
+            Expression::Unary { expr, .. } | Expression::Cast { expr, .. } => expr.has_param(),
+            Expression::Case {
+                expr,
+                whens,
+                else_expr,
+            } => {
+                expr.has_param()
+                    || else_expr.has_param()
+                    || whens
+                        .iter()
+                        .any(|(when, then)| when.has_param() || then.has_param())
+            }
+            Expression::InList { expr, list, .. } => {
+                expr.has_param() || list.iter().any(|e| e.has_param())
+            }
+            Expression::Function { args, .. } => args.iter().any(|e| e.has_param()),
+            Expression::Param { .. } => true,
         }
     }
 
@@ -143,6 +251,10 @@ impl<P: Plan> Expression<P> {
                 left: Box::new(left.replace_variables(src_to_dest)),
                 right: Box::new(right.replace_variables(src_to_dest)),
             },
+            Expression::Unary { op, expr } => Expression::Unary {
+                op,
+                expr: Box::new(expr.replace_variables(src_to_dest)),
+            },
             Expression::Case {
                 expr,
                 whens,
@@ -163,6 +275,30 @@ impl<P: Plan> Expression<P> {
             Expression::Subquery { expr } => Expression::Subquery {
                 expr: Box::new(expr.replace_variables(src_to_dest)),
             },
+            Expression::InList {
+                expr,
+                list,
+                negated,
+            } => Expression::InList {
+                expr: Box::new(expr.replace_variables(src_to_dest)),
+                list: list
+                    .into_iter()
+                    .map(|e| e.replace_variables(src_to_dest))
+                    .collect(),
+                negated,
+            },
+            Expression::Function { id, args } => Expression::Function {
+                id,
+                args: args
+                    .into_iter()
+                    .map(|e| e.replace_variables(src_to_dest))
+                    .collect(),
+            },
+            Expression::Cast { expr, dtype } => Expression::Cast {
+                expr: Box::new(expr.replace_variables(src_to_dest)),
+                dtype,
+            },
+            Expression::Param { idx } => Expression::Param { idx },
         }
     }
 
@@ -186,6 +322,10 @@ impl<P: Plan> Expression<P> {
                 left: Box::new(left.replace_variables_with_exprs(src_to_dest)),
                 right: Box::new(right.replace_variables_with_exprs(src_to_dest)),
             },
+            Expression::Unary { op, expr } => Expression::Unary {
+                op,
+                expr: Box::new(expr.replace_variables_with_exprs(src_to_dest)),
+            },
             Expression::Case {
                 expr,
                 whens,
@@ -207,6 +347,85 @@ impl<P: Plan> Expression<P> {
                 // Do nothing for subquery
                 expr,
             },
+            Expression::InList {
+                expr,
+                list,
+                negated,
+            } => Expression::InList {
+                expr: Box::new(expr.replace_variables_with_exprs(src_to_dest)),
+                list: list
+                    .into_iter()
+                    .map(|e| e.replace_variables_with_exprs(src_to_dest))
+                    .collect(),
+                negated,
+            },
+            Expression::Function { id, args } => Expression::Function {
+                id,
+                args: args
+                    .into_iter()
+                    .map(|e| e.replace_variables_with_exprs(src_to_dest))
+                    .collect(),
+            },
+            Expression::Cast { expr, dtype } => Expression::Cast {
+                expr: Box::new(expr.replace_variables_with_exprs(src_to_dest)),
+                dtype,
+            },
+            Expression::Param { idx } => Expression::Param { idx },
+        }
+    }
+
+    /// Replace the parameters in the expression with the given values.
+    /// * params: value of each parameter, by index
+    pub fn bind_params(self, params: &[Field]) -> Expression<P> {
+        match self {
+            Expression::ColRef { id } => Expression::ColRef { id },
+            Expression::Field { val } => Expression::Field { val },
+            Expression::Binary { op, left, right } => Expression::Binary {
+                op,
+                left: Box::new(left.bind_params(params)),
+                right: Box::new(right.bind_params(params)),
+            },
+            Expression::Unary { op, expr } => Expression::Unary {
+                op,
+                expr: Box::new(expr.bind_params(params)),
+            },
+            Expression::Case {
+                expr,
+                whens,
+                else_expr,
+            } => Expression::Case {
+                expr: Box::new(expr.bind_params(params)),
+                whens: whens
+                    .into_iter()
+                    .map(|(when, then)| (when.bind_params(params), then.bind_params(params)))
+                    .collect(),
+                else_expr: Box::new(else_expr.bind_params(params)),
+            },
+            Expression::Subquery { expr } => Expression::Subquery {
+                // Subqueries are hoisted out of the plans being bound
+                expr,
+            },
+            Expression::InList {
+                expr,
+                list,
+                negated,
+            } => Expression::InList {
+                expr: Box::new(expr.bind_params(params)),
+                list: list.into_iter().map(|e| e.bind_params(params)).collect(),
+                negated,
+            },
+            Expression::Function { id, args } => Expression::Function {
+                id,
+                args: args.into_iter().map(|e| e.bind_params(params)).collect(),
+            },
+            Expression::Cast { expr, dtype } => Expression::Cast {
+                expr: Box::new(expr.bind_params(params)),
+                dtype,
+            },
+            Expression::Param { idx } => match params.get(idx) {
+                Some(val) => Expression::Field { val: val.clone() },
+                None => Expression::Param { idx },
+            },
         }
     }
 
@@ -233,13 +452,25 @@ impl<P: Plan> Expression<P> {
                 out.push_str(&format!("{}", op));
                 right.print_inner(indent, out);
             }
+            Expression::Unary { op, expr } => {
+                if op.is_prefix() {
+                    out.push_str(&format!("{}", op));
+                    expr.print_inner(indent, out);
+                } else {
+                    expr.print_inner(indent, out);
+                    out.push_str(&format!(" {}", op));
+                }
+            }
             Expression::Case {
                 expr,
                 whens,
                 else_expr,
             } => {
-                out.push_str("case ");
-                expr.print_inner(indent, out);
+                out.push_str("case");
+                if !expr.is_searched_case_operand() {
+                    out.push(' ');
+                    expr.print_inner(indent, out);
+                }
                 for (when, then) in whens {
                     out.push_str(" when ");
                     when.print_inner(indent, out);
@@ -255,6 +486,40 @@ impl<P: Plan> Expression<P> {
                 expr.print_inner(indent + 6, out);
                 out.push_str(&format!("{})", " ".repeat(indent + 4)));
             }
+            Expression::InList {
+                expr,
+                list,
+                negated,
+            } => {
+                expr.print_inner(indent, out);
+                out.push_str(if *negated { " not in (" } else { " in (" });
+                for (i, e) in list.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    e.print_inner(indent, out);
+                }
+                out.push(')');
+            }
+            Expression::Function { id, args } => {
+                out.push_str(get_function(*id).name);
+                out.push('(');
+                for (i, e) in args.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    e.print_inner(indent, out);
+                }
+                out.push(')');
+            }
+            Expression::Cast { expr, dtype } => {
+                out.push_str("cast(");
+                expr.print_inner(indent, out);
+                out.push_str(&format!(" as {})", dtype));
+            }
+            Expression::Param { idx } => {
+                out.push_str(&format!("${}", idx + 1));
+            }
         }
     }
 
@@ -276,10 +541,22 @@ impl<P: Plan> Expression<P> {
                 src_schema.get_attribute(*offset).unwrap().clone()
             }
             Self::Field { val } => Attribute::new(self.pretty_string(), val.into()),
-            Self::Binary { op, left, right: _ } => {
+            Self::Binary { op, left, right } => {
                 let left_attr = left.to_attr(src_schema, col_id_to_offset);
                 match op {
-                    BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div => left_attr,
+                    BinaryOp::Add
+                    | BinaryOp::Sub
+                    | BinaryOp::Mul
+                    | BinaryOp::Div
+                    | BinaryOp::Mod => {
+                        let right_type = right.to_attr(src_schema, col_id_to_offset).dtype;
+                        match op.arithmetic_type(Some(left_attr.dtype.clone()), Some(right_type)) {
+                            Some(dtype) if dtype != left_attr.dtype => {
+                                Attribute::new(self.pretty_string(), dtype)
+                            }
+                            _ => left_attr,
+                        }
+                    }
                     BinaryOp::Eq
                     | BinaryOp::Neq
                     | BinaryOp::Lt
@@ -287,9 +564,48 @@ impl<P: Plan> Expression<P> {
                     | BinaryOp::Gt
                     | BinaryOp::Ge
                     | BinaryOp::And
-                    | BinaryOp::Or => Attribute::new(self.pretty_string(), DataType::Bool),
+                    | BinaryOp::Or
+                    | BinaryOp::Like
+                    | BinaryOp::ILike
+                    | BinaryOp::NotLike
+                    | BinaryOp::NotILike
+                    | BinaryOp::IsDistinctFrom
+                    | BinaryOp::IsNotDistinctFrom => {
+                        Attribute::new(self.pretty_string(), DataType::Bool)
+                    }
                 }
             }
+            Self::Unary { op, expr } => match op {
+                UnaryOp::Neg => expr.to_attr(src_schema, col_id_to_offset),
+                UnaryOp::IsNull | UnaryOp::IsNotNull | UnaryOp::Not => {
+                    Attribute::new(self.pretty_string(), DataType::Bool)
+                }
+            },
+            Self::InList { .. } => Attribute::new(self.pretty_string(), DataType::Bool),
+            Self::Function { id, args } => {
+                let arg_types = args
+                    .iter()
+                    .map(|e| Some(e.to_attr(src_schema, col_id_to_offset).dtype))
+                    .collect::<Vec<_>>();
+                let dtype = get_function(*id)
+                    .return_type(&arg_types)
+                    .unwrap_or(DataType::Null);
+                Attribute::new(self.pretty_string(), dtype)
+            }
+            Self::Cast { dtype, .. } => Attribute::new(self.pretty_string(), dtype.clone()),
+            // The type of the first result that is not NULL
+            Self::Case {
+                whens, else_expr, ..
+            } => {
+                let dtype = whens
+                    .iter()
+                    .map(|(_, then)| then)
+                    .chain([else_expr.as_ref()])
+                    .map(|e| e.to_attr(src_schema, col_id_to_offset).dtype)
+                    .find(|dtype| *dtype != DataType::Null)
+                    .unwrap_or(DataType::Null);
+                Attribute::new(self.pretty_string(), dtype)
+            }
             _ => unimplemented!(),
         }
     }
@@ -324,6 +640,7 @@ impl<P: Plan> Expression<P> {
                 set.extend(right.free());
                 set
             }
+            Expression::Unary { expr, .. } => expr.free(),
             Expression::Case {
                 expr,
                 whens,
@@ -338,6 +655,22 @@ impl<P: Plan> Expression<P> {
                 set
             }
             Expression::Subquery { expr } => expr.free(),
+            Expression::InList { expr, list, .. } => {
+                let mut set = expr.free();
+                for e in list {
+                    set.extend(e.free());
+                }
+                set
+            }
+            Expression::Function { args, .. } => {
+                let mut set = HashSet::new();
+                for e in args {
+                    set.extend(e.free());
+                }
+                set
+            }
+            Expression::Cast { expr, .. } => expr.free(),
+            Expression::Param { idx: _ } => HashSet::new(),
         }
     }
 
@@ -367,6 +700,7 @@ impl Expression<LogicalRelExpr> {
                 left.to_physical_expression(),
                 right.to_physical_expression(),
             ),
+            Self::Unary { op, expr } => Expression::unary(*op, expr.to_physical_expression()),
             Self::Case {
                 expr,
                 whens,
@@ -380,6 +714,23 @@ impl Expression<LogicalRelExpr> {
                 else_expr: Box::new(else_expr.to_physical_expression()),
             },
             Self::Subquery { expr } => Expression::subquery(expr.to_physical_plan()),
+            Self::InList {
+                expr,
+                list,
+                negated,
+            } => Expression::in_list(
+                expr.to_physical_expression(),
+                list.iter().map(|e| e.to_physical_expression()).collect(),
+                *negated,
+            ),
+            Self::Function { id, args } => Expression::function(
+                *id,
+                args.iter().map(|e| e.to_physical_expression()).collect(),
+            ),
+            Self::Cast { expr, dtype } => {
+                Expression::cast(expr.to_physical_expression(), dtype.clone())
+            }
+            Self::Param { idx } => Expression::Param { idx: *idx },
         }
     }
 }
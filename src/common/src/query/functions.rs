@@ -0,0 +1,1144 @@
+use chrono::{Datelike, Duration, Local, Months, NaiveDate};
+
+use crate::datatypes::{base_date, default_decimal_precision, default_decimal_scale, f_decimal};
+use crate::error::c_err;
+use crate::query::bytecode_expr::CallFn;
+use crate::{CrustyError, DataType, Field};
+
+/// Types accepted by a parameter of a scalar function. NULL is accepted by all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamType {
+    String,
+    Int,
+    Numeric,
+    Date,
+    /// A string naming a `DateUnit`
+    DateUnit,
+    Any,
+}
+
+impl ParamType {
+    fn accepts(&self, dtype: &DataType) -> bool {
+        match self {
+            ParamType::String | ParamType::DateUnit => {
+                matches!(dtype, DataType::String | DataType::Char(_))
+            }
+            ParamType::Int => {
+                matches!(dtype, DataType::BigInt | DataType::Int | DataType::SmallInt)
+            }
+            ParamType::Numeric => matches!(
+                dtype,
+                DataType::BigInt | DataType::Int | DataType::SmallInt | DataType::Decimal(_, _)
+            ),
+            ParamType::Date => *dtype == DataType::Date,
+            ParamType::Any => true,
+        }
+    }
+}
+
+/// Unit of a date accepted by DATE_PART, DATE_TRUNC and DATE_ADD, e.g. 'month'.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateUnit {
+    Millennium,
+    Century,
+    Decade,
+    Year,
+    /// ISO 8601 week-numbering year
+    IsoYear,
+    Quarter,
+    Month,
+    /// ISO 8601 week, starting on Monday
+    Week,
+    Day,
+    /// Day of the week, from 0 for Sunday to 6 for Saturday
+    Dow,
+    /// Day of the week, from 1 for Monday to 7 for Sunday
+    IsoDow,
+    /// Day of the year, from 1
+    Doy,
+    /// Seconds since 1970-01-01
+    Epoch,
+}
+
+impl DateUnit {
+    /// Parses a unit name, case-insensitive and singular or plural.
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        let unit = match name.as_str() {
+            "millennium" | "millennia" | "millenniums" => DateUnit::Millennium,
+            "century" | "centuries" => DateUnit::Century,
+            "decade" | "decades" => DateUnit::Decade,
+            "year" | "years" => DateUnit::Year,
+            "isoyear" => DateUnit::IsoYear,
+            "quarter" | "quarters" => DateUnit::Quarter,
+            "month" | "months" => DateUnit::Month,
+            "week" | "weeks" => DateUnit::Week,
+            "day" | "days" => DateUnit::Day,
+            "dow" => DateUnit::Dow,
+            "isodow" => DateUnit::IsoDow,
+            "doy" => DateUnit::Doy,
+            "epoch" => DateUnit::Epoch,
+            _ => return None,
+        };
+        Some(unit)
+    }
+}
+
+/// Type of the result of a scalar function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReturnType {
+    Fixed(DataType),
+    /// Decimal with the default precision and scale
+    Decimal,
+    /// Type of the first argument that is not NULL
+    FirstArg,
+    /// Common type of the arguments, to which they are converted before the call
+    CommonType,
+}
+
+/// A scalar function of the function registry: maps one row of arguments to a value.
+pub struct ScalarFunction {
+    pub name: &'static str,
+    params: &'static [ParamType],
+    /// Number of trailing parameters that can be omitted
+    optional: usize,
+    /// Whether the last parameter can be repeated
+    variadic: bool,
+    /// Whether the result is NULL if any argument is NULL, without calling `eval`
+    strict: bool,
+    ret: ReturnType,
+    eval: fn(&[Field]) -> Result<Field, CrustyError>,
+}
+
+impl ScalarFunction {
+    /// Checks the types of the arguments, where None is a type that is not known
+    /// before execution. Returns the type of the result, if known.
+    pub fn check_args(
+        &self,
+        arg_types: &[Option<DataType>],
+    ) -> Result<Option<DataType>, CrustyError> {
+        let min_args = self.params.len() - self.optional;
+        let arity_ok = if self.variadic {
+            arg_types.len() >= min_args
+        } else {
+            (min_args..=self.params.len()).contains(&arg_types.len())
+        };
+        if !arity_ok {
+            return Err(c_err(
+                format!(
+                    "Wrong number of arguments for {}: {}",
+                    self.name,
+                    arg_types.len()
+                )
+                .as_str(),
+            ));
+        }
+        for (i, dtype) in arg_types.iter().enumerate() {
+            let param = self.params[i.min(self.params.len() - 1)];
+            match dtype {
+                Some(dtype) if *dtype != DataType::Null && !param.accepts(dtype) => {
+                    return Err(c_err(
+                        format!(
+                            "Argument {} of {} has type {:?}, expected {:?}",
+                            i + 1,
+                            self.name,
+                            dtype,
+                            param
+                        )
+                        .as_str(),
+                    ));
+                }
+                _ => {}
+            }
+        }
+        if self.ret == ReturnType::CommonType {
+            let mut known = arg_types.iter().flatten();
+            if let Some(first) = known.next() {
+                known.try_fold(first.clone(), |common, dtype| {
+                    common.common_type(dtype).ok_or_else(|| {
+                        c_err(
+                            format!(
+                                "Arguments of {} have types {} and {} that cannot be matched",
+                                self.name, common, dtype
+                            )
+                            .as_str(),
+                        )
+                    })
+                })?;
+            }
+        }
+        Ok(self.return_type(arg_types))
+    }
+
+    /// Checks the arguments whose values are known before execution, given as
+    /// `Some(value)`, e.g. that the unit of `DATE_TRUNC('month', d)` is valid.
+    pub fn check_literal_args(&self, args: &[Option<&Field>]) -> Result<(), CrustyError> {
+        for (i, arg) in args.iter().enumerate() {
+            let param = self.params[i.min(self.params.len() - 1)];
+            if let (ParamType::DateUnit, Some(Field::String(unit) | Field::Char(_, unit))) =
+                (param, arg)
+            {
+                if DateUnit::parse(unit).is_none() {
+                    return Err(c_err(
+                        format!("Unknown date unit of {}: {}", self.name, unit).as_str(),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Type of the result given the types of the arguments, if known.
+    pub fn return_type(&self, arg_types: &[Option<DataType>]) -> Option<DataType> {
+        match &self.ret {
+            ReturnType::Fixed(dtype) => Some(dtype.clone()),
+            ReturnType::Decimal => Some(DataType::Decimal(
+                default_decimal_precision(),
+                default_decimal_scale(),
+            )),
+            ReturnType::FirstArg => arg_types
+                .iter()
+                .flatten()
+                .find(|dtype| **dtype != DataType::Null)
+                .cloned(),
+            ReturnType::CommonType => {
+                let mut known = arg_types.iter().flatten();
+                let first = known.next()?.clone();
+                known.try_fold(first, |common, dtype| common.common_type(dtype))
+            }
+        }
+    }
+
+    /// Whether the arguments are converted to their common type before the call.
+    pub fn unifies_args(&self) -> bool {
+        self.ret == ReturnType::CommonType
+    }
+
+    /// Type of a parameter of a prepared statement passed as argument `i`, if it can
+    /// be inferred.
+    pub fn param_type(&self, i: usize, arg_types: &[Option<DataType>]) -> Option<DataType> {
+        match self.params[i.min(self.params.len() - 1)] {
+            ParamType::String => Some(DataType::String),
+            ParamType::Int => Some(DataType::BigInt),
+            ParamType::Numeric => None,
+            ParamType::Date => Some(DataType::Date),
+            ParamType::DateUnit => Some(DataType::String),
+            // e.g. COALESCE(a, $1) has the type of `a`
+            ParamType::Any if matches!(self.ret, ReturnType::FirstArg | ReturnType::CommonType) => {
+                self.return_type(arg_types)
+            }
+            ParamType::Any => None,
+        }
+    }
+
+    pub fn call(&self, args: &[Field]) -> Result<Field, CrustyError> {
+        if self.strict && args.contains(&Field::Null) {
+            return Ok(Field::Null);
+        }
+        (self.eval)(args)
+    }
+}
+
+use ParamType::{Any, Date, DateUnit as Unit, Int, Numeric, String as Str};
+
+const FUNCTIONS: [ScalarFunction; 23] = [
+    // STRING FUNCTIONS
+    ScalarFunction {
+        name: "UPPER",
+        params: &[Str],
+        optional: 0,
+        variadic: false,
+        strict: true,
+        ret: ReturnType::Fixed(DataType::String),
+        eval: upper,
+    },
+    ScalarFunction {
+        name: "LOWER",
+        params: &[Str],
+        optional: 0,
+        variadic: false,
+        strict: true,
+        ret: ReturnType::Fixed(DataType::String),
+        eval: lower,
+    },
+    ScalarFunction {
+        name: "LENGTH",
+        params: &[Str],
+        optional: 0,
+        variadic: false,
+        strict: true,
+        ret: ReturnType::Fixed(DataType::BigInt),
+        eval: length,
+    },
+    ScalarFunction {
+        name: "SUBSTRING",
+        params: &[Str, Int, Int],
+        optional: 1,
+        variadic: false,
+        strict: true,
+        ret: ReturnType::Fixed(DataType::String),
+        eval: substring,
+    },
+    ScalarFunction {
+        name: "TRIM",
+        params: &[Str, Str],
+        optional: 1,
+        variadic: false,
+        strict: true,
+        ret: ReturnType::Fixed(DataType::String),
+        eval: trim,
+    },
+    ScalarFunction {
+        name: "LTRIM",
+        params: &[Str, Str],
+        optional: 1,
+        variadic: false,
+        strict: true,
+        ret: ReturnType::Fixed(DataType::String),
+        eval: ltrim,
+    },
+    ScalarFunction {
+        name: "RTRIM",
+        params: &[Str, Str],
+        optional: 1,
+        variadic: false,
+        strict: true,
+        ret: ReturnType::Fixed(DataType::String),
+        eval: rtrim,
+    },
+    // NULL arguments are ignored
+    ScalarFunction {
+        name: "CONCAT",
+        params: &[Any],
+        optional: 0,
+        variadic: true,
+        strict: false,
+        ret: ReturnType::Fixed(DataType::String),
+        eval: concat,
+    },
+    ScalarFunction {
+        name: "REPLACE",
+        params: &[Str, Str, Str],
+        optional: 0,
+        variadic: false,
+        strict: true,
+        ret: ReturnType::Fixed(DataType::String),
+        eval: replace,
+    },
+    // POSITION(substring IN string)
+    ScalarFunction {
+        name: "POSITION",
+        params: &[Str, Str],
+        optional: 0,
+        variadic: false,
+        strict: true,
+        ret: ReturnType::Fixed(DataType::BigInt),
+        eval: position,
+    },
+    // MATH FUNCTIONS
+    ScalarFunction {
+        name: "ABS",
+        params: &[Numeric],
+        optional: 0,
+        variadic: false,
+        strict: true,
+        ret: ReturnType::FirstArg,
+        eval: abs,
+    },
+    ScalarFunction {
+        name: "ROUND",
+        params: &[Numeric, Int],
+        optional: 1,
+        variadic: false,
+        strict: true,
+        ret: ReturnType::FirstArg,
+        eval: round,
+    },
+    ScalarFunction {
+        name: "FLOOR",
+        params: &[Numeric],
+        optional: 0,
+        variadic: false,
+        strict: true,
+        ret: ReturnType::FirstArg,
+        eval: floor,
+    },
+    ScalarFunction {
+        name: "CEIL",
+        params: &[Numeric],
+        optional: 0,
+        variadic: false,
+        strict: true,
+        ret: ReturnType::FirstArg,
+        eval: ceil,
+    },
+    ScalarFunction {
+        name: "POWER",
+        params: &[Numeric, Numeric],
+        optional: 0,
+        variadic: false,
+        strict: true,
+        ret: ReturnType::Decimal,
+        eval: power,
+    },
+    ScalarFunction {
+        name: "SQRT",
+        params: &[Numeric],
+        optional: 0,
+        variadic: false,
+        strict: true,
+        ret: ReturnType::Decimal,
+        eval: sqrt,
+    },
+    // NULL FUNCTIONS
+    ScalarFunction {
+        name: "COALESCE",
+        params: &[Any],
+        optional: 0,
+        variadic: true,
+        strict: false,
+        ret: ReturnType::CommonType,
+        eval: coalesce,
+    },
+    ScalarFunction {
+        name: "NULLIF",
+        params: &[Any, Any],
+        optional: 0,
+        variadic: false,
+        strict: false,
+        ret: ReturnType::CommonType,
+        eval: nullif,
+    },
+    // DATE FUNCTIONS
+    ScalarFunction {
+        name: "CURRENT_DATE",
+        params: &[],
+        optional: 0,
+        variadic: false,
+        strict: true,
+        ret: ReturnType::Fixed(DataType::Date),
+        eval: current_date,
+    },
+    ScalarFunction {
+        name: "DATE_PART",
+        params: &[Unit, Date],
+        optional: 0,
+        variadic: false,
+        strict: true,
+        ret: ReturnType::Fixed(DataType::BigInt),
+        eval: date_part,
+    },
+    ScalarFunction {
+        name: "DATE_TRUNC",
+        params: &[Unit, Date],
+        optional: 0,
+        variadic: false,
+        strict: true,
+        ret: ReturnType::Fixed(DataType::Date),
+        eval: date_trunc,
+    },
+    ScalarFunction {
+        name: "DATE_ADD",
+        params: &[Date, Int, Unit],
+        optional: 1,
+        variadic: false,
+        strict: true,
+        ret: ReturnType::Fixed(DataType::Date),
+        eval: date_add,
+    },
+    ScalarFunction {
+        name: "TO_CHAR",
+        params: &[Date, Str],
+        optional: 0,
+        variadic: false,
+        strict: true,
+        ret: ReturnType::Fixed(DataType::String),
+        eval: to_char,
+    },
+];
+
+/// Id of the function with the given name, case-insensitive.
+pub fn lookup_function(name: &str) -> Option<usize> {
+    FUNCTIONS
+        .iter()
+        .position(|f| f.name.eq_ignore_ascii_case(name))
+}
+
+/// The function with an id returned by `lookup_function`.
+pub fn get_function(id: usize) -> &'static ScalarFunction {
+    &FUNCTIONS[id]
+}
+
+impl CallFn for Field {
+    fn call_fn(id: usize, args: &[Self]) -> Result<Self, CrustyError> {
+        get_function(id).call(args)
+    }
+}
+
+// Argument conversions
+
+fn str_arg(field: &Field) -> Result<&str, CrustyError> {
+    match field {
+        Field::String(s) | Field::Char(_, s) => Ok(s),
+        _ => Err(c_err("Expected string")),
+    }
+}
+
+fn int_arg(field: &Field) -> Result<i64, CrustyError> {
+    match field {
+        Field::BigInt(i) => Ok(*i),
+        Field::Int(i) => Ok(*i as i64),
+        Field::SmallInt(i) => Ok(*i as i64),
+        _ => Err(c_err("Expected int")),
+    }
+}
+
+fn float_arg(field: &Field) -> Result<f64, CrustyError> {
+    match field {
+        Field::Decimal(v, s) => Ok(*v as f64 / 10f64.powi(*s as i32)),
+        field => int_arg(field)
+            .map(|i| i as f64)
+            .map_err(|_| c_err("Expected int or decimal")),
+    }
+}
+
+fn date_arg(field: &Field) -> Result<NaiveDate, CrustyError> {
+    match field {
+        Field::Date(days) => Ok(base_date() + Duration::days(*days)),
+        _ => Err(c_err("Expected date")),
+    }
+}
+
+fn unit_arg(field: &Field) -> Result<DateUnit, CrustyError> {
+    let unit = str_arg(field)?;
+    DateUnit::parse(unit).ok_or_else(|| c_err(format!("Unknown date unit: {}", unit).as_str()))
+}
+
+fn f_date(date: NaiveDate) -> Result<Field, CrustyError> {
+    Ok(Field::Date(
+        date.signed_duration_since(base_date()).num_days(),
+    ))
+}
+
+fn f_string(s: String) -> Result<Field, CrustyError> {
+    Ok(Field::String(s))
+}
+
+// String functions
+
+fn upper(args: &[Field]) -> Result<Field, CrustyError> {
+    f_string(str_arg(&args[0])?.to_uppercase())
+}
+
+fn lower(args: &[Field]) -> Result<Field, CrustyError> {
+    f_string(str_arg(&args[0])?.to_lowercase())
+}
+
+fn length(args: &[Field]) -> Result<Field, CrustyError> {
+    Ok(Field::BigInt(str_arg(&args[0])?.chars().count() as i64))
+}
+
+/// Characters from position `start` (1-based) on, or the `len` characters from it.
+/// Positions before the first character count towards `len`.
+fn substring(args: &[Field]) -> Result<Field, CrustyError> {
+    let s = str_arg(&args[0])?;
+    let start = int_arg(&args[1])?;
+    let end = match args.get(2) {
+        Some(len) => {
+            let len = int_arg(len)?;
+            if len < 0 {
+                return Err(c_err("Negative substring length not allowed"));
+            }
+            start.saturating_add(len)
+        }
+        None => i64::MAX,
+    };
+    let skip = (start.max(1) - 1) as usize;
+    let take = (end.max(1) - start.max(1)).max(0) as usize;
+    f_string(s.chars().skip(skip).take(take).collect())
+}
+
+/// Characters removed by TRIM: spaces, unless given.
+fn trim_chars(args: &[Field]) -> Result<Vec<char>, CrustyError> {
+    match args.get(1) {
+        Some(chars) => Ok(str_arg(chars)?.chars().collect()),
+        None => Ok(vec![' ']),
+    }
+}
+
+fn trim(args: &[Field]) -> Result<Field, CrustyError> {
+    let chars = trim_chars(args)?;
+    f_string(
+        str_arg(&args[0])?
+            .trim_matches(chars.as_slice())
+            .to_string(),
+    )
+}
+
+fn ltrim(args: &[Field]) -> Result<Field, CrustyError> {
+    let chars = trim_chars(args)?;
+    f_string(
+        str_arg(&args[0])?
+            .trim_start_matches(chars.as_slice())
+            .to_string(),
+    )
+}
+
+fn rtrim(args: &[Field]) -> Result<Field, CrustyError> {
+    let chars = trim_chars(args)?;
+    f_string(
+        str_arg(&args[0])?
+            .trim_end_matches(chars.as_slice())
+            .to_string(),
+    )
+}
+
+fn concat(args: &[Field]) -> Result<Field, CrustyError> {
+    f_string(
+        args.iter()
+            .filter(|arg| **arg != Field::Null)
+            .map(|arg| arg.to_string())
+            .collect(),
+    )
+}
+
+fn replace(args: &[Field]) -> Result<Field, CrustyError> {
+    let s = str_arg(&args[0])?;
+    let from = str_arg(&args[1])?;
+    let to = str_arg(&args[2])?;
+    if from.is_empty() {
+        return f_string(s.to_string());
+    }
+    f_string(s.replace(from, to))
+}
+
+/// Position (1-based) of the first occurrence of the substring, 0 if there is none.
+fn position(args: &[Field]) -> Result<Field, CrustyError> {
+    let sub = str_arg(&args[0])?;
+    let s = str_arg(&args[1])?;
+    let pos = match s.find(sub) {
+        Some(byte_idx) => s[..byte_idx].chars().count() as i64 + 1,
+        None => 0,
+    };
+    Ok(Field::BigInt(pos))
+}
+
+// Math functions
+
+#[derive(Clone, Copy)]
+enum Rounding {
+    /// Half away from zero
+    Nearest,
+    Floor,
+    Ceil,
+}
+
+/// `v / d` rounded as given, for a positive `d`.
+fn div_rounded(v: i64, d: i64, rounding: Rounding) -> i64 {
+    match rounding {
+        Rounding::Floor => v.div_euclid(d),
+        Rounding::Ceil => v.div_euclid(d) + (v.rem_euclid(d) != 0) as i64,
+        Rounding::Nearest => {
+            let q = v / d;
+            if 2 * (v % d).abs() >= d {
+                q + v.signum()
+            } else {
+                q
+            }
+        }
+    }
+}
+
+/// Rounds a number to `digits` digits after the decimal point, before it if negative.
+fn round_to(field: &Field, digits: i64, rounding: Rounding) -> Result<Field, CrustyError> {
+    let pow10 = |exp: i64| {
+        u32::try_from(exp)
+            .ok()
+            .and_then(|exp| 10i64.checked_pow(exp))
+            .ok_or_else(|| c_err("Number of digits out of range"))
+    };
+    let scale_up = |v: i64, d: i64| v.checked_mul(d).ok_or_else(|| c_err("Value out of range"));
+    match field {
+        Field::Decimal(v, s) => {
+            if digits >= *s as i64 {
+                return Ok(field.clone());
+            }
+            let rounded = div_rounded(*v, pow10(*s as i64 - digits)?, rounding);
+            if digits >= 0 {
+                Ok(Field::Decimal(rounded, digits as u32))
+            } else {
+                Ok(Field::Decimal(scale_up(rounded, pow10(-digits)?)?, 0))
+            }
+        }
+        field => {
+            let v = int_arg(field).map_err(|_| c_err("Expected int or decimal"))?;
+            if digits >= 0 {
+                return Ok(field.clone());
+            }
+            let d = pow10(-digits)?;
+            Ok(Field::BigInt(scale_up(div_rounded(v, d, rounding), d)?))
+        }
+    }
+}
+
+fn abs(args: &[Field]) -> Result<Field, CrustyError> {
+    let res = match &args[0] {
+        Field::BigInt(i) => i.checked_abs().map(Field::BigInt),
+        Field::Int(i) => i.checked_abs().map(Field::Int),
+        Field::SmallInt(i) => i.checked_abs().map(Field::SmallInt),
+        Field::Decimal(v, s) => v.checked_abs().map(|v| Field::Decimal(v, *s)),
+        _ => return Err(c_err("Expected int or decimal")),
+    };
+    res.ok_or_else(|| c_err("Value out of range"))
+}
+
+fn round(args: &[Field]) -> Result<Field, CrustyError> {
+    let digits = match args.get(1) {
+        Some(digits) => int_arg(digits)?,
+        None => 0,
+    };
+    round_to(&args[0], digits, Rounding::Nearest)
+}
+
+fn floor(args: &[Field]) -> Result<Field, CrustyError> {
+    round_to(&args[0], 0, Rounding::Floor)
+}
+
+fn ceil(args: &[Field]) -> Result<Field, CrustyError> {
+    round_to(&args[0], 0, Rounding::Ceil)
+}
+
+fn power(args: &[Field]) -> Result<Field, CrustyError> {
+    let res = float_arg(&args[0])?.powf(float_arg(&args[1])?);
+    // The result is stored as an i64 scaled by the default scale
+    let scaled = res * 10f64.powi(default_decimal_scale() as i32);
+    if !scaled.is_finite() || scaled.abs() >= i64::MAX as f64 {
+        return Err(c_err("Result of POWER out of range"));
+    }
+    Ok(f_decimal(res))
+}
+
+fn sqrt(args: &[Field]) -> Result<Field, CrustyError> {
+    let v = float_arg(&args[0])?;
+    if v < 0.0 {
+        return Err(c_err("Cannot take square root of a negative number"));
+    }
+    Ok(f_decimal(v.sqrt()))
+}
+
+// NULL functions
+
+fn coalesce(args: &[Field]) -> Result<Field, CrustyError> {
+    Ok(args
+        .iter()
+        .find(|arg| **arg != Field::Null)
+        .cloned()
+        .unwrap_or(Field::Null))
+}
+
+fn nullif(args: &[Field]) -> Result<Field, CrustyError> {
+    if args[0] == args[1] {
+        Ok(Field::Null)
+    } else {
+        Ok(args[0].clone())
+    }
+}
+
+// Date functions
+
+fn current_date(_args: &[Field]) -> Result<Field, CrustyError> {
+    f_date(Local::now().date_naive())
+}
+
+fn date_part(args: &[Field]) -> Result<Field, CrustyError> {
+    let date = date_arg(&args[1])?;
+    let year = date.year() as i64;
+    let part = match unit_arg(&args[0])? {
+        // The first millennium, century and decade start with year 1, 1 and 0
+        DateUnit::Millennium => (year - 1).div_euclid(1000) + 1,
+        DateUnit::Century => (year - 1).div_euclid(100) + 1,
+        DateUnit::Decade => year.div_euclid(10),
+        DateUnit::Year => year,
+        DateUnit::IsoYear => date.iso_week().year() as i64,
+        DateUnit::Quarter => date.month0() as i64 / 3 + 1,
+        DateUnit::Month => date.month() as i64,
+        DateUnit::Week => date.iso_week().week() as i64,
+        DateUnit::Day => date.day() as i64,
+        DateUnit::Dow => date.weekday().num_days_from_sunday() as i64,
+        DateUnit::IsoDow => date.weekday().number_from_monday() as i64,
+        DateUnit::Doy => date.ordinal() as i64,
+        DateUnit::Epoch => date.signed_duration_since(base_date()).num_seconds(),
+    };
+    Ok(Field::BigInt(part))
+}
+
+/// The first day of the unit containing the date, e.g. the first of the month.
+fn date_trunc(args: &[Field]) -> Result<Field, CrustyError> {
+    let date = date_arg(&args[1])?;
+    let first_of_year =
+        |year: i32| NaiveDate::from_ymd_opt(year, 1, 1).ok_or_else(|| c_err("Date out of range"));
+    let year = date.year();
+    let truncated = match unit_arg(&args[0])? {
+        DateUnit::Millennium => first_of_year((year - 1).div_euclid(1000) * 1000 + 1)?,
+        DateUnit::Century => first_of_year((year - 1).div_euclid(100) * 100 + 1)?,
+        DateUnit::Decade => first_of_year(year.div_euclid(10) * 10)?,
+        DateUnit::Year => first_of_year(year)?,
+        DateUnit::Quarter => date
+            .with_day(1)
+            .unwrap()
+            .with_month0(date.month0() / 3 * 3)
+            .unwrap(),
+        DateUnit::Month => date.with_day(1).unwrap(),
+        DateUnit::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+        DateUnit::Day => date,
+        unit => {
+            return Err(c_err(
+                format!("Unsupported unit of DATE_TRUNC: {:?}", unit).as_str(),
+            ))
+        }
+    };
+    f_date(truncated)
+}
+
+/// Adds a number of units, days unless given, to a date. Adding months keeps the day
+/// of the month unless the resulting month is shorter, e.g. 2024-01-31 plus one month
+/// is 2024-02-29.
+fn date_add(args: &[Field]) -> Result<Field, CrustyError> {
+    let date = date_arg(&args[0])?;
+    let n = int_arg(&args[1])?;
+    let unit = match args.get(2) {
+        Some(unit) => unit_arg(unit)?,
+        None => DateUnit::Day,
+    };
+    let months = match unit {
+        DateUnit::Day => return f_date(date + Duration::days(n)),
+        DateUnit::Week => return f_date(date + Duration::weeks(n)),
+        DateUnit::Month => n,
+        DateUnit::Quarter => n * 3,
+        DateUnit::Year => n * 12,
+        DateUnit::Decade => n * 120,
+        DateUnit::Century => n * 1200,
+        DateUnit::Millennium => n * 12000,
+        unit => {
+            return Err(c_err(
+                format!("Unsupported unit of DATE_ADD: {:?}", unit).as_str(),
+            ))
+        }
+    };
+    let shifted = match u32::try_from(months.unsigned_abs()) {
+        Ok(abs) if months >= 0 => date.checked_add_months(Months::new(abs)),
+        Ok(abs) => date.checked_sub_months(Months::new(abs)),
+        Err(_) => None,
+    };
+    f_date(shifted.ok_or_else(|| c_err("Date out of range"))?)
+}
+
+/// Patterns of TO_CHAR and their chrono format, longest first. The case of a name
+/// pattern gives the case of the name, e.g. `Month` is `January`.
+const TO_CHAR_PATTERNS: [(&str, &str); 18] = [
+    ("MONTH", "%B"),
+    ("Month", "%B"),
+    ("month", "%B"),
+    ("YYYY", "%Y"),
+    ("DDD", "%j"),
+    ("DAY", "%A"),
+    ("Day", "%A"),
+    ("day", "%A"),
+    ("MON", "%b"),
+    ("Mon", "%b"),
+    ("mon", "%b"),
+    ("YY", "%y"),
+    ("MM", "%m"),
+    ("DD", "%d"),
+    ("DY", "%a"),
+    ("Dy", "%a"),
+    ("dy", "%a"),
+    ("IW", "%V"),
+];
+
+/// Formats a date with a Postgres-style pattern: YYYY, YY, Q, MM, Month, Mon, DD, DDD,
+/// D (day of the week from 1 for Sunday), Day, Dy and IW. Text in double quotes and
+/// any other characters are copied as they are.
+fn to_char(args: &[Field]) -> Result<Field, CrustyError> {
+    let date = date_arg(&args[0])?;
+    let mut pattern = str_arg(&args[1])?;
+    let mut res = String::new();
+    while let Some(c) = pattern.chars().next() {
+        if let Some((token, format)) = TO_CHAR_PATTERNS
+            .iter()
+            .find(|(token, _)| pattern.starts_with(token))
+        {
+            let formatted = date.format(format).to_string();
+            match *token {
+                t if t.chars().all(|c| c.is_ascii_uppercase()) => {
+                    res.push_str(&formatted.to_uppercase())
+                }
+                t if t.chars().all(|c| c.is_ascii_lowercase()) => {
+                    res.push_str(&formatted.to_lowercase())
+                }
+                _ => res.push_str(&formatted),
+            }
+            pattern = &pattern[token.len()..];
+            continue;
+        }
+        match c {
+            'Q' => res.push_str(&(date.month0() / 3 + 1).to_string()),
+            'D' => res.push_str(&(date.weekday().num_days_from_sunday() + 1).to_string()),
+            '"' => {
+                let text = &pattern[1..];
+                let end = text.find('"').unwrap_or(text.len());
+                res.push_str(&text[..end]);
+                pattern = &text[(end + 1).min(text.len())..];
+                continue;
+            }
+            c => res.push(c),
+        }
+        pattern = &pattern[c.len_utf8()..];
+    }
+    f_string(res)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn call(name: &str, args: Vec<Field>) -> Result<Field, CrustyError> {
+        get_function(lookup_function(name).unwrap()).call(&args)
+    }
+
+    fn s(s: &str) -> Field {
+        Field::String(s.to_string())
+    }
+
+    #[test]
+    fn test_lookup_function() {
+        assert_eq!(lookup_function("upper"), lookup_function("UPPER"));
+        assert!(lookup_function("upper").is_some());
+        assert!(lookup_function("nope").is_none());
+    }
+
+    #[test]
+    fn test_check_args() {
+        let substring = get_function(lookup_function("SUBSTRING").unwrap());
+        let args = [Some(DataType::String), Some(DataType::BigInt)];
+        assert_eq!(substring.check_args(&args).unwrap(), Some(DataType::String));
+        assert!(substring.check_args(&args[..1]).is_err());
+        let args = [Some(DataType::BigInt), Some(DataType::BigInt)];
+        assert!(substring.check_args(&args).is_err());
+        // Unknown and NULL arguments are accepted
+        assert!(substring.check_args(&[None, Some(DataType::Null)]).is_ok());
+
+        let coalesce = get_function(lookup_function("COALESCE").unwrap());
+        let args = [Some(DataType::Null), None, Some(DataType::BigInt)];
+        assert_eq!(coalesce.check_args(&args).unwrap(), Some(DataType::BigInt));
+        assert!(coalesce.check_args(&[]).is_err());
+        let args = [Some(DataType::BigInt), Some(DataType::Decimal(10, 2))];
+        assert!(matches!(
+            coalesce.check_args(&args).unwrap(),
+            Some(DataType::Decimal(_, 2))
+        ));
+        let args = [Some(DataType::BigInt), Some(DataType::String)];
+        assert!(coalesce.check_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_string_functions() {
+        assert_eq!(call("UPPER", vec![s("aBc")]).unwrap(), s("ABC"));
+        assert_eq!(call("LOWER", vec![s("aBc")]).unwrap(), s("abc"));
+        assert_eq!(call("LENGTH", vec![s("héllo")]).unwrap(), Field::BigInt(5));
+        assert_eq!(call("UPPER", vec![Field::Null]).unwrap(), Field::Null);
+
+        let substring = |args: Vec<i64>| {
+            let mut args = args.into_iter().map(Field::BigInt).collect::<Vec<_>>();
+            args.insert(0, s("hello"));
+            call("SUBSTRING", args).unwrap()
+        };
+        assert_eq!(substring(vec![2]), s("ello"));
+        assert_eq!(substring(vec![2, 3]), s("ell"));
+        assert_eq!(substring(vec![0, 3]), s("he"));
+        assert_eq!(substring(vec![4, 10]), s("lo"));
+        assert_eq!(substring(vec![9]), s(""));
+        assert!(call(
+            "SUBSTRING",
+            vec![s("hello"), Field::BigInt(1), Field::BigInt(-1)]
+        )
+        .is_err());
+
+        assert_eq!(call("TRIM", vec![s("  a b  ")]).unwrap(), s("a b"));
+        assert_eq!(call("LTRIM", vec![s("xxaxx"), s("x")]).unwrap(), s("axx"));
+        assert_eq!(call("RTRIM", vec![s("xxaxx"), s("x")]).unwrap(), s("xxa"));
+        assert_eq!(
+            call("CONCAT", vec![s("a"), Field::Null, Field::BigInt(1)]).unwrap(),
+            s("a1")
+        );
+        assert_eq!(
+            call("REPLACE", vec![s("banana"), s("an"), s("AN")]).unwrap(),
+            s("bANANa")
+        );
+        assert_eq!(
+            call("POSITION", vec![s("é"), s("héllo")]).unwrap(),
+            Field::BigInt(2)
+        );
+        assert_eq!(
+            call("POSITION", vec![s("x"), s("hello")]).unwrap(),
+            Field::BigInt(0)
+        );
+    }
+
+    #[test]
+    fn test_math_functions() {
+        assert_eq!(
+            call("ABS", vec![Field::BigInt(-3)]).unwrap(),
+            Field::BigInt(3)
+        );
+        assert_eq!(
+            call("ABS", vec![Field::Decimal(-25, 1)]).unwrap(),
+            Field::Decimal(25, 1)
+        );
+        assert_eq!(
+            call("ROUND", vec![Field::Decimal(25, 1)]).unwrap(),
+            Field::Decimal(3, 0)
+        );
+        assert_eq!(
+            call("ROUND", vec![Field::Decimal(-25, 1)]).unwrap(),
+            Field::Decimal(-3, 0)
+        );
+        assert_eq!(
+            call("ROUND", vec![Field::Decimal(12345, 3), Field::BigInt(1)]).unwrap(),
+            Field::Decimal(123, 1)
+        );
+        assert_eq!(
+            call("ROUND", vec![Field::BigInt(1250), Field::BigInt(-2)]).unwrap(),
+            Field::BigInt(1300)
+        );
+        assert_eq!(
+            call("FLOOR", vec![Field::Decimal(-25, 1)]).unwrap(),
+            Field::Decimal(-3, 0)
+        );
+        assert_eq!(
+            call("CEIL", vec![Field::Decimal(-25, 1)]).unwrap(),
+            Field::Decimal(-2, 0)
+        );
+        assert_eq!(
+            call("CEIL", vec![Field::BigInt(7)]).unwrap(),
+            Field::BigInt(7)
+        );
+        assert_eq!(
+            call("POWER", vec![Field::BigInt(2), Field::BigInt(3)]).unwrap(),
+            f_decimal(8.0)
+        );
+        assert_eq!(
+            call("SQRT", vec![Field::Decimal(225, 2)]).unwrap(),
+            f_decimal(1.5)
+        );
+        assert!(call("SQRT", vec![Field::BigInt(-1)]).is_err());
+        assert!(call("POWER", vec![Field::BigInt(10), Field::BigInt(30)]).is_err());
+        // Results out of the range of the type are errors
+        assert!(call("ABS", vec![Field::BigInt(i64::MIN)]).is_err());
+        assert!(call("ABS", vec![Field::Int(i32::MIN)]).is_err());
+        assert!(call("ABS", vec![Field::Decimal(i64::MIN, 2)]).is_err());
+        assert_eq!(
+            call("ABS", vec![Field::BigInt(i64::MIN + 1)]).unwrap(),
+            Field::BigInt(i64::MAX)
+        );
+        assert!(call("ROUND", vec![Field::BigInt(i64::MAX), Field::BigInt(-1)]).is_err());
+        assert!(call("ROUND", vec![Field::BigInt(i64::MIN), Field::BigInt(-1)]).is_err());
+        assert!(call(
+            "ROUND",
+            vec![Field::Decimal(i64::MAX, 0), Field::BigInt(-1)]
+        )
+        .is_err());
+        assert_eq!(
+            call("ROUND", vec![Field::BigInt(i64::MAX), Field::BigInt(-2)]).unwrap(),
+            Field::BigInt(9223372036854775800)
+        );
+        assert_eq!(
+            call("FLOOR", vec![Field::Decimal(i64::MIN, 2)]).unwrap(),
+            Field::Decimal(i64::MIN.div_euclid(100), 0)
+        );
+        assert_eq!(
+            call("CEIL", vec![Field::Decimal(i64::MIN, 2)]).unwrap(),
+            Field::Decimal(i64::MIN / 100, 0)
+        );
+        assert_eq!(
+            call("CEIL", vec![Field::Decimal(i64::MAX, 2)]).unwrap(),
+            Field::Decimal(i64::MAX / 100 + 1, 0)
+        );
+    }
+
+    #[test]
+    fn test_null_functions() {
+        assert_eq!(
+            call(
+                "COALESCE",
+                vec![Field::Null, Field::BigInt(1), Field::BigInt(2)]
+            )
+            .unwrap(),
+            Field::BigInt(1)
+        );
+        assert_eq!(call("COALESCE", vec![Field::Null]).unwrap(), Field::Null);
+        assert_eq!(
+            call("NULLIF", vec![Field::BigInt(1), Field::BigInt(1)]).unwrap(),
+            Field::Null
+        );
+        assert_eq!(
+            call("NULLIF", vec![Field::BigInt(1), Field::Null]).unwrap(),
+            Field::BigInt(1)
+        );
+    }
+
+    #[test]
+    fn test_date_functions() {
+        use crate::datatypes::f_date as date;
+
+        let d = date("2024-05-15"); // a Wednesday
+        let part = |unit: &str| call("DATE_PART", vec![s(unit), d.clone()]).unwrap();
+        assert_eq!(part("year"), Field::BigInt(2024));
+        assert_eq!(part("QUARTER"), Field::BigInt(2));
+        assert_eq!(part("month"), Field::BigInt(5));
+        assert_eq!(part("day"), Field::BigInt(15));
+        assert_eq!(part("week"), Field::BigInt(20));
+        assert_eq!(part("dow"), Field::BigInt(3));
+        assert_eq!(part("isodow"), Field::BigInt(3));
+        assert_eq!(part("doy"), Field::BigInt(136));
+        assert_eq!(part("century"), Field::BigInt(21));
+        assert_eq!(part("epoch"), Field::BigInt(1_715_731_200));
+        assert!(call("DATE_PART", vec![s("hour"), d.clone()]).is_err());
+
+        let trunc = |unit: &str| call("DATE_TRUNC", vec![s(unit), d.clone()]).unwrap();
+        assert_eq!(trunc("year"), date("2024-01-01"));
+        assert_eq!(trunc("quarter"), date("2024-04-01"));
+        assert_eq!(trunc("month"), date("2024-05-01"));
+        assert_eq!(trunc("week"), date("2024-05-13"));
+        assert_eq!(trunc("day"), d);
+        assert!(call("DATE_TRUNC", vec![s("dow"), d.clone()]).is_err());
+
+        let add = |from: &str, n: i64, unit: &str| {
+            call("DATE_ADD", vec![date(from), Field::BigInt(n), s(unit)]).unwrap()
+        };
+        assert_eq!(add("2024-05-15", 20, "days"), date("2024-06-04"));
+        assert_eq!(add("2024-05-15", -2, "weeks"), date("2024-05-01"));
+        assert_eq!(add("2024-01-31", 1, "month"), date("2024-02-29"));
+        assert_eq!(add("2024-02-29", -1, "year"), date("2023-02-28"));
+        assert_eq!(
+            call("DATE_ADD", vec![d.clone(), Field::BigInt(1)]).unwrap(),
+            date("2024-05-16")
+        );
+
+        let to_char = |pattern: &str| call("TO_CHAR", vec![d.clone(), s(pattern)]).unwrap();
+        assert_eq!(to_char("YYYY-MM-DD"), s("2024-05-15"));
+        assert_eq!(to_char("Dy, DD Mon YY"), s("Wed, 15 May 24"));
+        assert_eq!(to_char("DAY month"), s("WEDNESDAY may"));
+        assert_eq!(to_char("YYYY \"Q\"Q DDD D"), s("2024 Q2 136 4"));
+
+        let check = get_function(lookup_function("DATE_TRUNC").unwrap());
+        assert!(check.check_literal_args(&[Some(&s("Month")), None]).is_ok());
+        assert!(check
+            .check_literal_args(&[Some(&s("fortnight")), None])
+            .is_err());
+        assert_eq!(
+            call("DATE_PART", vec![s("year"), Field::Null]).unwrap(),
+            Field::Null
+        );
+    }
+}
@@ -5,6 +5,13 @@ pub enum JoinType {
     RightOuter,
     FullOuter,
     CrossJoin,
+    /// Left rows with at least one match on the right (IN subquery).
+    /// Only the left columns are kept.
+    LeftSemi,
+    /// Left rows with no match on the right, with the NULL semantics of NOT IN:
+    /// a NULL on either side makes the comparison unknown, so the row is dropped
+    /// unless the right side is empty. Only the left columns are kept.
+    LeftAnti,
 }
 
 impl std::fmt::Display for JoinType {
@@ -15,6 +22,8 @@ impl std::fmt::Display for JoinType {
             JoinType::RightOuter => write!(f, "right_outer"),
             JoinType::FullOuter => write!(f, "full_outer"),
             JoinType::CrossJoin => write!(f, "cross"),
+            JoinType::LeftSemi => write!(f, "left_semi"),
+            JoinType::LeftAnti => write!(f, "left_anti"),
         }
     }
 }
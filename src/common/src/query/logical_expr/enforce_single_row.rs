@@ -0,0 +1,30 @@
+use super::prelude::*;
+
+impl LogicalRelExpr {
+    /// Make the current logical relational expression return exactly one row,
+    /// as required for the result of a scalar subquery.
+    pub fn enforce_single_row(self) -> LogicalRelExpr {
+        if self.returns_single_row() {
+            return self;
+        }
+        LogicalRelExpr::EnforceSingleRow {
+            src: Box::new(self),
+        }
+    }
+
+    /// Returns true if the expression is known to return exactly one row, e.g. an
+    /// aggregation without GROUP BY. Keeping such plans as they are lets correlated
+    /// aggregates be decorrelated.
+    fn returns_single_row(&self) -> bool {
+        match self {
+            LogicalRelExpr::Aggregate { group_by, .. } => group_by.is_empty(),
+            LogicalRelExpr::SingleRow => true,
+            LogicalRelExpr::Project { src, .. }
+            | LogicalRelExpr::Map { input: src, .. }
+            | LogicalRelExpr::Rename { src, .. }
+            | LogicalRelExpr::Distinct { src }
+            | LogicalRelExpr::EnforceSingleRow { src } => src.returns_single_row(),
+            _ => false,
+        }
+    }
+}
@@ -75,7 +75,7 @@ impl LogicalRelExpr {
                 let counts: Vec<usize> = aggrs
                     .iter()
                     .filter_map(|(id, (_src_id, op))| {
-                        if let AggOp::Count = op {
+                        if let AggOp::Count | AggOp::CountDistinct = op {
                             Some(*id)
                         } else {
                             None
@@ -65,8 +65,9 @@ impl LogicalRelExpr {
                 if att.len() != 1 {
                     panic!("Subquery has more than one column");
                 }
-                // Give the column the name that's expected
-                let rhs: LogicalRelExpr = expr.map(
+                // Give the column the name that's expected. A scalar subquery must
+                // produce a single row (NULL if empty, an error if more than one).
+                let rhs: LogicalRelExpr = expr.enforce_single_row().map(
                     true,
                     enabled_rules,
                     col_id_gen,
@@ -102,11 +103,75 @@ impl LogicalRelExpr {
                         false, // TODO: Check if `is_wildcard` should be set to False
                     )
             }
-            Expression::Field { .. } | Expression::ColRef { .. } => {
-                self.map(true, enabled_rules, col_id_gen, vec![(id, expr)])
+            Expression::Cast { expr, dtype } => {
+                // Hoist the operand, then convert it
+                let operand_id = col_id_gen.next();
+                let att = self.att();
+                self.hoist(enabled_rules, col_id_gen, operand_id, *expr)
+                    .map(
+                        true,
+                        enabled_rules,
+                        col_id_gen,
+                        [(id, Expression::cast(Expression::col_ref(operand_id), dtype))],
+                    )
+                    .project(
+                        true,
+                        enabled_rules,
+                        col_id_gen,
+                        att.into_iter().chain([id]).collect(),
+                        false,
+                    )
+            }
+            Expression::Unary { op, expr } => {
+                // Hoist the operand, then perform the unary operation
+                let operand_id = col_id_gen.next();
+                let att = self.att();
+                self.hoist(enabled_rules, col_id_gen, operand_id, *expr)
+                    .map(
+                        true,
+                        enabled_rules,
+                        col_id_gen,
+                        [(id, Expression::unary(op, Expression::col_ref(operand_id)))],
+                    )
+                    .project(
+                        true,
+                        enabled_rules,
+                        col_id_gen,
+                        att.into_iter().chain([id]).collect(),
+                        false,
+                    )
+            }
+            Expression::Function { id: fn_id, args } => {
+                // Hoist each argument, then call the function
+                let att = self.att();
+                let mut arg_ids = Vec::with_capacity(args.len());
+                let mut plan = self;
+                for arg in args {
+                    let arg_id = col_id_gen.next();
+                    plan = plan.hoist(enabled_rules, col_id_gen, arg_id, arg);
+                    arg_ids.push(Expression::col_ref(arg_id));
+                }
+                plan.map(
+                    true,
+                    enabled_rules,
+                    col_id_gen,
+                    [(id, Expression::function(fn_id, arg_ids))],
+                )
+                .project(
+                    true,
+                    enabled_rules,
+                    col_id_gen,
+                    att.into_iter().chain([id]).collect(),
+                    false,
+                )
             }
-            Expression::Case { .. } => {
-                panic!("Case expression is not supported in hoist")
+            // CASE expressions do not contain subqueries
+            Expression::Field { .. }
+            | Expression::ColRef { .. }
+            | Expression::InList { .. }
+            | Expression::Case { .. }
+            | Expression::Param { .. } => {
+                self.map(true, enabled_rules, col_id_gen, vec![(id, expr)])
             }
         }
     }
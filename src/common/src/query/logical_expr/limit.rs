@@ -0,0 +1,13 @@
+use super::prelude::*;
+
+impl LogicalRelExpr {
+    /// Skip the first `offset` rows of the current logical relational expression
+    /// and return at most `limit` rows after that. No limit if `limit` is None.
+    pub fn limit(self, limit: Option<usize>, offset: usize) -> LogicalRelExpr {
+        LogicalRelExpr::Limit {
+            src: Box::new(self),
+            limit,
+            offset,
+        }
+    }
+}
@@ -5,7 +5,7 @@ use std::collections::{HashMap, HashSet};
 use crate::{
     ids::{ColumnId, ContainerId},
     physical_expr::physical_rel_expr::PhysicalRelExpr,
-    query::{expr::Expression, join_type::JoinType},
+    query::{expr::Expression, join_type::JoinType, set_op_type::SetOpType},
     traits::plan::Plan,
     AggOp,
 };
@@ -33,11 +33,49 @@ pub enum LogicalRelExpr {
         src: Box<LogicalRelExpr>,
         cols: Vec<ColumnId>,
     },
-    // TODO: The current translator does not support ORDER BY
     OrderBy {
         src: Box<LogicalRelExpr>,
         cols: Vec<(ColumnId, bool, bool)>, // (column_id, asc, nulls_first)
     },
+    Limit {
+        // Skips the first `offset` rows and returns at most `limit` rows after that
+        src: Box<LogicalRelExpr>,
+        limit: Option<usize>,
+        offset: usize,
+    },
+    Distinct {
+        // Removes duplicate rows from the result
+        src: Box<LogicalRelExpr>,
+    },
+    EnforceSingleRow {
+        // Result of a scalar subquery: exactly one row. A row of NULLs if the
+        // source is empty, and an error at execution if it has more than one row
+        src: Box<LogicalRelExpr>,
+    },
+    SetOp {
+        // Combines the rows of both sides. The columns are matched by position
+        // and the result keeps the column ids of the left side.
+        set_op: SetOpType,
+        left: Box<LogicalRelExpr>,
+        right: Box<LogicalRelExpr>,
+    },
+    RecursiveUnion {
+        // Recursive CTE: returns the rows of the base, then repeatedly evaluates
+        // the recursive side over the rows produced by the previous iteration
+        // (read through the work table) until it produces no new rows.
+        // The result keeps the column ids of the base.
+        set_op: SetOpType, // Union or UnionAll
+        base: Box<LogicalRelExpr>,
+        recursive: Box<LogicalRelExpr>,
+        work_table: usize,
+    },
+    WorkTable {
+        // Rows produced by the previous iteration of the enclosing RecursiveUnion
+        id: usize,
+        cols: Vec<ColumnId>,
+    },
+    // A single row without columns, the source of a SELECT without FROM
+    SingleRow,
     Aggregate {
         src: Box<LogicalRelExpr>,
         group_by: Vec<ColumnId>,
@@ -118,6 +156,45 @@ impl Plan for LogicalRelExpr {
                     })
                     .collect(),
             },
+            LogicalRelExpr::Limit { src, limit, offset } => LogicalRelExpr::Limit {
+                src: Box::new(src.replace_variables(src_to_dest)),
+                limit,
+                offset,
+            },
+            LogicalRelExpr::Distinct { src } => LogicalRelExpr::Distinct {
+                src: Box::new(src.replace_variables(src_to_dest)),
+            },
+            LogicalRelExpr::EnforceSingleRow { src } => LogicalRelExpr::EnforceSingleRow {
+                src: Box::new(src.replace_variables(src_to_dest)),
+            },
+            LogicalRelExpr::SetOp {
+                set_op,
+                left,
+                right,
+            } => LogicalRelExpr::SetOp {
+                set_op,
+                left: Box::new(left.replace_variables(src_to_dest)),
+                right: Box::new(right.replace_variables(src_to_dest)),
+            },
+            LogicalRelExpr::RecursiveUnion {
+                set_op,
+                base,
+                recursive,
+                work_table,
+            } => LogicalRelExpr::RecursiveUnion {
+                set_op,
+                base: Box::new(base.replace_variables(src_to_dest)),
+                recursive: Box::new(recursive.replace_variables(src_to_dest)),
+                work_table,
+            },
+            LogicalRelExpr::WorkTable { id, cols } => LogicalRelExpr::WorkTable {
+                id,
+                cols: cols
+                    .into_iter()
+                    .map(|col| *src_to_dest.get(&col).unwrap_or(&col))
+                    .collect(),
+            },
+            LogicalRelExpr::SingleRow => LogicalRelExpr::SingleRow,
             LogicalRelExpr::Aggregate {
                 src,
                 group_by,
@@ -232,6 +309,60 @@ impl Plan for LogicalRelExpr {
                 out.push_str(&format!("{}-> order_by({:?})\n", " ".repeat(indent), cols));
                 src.print_inner(indent + 2, out);
             }
+            LogicalRelExpr::Limit { src, limit, offset } => {
+                out.push_str(&format!(
+                    "{}-> limit({:?}, offset: {})\n",
+                    " ".repeat(indent),
+                    limit,
+                    offset
+                ));
+                src.print_inner(indent + 2, out);
+            }
+            LogicalRelExpr::Distinct { src } => {
+                out.push_str(&format!("{}-> distinct\n", " ".repeat(indent)));
+                src.print_inner(indent + 2, out);
+            }
+            LogicalRelExpr::EnforceSingleRow { src } => {
+                out.push_str(&format!("{}-> enforce_single_row\n", " ".repeat(indent)));
+                src.print_inner(indent + 2, out);
+            }
+            LogicalRelExpr::SetOp {
+                set_op,
+                left,
+                right,
+            } => {
+                out.push_str(&format!("{}-> {}\n", " ".repeat(indent), set_op));
+                left.print_inner(indent + 2, out);
+                right.print_inner(indent + 2, out);
+            }
+            LogicalRelExpr::RecursiveUnion {
+                set_op,
+                base,
+                recursive,
+                work_table,
+            } => {
+                out.push_str(&format!(
+                    "{}-> recursive_{}(#{})\n",
+                    " ".repeat(indent),
+                    set_op,
+                    work_table
+                ));
+                base.print_inner(indent + 2, out);
+                recursive.print_inner(indent + 2, out);
+            }
+            LogicalRelExpr::WorkTable { id, cols } => {
+                out.push_str(&format!("{}-> work_table(#{}, [", " ".repeat(indent), id));
+                let mut split = "";
+                for col in cols {
+                    out.push_str(split);
+                    out.push_str(&format!("@{}", col));
+                    split = ", ";
+                }
+                out.push_str("])\n");
+            }
+            LogicalRelExpr::SingleRow => {
+                out.push_str(&format!("{}-> single_row\n", " ".repeat(indent)));
+            }
             LogicalRelExpr::Aggregate {
                 src,
                 group_by,
@@ -341,6 +472,22 @@ impl Plan for LogicalRelExpr {
                 }
                 set.difference(&src.att()).cloned().collect()
             }
+            LogicalRelExpr::Limit { src, .. }
+            | LogicalRelExpr::Distinct { src }
+            | LogicalRelExpr::EnforceSingleRow { src } => src.free(),
+            LogicalRelExpr::SetOp { left, right, .. } => {
+                let mut set = left.free();
+                set.extend(right.free());
+                set
+            }
+            LogicalRelExpr::RecursiveUnion {
+                base, recursive, ..
+            } => {
+                let mut set = base.free();
+                set.extend(recursive.free());
+                set
+            }
+            LogicalRelExpr::WorkTable { .. } | LogicalRelExpr::SingleRow => HashSet::new(),
             LogicalRelExpr::Aggregate {
                 src,
                 group_by,
@@ -387,13 +534,25 @@ impl Plan for LogicalRelExpr {
                 column_names,
             } => column_names.iter().cloned().collect(),
             LogicalRelExpr::Select { src, .. } => src.att(),
+            LogicalRelExpr::Join {
+                join_type: JoinType::LeftSemi | JoinType::LeftAnti,
+                left,
+                ..
+            } => left.att(),
             LogicalRelExpr::Join { left, right, .. } => {
                 let mut set = left.att();
                 set.extend(right.att());
                 set
             }
             LogicalRelExpr::Project { cols, .. } => cols.iter().cloned().collect(),
-            LogicalRelExpr::OrderBy { src, .. } => src.att(),
+            LogicalRelExpr::OrderBy { src, .. }
+            | LogicalRelExpr::Limit { src, .. }
+            | LogicalRelExpr::Distinct { src }
+            | LogicalRelExpr::EnforceSingleRow { src } => src.att(),
+            LogicalRelExpr::SetOp { left, .. } => left.att(),
+            LogicalRelExpr::RecursiveUnion { base, .. } => base.att(),
+            LogicalRelExpr::WorkTable { cols, .. } => cols.iter().cloned().collect(),
+            LogicalRelExpr::SingleRow => HashSet::new(),
             LogicalRelExpr::Aggregate {
                 group_by, aggrs, ..
             } => {
@@ -427,6 +586,53 @@ impl Plan for LogicalRelExpr {
 }
 
 impl LogicalRelExpr {
+    /// Attributes of the relational expression in the order in which they appear
+    /// in the result. This follows the column layout of the operators built by the
+    /// planner (e.g. a join yields the left columns followed by the right columns).
+    pub fn att_in_order(&self) -> Vec<ColumnId> {
+        match self {
+            LogicalRelExpr::Scan { column_names, .. } => column_names.clone(),
+            LogicalRelExpr::Select { src, .. }
+            | LogicalRelExpr::OrderBy { src, .. }
+            | LogicalRelExpr::Limit { src, .. }
+            | LogicalRelExpr::Distinct { src }
+            | LogicalRelExpr::EnforceSingleRow { src } => src.att_in_order(),
+            LogicalRelExpr::SetOp { left, .. } => left.att_in_order(),
+            LogicalRelExpr::RecursiveUnion { base, .. } => base.att_in_order(),
+            LogicalRelExpr::WorkTable { cols, .. } => cols.clone(),
+            LogicalRelExpr::SingleRow => Vec::new(),
+            LogicalRelExpr::Join {
+                join_type: JoinType::LeftSemi | JoinType::LeftAnti,
+                left,
+                ..
+            } => left.att_in_order(),
+            LogicalRelExpr::Join { left, right, .. } => {
+                [left.att_in_order(), right.att_in_order()].concat()
+            }
+            LogicalRelExpr::Project { cols, .. } => cols.clone(),
+            LogicalRelExpr::Aggregate {
+                group_by, aggrs, ..
+            } => group_by
+                .iter()
+                .cloned()
+                .chain(aggrs.iter().map(|(id, _)| *id))
+                .collect(),
+            LogicalRelExpr::Map { input, exprs } => {
+                let mut cols = input.att_in_order();
+                cols.extend(exprs.iter().map(|(id, _)| *id));
+                cols
+            }
+            LogicalRelExpr::FlatMap { input, func } => {
+                [input.att_in_order(), func.att_in_order()].concat()
+            }
+            LogicalRelExpr::Rename { src, src_to_dest } => src
+                .att_in_order()
+                .into_iter()
+                .map(|col| *src_to_dest.get(&col).unwrap_or(&col))
+                .collect(),
+        }
+    }
+
     pub fn pretty_print(&self) {
         println!("{}", self.pretty_string());
     }
@@ -463,6 +669,16 @@ impl LogicalRelExpr {
                 right,
                 predicates,
             } => {
+                if *join_type == JoinType::CrossJoin && predicates.is_empty() {
+                    // Plain cartesian product, e.g. with the single row of a scalar subquery
+                    return PhysicalRelExpr::CrossJoin {
+                        join_type: *join_type,
+                        left: Box::new(left.to_physical_plan()),
+                        right: Box::new(right.to_physical_plan()),
+                        predicates: vec![],
+                        tree_hash: None,
+                    };
+                }
                 let predicates: Vec<Expression<PhysicalRelExpr>> = predicates
                     .iter()
                     .map(|e| e.to_physical_expression())
@@ -501,6 +717,57 @@ impl LogicalRelExpr {
                 cols: cols.clone(),
                 tree_hash: None,
             },
+            Self::Limit { src, limit, offset } => PhysicalRelExpr::Limit {
+                src: Box::new(src.to_physical_plan()),
+                limit: *limit,
+                offset: *offset,
+                tree_hash: None,
+            },
+            Self::Distinct { src } => PhysicalRelExpr::Distinct {
+                src: Box::new(src.to_physical_plan()),
+                tree_hash: None,
+            },
+            Self::EnforceSingleRow { src } => PhysicalRelExpr::EnforceSingleRow {
+                src: Box::new(src.to_physical_plan()),
+                tree_hash: None,
+            },
+            Self::SetOp {
+                set_op: SetOpType::UnionAll,
+                left,
+                right,
+            } => PhysicalRelExpr::UnionAll {
+                left: Box::new(left.to_physical_plan()),
+                right: Box::new(right.to_physical_plan()),
+                tree_hash: None,
+            },
+            Self::SetOp {
+                set_op,
+                left,
+                right,
+            } => PhysicalRelExpr::HashSetOp {
+                set_op: *set_op,
+                left: Box::new(left.to_physical_plan()),
+                right: Box::new(right.to_physical_plan()),
+                tree_hash: None,
+            },
+            Self::RecursiveUnion {
+                set_op,
+                base,
+                recursive,
+                work_table,
+            } => PhysicalRelExpr::RecursiveUnion {
+                set_op: *set_op,
+                base: Box::new(base.to_physical_plan()),
+                recursive: Box::new(recursive.to_physical_plan()),
+                work_table: *work_table,
+                tree_hash: None,
+            },
+            Self::WorkTable { id, cols } => PhysicalRelExpr::WorkTableScan {
+                id: *id,
+                cols: cols.clone(),
+                tree_hash: None,
+            },
+            Self::SingleRow => PhysicalRelExpr::SingleRow { tree_hash: None },
             Self::Aggregate {
                 src,
                 group_by,
@@ -1,15 +1,21 @@
 // Reference: https://github.com/rotaki/decorrelator
 
 mod aggregate;
+mod distinct;
+mod enforce_single_row;
 mod flatmap;
 mod hoist;
 mod join;
+mod limit;
 mod logical_rel_expr;
 mod map;
+mod order_by;
 mod project;
+mod recursive_union;
 mod rename;
 mod scan;
 mod select;
+mod set_op;
 
 pub mod prelude {
     pub use super::logical_rel_expr::LogicalRelExpr;
@@ -17,5 +23,6 @@ pub mod prelude {
     pub use crate::query::expr::Expression;
     pub use crate::query::join_type::JoinType;
     pub use crate::query::operation::{AggOp, BinaryOp};
+    pub use crate::query::set_op_type::SetOpType;
     pub use crate::traits::plan::Plan;
 }
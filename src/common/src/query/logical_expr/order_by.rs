@@ -0,0 +1,12 @@
+use super::prelude::*;
+
+impl LogicalRelExpr {
+    /// Sort the current logical relational expression.
+    /// cols: (column_id, asc, nulls_first)
+    pub fn order_by(self, cols: Vec<(ColumnId, bool, bool)>) -> LogicalRelExpr {
+        LogicalRelExpr::OrderBy {
+            src: Box::new(self),
+            cols,
+        }
+    }
+}
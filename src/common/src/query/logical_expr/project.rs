@@ -144,9 +144,10 @@ impl LogicalRelExpr {
                 LogicalRelExpr::Scan {
                     cid,
                     table_name,
-                    mut column_names,
+                    column_names,
                 } => {
-                    column_names.retain(|col| cols.contains(col));
+                    // Scan the columns in the order of the projection
+                    let column_names = intersect(&column_names.into_iter().collect(), &cols);
                     LogicalRelExpr::scan(cid, table_name, column_names)
                 }
                 _ => self.project(false, enabled_rules, col_id_gen, cols, false),
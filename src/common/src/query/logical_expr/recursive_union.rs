@@ -0,0 +1,53 @@
+use super::prelude::*;
+
+impl LogicalRelExpr {
+    /// Combine the rows of the current logical relational expression (the base of a
+    /// recursive CTE) with the rows of `recursive`, evaluated repeatedly over the rows
+    /// of the previous iteration, which it reads from the work table `work_table`.
+    pub fn recursive_union(
+        self,
+        set_op: SetOpType,
+        recursive: LogicalRelExpr,
+        work_table: usize,
+    ) -> LogicalRelExpr {
+        LogicalRelExpr::RecursiveUnion {
+            set_op,
+            base: Box::new(self),
+            recursive: Box::new(recursive),
+            work_table,
+        }
+    }
+
+    /// Read the rows of the work table `id`, with the given column ids.
+    pub fn work_table(id: usize, cols: Vec<ColumnId>) -> LogicalRelExpr {
+        LogicalRelExpr::WorkTable { id, cols }
+    }
+
+    /// Returns true if the expression reads the work table `id`.
+    pub fn has_work_table(&self, id: usize) -> bool {
+        match self {
+            LogicalRelExpr::Scan { .. } | LogicalRelExpr::SingleRow => false,
+            LogicalRelExpr::WorkTable { id: table_id, .. } => *table_id == id,
+            LogicalRelExpr::Select { src, .. }
+            | LogicalRelExpr::Project { src, .. }
+            | LogicalRelExpr::OrderBy { src, .. }
+            | LogicalRelExpr::Limit { src, .. }
+            | LogicalRelExpr::Distinct { src }
+            | LogicalRelExpr::EnforceSingleRow { src }
+            | LogicalRelExpr::Aggregate { src, .. }
+            | LogicalRelExpr::Map { input: src, .. }
+            | LogicalRelExpr::Rename { src, .. } => src.has_work_table(id),
+            LogicalRelExpr::Join { left, right, .. }
+            | LogicalRelExpr::SetOp { left, right, .. }
+            | LogicalRelExpr::FlatMap {
+                input: left,
+                func: right,
+            }
+            | LogicalRelExpr::RecursiveUnion {
+                base: left,
+                recursive: right,
+                ..
+            } => left.has_work_table(id) || right.has_work_table(id),
+        }
+    }
+}
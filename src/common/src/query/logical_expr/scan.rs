@@ -17,4 +17,9 @@ impl LogicalRelExpr {
             column_names,
         }
     }
+
+    /// Create a node producing a single row without columns
+    pub fn single_row() -> LogicalRelExpr {
+        LogicalRelExpr::SingleRow
+    }
 }
@@ -31,6 +31,18 @@ impl LogicalRelExpr {
                     preds.append(&mut predicates);
                     src.select(true, enabled_rules, col_id_gen, preds)
                 }
+                LogicalRelExpr::Join {
+                    join_type: join_type @ (JoinType::LeftSemi | JoinType::LeftAnti),
+                    left,
+                    right,
+                    predicates: preds,
+                } => {
+                    // Only the left columns are visible above a semi/anti join, so the
+                    // predicates can be evaluated before the join. They must not become
+                    // join predicates since that changes the result of an anti join.
+                    left.select(true, enabled_rules, col_id_gen, predicates)
+                        .join(false, enabled_rules, col_id_gen, join_type, *right, preds)
+                }
                 LogicalRelExpr::Join {
                     join_type,
                     left,
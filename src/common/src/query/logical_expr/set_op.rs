@@ -0,0 +1,14 @@
+use super::prelude::*;
+
+impl LogicalRelExpr {
+    /// Combine the rows of the current logical relational expression with the rows of
+    /// `right`. Both sides must produce the same number of columns. The columns are
+    /// matched by position and the result uses the column ids of the current expression.
+    pub fn set_op(self, set_op: SetOpType, right: LogicalRelExpr) -> LogicalRelExpr {
+        LogicalRelExpr::SetOp {
+            set_op,
+            left: Box::new(self),
+            right: Box::new(right),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 pub mod bytecode_expr;
 pub mod expr;
+pub mod functions;
 pub mod join_type;
 pub mod logical_expr;
 pub mod operation;
@@ -8,3 +9,4 @@ pub mod physical_expr;
 pub mod query_registrar;
 pub mod query_result;
 pub mod rules;
+pub mod set_op_type;
@@ -12,6 +12,7 @@ pub enum BinaryOp {
     Sub,
     Mul,
     Div,
+    Mod,
     Eq,
     Neq,
     Lt,
@@ -20,6 +21,36 @@ pub enum BinaryOp {
     Ge,
     And,
     Or,
+    /// `l LIKE r`: `%` matches any sequence of characters, `_` any single character
+    /// and `\` escapes the next character.
+    Like,
+    /// Case-insensitive LIKE.
+    ILike,
+    NotLike,
+    NotILike,
+    /// Inequality treating NULL as a value: never NULL.
+    IsDistinctFrom,
+    /// Equality treating NULL as a value: never NULL.
+    IsNotDistinctFrom,
+}
+
+impl BinaryOp {
+    /// Type of the result of an arithmetic operator given the types of its operands,
+    /// if known: the common type of the operands (a decimal has the larger of their
+    /// scales), except that a date plus or minus a number of days is a date and the
+    /// difference of two dates is a number of days.
+    pub fn arithmetic_type(
+        &self,
+        left: Option<DataType>,
+        right: Option<DataType>,
+    ) -> Option<DataType> {
+        match (self, &left, &right) {
+            (BinaryOp::Sub, Some(DataType::Date), Some(DataType::Date)) => Some(DataType::BigInt),
+            (BinaryOp::Add, _, Some(DataType::Date)) => right,
+            (_, Some(l), Some(r)) if l.is_numeric() && r.is_numeric() => l.common_type(r),
+            _ => left.or(right),
+        }
+    }
 }
 
 impl std::fmt::Display for BinaryOp {
@@ -29,6 +60,7 @@ impl std::fmt::Display for BinaryOp {
             BinaryOp::Sub => write!(f, "-"),
             BinaryOp::Mul => write!(f, "*"),
             BinaryOp::Div => write!(f, "/"),
+            BinaryOp::Mod => write!(f, "%"),
             BinaryOp::Eq => write!(f, "="),
             BinaryOp::Neq => write!(f, "!="),
             BinaryOp::Lt => write!(f, "<"),
@@ -37,6 +69,38 @@ impl std::fmt::Display for BinaryOp {
             BinaryOp::Ge => write!(f, ">="),
             BinaryOp::And => write!(f, "&&"),
             BinaryOp::Or => write!(f, "||"),
+            BinaryOp::Like => write!(f, "LIKE"),
+            BinaryOp::ILike => write!(f, "ILIKE"),
+            BinaryOp::NotLike => write!(f, "NOT LIKE"),
+            BinaryOp::NotILike => write!(f, "NOT ILIKE"),
+            BinaryOp::IsDistinctFrom => write!(f, "IS DISTINCT FROM"),
+            BinaryOp::IsNotDistinctFrom => write!(f, "IS NOT DISTINCT FROM"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum UnaryOp {
+    IsNull,
+    IsNotNull,
+    Not,
+    Neg,
+}
+
+impl UnaryOp {
+    /// Whether the operator is written before its operand, e.g. `NOT x` but `x IS NULL`.
+    pub fn is_prefix(&self) -> bool {
+        matches!(self, UnaryOp::Not | UnaryOp::Neg)
+    }
+}
+
+impl std::fmt::Display for UnaryOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnaryOp::IsNull => write!(f, "IS NULL"),
+            UnaryOp::IsNotNull => write!(f, "IS NOT NULL"),
+            UnaryOp::Not => write!(f, "NOT "),
+            UnaryOp::Neg => write!(f, "-"),
         }
     }
 }
@@ -48,6 +112,8 @@ pub enum AggOp {
     Max,
     Min,
     Sum,
+    CountDistinct,
+    SumDistinct,
 }
 
 impl std::fmt::Display for AggOp {
@@ -59,13 +125,33 @@ impl std::fmt::Display for AggOp {
             Max => write!(f, "MAX"),
             Min => write!(f, "MIN"),
             Sum => write!(f, "SUM"),
+            CountDistinct => write!(f, "COUNT DISTINCT"),
+            SumDistinct => write!(f, "SUM DISTINCT"),
         }
     }
 }
 
 impl AggOp {
+    /// Returns true if duplicate values within a group are aggregated only once.
+    pub fn is_distinct(&self) -> bool {
+        matches!(self, AggOp::CountDistinct | AggOp::SumDistinct)
+    }
+
+    /// Returns the aggregation applied to the values that are not skipped.
+    pub fn without_distinct(&self) -> AggOp {
+        match self {
+            AggOp::CountDistinct => AggOp::Count,
+            AggOp::SumDistinct => AggOp::Sum,
+            op => *op,
+        }
+    }
+
     pub fn to_attr(&self, src_att: &Attribute) -> Attribute {
-        let new_name = format!("{}({})", self, src_att.name);
+        let new_name = if self.is_distinct() {
+            format!("{}(DISTINCT {})", self.without_distinct(), src_att.name)
+        } else {
+            format!("{}({})", self, src_att.name)
+        };
         match self {
             AggOp::Avg => {
                 if matches!(&src_att.dtype, DataType::Decimal(_, _)) {
@@ -77,7 +163,7 @@ impl AggOp {
                     )
                 }
             }
-            AggOp::Count => Attribute::new(new_name, DataType::BigInt),
+            AggOp::Count | AggOp::CountDistinct => Attribute::new(new_name, DataType::BigInt),
             _ => Attribute::new(new_name, src_att.dtype.clone()),
         }
     }
@@ -4,7 +4,7 @@ use crate::{
     ids::{ColumnId, ContainerId},
     logical_expr::prelude::{Expression, LogicalRelExpr},
     physical_expr::physical_rel_expr::PhysicalRelExpr,
-    BinaryOp, Field,
+    BinaryOp, DataType, Field, UnaryOp,
 };
 
 /// Similar to Expression<P> and MemoExpression. Its purpose is to map the uniquely
@@ -32,24 +32,51 @@ pub enum OriginExpression {
         left: Box<Self>,
         right: Box<Self>,
     },
+    Unary {
+        op: UnaryOp,
+        expr: Box<Self>,
+    },
     Case {
         expr: Box<Self>,
         whens: Vec<(Self, Self)>,
         else_expr: Box<Self>,
     },
-    // Does not support subquery for now
+    InList {
+        expr: Box<Self>,
+        list: Vec<Self>,
+        negated: bool,
+    },
+    Function {
+        id: usize,
+        args: Vec<Self>,
+    },
+    Cast {
+        expr: Box<Self>,
+        dtype: DataType,
+    },
+    /// Value computed by a scalar subquery. Its origin is not tracked.
+    Subquery,
+    /// Parameter of a prepared statement.
+    Param {
+        idx: usize,
+    },
 }
 
 impl OriginExpression {
     pub fn get_base_ids_and_index(&self) -> Vec<(ContainerId, usize)> {
         match self {
             OriginExpression::BaseCidAndIndex { cid, index } => vec![(*cid, *index)],
-            OriginExpression::Field { val: _ } => vec![],
+            OriginExpression::Field { val: _ }
+            | OriginExpression::Subquery
+            | OriginExpression::Param { idx: _ } => vec![],
             OriginExpression::Binary { left, right, .. } => {
                 let mut res = left.get_base_ids_and_index();
                 res.extend(right.get_base_ids_and_index());
                 res
             }
+            OriginExpression::Unary { expr, .. } | OriginExpression::Cast { expr, .. } => {
+                expr.get_base_ids_and_index()
+            }
             OriginExpression::Case {
                 expr,
                 whens,
@@ -63,6 +90,17 @@ impl OriginExpression {
                 res.extend(else_expr.get_base_ids_and_index());
                 res
             }
+            OriginExpression::InList { expr, list, .. } => {
+                let mut res = expr.get_base_ids_and_index();
+                for e in list {
+                    res.extend(e.get_base_ids_and_index());
+                }
+                res
+            }
+            OriginExpression::Function { args, .. } => args
+                .iter()
+                .flat_map(|e| e.get_base_ids_and_index())
+                .collect(),
             // DerivedColRef should already been resolved to BaseCidAndIndex before
             // by calling the `get_origin` function in the environment.
             OriginExpression::DerivedColRef { .. } => {
@@ -82,6 +120,10 @@ impl From<Expression<LogicalRelExpr>> for OriginExpression {
                 left: Box::new((*left).into()),
                 right: Box::new((*right).into()),
             },
+            Expression::Unary { op, expr } => OriginExpression::Unary {
+                op,
+                expr: Box::new((*expr).into()),
+            },
             Expression::Case {
                 expr,
                 whens,
@@ -94,9 +136,25 @@ impl From<Expression<LogicalRelExpr>> for OriginExpression {
                     .collect(),
                 else_expr: Box::new((*else_expr).into()),
             },
-            Expression::Subquery { .. } => {
-                unimplemented!("Subquery is currently not supported in OriginExpression")
-            }
+            Expression::InList {
+                expr,
+                list,
+                negated,
+            } => OriginExpression::InList {
+                expr: Box::new((*expr).into()),
+                list: list.into_iter().map(|e| e.into()).collect(),
+                negated,
+            },
+            Expression::Function { id, args } => OriginExpression::Function {
+                id,
+                args: args.into_iter().map(|e| e.into()).collect(),
+            },
+            Expression::Cast { expr, dtype } => OriginExpression::Cast {
+                expr: Box::new((*expr).into()),
+                dtype,
+            },
+            Expression::Subquery { .. } => OriginExpression::Subquery,
+            Expression::Param { idx } => OriginExpression::Param { idx },
         }
     }
 }
@@ -111,6 +169,10 @@ impl From<Expression<PhysicalRelExpr>> for OriginExpression {
                 left: Box::new((*left).into()),
                 right: Box::new((*right).into()),
             },
+            Expression::Unary { op, expr } => OriginExpression::Unary {
+                op,
+                expr: Box::new((*expr).into()),
+            },
             Expression::Case {
                 expr,
                 whens,
@@ -123,9 +185,25 @@ impl From<Expression<PhysicalRelExpr>> for OriginExpression {
                     .collect(),
                 else_expr: Box::new((*else_expr).into()),
             },
-            Expression::Subquery { .. } => {
-                unimplemented!("Subquery is currently not supported in OriginExpression")
-            }
+            Expression::InList {
+                expr,
+                list,
+                negated,
+            } => OriginExpression::InList {
+                expr: Box::new((*expr).into()),
+                list: list.into_iter().map(|e| e.into()).collect(),
+                negated,
+            },
+            Expression::Function { id, args } => OriginExpression::Function {
+                id,
+                args: args.into_iter().map(|e| e.into()).collect(),
+            },
+            Expression::Cast { expr, dtype } => OriginExpression::Cast {
+                expr: Box::new((*expr).into()),
+                dtype,
+            },
+            Expression::Subquery { .. } => OriginExpression::Subquery,
+            Expression::Param { idx } => OriginExpression::Param { idx },
         }
     }
 }
@@ -140,6 +218,7 @@ impl From<OriginExpression> for Expression<PhysicalRelExpr> {
                 left: Box::new((*left).into()),
                 right: Box::new((*right).into()),
             },
+            OriginExpression::Unary { op, expr } => Expression::unary(op, (*expr).into()),
             OriginExpression::Case {
                 expr,
                 whens,
@@ -152,6 +231,20 @@ impl From<OriginExpression> for Expression<PhysicalRelExpr> {
                     .collect(),
                 else_expr: Box::new((*else_expr).into()),
             },
+            OriginExpression::InList {
+                expr,
+                list,
+                negated,
+            } => Expression::in_list(
+                (*expr).into(),
+                list.into_iter().map(|e| e.into()).collect(),
+                negated,
+            ),
+            OriginExpression::Function { id, args } => {
+                Expression::function(id, args.into_iter().map(|e| e.into()).collect())
+            }
+            OriginExpression::Cast { expr, dtype } => Expression::cast((*expr).into(), dtype),
+            OriginExpression::Param { idx } => Expression::Param { idx },
             // DerivedColRef should already been resolved to BaseCidAndIndex before
             // calling this function. Call the `get_origin` function in the environment.
             _ => unimplemented!(),
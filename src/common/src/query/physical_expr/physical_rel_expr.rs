@@ -13,9 +13,9 @@ use std::{
 use crate::{
     error::c_err,
     ids::{ColumnId, ContainerId},
-    logical_expr::prelude::{Expression, JoinType},
+    logical_expr::prelude::{Expression, JoinType, SetOpType},
     traits::plan::Plan,
-    AggOp, CrustyError,
+    AggOp, CrustyError, Field,
 };
 
 #[derive(Debug, Clone)]
@@ -75,6 +75,55 @@ pub enum PhysicalRelExpr {
         cols: Vec<(ColumnId, bool, bool)>, // (column_id, asc, nulls_first)
         tree_hash: Option<u64>,            // Optional hash code for representing the plan
     },
+    Limit {
+        // Skips the first `offset` rows and returns at most `limit` rows after that
+        src: Box<PhysicalRelExpr>,
+        limit: Option<usize>,
+        offset: usize,
+        tree_hash: Option<u64>, // Optional hash code for representing the plan
+    },
+    Distinct {
+        // Removes duplicate rows from the result
+        src: Box<PhysicalRelExpr>,
+        tree_hash: Option<u64>, // Optional hash code for representing the plan
+    },
+    EnforceSingleRow {
+        // Returns exactly one row, or fails at execution if the source has more
+        src: Box<PhysicalRelExpr>,
+        tree_hash: Option<u64>, // Optional hash code for representing the plan
+    },
+    UnionAll {
+        // Returns the rows of the left side followed by the rows of the right side
+        left: Box<PhysicalRelExpr>,
+        right: Box<PhysicalRelExpr>,
+        tree_hash: Option<u64>, // Optional hash code for representing the plan
+    },
+    HashSetOp {
+        // Evaluates the set operation by hashing the rows of the right side
+        set_op: SetOpType,
+        left: Box<PhysicalRelExpr>,
+        right: Box<PhysicalRelExpr>,
+        tree_hash: Option<u64>, // Optional hash code for representing the plan
+    },
+    RecursiveUnion {
+        // Returns the rows of the base, then evaluates the recursive side over the
+        // rows of the previous iteration until no new rows are produced
+        set_op: SetOpType,
+        base: Box<PhysicalRelExpr>,
+        recursive: Box<PhysicalRelExpr>,
+        work_table: usize,
+        tree_hash: Option<u64>, // Optional hash code for representing the plan
+    },
+    WorkTableScan {
+        // Reads the rows produced by the previous iteration of a RecursiveUnion
+        id: usize,
+        cols: Vec<ColumnId>,
+        tree_hash: Option<u64>, // Optional hash code for representing the plan
+    },
+    SingleRow {
+        // Produces a single row without columns, the source of a SELECT without FROM
+        tree_hash: Option<u64>, // Optional hash code for representing the plan
+    },
     HashAggregate {
         src: Box<PhysicalRelExpr>,
         group_by: Vec<ColumnId>,
@@ -226,6 +275,73 @@ impl Plan for PhysicalRelExpr {
                     .collect(),
                 tree_hash,
             },
+            PhysicalRelExpr::Limit {
+                src,
+                limit,
+                offset,
+                tree_hash,
+            } => PhysicalRelExpr::Limit {
+                src: Box::new(src.replace_variables(src_to_dest)),
+                limit,
+                offset,
+                tree_hash,
+            },
+            PhysicalRelExpr::Distinct { src, tree_hash } => PhysicalRelExpr::Distinct {
+                src: Box::new(src.replace_variables(src_to_dest)),
+                tree_hash,
+            },
+            PhysicalRelExpr::EnforceSingleRow { src, tree_hash } => {
+                PhysicalRelExpr::EnforceSingleRow {
+                    src: Box::new(src.replace_variables(src_to_dest)),
+                    tree_hash,
+                }
+            }
+            PhysicalRelExpr::UnionAll {
+                left,
+                right,
+                tree_hash,
+            } => PhysicalRelExpr::UnionAll {
+                left: Box::new(left.replace_variables(src_to_dest)),
+                right: Box::new(right.replace_variables(src_to_dest)),
+                tree_hash,
+            },
+            PhysicalRelExpr::HashSetOp {
+                set_op,
+                left,
+                right,
+                tree_hash,
+            } => PhysicalRelExpr::HashSetOp {
+                set_op,
+                left: Box::new(left.replace_variables(src_to_dest)),
+                right: Box::new(right.replace_variables(src_to_dest)),
+                tree_hash,
+            },
+            PhysicalRelExpr::RecursiveUnion {
+                set_op,
+                base,
+                recursive,
+                work_table,
+                tree_hash,
+            } => PhysicalRelExpr::RecursiveUnion {
+                set_op,
+                base: Box::new(base.replace_variables(src_to_dest)),
+                recursive: Box::new(recursive.replace_variables(src_to_dest)),
+                work_table,
+                tree_hash,
+            },
+            PhysicalRelExpr::WorkTableScan {
+                id,
+                cols,
+                tree_hash,
+            } => PhysicalRelExpr::WorkTableScan {
+                id,
+                cols: cols
+                    .into_iter()
+                    .map(|col| *src_to_dest.get(&col).unwrap_or(&col))
+                    .collect(),
+                tree_hash,
+            },
+            PhysicalRelExpr::SingleRow { tree_hash } => PhysicalRelExpr::SingleRow { tree_hash },
             PhysicalRelExpr::HashAggregate {
                 src,
                 group_by,
@@ -428,6 +544,69 @@ impl Plan for PhysicalRelExpr {
                 out.push_str(&format!("{}-> order_by({:?})\n", " ".repeat(indent), cols));
                 src.print_inner(indent + 2, out);
             }
+            PhysicalRelExpr::Limit {
+                src, limit, offset, ..
+            } => {
+                out.push_str(&format!(
+                    "{}-> limit({:?}, offset: {})\n",
+                    " ".repeat(indent),
+                    limit,
+                    offset
+                ));
+                src.print_inner(indent + 2, out);
+            }
+            PhysicalRelExpr::Distinct { src, .. } => {
+                out.push_str(&format!("{}-> distinct\n", " ".repeat(indent)));
+                src.print_inner(indent + 2, out);
+            }
+            PhysicalRelExpr::EnforceSingleRow { src, .. } => {
+                out.push_str(&format!("{}-> enforce_single_row\n", " ".repeat(indent)));
+                src.print_inner(indent + 2, out);
+            }
+            PhysicalRelExpr::UnionAll { left, right, .. } => {
+                out.push_str(&format!("{}-> union_all\n", " ".repeat(indent)));
+                left.print_inner(indent + 2, out);
+                right.print_inner(indent + 2, out);
+            }
+            PhysicalRelExpr::HashSetOp {
+                set_op,
+                left,
+                right,
+                ..
+            } => {
+                out.push_str(&format!("{}-> Hash {}\n", " ".repeat(indent), set_op));
+                left.print_inner(indent + 2, out);
+                right.print_inner(indent + 2, out);
+            }
+            PhysicalRelExpr::RecursiveUnion {
+                set_op,
+                base,
+                recursive,
+                work_table,
+                ..
+            } => {
+                out.push_str(&format!(
+                    "{}-> recursive_{}(#{})\n",
+                    " ".repeat(indent),
+                    set_op,
+                    work_table
+                ));
+                base.print_inner(indent + 2, out);
+                recursive.print_inner(indent + 2, out);
+            }
+            PhysicalRelExpr::WorkTableScan { id, cols, .. } => {
+                out.push_str(&format!("{}-> work_table(#{}, [", " ".repeat(indent), id));
+                let mut split = "";
+                for col in cols {
+                    out.push_str(split);
+                    out.push_str(&format!("@{}", col));
+                    split = ", ";
+                }
+                out.push_str("])\n");
+            }
+            PhysicalRelExpr::SingleRow { .. } => {
+                out.push_str(&format!("{}-> single_row\n", " ".repeat(indent)));
+            }
             PhysicalRelExpr::HashAggregate {
                 src,
                 group_by,
@@ -559,6 +738,23 @@ impl Plan for PhysicalRelExpr {
                 }
                 set.difference(&src.att()).cloned().collect()
             }
+            PhysicalRelExpr::Limit { src, .. }
+            | PhysicalRelExpr::Distinct { src, .. }
+            | PhysicalRelExpr::EnforceSingleRow { src, .. } => src.free(),
+            PhysicalRelExpr::UnionAll { left, right, .. }
+            | PhysicalRelExpr::HashSetOp { left, right, .. }
+            | PhysicalRelExpr::RecursiveUnion {
+                base: left,
+                recursive: right,
+                ..
+            } => {
+                let mut set = left.free();
+                set.extend(right.free());
+                set
+            }
+            PhysicalRelExpr::WorkTableScan { .. } | PhysicalRelExpr::SingleRow { .. } => {
+                HashSet::new()
+            }
             PhysicalRelExpr::HashAggregate {
                 src,
                 group_by,
@@ -606,6 +802,16 @@ impl Plan for PhysicalRelExpr {
                 tree_hash: _,
             } => column_names.iter().cloned().collect(),
             PhysicalRelExpr::Select { src, .. } => src.att(),
+            PhysicalRelExpr::NestedLoopJoin {
+                join_type: JoinType::LeftSemi | JoinType::LeftAnti,
+                left,
+                ..
+            }
+            | PhysicalRelExpr::HashJoin {
+                join_type: JoinType::LeftSemi | JoinType::LeftAnti,
+                left,
+                ..
+            } => left.att(),
             PhysicalRelExpr::CrossJoin { left, right, .. }
             | PhysicalRelExpr::NestedLoopJoin { left, right, .. }
             | PhysicalRelExpr::HashJoin { left, right, .. }
@@ -615,7 +821,15 @@ impl Plan for PhysicalRelExpr {
                 set
             }
             PhysicalRelExpr::Project { cols, .. } => cols.iter().cloned().collect(),
-            PhysicalRelExpr::Sort { src, .. } => src.att(),
+            PhysicalRelExpr::Sort { src, .. }
+            | PhysicalRelExpr::Limit { src, .. }
+            | PhysicalRelExpr::Distinct { src, .. }
+            | PhysicalRelExpr::EnforceSingleRow { src, .. } => src.att(),
+            PhysicalRelExpr::UnionAll { left, .. }
+            | PhysicalRelExpr::HashSetOp { left, .. }
+            | PhysicalRelExpr::RecursiveUnion { base: left, .. } => left.att(),
+            PhysicalRelExpr::WorkTableScan { cols, .. } => cols.iter().cloned().collect(),
+            PhysicalRelExpr::SingleRow { .. } => HashSet::new(),
             PhysicalRelExpr::HashAggregate {
                 group_by, aggrs, ..
             } => {
@@ -667,6 +881,206 @@ impl PhysicalRelExpr {
         out
     }
 
+    /// Replace the free variables of the expressions in the plan with the given expressions.
+    /// Used to bind the columns of an outer row before executing a correlated subplan.
+    /// * src_to_dest: mapping from column id to the expression replacing it
+    pub fn replace_variables_with_exprs(
+        self,
+        src_to_dest: &HashMap<ColumnId, Expression<PhysicalRelExpr>>,
+    ) -> PhysicalRelExpr {
+        self.map_exprs(&|expr| expr.replace_variables_with_exprs(src_to_dest))
+    }
+
+    /// Replace the parameters of the expressions in the plan with the given values.
+    /// Used to execute the plan of a prepared statement.
+    /// * params: value of each parameter, by index
+    pub fn bind_params(self, params: &[Field]) -> PhysicalRelExpr {
+        self.map_exprs(&|expr| expr.bind_params(params))
+    }
+
+    /// Apply `f` to every expression of the plan.
+    fn map_exprs<F>(self, f: &F) -> PhysicalRelExpr
+    where
+        F: Fn(Expression<PhysicalRelExpr>) -> Expression<PhysicalRelExpr>,
+    {
+        let replace = |preds: Vec<Expression<PhysicalRelExpr>>| preds.into_iter().map(f).collect();
+        match self {
+            PhysicalRelExpr::Scan { .. } => self,
+            PhysicalRelExpr::Select {
+                src,
+                predicates,
+                tree_hash,
+            } => PhysicalRelExpr::Select {
+                src: Box::new(src.map_exprs(f)),
+                predicates: replace(predicates),
+                tree_hash,
+            },
+            PhysicalRelExpr::CrossJoin {
+                join_type,
+                left,
+                right,
+                predicates,
+                tree_hash,
+            } => PhysicalRelExpr::CrossJoin {
+                join_type,
+                left: Box::new(left.map_exprs(f)),
+                right: Box::new(right.map_exprs(f)),
+                predicates: replace(predicates),
+                tree_hash,
+            },
+            PhysicalRelExpr::NestedLoopJoin {
+                join_type,
+                left,
+                right,
+                predicates,
+                tree_hash,
+            } => PhysicalRelExpr::NestedLoopJoin {
+                join_type,
+                left: Box::new(left.map_exprs(f)),
+                right: Box::new(right.map_exprs(f)),
+                predicates: replace(predicates),
+                tree_hash,
+            },
+            PhysicalRelExpr::HashJoin {
+                join_type,
+                left,
+                right,
+                predicates,
+                tree_hash,
+            } => PhysicalRelExpr::HashJoin {
+                join_type,
+                left: Box::new(left.map_exprs(f)),
+                right: Box::new(right.map_exprs(f)),
+                predicates: replace(predicates),
+                tree_hash,
+            },
+            PhysicalRelExpr::SortMergeJoin {
+                join_type,
+                left,
+                right,
+                predicates,
+                tree_hash,
+            } => PhysicalRelExpr::SortMergeJoin {
+                join_type,
+                left: Box::new(left.map_exprs(f)),
+                right: Box::new(right.map_exprs(f)),
+                predicates: replace(predicates),
+                tree_hash,
+            },
+            PhysicalRelExpr::Project {
+                src,
+                cols,
+                tree_hash,
+            } => PhysicalRelExpr::Project {
+                src: Box::new(src.map_exprs(f)),
+                cols,
+                tree_hash,
+            },
+            PhysicalRelExpr::Sort {
+                src,
+                cols,
+                tree_hash,
+            } => PhysicalRelExpr::Sort {
+                src: Box::new(src.map_exprs(f)),
+                cols,
+                tree_hash,
+            },
+            PhysicalRelExpr::Limit {
+                src,
+                limit,
+                offset,
+                tree_hash,
+            } => PhysicalRelExpr::Limit {
+                src: Box::new(src.map_exprs(f)),
+                limit,
+                offset,
+                tree_hash,
+            },
+            PhysicalRelExpr::Distinct { src, tree_hash } => PhysicalRelExpr::Distinct {
+                src: Box::new(src.map_exprs(f)),
+                tree_hash,
+            },
+            PhysicalRelExpr::EnforceSingleRow { src, tree_hash } => {
+                PhysicalRelExpr::EnforceSingleRow {
+                    src: Box::new(src.map_exprs(f)),
+                    tree_hash,
+                }
+            }
+            PhysicalRelExpr::UnionAll {
+                left,
+                right,
+                tree_hash,
+            } => PhysicalRelExpr::UnionAll {
+                left: Box::new(left.map_exprs(f)),
+                right: Box::new(right.map_exprs(f)),
+                tree_hash,
+            },
+            PhysicalRelExpr::HashSetOp {
+                set_op,
+                left,
+                right,
+                tree_hash,
+            } => PhysicalRelExpr::HashSetOp {
+                set_op,
+                left: Box::new(left.map_exprs(f)),
+                right: Box::new(right.map_exprs(f)),
+                tree_hash,
+            },
+            PhysicalRelExpr::RecursiveUnion {
+                set_op,
+                base,
+                recursive,
+                work_table,
+                tree_hash,
+            } => PhysicalRelExpr::RecursiveUnion {
+                set_op,
+                base: Box::new(base.map_exprs(f)),
+                recursive: Box::new(recursive.map_exprs(f)),
+                work_table,
+                tree_hash,
+            },
+            PhysicalRelExpr::WorkTableScan { .. } | PhysicalRelExpr::SingleRow { .. } => self,
+            PhysicalRelExpr::HashAggregate {
+                src,
+                group_by,
+                aggrs,
+                tree_hash,
+            } => PhysicalRelExpr::HashAggregate {
+                src: Box::new(src.map_exprs(f)),
+                group_by,
+                aggrs,
+                tree_hash,
+            },
+            PhysicalRelExpr::Map {
+                input,
+                exprs,
+                tree_hash,
+            } => PhysicalRelExpr::Map {
+                input: Box::new(input.map_exprs(f)),
+                exprs: exprs.into_iter().map(|(id, expr)| (id, f(expr))).collect(),
+                tree_hash,
+            },
+            PhysicalRelExpr::FlatMap {
+                input,
+                func,
+                tree_hash,
+            } => PhysicalRelExpr::FlatMap {
+                input: Box::new(input.map_exprs(f)),
+                func: Box::new(func.map_exprs(f)),
+                tree_hash,
+            },
+            PhysicalRelExpr::Rename {
+                src,
+                src_to_dest: column_mappings,
+                tree_hash,
+            } => PhysicalRelExpr::Rename {
+                src: Box::new(src.map_exprs(f)),
+                src_to_dest: column_mappings,
+                tree_hash,
+            },
+        }
+    }
+
     /// Get all tables involved in expression
     pub fn get_tables_involved(&self, container_ids: &mut Vec<ContainerId>) {
         if let PhysicalRelExpr::Scan { cid, .. } = self {
@@ -676,6 +1090,9 @@ impl PhysicalRelExpr {
         if let PhysicalRelExpr::Select { src, .. }
         | PhysicalRelExpr::Project { src, .. }
         | PhysicalRelExpr::Sort { src, .. }
+        | PhysicalRelExpr::Limit { src, .. }
+        | PhysicalRelExpr::Distinct { src, .. }
+        | PhysicalRelExpr::EnforceSingleRow { src, .. }
         | PhysicalRelExpr::HashAggregate { src, .. }
         | PhysicalRelExpr::Map { input: src, .. }
         | PhysicalRelExpr::FlatMap { input: src, .. }
@@ -687,7 +1104,14 @@ impl PhysicalRelExpr {
         if let PhysicalRelExpr::CrossJoin { left, right, .. }
         | PhysicalRelExpr::NestedLoopJoin { left, right, .. }
         | PhysicalRelExpr::HashJoin { left, right, .. }
-        | PhysicalRelExpr::SortMergeJoin { left, right, .. } = self
+        | PhysicalRelExpr::SortMergeJoin { left, right, .. }
+        | PhysicalRelExpr::UnionAll { left, right, .. }
+        | PhysicalRelExpr::HashSetOp { left, right, .. }
+        | PhysicalRelExpr::RecursiveUnion {
+            base: left,
+            recursive: right,
+            ..
+        } = self
         {
             left.get_tables_involved(container_ids);
             right.get_tables_involved(container_ids);
@@ -708,6 +1132,14 @@ impl PhysicalRelExpr {
             | PhysicalRelExpr::SortMergeJoin { tree_hash, .. }
             | PhysicalRelExpr::Project { tree_hash, .. }
             | PhysicalRelExpr::Sort { tree_hash, .. }
+            | PhysicalRelExpr::Limit { tree_hash, .. }
+            | PhysicalRelExpr::Distinct { tree_hash, .. }
+            | PhysicalRelExpr::EnforceSingleRow { tree_hash, .. }
+            | PhysicalRelExpr::UnionAll { tree_hash, .. }
+            | PhysicalRelExpr::HashSetOp { tree_hash, .. }
+            | PhysicalRelExpr::RecursiveUnion { tree_hash, .. }
+            | PhysicalRelExpr::WorkTableScan { tree_hash, .. }
+            | PhysicalRelExpr::SingleRow { tree_hash }
             | PhysicalRelExpr::HashAggregate { tree_hash, .. }
             | PhysicalRelExpr::Map { tree_hash, .. }
             | PhysicalRelExpr::FlatMap { tree_hash, .. }
@@ -729,6 +1161,14 @@ impl PhysicalRelExpr {
             | PhysicalRelExpr::SortMergeJoin { tree_hash, .. }
             | PhysicalRelExpr::Project { tree_hash, .. }
             | PhysicalRelExpr::Sort { tree_hash, .. }
+            | PhysicalRelExpr::Limit { tree_hash, .. }
+            | PhysicalRelExpr::Distinct { tree_hash, .. }
+            | PhysicalRelExpr::EnforceSingleRow { tree_hash, .. }
+            | PhysicalRelExpr::UnionAll { tree_hash, .. }
+            | PhysicalRelExpr::HashSetOp { tree_hash, .. }
+            | PhysicalRelExpr::RecursiveUnion { tree_hash, .. }
+            | PhysicalRelExpr::WorkTableScan { tree_hash, .. }
+            | PhysicalRelExpr::SingleRow { tree_hash }
             | PhysicalRelExpr::HashAggregate { tree_hash, .. }
             | PhysicalRelExpr::Map { tree_hash, .. }
             | PhysicalRelExpr::FlatMap { tree_hash, .. }
@@ -879,6 +1319,94 @@ impl PhysicalRelExpr {
                 self.set_tree_hash(res)?;
                 Ok(res)
             }
+            PhysicalRelExpr::Limit {
+                src, limit, offset, ..
+            } => {
+                let src_hash = src.hash_node(Some(rename_map))?;
+                // tagged so that a limit is never mistaken for another node with the same fields
+                let limit_hash = compute_hash(&format!("limit{:?}offset{}", limit, offset));
+                // set and pass up subtree's hash value
+                let res = src_hash ^ limit_hash;
+                self.set_tree_hash(res)?;
+                Ok(res)
+            }
+            PhysicalRelExpr::Distinct { src, .. } => {
+                let src_hash = src.hash_node(Some(rename_map))?;
+                let distinct_hash = compute_hash("distinct");
+                // set and pass up subtree's hash value
+                let res = src_hash ^ distinct_hash;
+                self.set_tree_hash(res)?;
+                Ok(res)
+            }
+            PhysicalRelExpr::EnforceSingleRow { src, .. } => {
+                let src_hash = src.hash_node(Some(rename_map))?;
+                let single_row_hash = compute_hash("enforce_single_row");
+                // set and pass up subtree's hash value
+                let res = src_hash ^ single_row_hash;
+                self.set_tree_hash(res)?;
+                Ok(res)
+            }
+            PhysicalRelExpr::UnionAll { left, right, .. } => {
+                let left_hash = left.hash_node(Some(rename_map))?;
+                let right_hash = right.hash_node(Some(rename_map))?;
+                let set_op_hash = compute_hash(&format!("{}", SetOpType::UnionAll));
+                // set and pass up subtree's hash value
+                let res = set_op_hash ^ left_hash ^ right_hash;
+                self.set_tree_hash(res)?;
+                Ok(res)
+            }
+            PhysicalRelExpr::HashSetOp {
+                set_op,
+                left,
+                right,
+                ..
+            } => {
+                let left_hash = left.hash_node(Some(rename_map))?;
+                let right_hash = right.hash_node(Some(rename_map))?;
+                let set_op_hash = compute_hash(&format!("{}", set_op));
+                // XOR is commutative so the order of the sides is hashed in for EXCEPT
+                let res = if set_op.is_commutative() {
+                    set_op_hash ^ left_hash ^ right_hash
+                } else {
+                    set_op_hash ^ compute_hash(&format!("{}{}", left_hash, right_hash))
+                };
+                // set and pass up subtree's hash value
+                self.set_tree_hash(res)?;
+                Ok(res)
+            }
+            PhysicalRelExpr::RecursiveUnion {
+                set_op,
+                base,
+                recursive,
+                work_table,
+                ..
+            } => {
+                let base_hash = base.hash_node(Some(rename_map))?;
+                let recursive_hash = recursive.hash_node(Some(rename_map))?;
+                let set_op_hash = compute_hash(&format!("recursive_{}#{}", set_op, work_table));
+                // the base and the recursive side are not interchangeable
+                let res = set_op_hash ^ compute_hash(&format!("{}{}", base_hash, recursive_hash));
+                // set and pass up subtree's hash value
+                self.set_tree_hash(res)?;
+                Ok(res)
+            }
+            PhysicalRelExpr::WorkTableScan { id, cols, .. } => {
+                // rename col_id aliases and sort for consistency across queries
+                let mut renamed_cols: Vec<ColumnId> = cols
+                    .iter()
+                    .map(|col_id| *rename_map.get(col_id).unwrap_or(col_id))
+                    .collect();
+                renamed_cols.sort();
+                let res = compute_hash(&format!("work_table#{}{:?}", id, renamed_cols));
+                // set and pass up subtree's hash value
+                self.set_tree_hash(res)?;
+                Ok(res)
+            }
+            PhysicalRelExpr::SingleRow { .. } => {
+                let res = compute_hash("single_row");
+                self.set_tree_hash(res)?;
+                Ok(res)
+            }
             PhysicalRelExpr::Rename {
                 src, src_to_dest, ..
             } => {
@@ -943,12 +1471,17 @@ impl PhysicalRelExpr {
             let node = queue.pop_front().unwrap();
             // check tree + children root hashes to find match
             match node {
-                PhysicalRelExpr::Scan { tree_hash, .. } => {
+                PhysicalRelExpr::Scan { tree_hash, .. }
+                | PhysicalRelExpr::WorkTableScan { tree_hash, .. }
+                | PhysicalRelExpr::SingleRow { tree_hash } => {
                     hashes.push((tree_hash.unwrap(), node));
                 }
                 PhysicalRelExpr::Select { src, tree_hash, .. }
                 | PhysicalRelExpr::Project { src, tree_hash, .. }
                 | PhysicalRelExpr::Sort { src, tree_hash, .. }
+                | PhysicalRelExpr::Limit { src, tree_hash, .. }
+                | PhysicalRelExpr::Distinct { src, tree_hash }
+                | PhysicalRelExpr::EnforceSingleRow { src, tree_hash }
                 | PhysicalRelExpr::Rename { src, tree_hash, .. }
                 | PhysicalRelExpr::HashAggregate { src, tree_hash, .. } => {
                     hashes.push((tree_hash.unwrap(), node));
@@ -978,6 +1511,23 @@ impl PhysicalRelExpr {
                     right,
                     tree_hash,
                     ..
+                }
+                | PhysicalRelExpr::UnionAll {
+                    left,
+                    right,
+                    tree_hash,
+                }
+                | PhysicalRelExpr::HashSetOp {
+                    left,
+                    right,
+                    tree_hash,
+                    ..
+                }
+                | PhysicalRelExpr::RecursiveUnion {
+                    base: left,
+                    recursive: right,
+                    tree_hash,
+                    ..
                 } => {
                     hashes.push((tree_hash.unwrap(), node));
                     // add next level to back of queue
@@ -1008,7 +1558,9 @@ impl PhysicalRelExpr {
             let node = queue.pop_front().unwrap();
             // check tree + children root hashes to find match
             match node {
-                PhysicalRelExpr::Scan { tree_hash, .. } => {
+                PhysicalRelExpr::Scan { tree_hash, .. }
+                | PhysicalRelExpr::WorkTableScan { tree_hash, .. }
+                | PhysicalRelExpr::SingleRow { tree_hash } => {
                     if tree_hash.unwrap() == hash_val {
                         return Ok(Some(node));
                     }
@@ -1017,6 +1569,9 @@ impl PhysicalRelExpr {
                 PhysicalRelExpr::Select { src, tree_hash, .. }
                 | PhysicalRelExpr::Project { src, tree_hash, .. }
                 | PhysicalRelExpr::Sort { src, tree_hash, .. }
+                | PhysicalRelExpr::Limit { src, tree_hash, .. }
+                | PhysicalRelExpr::Distinct { src, tree_hash }
+                | PhysicalRelExpr::EnforceSingleRow { src, tree_hash }
                 | PhysicalRelExpr::Rename { src, tree_hash, .. } => {
                     if tree_hash.unwrap() == hash_val {
                         return Ok(Some(node));
@@ -1046,6 +1601,23 @@ impl PhysicalRelExpr {
                     right,
                     tree_hash,
                     ..
+                }
+                | PhysicalRelExpr::UnionAll {
+                    left,
+                    right,
+                    tree_hash,
+                }
+                | PhysicalRelExpr::HashSetOp {
+                    left,
+                    right,
+                    tree_hash,
+                    ..
+                }
+                | PhysicalRelExpr::RecursiveUnion {
+                    base: left,
+                    recursive: right,
+                    tree_hash,
+                    ..
                 } => {
                     if tree_hash.unwrap() == hash_val {
                         return Ok(Some(node));
@@ -1119,10 +1691,15 @@ impl PhysicalRelExpr {
             }
 
             match node {
-                PhysicalRelExpr::Scan { .. } => {}
+                PhysicalRelExpr::Scan { .. }
+                | PhysicalRelExpr::WorkTableScan { .. }
+                | PhysicalRelExpr::SingleRow { .. } => {}
                 PhysicalRelExpr::Select { src, .. }
                 | PhysicalRelExpr::Project { src, .. }
                 | PhysicalRelExpr::Sort { src, .. }
+                | PhysicalRelExpr::Limit { src, .. }
+                | PhysicalRelExpr::Distinct { src, .. }
+                | PhysicalRelExpr::EnforceSingleRow { src, .. }
                 | PhysicalRelExpr::Rename { src, .. }
                 | PhysicalRelExpr::HashAggregate { src, .. } => {
                     queue.push_back(src);
@@ -1130,7 +1707,14 @@ impl PhysicalRelExpr {
                 PhysicalRelExpr::CrossJoin { left, right, .. }
                 | PhysicalRelExpr::NestedLoopJoin { left, right, .. }
                 | PhysicalRelExpr::HashJoin { left, right, .. }
-                | PhysicalRelExpr::SortMergeJoin { left, right, .. } => {
+                | PhysicalRelExpr::SortMergeJoin { left, right, .. }
+                | PhysicalRelExpr::UnionAll { left, right, .. }
+                | PhysicalRelExpr::HashSetOp { left, right, .. }
+                | PhysicalRelExpr::RecursiveUnion {
+                    base: left,
+                    recursive: right,
+                    ..
+                } => {
                     queue.push_back(left);
                     queue.push_back(right);
                 }
@@ -5,7 +5,7 @@
 mod test {
     use crate::{
         physical_expr::physical_rel_expr::PhysicalRelExpr, query::expr::Expression,
-        query::join_type::JoinType, AggOp,
+        query::join_type::JoinType, query::set_op_type::SetOpType, AggOp,
     };
 
     #[test]
@@ -134,6 +134,98 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_limit_trees() {
+        let scan = PhysicalRelExpr::Scan {
+            cid: 1,
+            table_name: "test_table".to_string(),
+            column_names: vec![2, 3, 4],
+            tree_hash: None,
+        };
+        let limit = |limit: Option<usize>, offset: usize| PhysicalRelExpr::Limit {
+            src: Box::new(scan.clone()),
+            limit,
+            offset,
+            tree_hash: None,
+        };
+
+        // compute Merkle hash for the trees
+        let scan_hash = scan.clone().hash_plan().unwrap();
+        let hash1 = limit(Some(10), 0).hash_plan().unwrap();
+        let hash2 = limit(Some(10), 0).hash_plan().unwrap();
+        let hash3 = limit(Some(5), 0).hash_plan().unwrap();
+        let hash4 = limit(Some(10), 5).hash_plan().unwrap();
+        let hash5 = limit(None, 10).hash_plan().unwrap();
+
+        assert_eq!(
+            hash1, hash2,
+            "merkle hashes should be identical for identical trees"
+        );
+        assert_ne!(
+            hash1, scan_hash,
+            "limit should change the hash of its source"
+        );
+        assert_ne!(
+            hash1, hash3,
+            "different limits should have different hashes"
+        );
+        assert_ne!(
+            hash1, hash4,
+            "different offsets should have different hashes"
+        );
+        assert_ne!(
+            hash1, hash5,
+            "limit and offset should not be interchangeable in the hash"
+        );
+    }
+
+    #[test]
+    fn test_set_op_trees() {
+        let scan = |cid| PhysicalRelExpr::Scan {
+            cid,
+            table_name: format!("test_table_{}", cid),
+            column_names: vec![2, 3, 4],
+            tree_hash: None,
+        };
+        let set_op = |set_op: SetOpType, left, right| PhysicalRelExpr::HashSetOp {
+            set_op,
+            left: Box::new(scan(left)),
+            right: Box::new(scan(right)),
+            tree_hash: None,
+        };
+
+        // compute Merkle hash for the trees
+        let union_hash = set_op(SetOpType::Union, 1, 2).hash_plan().unwrap();
+        let union_swapped_hash = set_op(SetOpType::Union, 2, 1).hash_plan().unwrap();
+        let intersect_hash = set_op(SetOpType::Intersect, 1, 2).hash_plan().unwrap();
+        let except_hash = set_op(SetOpType::Except, 1, 2).hash_plan().unwrap();
+        let except_swapped_hash = set_op(SetOpType::Except, 2, 1).hash_plan().unwrap();
+        let union_all_hash = PhysicalRelExpr::UnionAll {
+            left: Box::new(scan(1)),
+            right: Box::new(scan(2)),
+            tree_hash: None,
+        }
+        .hash_plan()
+        .unwrap();
+
+        assert_eq!(
+            union_hash, union_swapped_hash,
+            "union is commutative so the order of the children should not matter"
+        );
+        assert_ne!(
+            except_hash, except_swapped_hash,
+            "except is not commutative so the order of the children should matter"
+        );
+        assert_ne!(
+            union_hash, intersect_hash,
+            "different set operations should have different hashes"
+        );
+        assert_ne!(
+            union_hash, union_all_hash,
+            "union and union all should have different hashes"
+        );
+    }
+
     #[test]
     fn test_flatmap_identical_trees() {
         // two identical flatmap trees
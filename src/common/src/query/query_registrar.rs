@@ -251,9 +251,11 @@ impl QueryStateRegistrar {
         let filename = {
             // can't hold read and write locks so change scope
             let qr_map = self.query_result_filenames.read().unwrap();
-            qr_map.get(query_name).unwrap().clone()
+            qr_map.get(query_name).cloned()
         };
-        fs::remove_file(filename)?;
+        if let Some(filename) = filename {
+            fs::remove_file(filename)?;
+        }
         self.query_result_filenames
             .write()
             .unwrap()
@@ -270,6 +272,44 @@ impl QueryStateRegistrar {
         Ok(())
     }
 
+    /// Purge every registered query whose plan reads the given container.
+    /// Returns the names of the purged queries.
+    ///
+    /// # Arguments
+    ///
+    /// * `c_id` - Container that is dropped or whose contents are discarded.
+    pub fn purge_queries_with_table(&self, c_id: ContainerId) -> Result<Vec<String>, CrustyError> {
+        let query_names: Vec<String> = self
+            .query_plans
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, pp)| {
+                let mut container_ids = Vec::new();
+                pp.get_tables_involved(&mut container_ids);
+                container_ids.contains(&c_id)
+            })
+            .map(|(query_name, _)| query_name.clone())
+            .collect();
+        for query_name in &query_names {
+            if self
+                .in_progress_queries
+                .read()
+                .unwrap()
+                .contains_key(query_name)
+            {
+                return Err(CrustyError::CrustyError(format!(
+                    "Query \"{}\" is in progress.",
+                    query_name
+                )));
+            }
+        }
+        for query_name in &query_names {
+            self.purge_query_with_name(query_name)?;
+        }
+        Ok(query_names)
+    }
+
     pub fn get_query_name_from_sql(&self, sql: &String) -> Result<Option<String>, CrustyError> {
         let sql_to_name_map = self.sql_to_query_name.read().unwrap();
         let name = sql_to_name_map.get(sql);
@@ -23,6 +23,14 @@ pub enum QueryResult {
         inserted: usize,
         table_name: String,
     },
+    Delete {
+        deleted: usize,
+        table_name: String,
+    },
+    Update {
+        updated: usize,
+        table_name: String,
+    },
 }
 
 impl QueryResult {
@@ -49,6 +57,20 @@ impl QueryResult {
         }
     }
 
+    pub fn new_delete_result(deleted: usize, table_name: String) -> Self {
+        QueryResult::Delete {
+            deleted,
+            table_name,
+        }
+    }
+
+    pub fn new_update_result(updated: usize, table_name: String) -> Self {
+        QueryResult::Update {
+            updated,
+            table_name,
+        }
+    }
+
     pub fn get_tuples(&self) -> Option<&Vec<Tuple>> {
         match self {
             QueryResult::Select { result, .. } => Some(result),
@@ -0,0 +1,29 @@
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Copy)]
+pub enum SetOpType {
+    Union,
+    UnionAll,
+    Intersect,
+    IntersectAll,
+    Except,
+    ExceptAll,
+}
+
+impl SetOpType {
+    /// Returns true if the result does not depend on the order of the inputs.
+    pub fn is_commutative(&self) -> bool {
+        !matches!(self, SetOpType::Except | SetOpType::ExceptAll)
+    }
+}
+
+impl std::fmt::Display for SetOpType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SetOpType::Union => write!(f, "union"),
+            SetOpType::UnionAll => write!(f, "union_all"),
+            SetOpType::Intersect => write!(f, "intersect"),
+            SetOpType::IntersectAll => write!(f, "intersect_all"),
+            SetOpType::Except => write!(f, "except"),
+            SetOpType::ExceptAll => write!(f, "except_all"),
+        }
+    }
+}
@@ -19,6 +19,23 @@ impl TableInfo {
     }
 }
 
+/// Non-materialized view, expanded by the translator wherever it is referenced.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ViewInfo {
+    /// View name.
+    pub name: String,
+    /// Names of the columns of the view. Empty if the names come from the query.
+    pub columns: Vec<String>,
+    /// SQL text of the query defining the view.
+    pub sql: String,
+}
+
+impl ViewInfo {
+    pub fn new(name: String, columns: Vec<String>, sql: String) -> Self {
+        ViewInfo { name, columns, sql }
+    }
+}
+
 /// Handle schemas.
 #[derive(Default, PartialEq, Eq, Clone, Debug)]
 pub struct TableSchema {
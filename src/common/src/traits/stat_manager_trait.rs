@@ -14,6 +14,9 @@ pub trait StatManagerTrait {
 
     fn register_table(&self, c_id: ContainerId, schema: TableSchema) -> Result<(), CrustyError>;
 
+    /// Forget everything known about the container (e.g. when the table is dropped).
+    fn deregister_table(&self, c_id: ContainerId) -> Result<(), CrustyError>;
+
     fn updated_record(
         &self,
         tuple: &Tuple,
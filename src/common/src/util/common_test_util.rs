@@ -80,6 +80,7 @@ pub fn gen_random_tuples_fixed(
         name: String::from("id"),
         dtype: DataType::BigInt,
         constraint: crate::Constraint::PrimaryKey,
+        default: None,
     });
 
     let fixed_dtypes = [
@@ -92,6 +93,7 @@ pub fn gen_random_tuples_fixed(
             name: format!("ia{}", i),
             dtype: fixed_dtypes.choose(rng).unwrap().clone(),
             constraint: crate::Constraint::None,
+            default: None,
         };
         attributes.push(attr);
     }
@@ -111,6 +113,7 @@ pub fn gen_random_tuples_var_and_null(
         name: String::from("id"),
         dtype: DataType::BigInt,
         constraint: crate::Constraint::PrimaryKey,
+        default: None,
     });
 
     let fixed_dtypes = [
@@ -124,6 +127,7 @@ pub fn gen_random_tuples_var_and_null(
             name: format!("ia{}", i),
             dtype: fixed_dtypes.choose(rng).unwrap().clone(),
             constraint: crate::Constraint::None,
+            default: None,
         };
         attributes.push(attr);
     }
@@ -233,6 +237,7 @@ pub fn gen_table_for_test_tuples(c_id: ContainerId, table_name: String) -> Table
         name: String::from("id"),
         dtype: DataType::BigInt,
         constraint: crate::Constraint::PrimaryKey,
+        default: None,
     };
     attributes.push(pk_attr);
 
@@ -241,6 +246,7 @@ pub fn gen_table_for_test_tuples(c_id: ContainerId, table_name: String) -> Table
             name: format!("ia{}", n),
             dtype: DataType::BigInt,
             constraint: crate::Constraint::None,
+            default: None,
         };
         attributes.push(attr);
     }
@@ -249,6 +255,7 @@ pub fn gen_table_for_test_tuples(c_id: ContainerId, table_name: String) -> Table
             name: format!("sa{}", n),
             dtype: DataType::String,
             constraint: crate::Constraint::None,
+            default: None,
         };
         attributes.push(attr);
     }
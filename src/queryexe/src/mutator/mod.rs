@@ -3,29 +3,32 @@ use crate::Managers;
 use common::{
     datatypes::{default_decimal_precision, default_decimal_scale},
     prelude::*,
+    query::bytecode_expr::Cast,
     traits::storage_trait::StorageTrait,
     traits::{stat_manager_trait::StatManagerTrait, state_tracker_trait::StateTrackerTrait},
     tuple::ConvertedResult,
     ConversionError,
 };
-use sqlparser::ast::{Value, Values};
+use sqlparser::ast::{Expr, UnaryOperator, Value, Values};
 
+/// Inserts validated tuples into a table, setting their `value_id`.
 pub(crate) fn insert_validated_tuples(
     table_id: ContainerId,
-    tuples: &Vec<Tuple>,
+    tuples: &mut [Tuple],
     txn_id: TransactionId,
     managers: &'static Managers,
 ) -> Result<usize, CrustyError> {
     let mut tuples_bytes = Vec::new();
-    for t in tuples {
+    for t in tuples.iter() {
         tuples_bytes.push(t.to_bytes());
     }
     let inserted = managers.sm.insert_values(table_id, tuples_bytes, txn_id);
     info!("TODO call tm and im for insert_values");
     let insert_count = inserted.len();
     if insert_count == tuples.len() {
-        for (t, v) in tuples.iter().zip(inserted.iter()) {
+        for (t, v) in tuples.iter_mut().zip(inserted.iter()) {
             managers.stats.new_record(t, *v)?;
+            t.value_id = Some(*v);
         }
         managers.stats.set_ts(table_id, txn_id.id());
         Ok(insert_count)
@@ -38,6 +41,33 @@ pub(crate) fn insert_validated_tuples(
     }
 }
 
+/// Check that a non-null field can be stored in an attribute of the given dtype
+pub fn is_valid_type(dtype: &DataType, field: &Field) -> bool {
+    matches!(
+        (dtype, field),
+        (DataType::BigInt, Field::BigInt(_))
+            | (DataType::String, Field::String(_))
+            | (DataType::Date, Field::Date(_))
+            | (DataType::Decimal(_, _), Field::Decimal(_, _))
+            | (DataType::Bool, Field::Bool(_))
+    )
+}
+
+/// Convert a field to the representation used by an attribute of the given dtype,
+/// following the implicit coercions of `DataType::common_type`. Numbers are also
+/// converted to the precision and scale of decimal attributes. Fields that do not
+/// convert, e.g. because they are out of range, are returned unchanged for validation
+/// to reject.
+pub fn coerce_field(field: Field, dtype: &DataType) -> Field {
+    let from = DataType::from(&field);
+    let convertible =
+        from.coerces_to(dtype) || (from.is_numeric() && matches!(dtype, DataType::Decimal(..)));
+    if from == *dtype || !convertible {
+        return field;
+    }
+    field.cast(dtype).unwrap_or(field)
+}
+
 /// Check new or updated records to ensure that they do not break any constraints
 pub(crate) fn validate_tuples(
     _table_id: &ContainerId,
@@ -46,53 +76,72 @@ pub(crate) fn validate_tuples(
     mut values: ConvertedResult,
     _txn_id: &TransactionId,
 ) -> Result<ConvertedResult, CrustyError> {
-    if col_order.is_some() {
-        return Err(CrustyError::CrustyError(String::from(
-            "Col ordering not supported",
+    let expected_len = col_order
+        .as_ref()
+        .map_or(schema.size(), |order| order.len());
+    if let Some((i, rec)) = values
+        .converted
+        .iter()
+        .enumerate()
+        .find(|(_, rec)| rec.field_vals.len() != expected_len)
+    {
+        return Err(CrustyError::ValidationError(format!(
+            "Record {} has {} values, expected {}",
+            i,
+            rec.field_vals.len(),
+            expected_len
         )));
     }
+    if let Some(col_order) = col_order {
+        // Expand the records to the full schema. Omitted columns get their default (or NULL)
+        for rec in values.converted.iter_mut() {
+            let mut fields: Vec<Field> = schema
+                .attributes()
+                .map(|attr| attr.default().cloned().unwrap_or(Field::Null))
+                .collect();
+            for (field, idx) in rec.field_vals.drain(..).zip(col_order.iter()) {
+                fields[*idx] = field;
+            }
+            rec.field_vals = fields;
+        }
+    }
     let mut values_to_remove: Vec<(usize, Vec<ConversionError>)> = Vec::new();
     warn!("PK, FK, Unique constraints not checked");
-    for (i, rec) in values.converted.iter().enumerate() {
-        for (j, (field, attr)) in (rec.field_vals()).zip(schema.attributes()).enumerate() {
+    for (i, rec) in values.converted.iter_mut().enumerate() {
+        let mut errors = Vec::new();
+        for (j, (field, attr)) in rec
+            .field_vals
+            .iter_mut()
+            .zip(schema.attributes())
+            .enumerate()
+        {
+            *field = coerce_field(std::mem::replace(field, Field::Null), &attr.dtype);
             if let Field::Null = field {
                 match attr.constraint {
                     common::Constraint::NotNull
                     | common::Constraint::UniqueNotNull
                     | common::Constraint::PrimaryKey
                     | common::Constraint::NotNullFKey(_) => {
-                        values_to_remove.push((i, vec![ConversionError::NullFieldNotAllowed(j)]));
+                        errors.push(ConversionError::NullFieldNotAllowed(j));
                     }
-                    _ => continue, // Null value so nothing to check
+                    _ => {}
                 }
+                continue; // Null value so nothing to check
             }
-            match (&attr.dtype, field) {
-                (DataType::BigInt, Field::BigInt(_v)) => {
-                    // Nothing for now
-                }
-                (DataType::String, Field::String(_v)) => {
-                    // Nothing for now
-                }
-                (DataType::Date, Field::Date(_v)) => {
-                    // Nothing for now
-                }
-                (DataType::Decimal(_, _), Field::Decimal(_, _)) => {
-                    // Nothing for now
-                }
-                (DataType::Bool, Field::Bool(_v)) => {
-                    // Nothing for now
-                }
-                _ => {
-                    debug!("Wrong field: {} for attr type: {}", field, &attr.dtype);
-                    values_to_remove.push((i, vec![ConversionError::WrongType]));
-                }
+            if !is_valid_type(&attr.dtype, field) {
+                debug!("Wrong field: {} for attr type: {}", field, &attr.dtype);
+                errors.push(ConversionError::WrongType);
             }
         }
+        if !errors.is_empty() {
+            values_to_remove.push((i, errors));
+        }
     }
     // Remove in reverse order records that were invalid
     for i in values_to_remove.iter().rev() {
         values.converted.remove(i.0);
     }
+    values.unconverted.extend(values_to_remove);
     Ok(values)
 }
 
@@ -106,51 +155,69 @@ pub(crate) fn convert_insert_vals(values: &Values) -> Result<ConvertedResult, Cr
     for (i, val) in values.rows.iter().enumerate() {
         let mut fields = Vec::new();
         for field in val {
-            if let sqlparser::ast::Expr::Value(value) = field {
-                match value {
-                    Value::Number(val, _long) => {
-                        let parts = val.split('.').collect::<Vec<&str>>();
-                        if parts.len() > 2 {
-                            res.unconverted.push((i, vec![ConversionError::ParseError]));
-                        } else if parts.len() == 2 {
-                            if let Ok(field) = Field::from_str_to_decimal(
-                                val,
-                                default_decimal_precision(),
-                                default_decimal_scale(),
-                            ) {
-                                fields.push(field);
-                            } else {
-                                res.unconverted.push((i, vec![ConversionError::ParseError]));
-                            }
-                        } else if let Ok(i) = Field::parse_int_from_str::<i64>(val) {
-                            //TODO merged - add schema and check type
-                            fields.push(Field::BigInt(i));
-                        } else {
-                            res.unconverted.push((i, vec![ConversionError::ParseError]));
-                        }
-                    }
-                    Value::DoubleQuotedString(val) | Value::SingleQuotedString(val) => {
-                        if let Ok(field) = Field::from_str_to_string(val) {
-                            fields.push(field);
-                        } else {
-                            res.unconverted.push((i, vec![ConversionError::ParseError]));
-                        }
-                    }
-                    Value::Null => {
-                        fields.push(Field::Null);
-                    }
-                    _ => {
-                        res.unconverted
-                            .push((i, vec![ConversionError::UnsupportedType]));
-                    }
+            match convert_literal(field) {
+                Some(Ok(field)) => fields.push(field),
+                Some(Err(e)) => res.unconverted.push((i, vec![e])),
+                None => {
+                    return Err(CrustyError::CrustyError(String::from(
+                        "Only values supported in insert",
+                    )));
                 }
-            } else {
-                return Err(CrustyError::CrustyError(String::from(
-                    "Only values supported in insert",
-                )));
             }
         }
         res.converted.push(Tuple::new(fields));
     }
     Ok(res)
 }
+
+/// Convert a literal expression, including a negated number such as `-1.5`, which the
+/// parser does not fold into the literal, and a date such as `DATE '2024-01-31'`.
+/// Returns None for any other expression.
+pub fn convert_literal(expr: &Expr) -> Option<Result<Field, ConversionError>> {
+    match expr {
+        Expr::Value(value) => Some(convert_value(value)),
+        Expr::TypedString {
+            data_type: sqlparser::ast::DataType::Date,
+            value,
+        } => Some(Field::from_str_to_date(value).map_err(|_| ConversionError::ParseError)),
+        Expr::UnaryOp {
+            op: UnaryOperator::Minus,
+            expr,
+        } => match expr.as_ref() {
+            Expr::Value(value @ Value::Number(..)) => Some(
+                convert_value(value).and_then(|val| (-val).map_err(|_| ConversionError::WrongType)),
+            ),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Convert a single SQL literal to its internal representation
+pub fn convert_value(value: &Value) -> Result<Field, ConversionError> {
+    match value {
+        Value::Number(val, _long) => {
+            let parts = val.split('.').collect::<Vec<&str>>();
+            if parts.len() > 2 {
+                Err(ConversionError::ParseError)
+            } else if parts.len() == 2 {
+                Field::from_str_to_decimal(
+                    val,
+                    default_decimal_precision(),
+                    default_decimal_scale(),
+                )
+                .map_err(|_| ConversionError::ParseError)
+            } else {
+                //TODO merged - add schema and check type
+                Field::parse_int_from_str::<i64>(val)
+                    .map(Field::BigInt)
+                    .map_err(|_| ConversionError::ParseError)
+            }
+        }
+        Value::DoubleQuotedString(val) | Value::SingleQuotedString(val) => {
+            Field::from_str_to_string(val).map_err(|_| ConversionError::ParseError)
+        }
+        Value::Null => Ok(Field::Null),
+        _ => Err(ConversionError::UnsupportedType),
+    }
+}
@@ -5,7 +5,7 @@ use common::datatypes::f_decimal; // For generating a decimal field
 use common::query::bytecode_expr::ByteCodeExpr;
 use common::{AggOp, CrustyError, Field, TableSchema, Tuple};
 use std::cmp::{max, min};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Aggregate operator. (You can add any other fields that you think are neccessary)
 pub struct Aggregate {
@@ -32,6 +32,8 @@ pub struct Aggregate {
     open: bool,
     /// Accumulator for the aggregation. Key:groupby values. Value: (count, aggregated values).
     acc: HashMap<Vec<Field>, (usize, Vec<Field>)>, // groupby values -> (count, aggregate values)
+    /// Values already aggregated by the DISTINCT aggregations. Key: (groupby values, aggregation index, value).
+    distinct_acc: HashSet<(Vec<Field>, usize, Field)>,
     /// Accumulator iter
     acc_iter: Vec<Tuple>,
     /// Index of the current tuple in the accumulator iter
@@ -69,6 +71,7 @@ impl Aggregate {
             child,
             will_rewind: true,
             acc: HashMap::new(),
+            distinct_acc: HashSet::new(),
             acc_iter: Vec::new(),
             index: 0,
         }
@@ -84,7 +87,7 @@ impl Aggregate {
     /// * `acc` - Current accumulated value.
     fn merge_fields(op: AggOp, field_val: &Field, acc: &mut Field) -> Result<(), CrustyError> {
         match op {
-            AggOp::Count => *acc = (acc.clone() + Field::BigInt(1))?,
+            AggOp::Count | AggOp::CountDistinct => *acc = (acc.clone() + Field::BigInt(1))?,
             AggOp::Max => {
                 let max = max(acc.clone(), field_val.clone());
                 *acc = max;
@@ -93,7 +96,7 @@ impl Aggregate {
                 let min = min(acc.clone(), field_val.clone());
                 *acc = min;
             }
-            AggOp::Sum => {
+            AggOp::Sum | AggOp::SumDistinct => {
                 *acc = (acc.clone() + field_val.clone())?;
             }
             AggOp::Avg => {
@@ -103,6 +106,29 @@ impl Aggregate {
         Ok(())
     }
 
+    /// Returns true if aggregation `i` is a DISTINCT aggregation (e.g. COUNT(DISTINCT x)) that
+    /// has already aggregated `field_val` in the group, in which case the value must be skipped.
+    /// Records `field_val` as aggregated otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `group_key` - Groupby values of the tuple.
+    /// * `i` - Index of the aggregation.
+    /// * `field_val` - Value of the aggregated field in the tuple.
+    pub fn is_distinct_duplicate(
+        &mut self,
+        group_key: &[Field],
+        i: usize,
+        field_val: &Field,
+    ) -> bool {
+        if !self.ops[i].is_distinct() {
+            return false;
+        }
+        !self
+            .distinct_acc
+            .insert((group_key.to_vec(), i, field_val.clone()))
+    }
+
     /// Handles the creation of groups for aggregation.
     ///
     /// If a group exists, then merge the tuple into the group's accumulated value.
@@ -111,6 +137,7 @@ impl Aggregate {
     /// Hint: you are trying to update self.acc based on the group key and the agg_expr.
     /// Hint: self.agg_expr is a vec of `ByteCodeExpr`. For each one you can call eval(record: &Tuple) -> Field  
     /// this will give you the value of the field in the tuple to either `merge_fields` to the acc or to create a new group.
+    /// Hint: values for which `is_distinct_duplicate` returns true must be skipped by the DISTINCT aggregations.
     ///
     /// # Arguments
     ///
@@ -121,7 +148,7 @@ impl Aggregate {
             .groupby_expr
             .iter()
             .map(|expr| expr.eval(tuple))
-            .collect::<Vec<Field>>();
+            .collect::<Result<Vec<Field>, CrustyError>>();
         panic!("TODO milestone op");
     }
 }
@@ -159,6 +186,10 @@ impl OpIterator for Aggregate {
     fn get_schema(&self) -> &TableSchema {
         &self.schema
     }
+
+    fn state_size(&self) -> usize {
+        self.acc.len()
+    }
 }
 
 #[cfg(test)]
@@ -388,6 +419,30 @@ mod test {
             );
         }
 
+        #[test]
+        fn test_is_distinct_duplicate() {
+            let setup = TestTuples::new("");
+            let mut agg = Aggregate::new(
+                new_test_managers(),
+                vec![colidx_expr(1)],
+                vec![colidx_expr(2), colidx_expr(2)],
+                vec![AggOp::CountDistinct, AggOp::Count],
+                TableSchema::new(vec![]),
+                Box::new(TupleIterator::new(
+                    setup.tuples.clone(),
+                    setup.schema.clone(),
+                )),
+            );
+            let group = vec![f_int(1)];
+            let other_group = vec![f_int(2)];
+            assert!(!agg.is_distinct_duplicate(&group, 0, &f_int(3)));
+            assert!(agg.is_distinct_duplicate(&group, 0, &f_int(3)));
+            assert!(!agg.is_distinct_duplicate(&other_group, 0, &f_int(3)));
+            // Only DISTINCT aggregations skip duplicates
+            assert!(!agg.is_distinct_duplicate(&group, 1, &f_int(3)));
+            assert!(!agg.is_distinct_duplicate(&group, 1, &f_int(3)));
+        }
+
         #[test]
         #[should_panic]
         fn test_merge_tuples_not_int() {
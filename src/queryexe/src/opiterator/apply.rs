@@ -0,0 +1,206 @@
+use super::OpIterator;
+use common::{CrustyError, TableSchema, Tuple};
+
+/// Builds the opiterator of the correlated side for a tuple of the input.
+pub type ApplyFn = Box<dyn Fn(&Tuple) -> Result<Box<dyn OpIterator>, CrustyError>>;
+
+/// Apply operator (also called dependent join), used for correlated subqueries.
+///
+/// For each tuple of the input, an opiterator is built by `func` with the tuple's
+/// values bound, and the input tuple is merged with each of the tuples it returns.
+pub struct Apply {
+    // Parameters (No need to reset on close)
+    /// Schema of the result (input schema followed by the schema of `func`'s iterators).
+    schema: TableSchema,
+    /// Child operator passing data into operator.
+    input: Box<dyn OpIterator>,
+    /// Builds the correlated opiterator for an input tuple.
+    func: ApplyFn,
+
+    // States (Need to reset on close)
+    /// Boolean determining if iterator is open.
+    open: bool,
+    /// Current input tuple and the opiterator built for it.
+    current: Option<(Tuple, Box<dyn OpIterator>)>,
+}
+
+impl Apply {
+    /// Apply constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema` - Schema of the result.
+    /// * `input` - Child OpIterator passing data into the operator.
+    /// * `func` - Builds the correlated opiterator for an input tuple.
+    pub fn new(schema: TableSchema, input: Box<dyn OpIterator>, func: ApplyFn) -> Self {
+        Self {
+            schema,
+            input,
+            func,
+            open: false,
+            current: None,
+        }
+    }
+}
+
+impl OpIterator for Apply {
+    fn configure(&mut self, will_rewind: bool) {
+        self.input.configure(will_rewind);
+    }
+
+    fn open(&mut self) -> Result<(), CrustyError> {
+        if !self.open {
+            self.input.open()?;
+            self.open = true;
+        }
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<Tuple>, CrustyError> {
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        loop {
+            if let Some((input_tuple, func_iter)) = &mut self.current {
+                if let Some(t) = func_iter.next()? {
+                    return Ok(Some(input_tuple.merge(&t)));
+                }
+                func_iter.close()?;
+                self.current = None;
+            }
+            match self.input.next()? {
+                Some(t) => {
+                    let mut func_iter = (self.func)(&t)?;
+                    func_iter.configure(false);
+                    func_iter.open()?;
+                    self.current = Some((t, func_iter));
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+
+    fn close(&mut self) -> Result<(), CrustyError> {
+        if let Some((_, mut func_iter)) = self.current.take() {
+            func_iter.close()?;
+        }
+        self.input.close()?;
+        self.open = false;
+        Ok(())
+    }
+
+    fn rewind(&mut self) -> Result<(), CrustyError> {
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        if let Some((_, mut func_iter)) = self.current.take() {
+            func_iter.close()?;
+        }
+        self.input.rewind()
+    }
+
+    fn get_schema(&self) -> &TableSchema {
+        &self.schema
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::opiterator::TupleIterator;
+    use crate::testutil::execute_iter;
+    use crate::testutil::TestTuples;
+    use common::datatypes::f_int;
+    use common::{Attribute, DataType, Field};
+
+    /// Applies to each test tuple a function returning the tuples (a + i) for
+    /// i in 0..b, where a and b are the first two columns.
+    fn get_iter() -> Box<dyn OpIterator> {
+        let setup = TestTuples::new("");
+        let func_schema = TableSchema::new(vec![Attribute::new("x".to_string(), DataType::BigInt)]);
+        let schema = setup.schema.merge(&func_schema);
+        let func: ApplyFn = Box::new(move |t: &Tuple| {
+            let (a, b) = match (t.get_field(0), t.get_field(1)) {
+                (Some(Field::BigInt(a)), Some(Field::BigInt(b))) => (*a, *b),
+                _ => return Err(CrustyError::ExecutionError("Bad tuple".to_string())),
+            };
+            let tuples = (0..b).map(|i| Tuple::new(vec![f_int(a + i)])).collect();
+            Ok(Box::new(TupleIterator::new(tuples, func_schema.clone())) as Box<dyn OpIterator>)
+        });
+        let mut iter = Box::new(Apply::new(
+            schema,
+            Box::new(TupleIterator::new(
+                setup.tuples.clone(),
+                setup.schema.clone(),
+            )),
+            func,
+        ));
+        iter.configure(false);
+        iter
+    }
+
+    mod apply_test {
+        use super::*;
+
+        #[test]
+        fn test_apply() {
+            // Input:
+            // 1 1 3 E
+            // 2 1 3 G
+            // 3 1 4 A
+            // 4 2 4 G
+            // 5 2 5 G
+            // 6 2 5 G
+            let setup = TestTuples::new("");
+            let t = execute_iter(&mut *get_iter(), false).unwrap();
+            // Output: 1 row for each of the first 3 tuples, 2 rows for the others
+            assert_eq!(t.len(), 9);
+            assert_eq!(t[0], setup.tuples[0].merge(&Tuple::new(vec![f_int(1)])));
+            assert_eq!(t[3], setup.tuples[3].merge(&Tuple::new(vec![f_int(4)])));
+            assert_eq!(t[4], setup.tuples[3].merge(&Tuple::new(vec![f_int(5)])));
+            assert_eq!(t[8], setup.tuples[5].merge(&Tuple::new(vec![f_int(7)])));
+        }
+    }
+
+    mod opiterator_test {
+        use super::*;
+
+        #[test]
+        #[should_panic]
+        fn test_next_not_open() {
+            let mut iter = get_iter();
+            let _ = iter.next();
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_rewind_not_open() {
+            let mut iter = get_iter();
+            let _ = iter.rewind();
+        }
+
+        #[test]
+        fn test_open() {
+            let mut iter = get_iter();
+            iter.open().unwrap();
+        }
+
+        #[test]
+        fn test_close() {
+            let mut iter = get_iter();
+            iter.open().unwrap();
+            iter.close().unwrap();
+        }
+
+        #[test]
+        fn test_rewind() {
+            let mut iter = get_iter();
+            iter.configure(true);
+            let t_before = execute_iter(&mut *iter, false).unwrap();
+            iter.rewind().unwrap();
+            let t_after = execute_iter(&mut *iter, false).unwrap();
+            assert_eq!(t_before.len(), 9);
+            assert_eq!(t_before, t_after);
+        }
+    }
+}
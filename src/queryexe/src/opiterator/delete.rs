@@ -0,0 +1,268 @@
+use super::OpIterator;
+use crate::Managers;
+use common::error::c_err;
+use common::prelude::*;
+use common::traits::stat_manager_trait::StatManagerTrait;
+use common::traits::state_tracker_trait::StateTrackerTrait;
+use common::traits::storage_trait::StorageTrait;
+
+/// Delete operator. Removes every tuple produced by the child from the storage
+/// manager and passes the deleted tuple on.
+pub struct Delete {
+    // Static objects (No need to reset on close)
+    managers: &'static Managers,
+
+    // Parameters (No need to reset on close)
+    schema: TableSchema,
+    tid: TransactionId,
+    child: Box<dyn OpIterator>,
+
+    // States (Need to reset on close)
+    open: bool,
+    /// Tuples to delete. The child is fully consumed on open so that it never
+    /// reads the storage while records are being deleted.
+    buffer: Vec<Tuple>,
+    index: usize,
+}
+
+impl Delete {
+    /// Constructor for the delete operator.
+    ///
+    /// # Arguments
+    ///
+    /// * `managers` - Managers used to remove the records.
+    /// * `tid` - Transaction deleting the records.
+    /// * `child` - Child producing the tuples to delete. The tuples must carry their `value_id`.
+    pub fn new(
+        managers: &'static Managers,
+        tid: TransactionId,
+        child: Box<dyn OpIterator>,
+    ) -> Self {
+        Self {
+            managers,
+            schema: child.get_schema().clone(),
+            tid,
+            child,
+            open: false,
+            buffer: Vec::new(),
+            index: 0,
+        }
+    }
+}
+
+impl OpIterator for Delete {
+    fn configure(&mut self, _will_rewind: bool) {
+        // The child is consumed once on open
+        self.child.configure(false);
+    }
+
+    fn open(&mut self) -> Result<(), CrustyError> {
+        if !self.open {
+            self.child.open()?;
+            while let Some(tuple) = self.child.next()? {
+                self.buffer.push(tuple);
+            }
+            self.index = 0;
+            self.open = true;
+        }
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<Tuple>, CrustyError> {
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        if self.index >= self.buffer.len() {
+            return Ok(None);
+        }
+        let tuple = self.buffer[self.index].clone();
+        self.index += 1;
+        let id = match tuple.value_id {
+            Some(id) => id,
+            None => {
+                return Err(CrustyError::CrustyError(
+                    "No value id set for record. Cannot delete".to_string(),
+                ));
+            }
+        };
+
+        self.managers.sm.delete_value(id, self.tid)?;
+        self.managers.stats.deleted_record(&id)?;
+        self.managers.stats.set_ts(id.container_id, self.tid.id());
+        Ok(Some(tuple))
+    }
+
+    fn close(&mut self) -> Result<(), CrustyError> {
+        self.child.close()?;
+        self.buffer.clear();
+        self.index = 0;
+        self.open = false;
+        Ok(())
+    }
+
+    fn rewind(&mut self) -> Result<(), CrustyError> {
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        Err(c_err("Delete cannot be rewound"))
+    }
+
+    fn get_schema(&self) -> &TableSchema {
+        &self.schema
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{SeqScan, TupleIterator};
+    use super::*;
+    use crate::testutil::{execute_iter, new_test_managers, TestTuples};
+
+    const CID: ContainerId = 0;
+
+    /// Creates a table holding the test tuples. Returns the managers and a scan of the table.
+    fn get_scan() -> (&'static Managers, Box<dyn OpIterator>) {
+        let managers = new_test_managers();
+        managers.sm.create_table(CID).unwrap();
+        let setup = TestTuples::new("");
+        managers
+            .stats
+            .register_table(CID, setup.schema.clone())
+            .unwrap();
+
+        let tid = TransactionId::new();
+        for t in setup.tuples {
+            let id = managers.sm.insert_value(CID, t.to_bytes(), tid);
+            managers.stats.new_record(&t, id).unwrap();
+        }
+        let scan = Box::new(SeqScan::new(managers, &setup.schema, &CID, tid, None, None));
+        (managers, scan)
+    }
+
+    fn get_iter() -> Box<dyn OpIterator> {
+        let (managers, scan) = get_scan();
+        let mut iter = Box::new(Delete::new(managers, TransactionId::new(), scan));
+        iter.configure(false);
+        iter
+    }
+
+    /// Remaining tuples of the table.
+    fn scan_table(managers: &'static Managers) -> Vec<Tuple> {
+        let setup = TestTuples::new("");
+        let tid = TransactionId::new();
+        let mut scan = SeqScan::new(managers, &setup.schema, &CID, tid, None, None);
+        scan.configure(false);
+        execute_iter(&mut scan, true).unwrap()
+    }
+
+    mod delete_test {
+        use super::*;
+
+        #[test]
+        fn test_delete() {
+            let (managers, scan) = get_scan();
+            let mut iter = Delete::new(managers, TransactionId::new(), scan);
+            iter.configure(false);
+            let deleted = execute_iter(&mut iter, false).unwrap();
+            let expected = TestTuples::new("");
+            assert_eq!(deleted.len(), expected.tuples.len());
+            for (t, e) in deleted.iter().zip(expected.tuples.iter()) {
+                assert_eq!(t.field_vals, e.field_vals);
+            }
+
+            // The records are removed from the storage and the stats
+            assert!(scan_table(managers).is_empty());
+            assert_eq!(managers.stats.get_container_record_count(CID).unwrap(), 0);
+        }
+
+        #[test]
+        fn test_delete_some() {
+            let (managers, mut scan) = get_scan();
+            scan.configure(false);
+            let mut tuples = execute_iter(&mut *scan, false).unwrap();
+            let kept = tuples.split_off(2);
+            let schema = scan.get_schema().clone();
+            let child = Box::new(TupleIterator::new(tuples, schema));
+            let mut iter = Delete::new(managers, TransactionId::new(), child);
+            iter.configure(false);
+            assert_eq!(execute_iter(&mut iter, false).unwrap().len(), 2);
+
+            let remaining = scan_table(managers)
+                .into_iter()
+                .map(|t| t.field_vals)
+                .collect::<Vec<_>>();
+            let kept = kept.into_iter().map(|t| t.field_vals).collect::<Vec<_>>();
+            assert_eq!(remaining, kept);
+            assert_eq!(
+                managers.stats.get_container_record_count(CID).unwrap(),
+                kept.len()
+            );
+        }
+
+        #[test]
+        fn test_delete_after_reading_child() {
+            // The scan is read on open, before any record is deleted
+            let (managers, scan) = get_scan();
+            let mut iter = Delete::new(managers, TransactionId::new(), scan);
+            iter.configure(false);
+            iter.open().unwrap();
+            let count = TestTuples::new("").tuples.len();
+            assert_eq!(scan_table(managers).len(), count);
+            assert!(iter.next().unwrap().is_some());
+            assert_eq!(scan_table(managers).len(), count - 1);
+            while iter.next().unwrap().is_some() {}
+            assert!(scan_table(managers).is_empty());
+        }
+
+        #[test]
+        fn test_delete_without_value_id() {
+            let managers = new_test_managers();
+            let setup = TestTuples::new("");
+            let child = Box::new(TupleIterator::new(setup.tuples, setup.schema));
+            let mut iter = Delete::new(managers, TransactionId::new(), child);
+            iter.configure(false);
+            iter.open().unwrap();
+            assert!(iter.next().is_err());
+        }
+    }
+
+    mod opiterator_test {
+        use super::*;
+
+        #[test]
+        #[should_panic]
+        fn test_next_not_open() {
+            let mut iter = get_iter();
+            let _ = iter.next();
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_rewind_not_open() {
+            let mut iter = get_iter();
+            let _ = iter.rewind();
+        }
+
+        #[test]
+        fn test_open() {
+            let mut iter = get_iter();
+            iter.open().unwrap();
+        }
+
+        #[test]
+        fn test_close() {
+            let mut iter = get_iter();
+            iter.open().unwrap();
+            iter.close().unwrap();
+        }
+
+        #[test]
+        fn test_rewind() {
+            // The deleted records are gone, so a delete cannot be run again
+            let mut iter = get_iter();
+            iter.open().unwrap();
+            while iter.next().unwrap().is_some() {}
+            assert!(iter.rewind().is_err());
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use super::OpIterator;
+use common::{CrustyError, Field, TableSchema, Tuple};
+use std::collections::HashSet;
+
+/// Distinct operator. Removes duplicate tuples from the child while keeping the
+/// order in which the tuples are first seen.
+pub struct Distinct {
+    // Parameters (No need to reset on close)
+    /// Schema of the child.
+    schema: TableSchema,
+    /// Child operator passing data into operator.
+    child: Box<dyn OpIterator>,
+
+    // States (Need to reset on close)
+    /// Boolean determining if iterator is open.
+    open: bool,
+    /// Field values of the tuples returned so far.
+    seen: HashSet<Vec<Field>>,
+}
+
+impl Distinct {
+    /// Distinct constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema` - Schema of the child.
+    /// * `child` - Child OpIterator passing data into the operator.
+    pub fn new(schema: TableSchema, child: Box<dyn OpIterator>) -> Self {
+        Self {
+            schema,
+            child,
+            open: false,
+            seen: HashSet::new(),
+        }
+    }
+}
+
+impl OpIterator for Distinct {
+    fn configure(&mut self, will_rewind: bool) {
+        self.child.configure(will_rewind);
+    }
+
+    fn open(&mut self) -> Result<(), CrustyError> {
+        if !self.open {
+            self.child.open()?;
+            self.open = true;
+        }
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<Tuple>, CrustyError> {
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        while let Some(t) = self.child.next()? {
+            if self.seen.insert(t.field_vals.clone()) {
+                return Ok(Some(t));
+            }
+        }
+        Ok(None)
+    }
+
+    fn close(&mut self) -> Result<(), CrustyError> {
+        self.child.close()?;
+        self.seen.clear();
+        self.open = false;
+        Ok(())
+    }
+
+    fn rewind(&mut self) -> Result<(), CrustyError> {
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        self.child.rewind()?;
+        self.seen.clear();
+        Ok(())
+    }
+
+    fn get_schema(&self) -> &TableSchema {
+        &self.schema
+    }
+
+    fn state_size(&self) -> usize {
+        self.seen.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::opiterator::{Project, TupleIterator};
+    use crate::testutil::execute_iter;
+    use crate::testutil::TestTuples;
+    use common::datatypes::{f_int, f_str};
+    use common::query::bytecode_expr::colidx_expr;
+
+    fn get_iter(cols: Vec<usize>) -> Box<dyn OpIterator> {
+        let setup = TestTuples::new("");
+        let schema = TableSchema::new(
+            cols.iter()
+                .map(|i| setup.schema.get_attribute(*i).unwrap().clone())
+                .collect(),
+        );
+        let project = Box::new(Project::new(
+            cols.into_iter().map(colidx_expr).collect(),
+            schema.clone(),
+            Box::new(TupleIterator::new(
+                setup.tuples.clone(),
+                setup.schema.clone(),
+            )),
+        ));
+        let mut iter = Box::new(Distinct::new(schema, project));
+        iter.configure(false);
+        iter
+    }
+
+    fn run_distinct(cols: Vec<usize>) -> Vec<Tuple> {
+        let mut iter = get_iter(cols);
+        // Not sorted to check that the first occurrences are returned in order
+        execute_iter(&mut *iter, false).unwrap()
+    }
+
+    mod distinct_test {
+        use super::*;
+
+        #[test]
+        fn test_distinct() {
+            // Input:
+            // 1 1 3 E
+            // 2 1 3 G
+            // 3 1 4 A
+            // 4 2 4 G
+            // 5 2 5 G
+            // 6 2 5 G
+            let t = run_distinct(vec![1, 3]);
+            // Output:
+            // 1 E
+            // 1 G
+            // 1 A
+            // 2 G
+            assert_eq!(t.len(), 4);
+            assert_eq!(t[0], Tuple::new(vec![f_int(1), f_str("E")]));
+            assert_eq!(t[1], Tuple::new(vec![f_int(1), f_str("G")]));
+            assert_eq!(t[2], Tuple::new(vec![f_int(1), f_str("A")]));
+            assert_eq!(t[3], Tuple::new(vec![f_int(2), f_str("G")]));
+        }
+
+        #[test]
+        fn test_distinct_no_duplicates() {
+            let setup = TestTuples::new("");
+            let t = run_distinct(vec![0, 1, 2, 3]);
+            assert_eq!(t, setup.tuples);
+        }
+    }
+
+    mod opiterator_test {
+        use super::*;
+
+        #[test]
+        #[should_panic]
+        fn test_next_not_open() {
+            let mut iter = get_iter(vec![1]);
+            let _ = iter.next();
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_rewind_not_open() {
+            let mut iter = get_iter(vec![1]);
+            let _ = iter.rewind();
+        }
+
+        #[test]
+        fn test_open() {
+            let mut iter = get_iter(vec![1]);
+            iter.open().unwrap();
+        }
+
+        #[test]
+        fn test_close() {
+            let mut iter = get_iter(vec![1]);
+            iter.open().unwrap();
+            iter.close().unwrap();
+        }
+
+        #[test]
+        fn test_rewind() {
+            let mut iter = get_iter(vec![2]);
+            iter.configure(true);
+            let t_before = execute_iter(&mut *iter, false).unwrap();
+            iter.rewind().unwrap();
+            let t_after = execute_iter(&mut *iter, false).unwrap();
+            assert_eq!(t_before.len(), 3);
+            assert_eq!(t_before, t_after);
+        }
+    }
+}
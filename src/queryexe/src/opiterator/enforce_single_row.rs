@@ -0,0 +1,181 @@
+use super::OpIterator;
+use common::{CrustyError, Field, TableSchema, Tuple};
+
+/// Single row operator, used for the result of a scalar subquery.
+///
+/// Returns the only tuple of the child, or a tuple of NULLs if the child is
+/// empty. Fails with an execution error if the child has more than one tuple.
+pub struct EnforceSingleRow {
+    // Parameters (No need to reset on close)
+    /// Schema of the child.
+    schema: TableSchema,
+    /// Child operator passing data into operator.
+    child: Box<dyn OpIterator>,
+
+    // States (Need to reset on close)
+    /// Boolean determining if iterator is open.
+    open: bool,
+    /// Boolean determining if the single tuple has been returned.
+    done: bool,
+}
+
+impl EnforceSingleRow {
+    /// EnforceSingleRow constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema` - Schema of the child.
+    /// * `child` - Child OpIterator passing data into the operator.
+    pub fn new(schema: TableSchema, child: Box<dyn OpIterator>) -> Self {
+        Self {
+            schema,
+            child,
+            open: false,
+            done: false,
+        }
+    }
+}
+
+impl OpIterator for EnforceSingleRow {
+    fn configure(&mut self, will_rewind: bool) {
+        self.child.configure(will_rewind);
+    }
+
+    fn open(&mut self) -> Result<(), CrustyError> {
+        if !self.open {
+            self.child.open()?;
+            self.open = true;
+        }
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<Tuple>, CrustyError> {
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        if self.done {
+            return Ok(None);
+        }
+        self.done = true;
+        let tuple = match self.child.next()? {
+            Some(t) => t,
+            None => Tuple::new(vec![Field::Null; self.schema.size()]),
+        };
+        if self.child.next()?.is_some() {
+            return Err(CrustyError::ExecutionError(
+                "Scalar subquery returned more than one row".to_string(),
+            ));
+        }
+        Ok(Some(tuple))
+    }
+
+    fn close(&mut self) -> Result<(), CrustyError> {
+        self.child.close()?;
+        self.done = false;
+        self.open = false;
+        Ok(())
+    }
+
+    fn rewind(&mut self) -> Result<(), CrustyError> {
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        self.child.rewind()?;
+        self.done = false;
+        Ok(())
+    }
+
+    fn get_schema(&self) -> &TableSchema {
+        &self.schema
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::opiterator::{Limit, TupleIterator};
+    use crate::testutil::execute_iter;
+    use crate::testutil::TestTuples;
+
+    /// Single row of the first `n` test tuples.
+    fn get_iter(n: usize) -> Box<dyn OpIterator> {
+        let setup = TestTuples::new("");
+        let child = Box::new(Limit::new(
+            Some(n),
+            0,
+            setup.schema.clone(),
+            Box::new(TupleIterator::new(
+                setup.tuples.clone(),
+                setup.schema.clone(),
+            )),
+        ));
+        let mut iter = Box::new(EnforceSingleRow::new(setup.schema.clone(), child));
+        iter.configure(false);
+        iter
+    }
+
+    mod enforce_single_row_test {
+        use super::*;
+
+        #[test]
+        fn test_single_row() {
+            let setup = TestTuples::new("");
+            let t = execute_iter(&mut *get_iter(1), false).unwrap();
+            assert_eq!(t, vec![setup.tuples[0].clone()]);
+        }
+
+        #[test]
+        fn test_empty() {
+            let t = execute_iter(&mut *get_iter(0), false).unwrap();
+            assert_eq!(t, vec![Tuple::new(vec![Field::Null; 4])]);
+        }
+
+        #[test]
+        fn test_more_than_one_row() {
+            let mut iter = get_iter(2);
+            assert!(execute_iter(&mut *iter, false).is_err());
+        }
+    }
+
+    mod opiterator_test {
+        use super::*;
+
+        #[test]
+        #[should_panic]
+        fn test_next_not_open() {
+            let mut iter = get_iter(1);
+            let _ = iter.next();
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_rewind_not_open() {
+            let mut iter = get_iter(1);
+            let _ = iter.rewind();
+        }
+
+        #[test]
+        fn test_open() {
+            let mut iter = get_iter(1);
+            iter.open().unwrap();
+        }
+
+        #[test]
+        fn test_close() {
+            let mut iter = get_iter(1);
+            iter.open().unwrap();
+            iter.close().unwrap();
+        }
+
+        #[test]
+        fn test_rewind() {
+            let mut iter = get_iter(1);
+            iter.configure(true);
+            let t_before = execute_iter(&mut *iter, false).unwrap();
+            iter.rewind().unwrap();
+            let t_after = execute_iter(&mut *iter, false).unwrap();
+            assert_eq!(t_before.len(), 1);
+            assert_eq!(t_before, t_after);
+        }
+    }
+}
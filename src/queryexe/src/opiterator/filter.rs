@@ -55,13 +55,15 @@ impl OpIterator for Filter {
 
         let mut res = None;
         while let Some(t) = self.child.next()? {
-            match self.predicate.eval(&t) {
+            match self.predicate.eval(&t)? {
                 Field::Bool(b) => {
                     if b {
                         res = Some(t);
                         break;
                     }
                 }
+                // An unknown (NULL) predicate does not select the tuple
+                Field::Null => {}
                 _ => {
                     return Err(c_err("Predicate did not evaluate to a boolean"));
                 }
@@ -159,6 +161,27 @@ mod test {
         expr
     }
 
+    fn get_in_list_predicate(list: Vec<Field>, negated: bool) -> ByteCodeExpr {
+        // Predicate:
+        // col(2) [NOT] IN (list)
+        let mut expr = ByteCodeExpr::new();
+        expr.add_code(ByteCodes::PushField as usize);
+        expr.add_code(2);
+        let n = list.len();
+        for val in list {
+            expr.add_code(ByteCodes::PushLit as usize);
+            let i = expr.add_literal(val);
+            expr.add_code(i);
+        }
+        if negated {
+            expr.add_code(ByteCodes::NotIn as usize);
+        } else {
+            expr.add_code(ByteCodes::In as usize);
+        }
+        expr.add_code(n);
+        expr
+    }
+
     fn run_filter(predicate: ByteCodeExpr) -> Vec<Tuple> {
         let mut iter = get_iter(predicate);
         execute_iter(&mut *iter, true).unwrap()
@@ -204,6 +227,34 @@ mod test {
         }
     }
 
+    mod in_list_test {
+        use super::*;
+
+        #[test]
+        fn test_in_list() {
+            let predicate = get_in_list_predicate(vec![f_int(3), f_int(5), Field::Null], false);
+            let t = run_filter(predicate);
+            assert_eq!(t.len(), 4);
+            assert!(t.iter().all(|t| t.get_field(2) != Some(&f_int(4))));
+        }
+
+        #[test]
+        fn test_not_in_list() {
+            let predicate = get_in_list_predicate(vec![f_int(3), f_int(5)], true);
+            let t = run_filter(predicate);
+            assert_eq!(t.len(), 2);
+            assert!(t.iter().all(|t| t.get_field(2) == Some(&f_int(4))));
+        }
+
+        #[test]
+        fn test_not_in_list_with_null() {
+            // 4 NOT IN (3, 5, NULL) is unknown, so no tuple is selected
+            let predicate = get_in_list_predicate(vec![f_int(3), f_int(5), Field::Null], true);
+            let t = run_filter(predicate);
+            assert!(t.is_empty());
+        }
+    }
+
     mod opiterator_test {
         use super::*;
 
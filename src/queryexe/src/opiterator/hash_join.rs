@@ -78,6 +78,10 @@ impl OpIterator for HashEqJoin {
     fn get_schema(&self) -> &TableSchema {
         &self.schema
     }
+
+    fn state_size(&self) -> usize {
+        self.join_map.values().map(|bucket| bucket.len()).sum()
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,278 @@
+use super::OpIterator;
+use common::logical_expr::prelude::SetOpType;
+use common::{CrustyError, Field, TableSchema, Tuple};
+use std::collections::{HashMap, HashSet};
+
+/// Hash based set operator (UNION, INTERSECT and EXCEPT, with or without ALL).
+/// UNION ALL does not need hashing and is evaluated by `UnionAll` instead.
+///
+/// For INTERSECT and EXCEPT, the right child is consumed on open into a hash
+/// table counting its tuples, and the left child is streamed against it. UNION
+/// streams both children and only keeps the tuples it has not returned yet.
+/// The tuples are returned in the order of the left child (then the right child).
+pub struct HashSetOp {
+    // Parameters (No need to reset on close)
+    schema: TableSchema,
+    set_op: SetOpType,
+    left_child: Box<dyn OpIterator>,
+    right_child: Box<dyn OpIterator>,
+    // States (Need to reset on close)
+    open: bool,
+    /// Boolean determining if the left child has been exhausted. Only used by UNION.
+    left_done: bool,
+    /// Number of occurrences of each tuple of the right child.
+    right_counts: HashMap<Vec<Field>, usize>,
+    /// Occurrences of the right tuples not matched yet. Only used by the ALL variants.
+    remaining: HashMap<Vec<Field>, usize>,
+    /// Tuples returned so far. Only used by the distinct variants.
+    seen: HashSet<Vec<Field>>,
+}
+
+impl HashSetOp {
+    /// Hash set operator constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `set_op` - Set operation to evaluate. Anything but UNION ALL.
+    /// * `schema` - Output schema. Uses the names of the left child.
+    /// * `left_child` - Left side of the set operation.
+    /// * `right_child` - Right side of the set operation.
+    pub fn new(
+        set_op: SetOpType,
+        schema: TableSchema,
+        left_child: Box<dyn OpIterator>,
+        right_child: Box<dyn OpIterator>,
+    ) -> Self {
+        Self {
+            schema,
+            set_op,
+            left_child,
+            right_child,
+            open: false,
+            left_done: false,
+            right_counts: HashMap::new(),
+            remaining: HashMap::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Returns true if the right child is streamed instead of being hashed.
+    fn streams_right(&self) -> bool {
+        self.set_op == SetOpType::Union
+    }
+
+    /// Decides whether a tuple of the left child is part of the result.
+    fn keep_left(&mut self, vals: &[Field]) -> bool {
+        match self.set_op {
+            SetOpType::Union => self.seen.insert(vals.to_vec()),
+            SetOpType::UnionAll => unreachable!("UNION ALL is evaluated by UnionAll"),
+            SetOpType::Intersect => {
+                self.right_counts.contains_key(vals) && self.seen.insert(vals.to_vec())
+            }
+            SetOpType::Except => {
+                !self.right_counts.contains_key(vals) && self.seen.insert(vals.to_vec())
+            }
+            SetOpType::IntersectAll | SetOpType::ExceptAll => {
+                // Each right tuple cancels out (EXCEPT ALL) or matches (INTERSECT ALL) one left tuple
+                let matched = match self.remaining.get_mut(vals) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        true
+                    }
+                    _ => false,
+                };
+                matched == (self.set_op == SetOpType::IntersectAll)
+            }
+        }
+    }
+}
+
+impl OpIterator for HashSetOp {
+    fn configure(&mut self, will_rewind: bool) {
+        self.left_child.configure(will_rewind);
+        // The right child is consumed once on open unless it is streamed
+        let streams_right = self.streams_right();
+        self.right_child.configure(will_rewind && streams_right);
+    }
+
+    fn open(&mut self) -> Result<(), CrustyError> {
+        if !self.open {
+            self.left_child.open()?;
+            self.right_child.open()?;
+            if !self.streams_right() {
+                while let Some(t) = self.right_child.next()? {
+                    *self.right_counts.entry(t.field_vals).or_insert(0) += 1;
+                }
+                self.remaining = self.right_counts.clone();
+            }
+            self.left_done = false;
+            self.open = true;
+        }
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<Tuple>, CrustyError> {
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        if !self.left_done {
+            while let Some(t) = self.left_child.next()? {
+                if self.keep_left(&t.field_vals) {
+                    return Ok(Some(t));
+                }
+            }
+            self.left_done = true;
+        }
+        if self.streams_right() {
+            while let Some(t) = self.right_child.next()? {
+                if self.seen.insert(t.field_vals.clone()) {
+                    return Ok(Some(t));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn close(&mut self) -> Result<(), CrustyError> {
+        self.left_child.close()?;
+        self.right_child.close()?;
+        self.right_counts.clear();
+        self.remaining.clear();
+        self.seen.clear();
+        self.open = false;
+        Ok(())
+    }
+
+    fn rewind(&mut self) -> Result<(), CrustyError> {
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        self.left_child.rewind()?;
+        if self.streams_right() {
+            self.right_child.rewind()?;
+        }
+        self.remaining = self.right_counts.clone();
+        self.seen.clear();
+        self.left_done = false;
+        Ok(())
+    }
+
+    fn get_schema(&self) -> &TableSchema {
+        &self.schema
+    }
+
+    fn state_size(&self) -> usize {
+        self.right_counts.len() + self.seen.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::TupleIterator;
+    use super::*;
+    use crate::testutil::execute_iter;
+    use crate::testutil::TestTuples;
+
+    /// Left: tuples 0, 1, 2, 3, 3. Right: tuples 2, 3, 4, 5, 2.
+    fn get_iter(set_op: SetOpType) -> Box<dyn OpIterator> {
+        let setup = TestTuples::new("");
+        let t = &setup.tuples;
+        let left = vec![
+            t[0].clone(),
+            t[1].clone(),
+            t[2].clone(),
+            t[3].clone(),
+            t[3].clone(),
+        ];
+        let right = vec![
+            t[2].clone(),
+            t[3].clone(),
+            t[4].clone(),
+            t[5].clone(),
+            t[2].clone(),
+        ];
+        let mut iter = Box::new(HashSetOp::new(
+            set_op,
+            setup.schema.clone(),
+            Box::new(TupleIterator::new(left, setup.schema.clone())),
+            Box::new(TupleIterator::new(right, setup.schema.clone())),
+        ));
+        iter.configure(false);
+        iter
+    }
+
+    fn run_set_op(set_op: SetOpType, expected: &[usize]) {
+        let setup = TestTuples::new("");
+        let mut iter = get_iter(set_op);
+        let t = execute_iter(&mut *iter, false).unwrap();
+        let expected = expected
+            .iter()
+            .map(|i| setup.tuples[*i].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(t, expected, "{}", set_op);
+    }
+
+    mod set_op_test {
+        use super::*;
+
+        #[test]
+        fn test_union() {
+            run_set_op(SetOpType::Union, &[0, 1, 2, 3, 4, 5]);
+        }
+
+        #[test]
+        fn test_intersect() {
+            run_set_op(SetOpType::Intersect, &[2, 3]);
+            run_set_op(SetOpType::IntersectAll, &[2, 3]);
+        }
+
+        #[test]
+        fn test_except() {
+            run_set_op(SetOpType::Except, &[0, 1]);
+            run_set_op(SetOpType::ExceptAll, &[0, 1, 3]);
+        }
+    }
+
+    mod opiterator_test {
+        use super::*;
+
+        #[test]
+        #[should_panic]
+        fn test_next_not_open() {
+            let mut iter = get_iter(SetOpType::Intersect);
+            let _ = iter.next();
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_rewind_not_open() {
+            let mut iter = get_iter(SetOpType::Intersect);
+            let _ = iter.rewind();
+        }
+
+        #[test]
+        fn test_open() {
+            let mut iter = get_iter(SetOpType::Intersect);
+            iter.open().unwrap();
+        }
+
+        #[test]
+        fn test_close() {
+            let mut iter = get_iter(SetOpType::Intersect);
+            iter.open().unwrap();
+            iter.close().unwrap();
+        }
+
+        #[test]
+        fn test_rewind() {
+            for set_op in [SetOpType::Union, SetOpType::ExceptAll] {
+                let mut iter = get_iter(set_op);
+                iter.configure(true);
+                let t_before = execute_iter(&mut *iter, false).unwrap();
+                iter.rewind().unwrap();
+                let t_after = execute_iter(&mut *iter, false).unwrap();
+                assert_eq!(t_before, t_after);
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use super::OpIterator;
+use common::{CrustyError, TableSchema, Tuple};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Runtime metrics of an operator, collected by `Instrumented`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct OperatorMetrics {
+    /// Number of times the operator was opened.
+    pub loops: usize,
+    /// Number of tuples produced.
+    pub rows: usize,
+    /// Number of calls to `next()`.
+    pub next_calls: usize,
+    /// Time spent in `open()`.
+    pub open_time: Duration,
+    /// Time spent in `next()`.
+    pub next_time: Duration,
+    /// Time spent in `close()`.
+    pub close_time: Duration,
+    /// Largest state held by the operator (see `OpIterator::state_size`).
+    pub peak_state_size: usize,
+}
+
+/// Metrics shared between an `Instrumented` operator and the plan it was built for.
+pub type OperatorMetricsRef = Arc<Mutex<OperatorMetrics>>;
+
+/// Wrapper around an operator recording its runtime metrics, used by EXPLAIN ANALYZE.
+/// The times include the time spent in the children of the operator.
+pub struct Instrumented {
+    // Parameters (No need to reset on close)
+    /// Wrapped operator.
+    child: Box<dyn OpIterator>,
+    /// Metrics of the wrapped operator.
+    metrics: OperatorMetricsRef,
+}
+
+impl Instrumented {
+    /// Instrumented constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `child` - Operator to instrument.
+    /// * `metrics` - Metrics updated by the operator.
+    pub fn new(child: Box<dyn OpIterator>, metrics: OperatorMetricsRef) -> Self {
+        Self { child, metrics }
+    }
+
+    fn record_state_size(&self, metrics: &mut OperatorMetrics) {
+        metrics.peak_state_size = metrics.peak_state_size.max(self.child.state_size());
+    }
+}
+
+impl OpIterator for Instrumented {
+    fn configure(&mut self, will_rewind: bool) {
+        self.child.configure(will_rewind);
+    }
+
+    fn open(&mut self) -> Result<(), CrustyError> {
+        let start = Instant::now();
+        let result = self.child.open();
+        let mut metrics = self.metrics.lock().unwrap();
+        metrics.open_time += start.elapsed();
+        metrics.loops += 1;
+        self.record_state_size(&mut metrics);
+        result
+    }
+
+    fn next(&mut self) -> Result<Option<Tuple>, CrustyError> {
+        let start = Instant::now();
+        let result = self.child.next();
+        let mut metrics = self.metrics.lock().unwrap();
+        metrics.next_time += start.elapsed();
+        metrics.next_calls += 1;
+        if let Ok(Some(_)) = result {
+            metrics.rows += 1;
+        }
+        self.record_state_size(&mut metrics);
+        result
+    }
+
+    fn close(&mut self) -> Result<(), CrustyError> {
+        let start = Instant::now();
+        let result = self.child.close();
+        self.metrics.lock().unwrap().close_time += start.elapsed();
+        result
+    }
+
+    fn rewind(&mut self) -> Result<(), CrustyError> {
+        self.child.rewind()
+    }
+
+    fn get_schema(&self) -> &TableSchema {
+        self.child.get_schema()
+    }
+
+    fn state_size(&self) -> usize {
+        self.child.state_size()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::opiterator::TupleIterator;
+    use crate::testutil::execute_iter;
+    use common::datatypes::f_int;
+    use common::{Attribute, DataType};
+
+    fn get_iter(n: i64) -> (Instrumented, OperatorMetricsRef) {
+        let schema = TableSchema::new(vec![Attribute::new("n".to_string(), DataType::BigInt)]);
+        let tuples = (0..n).map(|i| Tuple::new(vec![f_int(i)])).collect();
+        let metrics = OperatorMetricsRef::default();
+        let iter = Instrumented::new(
+            Box::new(TupleIterator::new(tuples, schema)),
+            metrics.clone(),
+        );
+        (iter, metrics)
+    }
+
+    #[test]
+    fn test_metrics() {
+        let (mut iter, metrics) = get_iter(3);
+        iter.configure(true);
+        let t = execute_iter(&mut iter, false).unwrap();
+        assert_eq!(t.len(), 3);
+        {
+            let metrics = metrics.lock().unwrap();
+            assert_eq!(metrics.loops, 1);
+            assert_eq!(metrics.rows, 3);
+            assert_eq!(metrics.next_calls, 4);
+        }
+        iter.rewind().unwrap();
+        while iter.next().unwrap().is_some() {}
+        iter.close().unwrap();
+        iter.open().unwrap();
+        let metrics = metrics.lock().unwrap();
+        assert_eq!(metrics.loops, 2);
+        assert_eq!(metrics.rows, 6);
+        assert_eq!(metrics.next_calls, 8);
+    }
+
+    #[test]
+    fn test_schema() {
+        let (iter, _) = get_iter(1);
+        assert_eq!(iter.get_schema().size(), 1);
+    }
+}
@@ -0,0 +1,206 @@
+use super::OpIterator;
+use common::{CrustyError, TableSchema, Tuple};
+
+/// Limit operator. Skips the first `offset` tuples of the child and returns at
+/// most `limit` tuples after that.
+pub struct Limit {
+    // Parameters (No need to reset on close)
+    /// Schema of the child.
+    schema: TableSchema,
+    /// Maximum number of tuples to return. None if there is no limit.
+    limit: Option<usize>,
+    /// Number of tuples to skip.
+    offset: usize,
+    /// Child operator passing data into operator.
+    child: Box<dyn OpIterator>,
+
+    // States (Need to reset on close)
+    /// Boolean determining if iterator is open.
+    open: bool,
+    /// Number of tuples returned so far.
+    count: usize,
+    /// Boolean determining if the first `offset` tuples have been skipped.
+    skipped: bool,
+}
+
+impl Limit {
+    /// Limit constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - Maximum number of tuples to return. None if there is no limit.
+    /// * `offset` - Number of tuples to skip before returning any.
+    /// * `schema` - Schema of the child.
+    /// * `child` - Child OpIterator passing data into the operator.
+    pub fn new(
+        limit: Option<usize>,
+        offset: usize,
+        schema: TableSchema,
+        child: Box<dyn OpIterator>,
+    ) -> Self {
+        Self {
+            schema,
+            limit,
+            offset,
+            child,
+            open: false,
+            count: 0,
+            skipped: false,
+        }
+    }
+}
+
+impl OpIterator for Limit {
+    fn configure(&mut self, will_rewind: bool) {
+        self.child.configure(will_rewind);
+    }
+
+    fn open(&mut self) -> Result<(), CrustyError> {
+        if !self.open {
+            self.child.open()?;
+            self.count = 0;
+            self.skipped = false;
+            self.open = true;
+        }
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<Tuple>, CrustyError> {
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        // Stop pulling from the child once enough tuples have been returned
+        if self.limit.is_some_and(|limit| self.count >= limit) {
+            return Ok(None);
+        }
+        if !self.skipped {
+            for _ in 0..self.offset {
+                if self.child.next()?.is_none() {
+                    break;
+                }
+            }
+            self.skipped = true;
+        }
+        let res = self.child.next()?;
+        if res.is_some() {
+            self.count += 1;
+        }
+        Ok(res)
+    }
+
+    fn close(&mut self) -> Result<(), CrustyError> {
+        self.child.close()?;
+        self.open = false;
+        Ok(())
+    }
+
+    fn rewind(&mut self) -> Result<(), CrustyError> {
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        self.child.rewind()?;
+        self.count = 0;
+        self.skipped = false;
+        Ok(())
+    }
+
+    fn get_schema(&self) -> &TableSchema {
+        &self.schema
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::opiterator::TupleIterator;
+    use crate::testutil::execute_iter;
+    use crate::testutil::TestTuples;
+
+    fn get_iter(limit: Option<usize>, offset: usize) -> Box<dyn OpIterator> {
+        let setup = TestTuples::new("");
+        let mut iter = Box::new(Limit::new(
+            limit,
+            offset,
+            setup.schema.clone(),
+            Box::new(TupleIterator::new(
+                setup.tuples.clone(),
+                setup.schema.clone(),
+            )),
+        ));
+        iter.configure(false);
+        iter
+    }
+
+    fn run_limit(limit: Option<usize>, offset: usize) -> Vec<Tuple> {
+        let mut iter = get_iter(limit, offset);
+        execute_iter(&mut *iter, true).unwrap()
+    }
+
+    mod limit_test {
+        use super::*;
+
+        #[test]
+        fn test_limit() {
+            let setup = TestTuples::new("");
+            let t = run_limit(Some(2), 0);
+            assert_eq!(t, setup.tuples[..2]);
+            let t = run_limit(Some(0), 0);
+            assert!(t.is_empty());
+            let t = run_limit(Some(100), 0);
+            assert_eq!(t, setup.tuples);
+        }
+
+        #[test]
+        fn test_offset() {
+            let setup = TestTuples::new("");
+            let t = run_limit(None, 4);
+            assert_eq!(t, setup.tuples[4..]);
+            let t = run_limit(Some(1), 2);
+            assert_eq!(t, setup.tuples[2..3]);
+            let t = run_limit(Some(2), 100);
+            assert!(t.is_empty());
+        }
+    }
+
+    mod opiterator_test {
+        use super::*;
+
+        #[test]
+        #[should_panic]
+        fn test_next_not_open() {
+            let mut iter = get_iter(Some(1), 0);
+            let _ = iter.next();
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_rewind_not_open() {
+            let mut iter = get_iter(Some(1), 0);
+            let _ = iter.rewind();
+        }
+
+        #[test]
+        fn test_open() {
+            let mut iter = get_iter(Some(1), 0);
+            iter.open().unwrap();
+        }
+
+        #[test]
+        fn test_close() {
+            let mut iter = get_iter(Some(1), 0);
+            iter.open().unwrap();
+            iter.close().unwrap();
+        }
+
+        #[test]
+        fn test_rewind() {
+            let mut iter = get_iter(Some(2), 1);
+            iter.configure(true);
+            let t_before = execute_iter(&mut *iter, false).unwrap();
+            iter.rewind().unwrap();
+            let t_after = execute_iter(&mut *iter, false).unwrap();
+            assert_eq!(t_before.len(), 2);
+            assert_eq!(t_before, t_after);
+        }
+    }
+}
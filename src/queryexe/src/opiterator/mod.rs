@@ -1,27 +1,49 @@
 pub use self::aggregate::Aggregate;
+pub use self::apply::{Apply, ApplyFn};
 pub use self::cross_join::CrossJoin;
+pub use self::delete::Delete;
+pub use self::distinct::Distinct;
+pub use self::enforce_single_row::EnforceSingleRow;
 pub use self::filter::Filter;
 pub use self::hash_join::HashEqJoin;
+pub use self::hash_set_op::HashSetOp;
+pub use self::instrumented::{Instrumented, OperatorMetrics, OperatorMetricsRef};
+pub use self::limit::Limit;
 pub use self::nested_loop_join::NestedLoopJoin;
 pub use self::project::Project;
+pub use self::recursive_union::RecursiveUnion;
+pub use self::semi_join::SemiJoin;
 pub use self::seqscan::SeqScan;
 pub use self::sort::Sort;
 pub use self::sort_merge_join::SortMergeJoin;
 pub use self::tuple_iterator::TupleIterator;
+pub use self::union_all::UnionAll;
 pub use self::update::Update;
+pub use self::work_table_scan::{WorkTableRef, WorkTableScan};
 use common::{CrustyError, TableSchema, Tuple};
 
 mod aggregate;
+mod apply;
 mod cross_join;
+mod delete;
+mod distinct;
+mod enforce_single_row;
 mod filter;
 mod hash_join;
+mod hash_set_op;
+mod instrumented;
+mod limit;
 mod nested_loop_join;
 mod project;
+mod recursive_union;
+mod semi_join;
 mod seqscan;
 mod sort;
 mod sort_merge_join;
 mod tuple_iterator;
+mod union_all;
 mod update;
+mod work_table_scan;
 
 pub trait OpIterator {
     /// conifgure the opiterator
@@ -112,6 +134,13 @@ pub trait OpIterator {
 
     /// Returns the schema associated with this OpIterator.
     fn get_schema(&self) -> &TableSchema;
+
+    /// Returns the number of tuples or hash table entries currently held in memory by
+    /// the operator (e.g. the hash table of a hash join or the buffered tuples of a
+    /// sort). Used by EXPLAIN ANALYZE to report the peak state size.
+    fn state_size(&self) -> usize {
+        0
+    }
 }
 
 pub struct DummyOpIterator {}
@@ -45,7 +45,7 @@ impl OpIterator for Project {
         if let Some(tuple) = self.child.next()? {
             let mut new_field_vals = Vec::with_capacity(self.fields.len());
             for expr in &self.fields {
-                let t = expr.eval(&tuple);
+                let t = expr.eval(&tuple)?;
                 new_field_vals.push(t);
             }
             let t = Tuple::new(new_field_vals);
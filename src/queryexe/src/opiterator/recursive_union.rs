@@ -0,0 +1,285 @@
+use super::{OpIterator, WorkTableRef};
+use common::{CrustyError, Field, TableSchema, Tuple};
+use std::collections::HashSet;
+
+/// Recursive union operator, used for recursive common table expressions.
+///
+/// Returns the tuples of the base, then evaluates the recursive side in iterations.
+/// Before each iteration, the tuples returned by the previous one are moved to the
+/// work table, which the recursive side reads through `WorkTableScan`. It stops when
+/// an iteration returns no tuples. With `distinct`, the duplicates (of any tuple
+/// returned so far) are dropped, which also ends the recursion on cyclic data.
+pub struct RecursiveUnion {
+    // Parameters (No need to reset on close)
+    /// Schema of the base.
+    schema: TableSchema,
+    /// Boolean determining if duplicates are removed (UNION instead of UNION ALL).
+    distinct: bool,
+    /// Non-recursive side.
+    base: Box<dyn OpIterator>,
+    /// Recursive side, reading the work table.
+    recursive: Box<dyn OpIterator>,
+    /// Tuples of the previous iteration.
+    work_table: WorkTableRef,
+
+    // States (Need to reset on close)
+    /// Boolean determining if iterator is open.
+    open: bool,
+    /// Boolean determining if the tuples are read from the recursive side.
+    in_recursion: bool,
+    /// Boolean determining if the recursion has ended.
+    done: bool,
+    /// Tuples returned by the current iteration.
+    next_work: Vec<Tuple>,
+    /// Field values of the tuples returned so far (only with `distinct`).
+    seen: HashSet<Vec<Field>>,
+}
+
+impl RecursiveUnion {
+    /// RecursiveUnion constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `distinct` - Whether duplicates are removed.
+    /// * `schema` - Schema of the base.
+    /// * `base` - Non-recursive side.
+    /// * `recursive` - Recursive side.
+    /// * `work_table` - Work table read by the recursive side.
+    pub fn new(
+        distinct: bool,
+        schema: TableSchema,
+        base: Box<dyn OpIterator>,
+        recursive: Box<dyn OpIterator>,
+        work_table: WorkTableRef,
+    ) -> Self {
+        Self {
+            schema,
+            distinct,
+            base,
+            recursive,
+            work_table,
+            open: false,
+            in_recursion: false,
+            done: false,
+            next_work: Vec::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Start the next iteration over the tuples returned by the current one.
+    fn next_iteration(&mut self) -> Result<(), CrustyError> {
+        if self.in_recursion {
+            self.recursive.close()?;
+        }
+        if self.next_work.is_empty() {
+            self.done = true;
+            return Ok(());
+        }
+        *self.work_table.write().unwrap() = std::mem::take(&mut self.next_work);
+        self.recursive.open()?;
+        self.in_recursion = true;
+        Ok(())
+    }
+
+    fn reset(&mut self) -> Result<(), CrustyError> {
+        if self.in_recursion {
+            self.recursive.close()?;
+        }
+        self.work_table.write().unwrap().clear();
+        self.in_recursion = false;
+        self.done = false;
+        self.next_work.clear();
+        self.seen.clear();
+        Ok(())
+    }
+}
+
+impl OpIterator for RecursiveUnion {
+    fn configure(&mut self, will_rewind: bool) {
+        self.base.configure(will_rewind);
+        self.recursive.configure(true); // recursive side is evaluated once per iteration
+    }
+
+    fn open(&mut self) -> Result<(), CrustyError> {
+        if !self.open {
+            self.base.open()?;
+            self.open = true;
+        }
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<Tuple>, CrustyError> {
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        while !self.done {
+            let tuple = if self.in_recursion {
+                self.recursive.next()?
+            } else {
+                self.base.next()?
+            };
+            match tuple {
+                Some(t) => {
+                    if self.distinct && !self.seen.insert(t.field_vals.clone()) {
+                        continue;
+                    }
+                    self.next_work.push(t.clone());
+                    return Ok(Some(t));
+                }
+                None => self.next_iteration()?,
+            }
+        }
+        Ok(None)
+    }
+
+    fn close(&mut self) -> Result<(), CrustyError> {
+        self.reset()?;
+        self.base.close()?;
+        self.open = false;
+        Ok(())
+    }
+
+    fn rewind(&mut self) -> Result<(), CrustyError> {
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        self.reset()?;
+        self.base.rewind()
+    }
+
+    fn get_schema(&self) -> &TableSchema {
+        &self.schema
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::opiterator::{Filter, Project, TupleIterator, WorkTableScan};
+    use crate::testutil::execute_iter;
+    use common::datatypes::f_int;
+    use common::query::bytecode_expr::{ByteCodeExpr, ByteCodes};
+    use common::{Attribute, DataType};
+    use std::sync::{Arc, RwLock};
+
+    fn int_schema() -> TableSchema {
+        TableSchema::new(vec![Attribute::new("n".to_string(), DataType::BigInt)])
+    }
+
+    /// Bytecode for `@0 <op> value`.
+    fn col_op_value(op: ByteCodes, value: i64) -> ByteCodeExpr {
+        let mut expr = ByteCodeExpr::new();
+        expr.add_code(ByteCodes::PushField as usize);
+        expr.add_code(0);
+        let i = expr.add_literal(f_int(value));
+        expr.add_code(ByteCodes::PushLit as usize);
+        expr.add_code(i);
+        expr.add_code(op as usize);
+        expr
+    }
+
+    /// Recursive union of `base` with `SELECT n <op> step FROM work WHERE n < 5`.
+    fn get_iter(distinct: bool, base: Vec<i64>, op: ByteCodes, step: i64) -> Box<dyn OpIterator> {
+        let schema = int_schema();
+        let work_table: WorkTableRef = Arc::new(RwLock::new(Vec::new()));
+        let base = base
+            .into_iter()
+            .map(|n| Tuple::new(vec![f_int(n)]))
+            .collect();
+        let scan = Box::new(WorkTableScan::new(schema.clone(), work_table.clone()));
+        let filter = Box::new(Filter::new(
+            col_op_value(ByteCodes::Lt, 5),
+            schema.clone(),
+            scan,
+        ));
+        let recursive = Box::new(Project::new(
+            vec![col_op_value(op, step)],
+            schema.clone(),
+            filter,
+        ));
+        let mut iter = Box::new(RecursiveUnion::new(
+            distinct,
+            schema.clone(),
+            Box::new(TupleIterator::new(base, schema)),
+            recursive,
+            work_table,
+        ));
+        iter.configure(false);
+        iter
+    }
+
+    fn run(distinct: bool, base: Vec<i64>, op: ByteCodes, step: i64) -> Vec<Tuple> {
+        let mut iter = get_iter(distinct, base, op, step);
+        execute_iter(&mut *iter, false).unwrap()
+    }
+
+    fn ints(values: &[i64]) -> Vec<Tuple> {
+        values.iter().map(|n| Tuple::new(vec![f_int(*n)])).collect()
+    }
+
+    mod recursive_union_test {
+        use super::*;
+
+        #[test]
+        fn test_recursive_union_all() {
+            // n = 1, 3 then n + 1 while n < 5
+            let t = run(false, vec![1, 3], ByteCodes::Add, 1);
+            assert_eq!(t, ints(&[1, 3, 2, 4, 3, 5, 4, 5]));
+        }
+
+        #[test]
+        fn test_recursive_union_distinct() {
+            let t = run(true, vec![1, 3], ByteCodes::Add, 1);
+            assert_eq!(t, ints(&[1, 3, 2, 4, 5]));
+        }
+
+        #[test]
+        fn test_recursive_union_cycle() {
+            // n * 1 produces the same tuples again, which ends the recursion with UNION
+            let t = run(true, vec![1, 2], ByteCodes::Mul, 1);
+            assert_eq!(t, ints(&[1, 2]));
+        }
+    }
+
+    mod opiterator_test {
+        use super::*;
+
+        #[test]
+        #[should_panic]
+        fn test_next_not_open() {
+            let mut iter = get_iter(false, vec![1], ByteCodes::Add, 1);
+            let _ = iter.next();
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_rewind_not_open() {
+            let mut iter = get_iter(false, vec![1], ByteCodes::Add, 1);
+            let _ = iter.rewind();
+        }
+
+        #[test]
+        fn test_open() {
+            let mut iter = get_iter(false, vec![1], ByteCodes::Add, 1);
+            iter.open().unwrap();
+        }
+
+        #[test]
+        fn test_close() {
+            let mut iter = get_iter(false, vec![1], ByteCodes::Add, 1);
+            iter.open().unwrap();
+            iter.close().unwrap();
+        }
+
+        #[test]
+        fn test_rewind() {
+            let mut iter = get_iter(false, vec![1], ByteCodes::Add, 1);
+            iter.configure(true);
+            let t_before = execute_iter(&mut *iter, false).unwrap();
+            iter.rewind().unwrap();
+            let t_after = execute_iter(&mut *iter, false).unwrap();
+            assert_eq!(t_before, ints(&[1, 2, 3, 4, 5]));
+            assert_eq!(t_before, t_after);
+        }
+    }
+}
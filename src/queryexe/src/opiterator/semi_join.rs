@@ -0,0 +1,246 @@
+use super::OpIterator;
+use common::logical_expr::prelude::JoinType;
+use common::query::bytecode_expr::ByteCodeExpr;
+use common::{CrustyError, Field, TableSchema, Tuple};
+use std::collections::HashSet;
+
+/// Hash semi-join and anti-join implementation, used for IN and NOT IN subqueries.
+///
+/// The right child is consumed on open into a set of join keys. The left tuples
+/// are then streamed and returned as they are (only the left columns are kept).
+/// * `LeftSemi` returns the left tuples whose key is in the set (`x IN (...)`).
+/// * `LeftAnti` returns the left tuples for which `x NOT IN (...)` is true: the
+///   right side is empty, or the key is not NULL, not in the set and the set has
+///   no NULL.
+pub struct SemiJoin {
+    // Parameters (No need to reset on close)
+    schema: TableSchema,
+    join_type: JoinType,
+    left_expr: ByteCodeExpr,
+    right_expr: ByteCodeExpr,
+    left_child: Box<dyn OpIterator>,
+    right_child: Box<dyn OpIterator>,
+
+    // States (Need to reset on close)
+    open: bool,
+    /// Non-NULL join keys of the right child.
+    right_keys: HashSet<Field>,
+    /// Boolean determining if the right child has a NULL join key.
+    right_has_null: bool,
+    /// Boolean determining if the right child is empty.
+    right_is_empty: bool,
+}
+
+impl SemiJoin {
+    /// Constructor for a semi-join or anti-join operator.
+    ///
+    /// # Arguments
+    ///
+    /// * `join_type` - Either `JoinType::LeftSemi` or `JoinType::LeftAnti`.
+    /// * `schema` - Schema of the left child.
+    /// * `left_expr` - Join key of the left child.
+    /// * `right_expr` - Join key of the right child.
+    /// * `left_child` - Left child of join operator.
+    /// * `right_child` - Right child of join operator.
+    pub fn new(
+        join_type: JoinType,
+        schema: TableSchema,
+        left_expr: ByteCodeExpr,
+        right_expr: ByteCodeExpr,
+        left_child: Box<dyn OpIterator>,
+        right_child: Box<dyn OpIterator>,
+    ) -> Self {
+        debug_assert!(matches!(join_type, JoinType::LeftSemi | JoinType::LeftAnti));
+        Self {
+            schema,
+            join_type,
+            left_expr,
+            right_expr,
+            left_child,
+            right_child,
+            open: false,
+            right_keys: HashSet::new(),
+            right_has_null: false,
+            right_is_empty: true,
+        }
+    }
+
+    /// Decides whether a left tuple with the given join key is part of the result.
+    fn keep(&self, key: &Field) -> bool {
+        match self.join_type {
+            JoinType::LeftSemi => !matches!(key, Field::Null) && self.right_keys.contains(key),
+            _ => {
+                self.right_is_empty
+                    || (!matches!(key, Field::Null)
+                        && !self.right_has_null
+                        && !self.right_keys.contains(key))
+            }
+        }
+    }
+}
+
+impl OpIterator for SemiJoin {
+    fn configure(&mut self, will_rewind: bool) {
+        self.left_child.configure(will_rewind);
+        self.right_child.configure(false); // right child is consumed once on open
+    }
+
+    fn open(&mut self) -> Result<(), CrustyError> {
+        if !self.open {
+            self.left_child.open()?;
+            self.right_child.open()?;
+            while let Some(t) = self.right_child.next()? {
+                self.right_is_empty = false;
+                match self.right_expr.eval(&t)? {
+                    Field::Null => self.right_has_null = true,
+                    key => {
+                        self.right_keys.insert(key);
+                    }
+                }
+            }
+            self.open = true;
+        }
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<Tuple>, CrustyError> {
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        while let Some(t) = self.left_child.next()? {
+            if self.keep(&self.left_expr.eval(&t)?) {
+                return Ok(Some(t));
+            }
+        }
+        Ok(None)
+    }
+
+    fn close(&mut self) -> Result<(), CrustyError> {
+        self.left_child.close()?;
+        self.right_child.close()?;
+        self.right_keys.clear();
+        self.right_has_null = false;
+        self.right_is_empty = true;
+        self.open = false;
+        Ok(())
+    }
+
+    fn rewind(&mut self) -> Result<(), CrustyError> {
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        self.left_child.rewind()
+    }
+
+    fn get_schema(&self) -> &TableSchema {
+        &self.schema
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::TupleIterator;
+    use super::*;
+    use crate::testutil::execute_iter;
+    use crate::testutil::TestTuples;
+    use common::datatypes::f_int;
+    use common::query::bytecode_expr::colidx_expr;
+
+    /// Semi/anti join of the test tuples on column 2 (values 3, 3, 4, 4, 5, 5)
+    /// with a single column right side.
+    fn get_iter(join_type: JoinType, right: Vec<Field>) -> Box<dyn OpIterator> {
+        let setup = TestTuples::new("");
+        let right_schema = TableSchema::new(vec![setup.schema.get_attribute(2).unwrap().clone()]);
+        let right = right.into_iter().map(|f| Tuple::new(vec![f])).collect();
+        let mut iter = Box::new(SemiJoin::new(
+            join_type,
+            setup.schema.clone(),
+            colidx_expr(2),
+            colidx_expr(0),
+            Box::new(TupleIterator::new(
+                setup.tuples.clone(),
+                setup.schema.clone(),
+            )),
+            Box::new(TupleIterator::new(right, right_schema)),
+        ));
+        iter.configure(false);
+        iter
+    }
+
+    fn run_join(join_type: JoinType, right: Vec<Field>) -> Vec<Tuple> {
+        let mut iter = get_iter(join_type, right);
+        execute_iter(&mut *iter, false).unwrap()
+    }
+
+    mod semi_join_test {
+        use super::*;
+
+        #[test]
+        fn test_semi_join() {
+            let setup = TestTuples::new("");
+            // Duplicates on the right do not duplicate the left tuples
+            let t = run_join(JoinType::LeftSemi, vec![f_int(4), f_int(4), f_int(5)]);
+            assert_eq!(t, setup.tuples[2..].to_vec());
+            let t = run_join(JoinType::LeftSemi, vec![f_int(3), Field::Null]);
+            assert_eq!(t, setup.tuples[..2].to_vec());
+        }
+
+        #[test]
+        fn test_anti_join() {
+            let setup = TestTuples::new("");
+            let t = run_join(JoinType::LeftAnti, vec![f_int(4), f_int(5)]);
+            assert_eq!(t, setup.tuples[..2].to_vec());
+            let t = run_join(JoinType::LeftAnti, vec![]);
+            assert_eq!(t, setup.tuples);
+        }
+
+        #[test]
+        fn test_anti_join_with_null() {
+            // x NOT IN (4, NULL) is never true
+            let t = run_join(JoinType::LeftAnti, vec![f_int(4), Field::Null]);
+            assert!(t.is_empty());
+        }
+    }
+
+    mod opiterator_test {
+        use super::*;
+
+        #[test]
+        #[should_panic]
+        fn test_next_not_open() {
+            let mut iter = get_iter(JoinType::LeftSemi, vec![f_int(4)]);
+            let _ = iter.next();
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_rewind_not_open() {
+            let mut iter = get_iter(JoinType::LeftSemi, vec![f_int(4)]);
+            let _ = iter.rewind();
+        }
+
+        #[test]
+        fn test_open() {
+            let mut iter = get_iter(JoinType::LeftSemi, vec![f_int(4)]);
+            iter.open().unwrap();
+        }
+
+        #[test]
+        fn test_close() {
+            let mut iter = get_iter(JoinType::LeftSemi, vec![f_int(4)]);
+            iter.open().unwrap();
+            iter.close().unwrap();
+        }
+
+        #[test]
+        fn test_rewind() {
+            let mut iter = get_iter(JoinType::LeftAnti, vec![f_int(4)]);
+            iter.configure(true);
+            let t_before = execute_iter(&mut *iter, false).unwrap();
+            iter.rewind().unwrap();
+            let t_after = execute_iter(&mut *iter, false).unwrap();
+            assert_eq!(t_before.len(), 4);
+            assert_eq!(t_before, t_after);
+        }
+    }
+}
@@ -95,12 +95,14 @@ impl OpIterator for SeqScan {
             self.index = Some(id);
 
             if let Some(filter) = &self.filter {
-                match filter.eval(&tuple) {
+                match filter.eval(&tuple)? {
                     Field::Bool(b) => {
                         if !b {
                             continue;
                         }
                     }
+                    // An unknown (NULL) filter does not select the tuple
+                    Field::Null => continue,
                     _ => panic!("Filter must evaluate to a boolean"),
                 }
             }
@@ -108,7 +110,7 @@ impl OpIterator for SeqScan {
             if let Some(projection) = &self.projection {
                 let mut new_field_vals = Vec::with_capacity(projection.len());
                 for expr in projection {
-                    let t = expr.eval(&tuple);
+                    let t = expr.eval(&tuple)?;
                     new_field_vals.push(t);
                 }
                 return Ok(Some(Tuple::new(new_field_vals)));
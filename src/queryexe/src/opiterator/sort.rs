@@ -14,7 +14,7 @@ pub struct Sort {
 
     // Parameters (No need to reset on close)
     schema: TableSchema,
-    fields: Vec<(ByteCodeExpr, bool)>, // (field, asc)
+    fields: Vec<(ByteCodeExpr, bool, bool)>, // (field, asc, nulls_first)
     child: Box<dyn OpIterator>,
     will_rewind: bool,
 
@@ -27,7 +27,7 @@ pub struct Sort {
 impl Sort {
     pub fn new(
         managers: &'static Managers,
-        fields: Vec<(ByteCodeExpr, bool)>,
+        fields: Vec<(ByteCodeExpr, bool, bool)>,
         schema: TableSchema,
         child: Box<dyn OpIterator>,
     ) -> Self {
@@ -55,26 +55,30 @@ impl OpIterator for Sort {
             self.child.open()?;
             while let Some(tuple) = self.child.next()? {
                 let mut sort_key = Vec::new();
-                for (field, _) in &self.fields {
-                    sort_key.push(field.eval(&tuple));
+                for (field, _, _) in &self.fields {
+                    sort_key.push(field.eval(&tuple)?);
                 }
                 self.sorted_data.push((sort_key, tuple));
             }
             self.child.close()?;
             self.sorted_data
                 .sort_by(|(a_sort_key, _), (b_sort_key, _)| {
-                    for (i, (_, asc)) in self.fields.iter().enumerate() {
+                    for (i, (_, asc, nulls_first)) in self.fields.iter().enumerate() {
+                        let res = match (&a_sort_key[i], &b_sort_key[i]) {
+                            (Field::Null, Field::Null) => Ordering::Equal,
+                            (Field::Null, _) if *nulls_first => Ordering::Less,
+                            (Field::Null, _) => Ordering::Greater,
+                            (_, Field::Null) if *nulls_first => Ordering::Greater,
+                            (_, Field::Null) => Ordering::Less,
+                            (a, b) if *asc => a.cmp(b),
+                            (a, b) => b.cmp(a),
+                        };
                         // Sort it by reverse order so that we can
                         // pop the elements from the back when
                         // returning the tuples by next().
                         // Note that pop is O(1), but remove(0) is O(n)
-                        let res = if !*asc {
-                            a_sort_key[i].cmp(&b_sort_key[i])
-                        } else {
-                            b_sort_key[i].cmp(&a_sort_key[i])
-                        };
                         if res != Ordering::Equal {
-                            return res;
+                            return res.reverse();
                         }
                     }
                     Ordering::Equal
@@ -131,6 +135,10 @@ impl OpIterator for Sort {
     fn get_schema(&self) -> &TableSchema {
         &self.schema
     }
+
+    fn state_size(&self) -> usize {
+        self.sorted_data.len()
+    }
 }
 
 #[cfg(test)]
@@ -144,7 +152,7 @@ mod test {
     use crate::testutil::execute_iter;
     use crate::testutil::TestTuples;
 
-    fn get_iter(fields: Vec<(ByteCodeExpr, bool)>) -> Box<dyn OpIterator> {
+    fn get_iter(fields: Vec<(ByteCodeExpr, bool, bool)>) -> Box<dyn OpIterator> {
         let setup = TestTuples::new("");
         let managers = crate::testutil::new_test_managers();
         let mut iter = Box::new(Sort::new(
@@ -157,7 +165,7 @@ mod test {
         iter
     }
 
-    fn get_sort_fields() -> Vec<(ByteCodeExpr, bool)> {
+    fn get_sort_fields() -> Vec<(ByteCodeExpr, bool, bool)> {
         // Input:
         // 1 1 3 E
         // 2 1 3 G
@@ -173,10 +181,10 @@ mod test {
         // 3 1 4 A
         // 2 1 3 G
         // 1 1 3 E
-        vec![(colidx_expr(1), false), (colidx_expr(0), false)]
+        vec![(colidx_expr(1), false, true), (colidx_expr(0), false, true)]
     }
 
-    fn run_sort(fields: Vec<(ByteCodeExpr, bool)>) -> Vec<Tuple> {
+    fn run_sort(fields: Vec<(ByteCodeExpr, bool, bool)>) -> Vec<Tuple> {
         let mut iter = get_iter(fields);
         execute_iter(&mut *iter, false).unwrap()
     }
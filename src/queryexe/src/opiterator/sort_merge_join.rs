@@ -107,7 +107,7 @@ impl OpIterator for SortMergeJoin {
                 while let Some(left_tuple) = self.left_child.next()? {
                     let mut sort_key = Vec::new();
                     for (field, _) in &self.left_expr {
-                        sort_key.push(field.eval(&left_tuple));
+                        sort_key.push(field.eval(&left_tuple)?);
                     }
                     self.left_sorted_data.push((sort_key, left_tuple));
                 }
@@ -133,7 +133,7 @@ impl OpIterator for SortMergeJoin {
                 while let Some(right_tuple) = self.right_child.next()? {
                     let mut sort_key = Vec::new();
                     for (field, _) in &self.right_expr {
-                        sort_key.push(field.eval(&right_tuple));
+                        sort_key.push(field.eval(&right_tuple)?);
                     }
                     self.right_sorted_data.push((sort_key, right_tuple));
                 }
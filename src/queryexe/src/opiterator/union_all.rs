@@ -0,0 +1,169 @@
+use super::OpIterator;
+use common::{CrustyError, TableSchema, Tuple};
+
+/// Union all operator. Returns the tuples of the left child followed by the
+/// tuples of the right child. Both children must produce union-compatible tuples.
+pub struct UnionAll {
+    // Parameters (No need to reset on close)
+    schema: TableSchema,
+    left_child: Box<dyn OpIterator>,
+    right_child: Box<dyn OpIterator>,
+    // States (Need to reset on close)
+    open: bool,
+    /// Boolean determining if the left child has been exhausted.
+    left_done: bool,
+}
+
+impl UnionAll {
+    /// Union all constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema` - Output schema. Uses the names of the left child.
+    /// * `left_child` - Child whose tuples are returned first.
+    /// * `right_child` - Child whose tuples are returned after the left child is exhausted.
+    pub fn new(
+        schema: TableSchema,
+        left_child: Box<dyn OpIterator>,
+        right_child: Box<dyn OpIterator>,
+    ) -> Self {
+        Self {
+            schema,
+            left_child,
+            right_child,
+            open: false,
+            left_done: false,
+        }
+    }
+}
+
+impl OpIterator for UnionAll {
+    fn configure(&mut self, will_rewind: bool) {
+        self.left_child.configure(will_rewind);
+        self.right_child.configure(will_rewind);
+    }
+
+    fn open(&mut self) -> Result<(), CrustyError> {
+        if !self.open {
+            self.left_child.open()?;
+            self.right_child.open()?;
+            self.left_done = false;
+            self.open = true;
+        }
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<Tuple>, CrustyError> {
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        if !self.left_done {
+            if let Some(t) = self.left_child.next()? {
+                return Ok(Some(t));
+            }
+            self.left_done = true;
+        }
+        self.right_child.next()
+    }
+
+    fn close(&mut self) -> Result<(), CrustyError> {
+        self.left_child.close()?;
+        self.right_child.close()?;
+        self.open = false;
+        Ok(())
+    }
+
+    fn rewind(&mut self) -> Result<(), CrustyError> {
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        self.left_child.rewind()?;
+        self.right_child.rewind()?;
+        self.left_done = false;
+        Ok(())
+    }
+
+    fn get_schema(&self) -> &TableSchema {
+        &self.schema
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::TupleIterator;
+    use super::*;
+    use crate::testutil::execute_iter;
+    use crate::testutil::TestTuples;
+
+    fn get_iter() -> Box<dyn OpIterator> {
+        let setup = TestTuples::new("");
+        let mut iter = Box::new(UnionAll::new(
+            setup.schema.clone(),
+            Box::new(TupleIterator::new(
+                setup.tuples[..4].to_vec(),
+                setup.schema.clone(),
+            )),
+            Box::new(TupleIterator::new(
+                setup.tuples[2..].to_vec(),
+                setup.schema.clone(),
+            )),
+        ));
+        iter.configure(false);
+        iter
+    }
+
+    mod union_all_test {
+        use super::*;
+
+        #[test]
+        fn test_union_all() {
+            let setup = TestTuples::new("");
+            let mut iter = get_iter();
+            let t = execute_iter(&mut *iter, false).unwrap();
+            // Left tuples then right tuples, duplicates are kept
+            let expected = [&setup.tuples[..4], &setup.tuples[2..]].concat();
+            assert_eq!(t, expected);
+        }
+    }
+
+    mod opiterator_test {
+        use super::*;
+
+        #[test]
+        #[should_panic]
+        fn test_next_not_open() {
+            let mut iter = get_iter();
+            let _ = iter.next();
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_rewind_not_open() {
+            let mut iter = get_iter();
+            let _ = iter.rewind();
+        }
+
+        #[test]
+        fn test_open() {
+            let mut iter = get_iter();
+            iter.open().unwrap();
+        }
+
+        #[test]
+        fn test_close() {
+            let mut iter = get_iter();
+            iter.open().unwrap();
+            iter.close().unwrap();
+        }
+
+        #[test]
+        fn test_rewind() {
+            let mut iter = get_iter();
+            iter.configure(true);
+            let t_before = execute_iter(&mut *iter, false).unwrap();
+            iter.rewind().unwrap();
+            let t_after = execute_iter(&mut *iter, false).unwrap();
+            assert_eq!(t_before, t_after);
+        }
+    }
+}
@@ -1,123 +1,194 @@
 use super::OpIterator;
+use crate::mutator;
 use crate::Managers;
+use common::error::c_err;
 use common::physical::TupleAssignments;
 use common::prelude::*;
+use common::query::bytecode_expr::ByteCodeExpr;
+use common::traits::stat_manager_trait::StatManagerTrait;
 use common::traits::state_tracker_trait::StateTrackerTrait;
 use common::traits::storage_trait::StorageTrait;
 use common::traits::transaction_manager_trait::TransactionManagerTrait;
+use common::tuple::ConvertedResult;
 
 /// Update operator
 pub struct Update {
-    schema: TableSchema,
-    open: bool,
+    // Static objects (No need to reset on close)
     managers: &'static Managers,
-    _container_id: ContainerId,
+
+    // Parameters (No need to reset on close)
+    schema: TableSchema,
+    container_id: ContainerId,
     tid: TransactionId,
-    assignments: TupleAssignments,
+    /// Index of the field to update and the expression computing its new value.
+    /// The expressions are evaluated against the tuple before the update.
+    assignments: Vec<(usize, ByteCodeExpr)>,
     child: Box<dyn OpIterator>,
+
+    // States (Need to reset on close)
+    open: bool,
+    /// Tuples to update. The child is fully consumed on open so that updated
+    /// records are never seen (and updated) again by the child.
+    buffer: Vec<Tuple>,
+    index: usize,
     count: usize,
 }
 
 impl Update {
+    /// Constructor for the update operator.
+    ///
+    /// # Arguments
+    ///
+    /// * `managers` - Managers used to update the records.
+    /// * `container_id` - Container holding the records.
+    /// * `tid` - Transaction updating the records.
+    /// * `assignments` - Field index and the expression computing its new value.
+    /// * `child` - Child producing the tuples to update. The tuples must carry their `value_id`.
     pub fn new(
         managers: &'static Managers,
         container_id: &ContainerId,
         tid: TransactionId,
-        assignments: TupleAssignments,
+        assignments: Vec<(usize, ByteCodeExpr)>,
         child: Box<dyn OpIterator>,
     ) -> Self {
         Self {
-            schema: child.get_schema().clone(),
-            open: false,
             managers,
-            _container_id: *container_id,
+            schema: child.get_schema().clone(),
+            container_id: *container_id,
             tid,
             assignments,
             child,
+            open: false,
+            buffer: Vec::new(),
+            index: 0,
             count: 0,
         }
     }
+
+    /// Evaluates the assignments against the tuple and checks the new values
+    /// against the schema. The new values are converted to the types of their columns.
+    fn get_changes(&self, tuple: &Tuple) -> Result<TupleAssignments, CrustyError> {
+        let changes: TupleAssignments = self
+            .assignments
+            .iter()
+            .map(|(i, expr)| Ok((*i, expr.eval(tuple)?)))
+            .collect::<Result<_, CrustyError>>()?;
+        let mut new_tuple = tuple.clone();
+        for (i, new_value) in &changes {
+            new_tuple.set_field(*i, new_value.clone());
+        }
+        let mut validated = mutator::validate_tuples(
+            &self.container_id,
+            &self.schema,
+            None,
+            ConvertedResult {
+                converted: vec![new_tuple],
+                unconverted: Vec::new(),
+            },
+            &self.tid,
+        )?;
+        let new_tuple = match validated.converted.pop() {
+            Some(new_tuple) => new_tuple,
+            None => {
+                return Err(CrustyError::ValidationError(format!(
+                    "Updated record is not valid: {:?}",
+                    changes
+                )))
+            }
+        };
+        Ok(changes
+            .into_iter()
+            .map(|(i, _)| (i, new_tuple.field_vals[i].clone()))
+            .collect())
+    }
 }
 
 impl OpIterator for Update {
     fn configure(&mut self, _will_rewind: bool) {
-        unimplemented!()
+        // The child is consumed once on open
+        self.child.configure(false);
     }
 
     fn open(&mut self) -> Result<(), CrustyError> {
-        self.open = true;
-        self.child.open()
+        if !self.open {
+            self.child.open()?;
+            while let Some(tuple) = self.child.next()? {
+                self.buffer.push(tuple);
+            }
+            self.index = 0;
+            self.open = true;
+        }
+        Ok(())
     }
 
     fn next(&mut self) -> Result<Option<Tuple>, CrustyError> {
         if !self.open {
             panic!("Operator has not been opened")
         }
-        let next = self.child.next()?;
-        if let Some(mut tuple) = next {
-            let id = match tuple.value_id {
-                Some(id) => id,
-                None => {
-                    return Err(CrustyError::CrustyError(
-                        "No value id set for record. Cannot update".to_string(),
-                    ));
-                }
-            };
-
-            //TODO determine should check for constraints and maintain indexes
-
-            // Update values
-            self.managers
-                .tm
-                .pre_update_record(&mut tuple, &id, &self.tid, &self.assignments)?;
-            for (field_idx, new_value) in &self.assignments {
-                tuple.set_field(*field_idx, new_value.clone());
+        if self.index >= self.buffer.len() {
+            return Ok(None);
+        }
+        let mut tuple = self.buffer[self.index].clone();
+        self.index += 1;
+        let id = match tuple.value_id {
+            Some(id) => id,
+            None => {
+                return Err(CrustyError::CrustyError(
+                    "No value id set for record. Cannot update".to_string(),
+                ));
             }
-            // Persist change
-            let res = self
-                .managers
-                .sm
-                .update_value(tuple.to_bytes(), id, self.tid);
-            //Check result
-            match res {
-                Ok(new_value_id) => {
-                    // notify txn manager
-                    self.managers.tm.post_update_record(
-                        &mut tuple,
-                        &new_value_id,
-                        &id,
-                        &self.tid,
-                        &self.assignments,
-                    )?;
-                    if new_value_id != id {
-                        // The record moved. Update index if not using PK
-                        debug!("record moved on update");
-                    }
-                    // update indexes for values that changed
-                    self.count += 1;
+        };
 
-                    // Update state tracker
-                    self.managers
-                        .stats
-                        .set_ts(new_value_id.container_id, self.tid.id());
-                }
-                Err(e) => {
-                    return Err(e);
-                }
-            }
-            return Ok(Some(tuple));
+        //TODO determine should check for constraints and maintain indexes
+        let changes = self.get_changes(&tuple)?;
+
+        // Update values
+        self.managers
+            .tm
+            .pre_update_record(&mut tuple, &id, &self.tid, &changes)?;
+        for (field_idx, new_value) in &changes {
+            tuple.set_field(*field_idx, new_value.clone());
         }
-        Ok(next)
+        // Persist change
+        let new_value_id = self
+            .managers
+            .sm
+            .update_value(tuple.to_bytes(), id, self.tid)?;
+        // notify txn manager
+        self.managers
+            .tm
+            .post_update_record(&mut tuple, &new_value_id, &id, &self.tid, &changes)?;
+        if new_value_id != id {
+            // The record moved. Update index if not using PK
+            debug!("record moved on update");
+        }
+        // update indexes for values that changed
+        self.count += 1;
+
+        // Update stats and state tracker
+        self.managers
+            .stats
+            .updated_record(&tuple, &new_value_id, Some(&id))?;
+        self.managers
+            .stats
+            .set_ts(new_value_id.container_id, self.tid.id());
+        tuple.value_id = Some(new_value_id);
+        Ok(Some(tuple))
     }
 
     fn close(&mut self) -> Result<(), CrustyError> {
         self.child.close()?;
+        self.buffer.clear();
+        self.index = 0;
         self.open = false;
         Ok(())
     }
 
     fn rewind(&mut self) -> Result<(), CrustyError> {
-        unimplemented!();
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        Err(c_err("Update cannot be rewound"))
     }
 
     fn get_schema(&self) -> &TableSchema {
@@ -0,0 +1,80 @@
+use super::OpIterator;
+use common::{CrustyError, TableSchema, Tuple};
+use std::sync::{Arc, RwLock};
+
+/// Tuples shared between a `RecursiveUnion` and the `WorkTableScan`s of its
+/// recursive side: the tuples produced by the previous iteration.
+pub type WorkTableRef = Arc<RwLock<Vec<Tuple>>>;
+
+/// Iterator over the current content of a work table.
+pub struct WorkTableScan {
+    // Parameters (No need to reset on close)
+    /// Schema of the work table.
+    schema: TableSchema,
+    /// Work table filled by the enclosing `RecursiveUnion`.
+    table: WorkTableRef,
+
+    // States (Need to reset on close)
+    /// Boolean determining if iterator is open.
+    open: bool,
+    /// Index of the next tuple to return.
+    index: usize,
+}
+
+impl WorkTableScan {
+    /// WorkTableScan constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema` - Schema of the work table.
+    /// * `table` - Work table filled by the enclosing `RecursiveUnion`.
+    pub fn new(schema: TableSchema, table: WorkTableRef) -> Self {
+        Self {
+            schema,
+            table,
+            open: false,
+            index: 0,
+        }
+    }
+}
+
+impl OpIterator for WorkTableScan {
+    fn configure(&mut self, _will_rewind: bool) {
+        // Do nothing: the work table is kept until the next iteration replaces it
+    }
+
+    fn open(&mut self) -> Result<(), CrustyError> {
+        self.open = true;
+        self.index = 0;
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<Tuple>, CrustyError> {
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        let tuple = self.table.read().unwrap().get(self.index).cloned();
+        if tuple.is_some() {
+            self.index += 1;
+        }
+        Ok(tuple)
+    }
+
+    fn close(&mut self) -> Result<(), CrustyError> {
+        self.open = false;
+        self.index = 0;
+        Ok(())
+    }
+
+    fn rewind(&mut self) -> Result<(), CrustyError> {
+        if !self.open {
+            panic!("Operator has not been opened")
+        }
+        self.index = 0;
+        Ok(())
+    }
+
+    fn get_schema(&self) -> &TableSchema {
+        &self.schema
+    }
+}
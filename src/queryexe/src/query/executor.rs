@@ -58,17 +58,93 @@ impl Executor {
         Ok(QueryResult::new_select_result(&schema, res, None)) // Setting paging_info as None.
     }
 
+    /// Consumes the opiterator and appends the tuples it produces to `res`. The tuples
+    /// produced before an error are kept, e.g. the records changed by a failed UPDATE.
+    pub fn execute_into(&mut self, res: &mut Vec<Tuple>) -> Result<(), CrustyError> {
+        let mut opiterator = self.plan.take().unwrap();
+
+        opiterator.configure(false);
+        opiterator.open()?;
+        while let Some(t) = opiterator.next()? {
+            res.push(t);
+        }
+        opiterator.close()
+    }
+
+    /// Consumes the opiterator and returns the number of tuples it produced.
+    /// Used for mutations (e.g. DELETE) where only the affected row count is reported.
+    pub fn execute_count(&mut self) -> Result<usize, CrustyError> {
+        let mut opiterator = self.plan.take().unwrap();
+
+        let mut count = 0;
+        opiterator.configure(false);
+        opiterator.open()?;
+        while opiterator.next()?.is_some() {
+            count += 1;
+        }
+        opiterator.close()?;
+
+        Ok(count)
+    }
+
+    /// Inserts the given values into the table. Returns the inserted tuples.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - Rows to insert.
+    /// * `table_id` - Destination table.
+    /// * `table_schema` - Schema of the destination table.
+    /// * `col_order` - Schema index of each value in a row. None if the rows cover every column in order.
+    /// * `txn_id` - Transaction inserting the rows.
     pub fn import_tuples(
         &self,
         values: &Values,
         _table_name: &str,
         table_id: &ContainerId,
         table_schema: &TableSchema,
+        col_order: Option<Vec<usize>>,
         txn_id: TransactionId,
-    ) -> Result<usize, CrustyError> {
+    ) -> Result<Vec<Tuple>, CrustyError> {
         let converted_result = mutator::convert_insert_vals(values)?; // This returns Vec<u8>
-        let validated_converted_result =
-            mutator::validate_tuples(table_id, table_schema, None, converted_result, &txn_id)?;
+        if !converted_result.unconverted.is_empty() {
+            return Err(CrustyError::ValidationError(format!(
+                "Some records were not valid: {:?}",
+                converted_result.unconverted
+            )));
+        }
+        self.insert_tuples(
+            converted_result.converted,
+            table_id,
+            table_schema,
+            col_order,
+            txn_id,
+        )
+    }
+
+    /// Coerces the tuples to the table schema, validates and inserts them.
+    /// Nothing is inserted if any tuple is invalid. Returns the inserted tuples.
+    ///
+    /// # Arguments
+    ///
+    /// * `tuples` - Tuples to insert.
+    /// * `table_id` - Destination table.
+    /// * `table_schema` - Schema of the destination table.
+    /// * `col_order` - Schema index of each field in a tuple. None if the tuples cover every column in order.
+    /// * `txn_id` - Transaction inserting the tuples.
+    pub fn insert_tuples(
+        &self,
+        tuples: Vec<Tuple>,
+        table_id: &ContainerId,
+        table_schema: &TableSchema,
+        col_order: Option<Vec<usize>>,
+        txn_id: TransactionId,
+    ) -> Result<Vec<Tuple>, CrustyError> {
+        let converted_result = ConvertedResult {
+            converted: tuples,
+            unconverted: Vec::new(),
+        };
+        let mut validated_converted_result =
+            mutator::validate_tuples(table_id, table_schema, col_order, converted_result, &txn_id)?;
 
         if !validated_converted_result.unconverted.is_empty() {
             return Err(CrustyError::ValidationError(format!(
@@ -77,14 +153,14 @@ impl Executor {
             )));
         }
 
-        let insert_count = mutator::insert_validated_tuples(
+        mutator::insert_validated_tuples(
             *table_id,
-            &validated_converted_result.converted,
+            &mut validated_converted_result.converted,
             txn_id,
             self.managers,
         )?;
 
-        Ok(insert_count)
+        Ok(validated_converted_result.converted)
     }
 
     /// Import database from csv file at path.
@@ -137,7 +213,7 @@ impl Executor {
             } else {
                 let insert_count = mutator::insert_validated_tuples(
                     *table_id,
-                    &result_set.converted,
+                    &mut result_set.converted,
                     txn_id,
                     self.managers,
                 )?;
@@ -0,0 +1,573 @@
+use super::planner::PlanMetrics;
+use super::translate_and_validate::EnvironmentRef;
+use crate::opiterator::OperatorMetrics;
+use common::catalog::{get_column_index_from_temp_col_id, CatalogRef};
+use common::ids::{ColumnId, ContainerId};
+use common::physical_expr::physical_rel_expr::PhysicalRelExpr;
+use common::query::expr::Expression;
+use common::query::functions::get_function;
+use common::traits::stat_manager_trait::StatManagerTrait;
+use std::collections::HashMap;
+
+/// Operator of a physical plan, as shown by EXPLAIN.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExplainNode {
+    /// Name of the operator (e.g. `hash_join`).
+    pub operator: String,
+    /// Arguments of the operator, with the column names resolved.
+    pub details: String,
+    /// Estimated number of rows produced, if the statistics allow an estimate.
+    pub estimated_rows: Option<usize>,
+    /// Runtime metrics of the operator, with EXPLAIN ANALYZE.
+    pub actual: Option<OperatorMetrics>,
+    /// Inputs of the operator.
+    pub children: Vec<ExplainNode>,
+}
+
+impl ExplainNode {
+    /// Plan tree as indented text, one operator per line.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        self.text_inner(0, &mut out);
+        out
+    }
+
+    fn text_inner(&self, indent: usize, out: &mut String) {
+        out.push_str(&format!("{}-> {}", " ".repeat(indent), self.operator));
+        if !self.details.is_empty() {
+            out.push_str(&format!("({})", self.details));
+        }
+        if let Some(rows) = self.estimated_rows {
+            out.push_str(&format!("  (rows={})", rows));
+        }
+        if let Some(actual) = &self.actual {
+            out.push_str(&format!(
+                "  (actual rows={} loops={} next_calls={} open={:.3}ms next={:.3}ms close={:.3}ms peak_state={})",
+                actual.rows,
+                actual.loops,
+                actual.next_calls,
+                millis(actual.open_time),
+                millis(actual.next_time),
+                millis(actual.close_time),
+                actual.peak_state_size
+            ));
+        }
+        out.push('\n');
+        for child in &self.children {
+            child.text_inner(indent + 2, out);
+        }
+    }
+
+    /// Plan tree as a JSON object, with the inputs of an operator in `children`.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut json = serde_json::json!({
+            "operator": self.operator,
+            "details": self.details,
+            "estimated_rows": self.estimated_rows,
+            "children": self.children.iter().map(|c| c.to_json()).collect::<Vec<_>>(),
+        });
+        if let Some(actual) = &self.actual {
+            json["actual"] = serde_json::json!({
+                "rows": actual.rows,
+                "loops": actual.loops,
+                "next_calls": actual.next_calls,
+                "open_ms": millis(actual.open_time),
+                "next_ms": millis(actual.next_time),
+                "close_ms": millis(actual.close_time),
+                "peak_state_size": actual.peak_state_size,
+            });
+        }
+        json
+    }
+}
+
+fn millis(duration: std::time::Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Builds the EXPLAIN output of a physical plan. Columns are named after the
+/// translator environment of the query, and the cardinalities are estimated with the
+/// statistics of the scanned tables.
+pub struct Explainer<'a, S: StatManagerTrait> {
+    catalog: &'a CatalogRef,
+    env: &'a EnvironmentRef,
+    stats: &'a S,
+    /// Runtime metrics of the operators, with EXPLAIN ANALYZE.
+    metrics: Option<&'a PlanMetrics>,
+    /// Names of the columns read from the tables (by temporary column id).
+    scan_names: HashMap<ColumnId, String>,
+}
+
+impl<'a, S: StatManagerTrait> Explainer<'a, S> {
+    /// Explainer constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `catalog` - Catalog of the database.
+    /// * `env` - Environment of the translated query.
+    /// * `stats` - Statistics used for the estimated cardinalities.
+    pub fn new(catalog: &'a CatalogRef, env: &'a EnvironmentRef, stats: &'a S) -> Self {
+        Self {
+            catalog,
+            env,
+            stats,
+            metrics: None,
+            scan_names: HashMap::new(),
+        }
+    }
+
+    /// Report the runtime metrics collected while running the plan, which must have been
+    /// converted with `physical_plan_to_instrumented_op_iterator`.
+    pub fn with_metrics(mut self, metrics: &'a PlanMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Describe the plan tree.
+    pub fn explain(&mut self, plan: &PhysicalRelExpr) -> ExplainNode {
+        // The inputs are described first so that the names of the scanned columns are known
+        let children: Vec<ExplainNode> = children(plan)
+            .into_iter()
+            .map(|child| self.explain(child))
+            .collect();
+        let (operator, details) = self.describe(plan);
+        let estimated_rows = self.estimate(plan, &children);
+        let actual = self.metrics.and_then(|metrics| metrics.get(plan));
+        ExplainNode {
+            operator,
+            details,
+            estimated_rows,
+            actual,
+            children,
+        }
+    }
+
+    fn describe(&mut self, plan: &PhysicalRelExpr) -> (String, String) {
+        match plan {
+            PhysicalRelExpr::Scan {
+                cid,
+                table_name,
+                column_names,
+                ..
+            } => {
+                if let Some(schema) = self.catalog.get_table_schema(*cid) {
+                    for id in column_names {
+                        let index = get_column_index_from_temp_col_id(*id);
+                        if let Some(attr) = schema.get_attribute(index) {
+                            self.scan_names
+                                .insert(*id, format!("{}.{}", table_name, attr.name()));
+                        }
+                    }
+                }
+                (
+                    "scan".to_string(),
+                    format!("{}, [{}]", table_name, self.col_list(column_names)),
+                )
+            }
+            PhysicalRelExpr::Select { predicates, .. } => {
+                ("select".to_string(), self.predicate_list(predicates))
+            }
+            PhysicalRelExpr::CrossJoin {
+                join_type,
+                predicates,
+                ..
+            } => self.describe_join("cross", join_type, predicates),
+            PhysicalRelExpr::NestedLoopJoin {
+                join_type,
+                predicates,
+                ..
+            } => self.describe_join("nested_loop", join_type, predicates),
+            PhysicalRelExpr::HashJoin {
+                join_type,
+                predicates,
+                ..
+            } => self.describe_join("hash", join_type, predicates),
+            PhysicalRelExpr::SortMergeJoin {
+                join_type,
+                predicates,
+                ..
+            } => self.describe_join("sort_merge", join_type, predicates),
+            PhysicalRelExpr::Project { cols, .. } => ("project".to_string(), self.col_list(cols)),
+            PhysicalRelExpr::Sort { cols, .. } => {
+                let cols = cols
+                    .iter()
+                    .map(|(id, asc, nulls_first)| {
+                        format!(
+                            "{} {}{}",
+                            self.col_name(*id),
+                            if *asc { "asc" } else { "desc" },
+                            if *nulls_first { " nulls first" } else { "" }
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                ("sort".to_string(), cols)
+            }
+            PhysicalRelExpr::Limit { limit, offset, .. } => {
+                let limit = match limit {
+                    Some(limit) => limit.to_string(),
+                    None => "all".to_string(),
+                };
+                ("limit".to_string(), format!("{}, offset {}", limit, offset))
+            }
+            PhysicalRelExpr::Distinct { .. } => ("distinct".to_string(), String::new()),
+            PhysicalRelExpr::EnforceSingleRow { .. } => {
+                ("enforce_single_row".to_string(), String::new())
+            }
+            PhysicalRelExpr::UnionAll { .. } => ("union_all".to_string(), String::new()),
+            PhysicalRelExpr::HashSetOp { set_op, .. } => {
+                (format!("hash_{}", set_op), String::new())
+            }
+            PhysicalRelExpr::RecursiveUnion {
+                set_op, work_table, ..
+            } => (format!("recursive_{}", set_op), format!("#{}", work_table)),
+            PhysicalRelExpr::WorkTableScan { id, cols, .. } => (
+                "work_table".to_string(),
+                format!("#{}, [{}]", id, self.col_list(cols)),
+            ),
+            PhysicalRelExpr::SingleRow { .. } => ("single_row".to_string(), String::new()),
+            PhysicalRelExpr::HashAggregate {
+                group_by, aggrs, ..
+            } => {
+                let aggrs = aggrs
+                    .iter()
+                    .map(|(id, (src, op))| {
+                        format!(
+                            "{} <- {:?}({})",
+                            self.col_name(*id),
+                            op,
+                            self.col_name(*src)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                (
+                    "hash_aggregate".to_string(),
+                    format!(
+                        "group_by: [{}], aggrs: [{}]",
+                        self.col_list(group_by),
+                        aggrs
+                    ),
+                )
+            }
+            PhysicalRelExpr::Map { exprs, .. } => {
+                let exprs = exprs
+                    .iter()
+                    .map(|(id, expr)| format!("{} <- {}", self.col_name(*id), self.expr(expr)))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                ("map".to_string(), exprs)
+            }
+            PhysicalRelExpr::FlatMap { .. } => ("flatmap".to_string(), String::new()),
+            PhysicalRelExpr::Rename { src_to_dest, .. } => {
+                let mut renames: Vec<_> = src_to_dest.iter().collect();
+                renames.sort_by_key(|(src, _)| **src);
+                let renames = renames
+                    .into_iter()
+                    .map(|(src, dest)| {
+                        format!("{} <- {}", self.col_name(*dest), self.col_name(*src))
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                ("rename".to_string(), renames)
+            }
+        }
+    }
+
+    fn describe_join(
+        &self,
+        algorithm: &str,
+        join_type: &impl std::fmt::Display,
+        predicates: &[Expression<PhysicalRelExpr>],
+    ) -> (String, String) {
+        let join_type = join_type.to_string();
+        let operator = if join_type == algorithm {
+            format!("{}_join", algorithm)
+        } else {
+            format!("{}_{}_join", algorithm, join_type)
+        };
+        (operator, self.predicate_list(predicates))
+    }
+
+    /// Estimated number of rows produced by the operator, given the estimates of its
+    /// inputs.
+    fn estimate(&self, plan: &PhysicalRelExpr, children: &[ExplainNode]) -> Option<usize> {
+        let child_rows = |i: usize| children.get(i).and_then(|c| c.estimated_rows);
+        match plan {
+            PhysicalRelExpr::Scan { cid, .. } => self.stats.get_container_record_count(*cid).ok(),
+            PhysicalRelExpr::Select { .. } => {
+                let (cid, predicates) = base_table_predicates(plan)?;
+                self.stats
+                    .estimate_count_and_sel(cid, &predicates)
+                    .ok()
+                    .map(|(count, _)| count)
+            }
+            PhysicalRelExpr::CrossJoin { predicates, .. } if predicates.is_empty() => {
+                Some(child_rows(0)? * child_rows(1)?)
+            }
+            PhysicalRelExpr::Project { .. }
+            | PhysicalRelExpr::Sort { .. }
+            | PhysicalRelExpr::Map { .. }
+            | PhysicalRelExpr::Rename { .. } => child_rows(0),
+            PhysicalRelExpr::Limit { limit, offset, .. } => {
+                let rows = child_rows(0)?.saturating_sub(*offset);
+                Some(limit.map_or(rows, |limit| rows.min(limit)))
+            }
+            PhysicalRelExpr::EnforceSingleRow { .. } => Some(1),
+            PhysicalRelExpr::UnionAll { .. } => Some(child_rows(0)? + child_rows(1)?),
+            _ => None,
+        }
+    }
+
+    /// Name of a column: the name given by the query, or the column of the table it is
+    /// read from, or its id.
+    fn col_name(&self, id: ColumnId) -> String {
+        self.env
+            .get_column_name(id)
+            .or_else(|| self.scan_names.get(&id).cloned())
+            .unwrap_or_else(|| format!("@{}", id))
+    }
+
+    fn col_list(&self, cols: &[ColumnId]) -> String {
+        cols.iter()
+            .map(|id| self.col_name(*id))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn predicate_list(&self, predicates: &[Expression<PhysicalRelExpr>]) -> String {
+        predicates
+            .iter()
+            .map(|pred| self.expr(pred))
+            .collect::<Vec<_>>()
+            .join(" && ")
+    }
+
+    /// Operand of a binary expression, in parentheses if it is itself a binary expression.
+    fn operand(&self, expr: &Expression<PhysicalRelExpr>) -> String {
+        match expr {
+            Expression::Binary { .. } => format!("({})", self.expr(expr)),
+            _ => self.expr(expr),
+        }
+    }
+
+    fn expr(&self, expr: &Expression<PhysicalRelExpr>) -> String {
+        match expr {
+            Expression::ColRef { id } => self.col_name(*id),
+            Expression::Field { val } => format!("{}", val),
+            Expression::Binary { op, left, right } => {
+                format!("{} {} {}", self.operand(left), op, self.operand(right))
+            }
+            Expression::Unary { op, expr } if op.is_prefix() => {
+                format!("{}{}", op, self.operand(expr))
+            }
+            Expression::Unary { op, expr } => format!("{} {}", self.operand(expr), op),
+            Expression::Case {
+                expr,
+                whens,
+                else_expr,
+            } => {
+                let mut out = "case".to_string();
+                if !expr.is_searched_case_operand() {
+                    out.push_str(&format!(" {}", self.expr(expr)));
+                }
+                for (when, then) in whens {
+                    out.push_str(&format!(
+                        " when {} then {}",
+                        self.expr(when),
+                        self.expr(then)
+                    ));
+                }
+                out.push_str(&format!(" else {} end", self.expr(else_expr)));
+                out
+            }
+            Expression::Subquery { .. } => "(subquery)".to_string(),
+            Expression::InList {
+                expr,
+                list,
+                negated,
+            } => format!(
+                "{} {}in ({})",
+                self.expr(expr),
+                if *negated { "not " } else { "" },
+                list.iter()
+                    .map(|e| self.expr(e))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Expression::Function { id, args } => format!(
+                "{}({})",
+                get_function(*id).name,
+                args.iter()
+                    .map(|e| self.expr(e))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Expression::Cast { expr, dtype } => format!("cast({} as {})", self.expr(expr), dtype),
+            Expression::Param { idx } => format!("${}", idx + 1),
+        }
+    }
+}
+
+/// Inputs of an operator, in the order they are shown.
+pub(crate) fn children(plan: &PhysicalRelExpr) -> Vec<&PhysicalRelExpr> {
+    match plan {
+        PhysicalRelExpr::Scan { .. }
+        | PhysicalRelExpr::WorkTableScan { .. }
+        | PhysicalRelExpr::SingleRow { .. } => vec![],
+        PhysicalRelExpr::Select { src, .. }
+        | PhysicalRelExpr::Project { src, .. }
+        | PhysicalRelExpr::Sort { src, .. }
+        | PhysicalRelExpr::Limit { src, .. }
+        | PhysicalRelExpr::Distinct { src, .. }
+        | PhysicalRelExpr::EnforceSingleRow { src, .. }
+        | PhysicalRelExpr::HashAggregate { src, .. }
+        | PhysicalRelExpr::Map { input: src, .. }
+        | PhysicalRelExpr::Rename { src, .. } => vec![src],
+        PhysicalRelExpr::CrossJoin { left, right, .. }
+        | PhysicalRelExpr::NestedLoopJoin { left, right, .. }
+        | PhysicalRelExpr::HashJoin { left, right, .. }
+        | PhysicalRelExpr::SortMergeJoin { left, right, .. }
+        | PhysicalRelExpr::UnionAll { left, right, .. }
+        | PhysicalRelExpr::HashSetOp { left, right, .. }
+        | PhysicalRelExpr::FlatMap {
+            input: left,
+            func: right,
+            ..
+        }
+        | PhysicalRelExpr::RecursiveUnion {
+            base: left,
+            recursive: right,
+            ..
+        } => vec![left, right],
+    }
+}
+
+/// If the plan only filters a table, returns the table and the predicates expressed in
+/// terms of the column indexes of the table, as expected by the statistics.
+fn base_table_predicates(
+    plan: &PhysicalRelExpr,
+) -> Option<(ContainerId, Vec<Expression<PhysicalRelExpr>>)> {
+    match plan {
+        PhysicalRelExpr::Select {
+            src, predicates, ..
+        } => {
+            let (cid, mut base_predicates) = base_table_predicates(src)?;
+            let indexes = base_table_columns(src)?;
+            for pred in predicates {
+                if pred.has_subquery() || !pred.free().iter().all(|id| indexes.contains_key(id)) {
+                    return None;
+                }
+                base_predicates.push(pred.clone().replace_variables(&indexes));
+            }
+            Some((cid, base_predicates))
+        }
+        PhysicalRelExpr::Scan { cid, .. } => Some((*cid, Vec::new())),
+        PhysicalRelExpr::Rename { src, .. } => base_table_predicates(src),
+        _ => None,
+    }
+}
+
+/// Index in the table of each column produced by a scan, possibly renamed and filtered.
+fn base_table_columns(plan: &PhysicalRelExpr) -> Option<HashMap<ColumnId, ColumnId>> {
+    match plan {
+        PhysicalRelExpr::Scan { column_names, .. } => Some(
+            column_names
+                .iter()
+                .map(|id| (*id, get_column_index_from_temp_col_id(*id)))
+                .collect(),
+        ),
+        PhysicalRelExpr::Rename {
+            src, src_to_dest, ..
+        } => {
+            let indexes = base_table_columns(src)?;
+            Some(
+                indexes
+                    .into_iter()
+                    .map(|(id, index)| (*src_to_dest.get(&id).unwrap_or(&id), index))
+                    .collect(),
+            )
+        }
+        PhysicalRelExpr::Select { src, .. } => base_table_columns(src),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn node(
+        operator: &str,
+        details: &str,
+        rows: Option<usize>,
+        children: Vec<ExplainNode>,
+    ) -> ExplainNode {
+        ExplainNode {
+            operator: operator.to_string(),
+            details: details.to_string(),
+            estimated_rows: rows,
+            actual: None,
+            children,
+        }
+    }
+
+    fn get_plan() -> ExplainNode {
+        node(
+            "cross_inner_join",
+            "",
+            Some(6),
+            vec![
+                node("scan", "t1, [t1.a]", Some(2), vec![]),
+                node(
+                    "distinct",
+                    "",
+                    None,
+                    vec![node("scan", "t2, [t2.b]", Some(3), vec![])],
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_to_text() {
+        assert_eq!(
+            get_plan().to_text(),
+            "-> cross_inner_join  (rows=6)\n  -> scan(t1, [t1.a])  (rows=2)\n  -> distinct\n    -> scan(t2, [t2.b])  (rows=3)\n"
+        );
+    }
+
+    #[test]
+    fn test_to_json() {
+        let json = get_plan().to_json();
+        assert_eq!(json["operator"], "cross_inner_join");
+        assert_eq!(json["estimated_rows"], 6);
+        assert_eq!(json["children"][0]["details"], "t1, [t1.a]");
+        assert_eq!(
+            json["children"][1]["estimated_rows"],
+            serde_json::Value::Null
+        );
+        assert_eq!(json["children"][1]["children"][0]["operator"], "scan");
+        assert_eq!(json["actual"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_actual() {
+        let mut plan = node("scan", "t1, [t1.a]", Some(2), vec![]);
+        plan.actual = Some(OperatorMetrics {
+            loops: 1,
+            rows: 3,
+            next_calls: 4,
+            open_time: std::time::Duration::from_micros(1500),
+            ..Default::default()
+        });
+        assert_eq!(
+            plan.to_text(),
+            "-> scan(t1, [t1.a])  (rows=2)  (actual rows=3 loops=1 next_calls=4 open=1.500ms next=0.000ms close=0.000ms peak_state=0)\n"
+        );
+        let json = plan.to_json();
+        assert_eq!(json["estimated_rows"], 2);
+        assert_eq!(json["actual"]["rows"], 3);
+        assert_eq!(json["actual"]["open_ms"], 1.5);
+    }
+}
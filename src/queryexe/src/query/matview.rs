@@ -0,0 +1,714 @@
+use std::collections::{BTreeMap, HashMap};
+
+use common::catalog::{get_column_index_from_temp_col_id, CatalogRef};
+use common::datatypes::f_decimal;
+use common::ids::{ColumnId, ContainerId};
+use common::logical_expr::prelude::{JoinType, LogicalRelExpr};
+use common::query::bytecode_expr::ByteCodeExpr;
+use common::{AggOp, Attribute, CrustyError, Field, TableSchema, Tuple};
+
+use crate::query::planner::convert_expr_to_bytecode;
+
+/// Signed multiset of rows: the weight of a row is the number of times it was added
+/// minus the number of times it was removed. Rows with a zero weight are not kept.
+pub type ZSet = HashMap<Vec<Field>, i64>;
+
+/// Add `weight` to the weight of `row` in `zset`.
+pub fn zset_add(zset: &mut ZSet, row: Vec<Field>, weight: i64) {
+    if weight == 0 {
+        return;
+    }
+    let entry = zset.entry(row.clone()).or_insert(0);
+    *entry += weight;
+    if *entry == 0 {
+        zset.remove(&row);
+    }
+}
+
+/// Add every row of `other` to `zset`.
+pub fn zset_merge(zset: &mut ZSet, other: &ZSet) {
+    for (row, weight) in other {
+        zset_add(zset, row.clone(), *weight);
+    }
+}
+
+/// Plan of a materialized view, maintained incrementally.
+///
+/// Given the rows inserted into and deleted from the tables of the view, `apply`
+/// returns the rows to insert into and delete from the view. Joins keep the rows of
+/// both of their inputs and aggregates keep the state of each group, so that the
+/// plan never needs to read the tables again. Starting from empty tables, the first
+/// delta (the content of the tables) gives the initial content of the view.
+pub struct DeltaPlan {
+    root: DeltaNode,
+    schema: TableSchema,
+    dependencies: Vec<ContainerId>,
+}
+
+enum DeltaNode {
+    /// Rows of a table. `indexes` are the positions of the columns in the stored tuples.
+    Scan {
+        cid: ContainerId,
+        indexes: Vec<usize>,
+    },
+    /// Rows for which every predicate is true.
+    Filter {
+        src: Box<DeltaNode>,
+        predicates: Vec<ByteCodeExpr>,
+    },
+    /// Columns computed from the input row (Project, Map and Rename).
+    Project {
+        src: Box<DeltaNode>,
+        exprs: Vec<ByteCodeExpr>,
+    },
+    /// Inner join: delta(L x R) = delta(L) x R + (L + delta(L)) x delta(R).
+    Join {
+        left: Box<DeltaNode>,
+        right: Box<DeltaNode>,
+        predicates: Vec<ByteCodeExpr>,
+        left_rows: ZSet,
+        right_rows: ZSet,
+    },
+    Aggregate {
+        src: Box<DeltaNode>,
+        group_by: Vec<usize>,
+        aggrs: Vec<(usize, AggOp)>,
+        groups: HashMap<Vec<Field>, GroupState>,
+        /// True before the first delta. A global aggregate (without GROUP BY) has a
+        /// row even without input rows, which must be produced by the first delta.
+        initial: bool,
+    },
+    Distinct {
+        src: Box<DeltaNode>,
+        counts: ZSet,
+    },
+}
+
+/// State of a group of an aggregate.
+#[derive(Default)]
+struct GroupState {
+    /// Number of input rows in the group.
+    count: i64,
+    aggrs: Vec<AggState>,
+}
+
+/// State of an aggregation in a group.
+#[derive(Default, Clone)]
+struct AggState {
+    /// Number of non-NULL values.
+    count: i64,
+    /// Sum of the non-NULL values (SUM and AVG).
+    sum: Option<Field>,
+    /// Number of occurrences of each non-NULL value (MIN, MAX and the DISTINCT
+    /// aggregations), so that removing the current minimum finds the next one.
+    values: BTreeMap<Field, i64>,
+}
+
+impl AggState {
+    fn update(&mut self, op: AggOp, value: Field, weight: i64) -> Result<(), CrustyError> {
+        if value == Field::Null {
+            return Ok(());
+        }
+        self.count += weight;
+        match op {
+            AggOp::Count => {}
+            AggOp::Sum | AggOp::Avg => {
+                let value = (value * Field::BigInt(weight))?;
+                self.sum = match self.sum.take() {
+                    Some(sum) => Some((sum + value)?),
+                    None => Some(value),
+                };
+                if self.count == 0 {
+                    self.sum = None;
+                }
+            }
+            AggOp::Max | AggOp::Min | AggOp::CountDistinct | AggOp::SumDistinct => {
+                let count = self.values.entry(value.clone()).or_insert(0);
+                *count += weight;
+                if *count == 0 {
+                    self.values.remove(&value);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn value(&self, op: AggOp) -> Result<Field, CrustyError> {
+        let value = match op {
+            AggOp::Count => Field::BigInt(self.count),
+            AggOp::CountDistinct => Field::BigInt(self.values.len() as i64),
+            AggOp::Sum => self.sum.clone().unwrap_or(Field::Null),
+            AggOp::Avg => match &self.sum {
+                Some(sum) => f_decimal(to_f64(sum)? / self.count as f64),
+                None => Field::Null,
+            },
+            AggOp::Max => self
+                .values
+                .keys()
+                .next_back()
+                .cloned()
+                .unwrap_or(Field::Null),
+            AggOp::Min => self.values.keys().next().cloned().unwrap_or(Field::Null),
+            AggOp::SumDistinct => {
+                let mut sum = None;
+                for value in self.values.keys() {
+                    sum = Some(match sum {
+                        Some(sum) => (sum + value.clone())?,
+                        None => value.clone(),
+                    });
+                }
+                sum.unwrap_or(Field::Null)
+            }
+        };
+        Ok(value)
+    }
+}
+
+fn to_f64(field: &Field) -> Result<f64, CrustyError> {
+    match field {
+        Field::BigInt(i) => Ok(*i as f64),
+        Field::Decimal(d, scale) => Ok(*d as f64 / 10f64.powi(*scale as i32)),
+        _ => Err(CrustyError::ExecutionError(format!(
+            "Cannot average {}",
+            field
+        ))),
+    }
+}
+
+fn eval(expr: &ByteCodeExpr, row: &[Field]) -> Result<Field, CrustyError> {
+    expr.eval(&Tuple::new(row.to_vec()))
+}
+
+fn satisfies(predicates: &[ByteCodeExpr], row: &[Field]) -> Result<bool, CrustyError> {
+    for predicate in predicates {
+        if eval(predicate, row)? != Field::Bool(true) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Join the rows of `left` with the rows of `right` into `out`.
+fn join_into(
+    out: &mut ZSet,
+    left: &ZSet,
+    right: &ZSet,
+    predicates: &[ByteCodeExpr],
+) -> Result<(), CrustyError> {
+    for (l, l_weight) in left {
+        for (r, r_weight) in right {
+            let row = [l.as_slice(), r.as_slice()].concat();
+            if satisfies(predicates, &row)? {
+                zset_add(out, row, l_weight * r_weight);
+            }
+        }
+    }
+    Ok(())
+}
+
+impl DeltaNode {
+    fn apply(&mut self, deltas: &HashMap<ContainerId, ZSet>) -> Result<ZSet, CrustyError> {
+        match self {
+            DeltaNode::Scan { cid, indexes } => {
+                let mut out = ZSet::new();
+                if let Some(delta) = deltas.get(cid) {
+                    for (row, weight) in delta {
+                        let row = indexes.iter().map(|i| row[*i].clone()).collect();
+                        zset_add(&mut out, row, *weight);
+                    }
+                }
+                Ok(out)
+            }
+            DeltaNode::Filter { src, predicates } => {
+                let mut out = ZSet::new();
+                for (row, weight) in src.apply(deltas)? {
+                    if satisfies(predicates, &row)? {
+                        out.insert(row, weight);
+                    }
+                }
+                Ok(out)
+            }
+            DeltaNode::Project { src, exprs } => {
+                let mut out = ZSet::new();
+                for (row, weight) in src.apply(deltas)? {
+                    let row = exprs
+                        .iter()
+                        .map(|expr| eval(expr, &row))
+                        .collect::<Result<_, _>>()?;
+                    zset_add(&mut out, row, weight);
+                }
+                Ok(out)
+            }
+            DeltaNode::Join {
+                left,
+                right,
+                predicates,
+                left_rows,
+                right_rows,
+            } => {
+                let left_delta = left.apply(deltas)?;
+                let right_delta = right.apply(deltas)?;
+                let mut out = ZSet::new();
+                join_into(&mut out, &left_delta, right_rows, predicates)?;
+                zset_merge(left_rows, &left_delta);
+                join_into(&mut out, left_rows, &right_delta, predicates)?;
+                zset_merge(right_rows, &right_delta);
+                Ok(out)
+            }
+            DeltaNode::Aggregate {
+                src,
+                group_by,
+                aggrs,
+                groups,
+                initial,
+            } => {
+                let delta = src.apply(deltas)?;
+                let mut touched: HashMap<Vec<Field>, Option<Vec<Field>>> = HashMap::new();
+                if *initial && group_by.is_empty() {
+                    // The row of an empty global aggregate did not exist before
+                    touched.insert(Vec::new(), None);
+                }
+                *initial = false;
+                for (row, weight) in delta {
+                    let key: Vec<Field> = group_by.iter().map(|i| row[*i].clone()).collect();
+                    let group = groups.entry(key.clone()).or_insert_with(|| GroupState {
+                        count: 0,
+                        aggrs: vec![AggState::default(); aggrs.len()],
+                    });
+                    if !touched.contains_key(&key) {
+                        let old = Self::group_row(&key, group, aggrs, group_by.is_empty())?;
+                        touched.insert(key.clone(), old);
+                    }
+                    group.count += weight;
+                    for ((i, op), state) in aggrs.iter().zip(group.aggrs.iter_mut()) {
+                        state.update(*op, row[*i].clone(), weight)?;
+                    }
+                }
+
+                let mut out = ZSet::new();
+                for (key, old) in touched {
+                    let group = groups.entry(key.clone()).or_insert_with(|| GroupState {
+                        count: 0,
+                        aggrs: vec![AggState::default(); aggrs.len()],
+                    });
+                    let new = Self::group_row(&key, group, aggrs, group_by.is_empty())?;
+                    if group.count == 0 && !group_by.is_empty() {
+                        groups.remove(&key);
+                    }
+                    if old != new {
+                        if let Some(old) = old {
+                            zset_add(&mut out, old, -1);
+                        }
+                        if let Some(new) = new {
+                            zset_add(&mut out, new, 1);
+                        }
+                    }
+                }
+                Ok(out)
+            }
+            DeltaNode::Distinct { src, counts } => {
+                let mut out = ZSet::new();
+                for (row, weight) in src.apply(deltas)? {
+                    let old = counts.get(&row).copied().unwrap_or(0);
+                    zset_add(counts, row.clone(), weight);
+                    let new = old + weight;
+                    if old <= 0 && new > 0 {
+                        zset_add(&mut out, row, 1);
+                    } else if old > 0 && new <= 0 {
+                        zset_add(&mut out, row, -1);
+                    }
+                }
+                Ok(out)
+            }
+        }
+    }
+
+    /// Output row of a group: the group by values followed by the aggregated values.
+    /// None if the group has no rows, unless the aggregate is global.
+    fn group_row(
+        key: &[Field],
+        group: &GroupState,
+        aggrs: &[(usize, AggOp)],
+        global: bool,
+    ) -> Result<Option<Vec<Field>>, CrustyError> {
+        if group.count <= 0 && !global {
+            return Ok(None);
+        }
+        let mut row = key.to_vec();
+        for ((_, op), state) in aggrs.iter().zip(&group.aggrs) {
+            row.push(state.value(*op)?);
+        }
+        Ok(Some(row))
+    }
+}
+
+impl DeltaPlan {
+    /// Build the incremental plan of a logical plan made of scans, selections,
+    /// projections, maps, renames, inner joins, aggregates and DISTINCT. An ORDER BY
+    /// at the top of the plan is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `plan` - Logical plan of the view.
+    /// * `catalog` - Catalog with the tables of the plan.
+    pub fn new(plan: &LogicalRelExpr, catalog: &CatalogRef) -> Result<Self, CrustyError> {
+        let plan = match plan {
+            LogicalRelExpr::OrderBy { src, .. } => src,
+            plan => plan,
+        };
+        let mut dependencies = Vec::new();
+        let (root, attributes, _) = Self::build(plan, catalog, &mut dependencies)?;
+        dependencies.sort();
+        dependencies.dedup();
+        Ok(Self {
+            root,
+            schema: TableSchema::new(attributes),
+            dependencies,
+        })
+    }
+
+    /// Schema of the rows of the view.
+    pub fn schema(&self) -> &TableSchema {
+        &self.schema
+    }
+
+    /// Tables read by the view.
+    pub fn dependencies(&self) -> &[ContainerId] {
+        &self.dependencies
+    }
+
+    /// Returns the changes to the view given the changes to its tables.
+    ///
+    /// # Arguments
+    ///
+    /// * `deltas` - Rows (as stored in the table) inserted with a positive weight and
+    ///   deleted with a negative weight, for each changed table.
+    pub fn apply(&mut self, deltas: &HashMap<ContainerId, ZSet>) -> Result<ZSet, CrustyError> {
+        self.root.apply(deltas)
+    }
+
+    /// Returns the node, its attributes and the column ids of its rows.
+    fn build(
+        plan: &LogicalRelExpr,
+        catalog: &CatalogRef,
+        dependencies: &mut Vec<ContainerId>,
+    ) -> Result<(DeltaNode, Vec<Attribute>, Vec<ColumnId>), CrustyError> {
+        let offsets = |cols: &[ColumnId]| -> HashMap<ColumnId, ColumnId> {
+            cols.iter().enumerate().map(|(i, id)| (*id, i)).collect()
+        };
+        match plan {
+            LogicalRelExpr::Scan {
+                cid,
+                table_name,
+                column_names,
+            } => {
+                let schema = catalog.get_table_schema(*cid).ok_or_else(|| {
+                    CrustyError::ExecutionError(format!("Table {} not found", table_name))
+                })?;
+                dependencies.push(*cid);
+                let indexes: Vec<usize> = column_names
+                    .iter()
+                    .map(|id| get_column_index_from_temp_col_id(*id))
+                    .collect();
+                let attributes = indexes
+                    .iter()
+                    .map(|i| schema.get_attribute(*i).unwrap().clone())
+                    .collect();
+                Ok((
+                    DeltaNode::Scan { cid: *cid, indexes },
+                    attributes,
+                    column_names.clone(),
+                ))
+            }
+            LogicalRelExpr::Select { src, predicates } => {
+                let (src, attributes, cols) = Self::build(src, catalog, dependencies)?;
+                let col_id_to_idx = offsets(&cols);
+                let predicates = predicates
+                    .iter()
+                    .map(|p| convert_expr_to_bytecode(p.clone(), Some(&col_id_to_idx)))
+                    .collect::<Result<_, _>>()?;
+                let node = DeltaNode::Filter {
+                    src: Box::new(src),
+                    predicates,
+                };
+                Ok((node, attributes, cols))
+            }
+            LogicalRelExpr::Project { src, cols: out } => {
+                let (src, attributes, cols) = Self::build(src, catalog, dependencies)?;
+                let col_id_to_idx = offsets(&cols);
+                let mut exprs = Vec::new();
+                let mut out_attributes = Vec::new();
+                for col in out {
+                    let idx = col_id_to_idx[col];
+                    exprs.push(common::query::bytecode_expr::colidx_expr(idx));
+                    out_attributes.push(attributes[idx].clone());
+                }
+                let node = DeltaNode::Project {
+                    src: Box::new(src),
+                    exprs,
+                };
+                Ok((node, out_attributes, out.clone()))
+            }
+            LogicalRelExpr::Map { input, exprs } => {
+                let (src, mut attributes, mut cols) = Self::build(input, catalog, dependencies)?;
+                let col_id_to_idx = offsets(&cols);
+                let schema = TableSchema::new(attributes.clone());
+                let mut out_exprs: Vec<ByteCodeExpr> = (0..cols.len())
+                    .map(common::query::bytecode_expr::colidx_expr)
+                    .collect();
+                for (id, expr) in exprs {
+                    attributes.push(expr.to_attr(&schema, &col_id_to_idx));
+                    out_exprs.push(convert_expr_to_bytecode(
+                        expr.clone(),
+                        Some(&col_id_to_idx),
+                    )?);
+                    cols.push(*id);
+                }
+                let node = DeltaNode::Project {
+                    src: Box::new(src),
+                    exprs: out_exprs,
+                };
+                Ok((node, attributes, cols))
+            }
+            LogicalRelExpr::Rename { src, src_to_dest } => {
+                let (src, attributes, cols) = Self::build(src, catalog, dependencies)?;
+                let cols = cols
+                    .into_iter()
+                    .map(|col| *src_to_dest.get(&col).unwrap_or(&col))
+                    .collect();
+                Ok((src, attributes, cols))
+            }
+            LogicalRelExpr::Join {
+                join_type: JoinType::Inner | JoinType::CrossJoin,
+                left,
+                right,
+                predicates,
+            } => {
+                let (left, left_attributes, left_cols) = Self::build(left, catalog, dependencies)?;
+                let (right, right_attributes, right_cols) =
+                    Self::build(right, catalog, dependencies)?;
+                let cols = [left_cols, right_cols].concat();
+                let col_id_to_idx = offsets(&cols);
+                let predicates = predicates
+                    .iter()
+                    .map(|p| convert_expr_to_bytecode(p.clone(), Some(&col_id_to_idx)))
+                    .collect::<Result<_, _>>()?;
+                let node = DeltaNode::Join {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                    predicates,
+                    left_rows: ZSet::new(),
+                    right_rows: ZSet::new(),
+                };
+                Ok((node, [left_attributes, right_attributes].concat(), cols))
+            }
+            LogicalRelExpr::Aggregate {
+                src,
+                group_by,
+                aggrs,
+            } => {
+                let (src, attributes, cols) = Self::build(src, catalog, dependencies)?;
+                let col_id_to_idx = offsets(&cols);
+                let mut out_attributes = Vec::new();
+                let mut out_cols = Vec::new();
+                let mut group_by_idx = Vec::new();
+                for col in group_by {
+                    let idx = col_id_to_idx[col];
+                    group_by_idx.push(idx);
+                    out_attributes.push(attributes[idx].clone());
+                    out_cols.push(*col);
+                }
+                let mut aggrs_idx = Vec::new();
+                for (dest, (col, op)) in aggrs {
+                    let idx = col_id_to_idx[col];
+                    aggrs_idx.push((idx, *op));
+                    out_attributes.push(op.to_attr(&attributes[idx]));
+                    out_cols.push(*dest);
+                }
+                let node = DeltaNode::Aggregate {
+                    src: Box::new(src),
+                    group_by: group_by_idx,
+                    aggrs: aggrs_idx,
+                    groups: HashMap::new(),
+                    initial: true,
+                };
+                Ok((node, out_attributes, out_cols))
+            }
+            LogicalRelExpr::Distinct { src } => {
+                let (src, attributes, cols) = Self::build(src, catalog, dependencies)?;
+                let node = DeltaNode::Distinct {
+                    src: Box::new(src),
+                    counts: ZSet::new(),
+                };
+                Ok((node, attributes, cols))
+            }
+            LogicalRelExpr::Join { join_type, .. } => Err(CrustyError::ValidationError(format!(
+                "{:?} joins are not supported in materialized views",
+                join_type
+            ))),
+            _ => Err(CrustyError::ValidationError(format!(
+                "Materialized views only support selections, projections, inner joins, \
+                 aggregates and DISTINCT:\n{}",
+                plan.pretty_string()
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::datatypes::f_int;
+    use common::physical::col_id_generator::ColIdGenerator;
+    use common::query::rules::Rules;
+    use common::table::TableInfo;
+    use common::{catalog::Catalog, DataType};
+    use std::sync::Arc;
+
+    use crate::query::Translator;
+
+    fn get_catalog() -> CatalogRef {
+        let catalog = Catalog::new();
+        for (name, cols) in [("t1", vec!["a", "b"]), ("t2", vec!["c", "d"])] {
+            let dtypes = cols.iter().map(|_| DataType::BigInt).collect();
+            let schema = TableSchema::from_vecs(cols, dtypes);
+            let cid = catalog.get_table_id(name);
+            catalog.add_table(TableInfo::new(cid, name.to_string(), schema));
+        }
+        catalog
+    }
+
+    fn try_delta_plan(catalog: &CatalogRef, sql: &str) -> Result<DeltaPlan, CrustyError> {
+        let dialect = sqlparser::dialect::GenericDialect {};
+        let statement = sqlparser::parser::Parser::parse_sql(&dialect, sql)
+            .unwrap()
+            .pop()
+            .unwrap();
+        let query = match statement {
+            sqlparser::ast::Statement::Query(query) => query,
+            _ => panic!("Expected a query"),
+        };
+        let enabled_rules = Arc::new(Rules::default());
+        let col_id_gen = Arc::new(ColIdGenerator::new());
+        let query = Translator::from_sql(&query, catalog, &enabled_rules, &col_id_gen).unwrap();
+        DeltaPlan::new(query.get_plan(), catalog)
+    }
+
+    fn get_delta_plan(catalog: &CatalogRef, sql: &str) -> DeltaPlan {
+        try_delta_plan(catalog, sql).unwrap()
+    }
+
+    fn rows(values: &[&[i64]], weight: i64) -> ZSet {
+        let mut zset = ZSet::new();
+        for row in values {
+            zset_add(&mut zset, row.iter().map(|v| f_int(*v)).collect(), weight);
+        }
+        zset
+    }
+
+    fn delta(catalog: &CatalogRef, table: &str, zset: ZSet) -> HashMap<ContainerId, ZSet> {
+        HashMap::from([(catalog.get_table_id_if_exists(table).unwrap(), zset)])
+    }
+
+    #[test]
+    fn test_select_project() {
+        let catalog = get_catalog();
+        let mut plan = get_delta_plan(&catalog, "SELECT b, a + 1 FROM t1 WHERE a > 1");
+        assert_eq!(plan.schema().size(), 2);
+        let out = plan
+            .apply(&delta(&catalog, "t1", rows(&[&[1, 10], &[2, 20]], 1)))
+            .unwrap();
+        assert_eq!(out, rows(&[&[20, 3]], 1));
+        let out = plan
+            .apply(&delta(&catalog, "t1", rows(&[&[2, 20], &[1, 10]], -1)))
+            .unwrap();
+        assert_eq!(out, rows(&[&[20, 3]], -1));
+    }
+
+    #[test]
+    fn test_join() {
+        let catalog = get_catalog();
+        let mut plan = get_delta_plan(&catalog, "SELECT a, d FROM t1, t2 WHERE b = c");
+        assert_eq!(plan.dependencies().len(), 2);
+        let out = plan
+            .apply(&delta(&catalog, "t1", rows(&[&[1, 10], &[2, 20]], 1)))
+            .unwrap();
+        assert!(out.is_empty());
+        let out = plan
+            .apply(&delta(&catalog, "t2", rows(&[&[10, 100], &[10, 101]], 1)))
+            .unwrap();
+        assert_eq!(out, rows(&[&[1, 100], &[1, 101]], 1));
+        // Both sides change at once
+        let mut deltas = delta(&catalog, "t1", rows(&[&[3, 10]], 1));
+        deltas.extend(delta(&catalog, "t2", rows(&[&[10, 100]], -1)));
+        let out = plan.apply(&deltas).unwrap();
+        let mut expected = rows(&[&[3, 101]], 1);
+        zset_add(&mut expected, vec![f_int(1), f_int(100)], -1);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let catalog = get_catalog();
+        let mut plan = get_delta_plan(
+            &catalog,
+            "SELECT a, COUNT(b), SUM(b), MIN(b), MAX(b) FROM t1 GROUP BY a",
+        );
+        let out = plan
+            .apply(&delta(
+                &catalog,
+                "t1",
+                rows(&[&[1, 10], &[1, 20], &[2, 5]], 1),
+            ))
+            .unwrap();
+        let mut expected = rows(&[&[1, 2, 30, 10, 20]], 1);
+        zset_merge(&mut expected, &rows(&[&[2, 1, 5, 5, 5]], 1));
+        assert_eq!(out, expected);
+        // Deleting the minimum of a group and the last row of another
+        let out = plan
+            .apply(&delta(&catalog, "t1", rows(&[&[1, 10], &[2, 5]], -1)))
+            .unwrap();
+        let mut expected = rows(&[&[1, 2, 30, 10, 20], &[2, 1, 5, 5, 5]], -1);
+        zset_merge(&mut expected, &rows(&[&[1, 1, 20, 20, 20]], 1));
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_global_aggregate() {
+        let catalog = get_catalog();
+        let mut plan = get_delta_plan(&catalog, "SELECT COUNT(a), SUM(a) FROM t1");
+        let out = plan.apply(&HashMap::new()).unwrap();
+        assert_eq!(out, ZSet::from([(vec![f_int(0), Field::Null], 1)]));
+        let out = plan
+            .apply(&delta(&catalog, "t1", rows(&[&[1, 10], &[2, 20]], 1)))
+            .unwrap();
+        let mut expected = ZSet::from([(vec![f_int(0), Field::Null], -1)]);
+        zset_merge(&mut expected, &rows(&[&[2, 3]], 1));
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_distinct() {
+        let catalog = get_catalog();
+        let mut plan = get_delta_plan(&catalog, "SELECT DISTINCT a FROM t1");
+        let out = plan
+            .apply(&delta(&catalog, "t1", rows(&[&[1, 10], &[1, 20]], 1)))
+            .unwrap();
+        assert_eq!(out, rows(&[&[1]], 1));
+        let out = plan
+            .apply(&delta(&catalog, "t1", rows(&[&[1, 10]], -1)))
+            .unwrap();
+        assert!(out.is_empty());
+        let out = plan
+            .apply(&delta(&catalog, "t1", rows(&[&[1, 20]], -1)))
+            .unwrap();
+        assert_eq!(out, rows(&[&[1]], -1));
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let catalog = get_catalog();
+        assert!(try_delta_plan(&catalog, "SELECT a FROM t1 LIMIT 1").is_err());
+        assert!(try_delta_plan(&catalog, "SELECT a FROM t1 UNION SELECT c FROM t2").is_err());
+    }
+}
@@ -2,6 +2,9 @@ pub use executor::Executor;
 pub use translate_and_validate::get_attr;
 pub use translate_and_validate::Translator;
 mod executor;
+pub mod explain;
+pub mod matview;
+pub mod params;
 // pub mod planner0;
 pub mod planner;
 pub mod translate_and_validate;
@@ -0,0 +1,390 @@
+use super::explain::children;
+use crate::mutator::{coerce_field, is_valid_type};
+use common::catalog::{get_column_index_from_temp_col_id, CatalogRef};
+use common::error::c_err;
+use common::ids::ColumnId;
+use common::physical_expr::physical_rel_expr::PhysicalRelExpr;
+use common::query::expr::Expression;
+use common::query::functions::get_function;
+use common::traits::plan::Plan;
+use common::{AggOp, BinaryOp, CrustyError, DataType, Field, UnaryOp};
+use std::collections::HashMap;
+
+/// Types of the parameters (`$1`, `$2`, ...) of a prepared statement. Types are declared
+/// by the statement or inferred from the expressions the parameters are compared with,
+/// combined with or assigned to. A parameter of unknown type accepts any value.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParamTypes {
+    types: Vec<Option<DataType>>,
+}
+
+impl ParamTypes {
+    /// Parameter types declared by the statement, e.g. `PREPARE q (INT, TEXT) AS ...`.
+    pub fn new(declared: Vec<DataType>) -> Self {
+        Self {
+            types: declared.into_iter().map(Some).collect(),
+        }
+    }
+
+    /// Number of parameters of the statement.
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    /// Type of a parameter, if known.
+    pub fn get(&self, idx: usize) -> Option<&DataType> {
+        self.types.get(idx).and_then(|dtype| dtype.as_ref())
+    }
+
+    /// Sets the type of a parameter, unless it is already known.
+    pub fn set(&mut self, idx: usize, dtype: Option<DataType>) {
+        if self.types.len() <= idx {
+            self.types.resize(idx + 1, None);
+        }
+        if self.types[idx].is_none() && dtype != Some(DataType::Null) {
+            self.types[idx] = dtype;
+        }
+    }
+
+    /// Infers the types of the parameters used by a physical plan.
+    ///
+    /// # Arguments
+    ///
+    /// * `plan` - Plan of the prepared query.
+    /// * `catalog` - Catalog holding the schemas of the scanned tables.
+    pub fn infer_plan(&mut self, plan: &PhysicalRelExpr, catalog: &CatalogRef) {
+        let mut col_types = HashMap::new();
+        self.infer_plan_inner(plan, catalog, &mut col_types);
+    }
+
+    /// Infers the parameter types of the plan bottom-up, collecting the type of each column.
+    fn infer_plan_inner(
+        &mut self,
+        plan: &PhysicalRelExpr,
+        catalog: &CatalogRef,
+        col_types: &mut HashMap<ColumnId, DataType>,
+    ) {
+        for child in children(plan) {
+            self.infer_plan_inner(child, catalog, col_types);
+        }
+        match plan {
+            PhysicalRelExpr::Scan {
+                cid, column_names, ..
+            } => {
+                if let Some(schema) = catalog.get_table_schema(*cid) {
+                    for id in column_names {
+                        let index = get_column_index_from_temp_col_id(*id);
+                        if let Some(attr) = schema.get_attribute(index) {
+                            col_types.insert(*id, attr.dtype.clone());
+                        }
+                    }
+                }
+            }
+            PhysicalRelExpr::Select { predicates, .. }
+            | PhysicalRelExpr::CrossJoin { predicates, .. }
+            | PhysicalRelExpr::NestedLoopJoin { predicates, .. }
+            | PhysicalRelExpr::HashJoin { predicates, .. }
+            | PhysicalRelExpr::SortMergeJoin { predicates, .. } => {
+                for predicate in predicates {
+                    self.infer_predicate(predicate, col_types);
+                }
+            }
+            PhysicalRelExpr::Map { exprs, .. } => {
+                for (id, expr) in exprs {
+                    self.infer_expr(expr, col_types);
+                    if let Some(dtype) = self.expr_type(expr, col_types) {
+                        col_types.insert(*id, dtype);
+                    }
+                }
+            }
+            PhysicalRelExpr::HashAggregate { aggrs, .. } => {
+                for (dest, (src, op)) in aggrs {
+                    let dtype = match op {
+                        AggOp::Count | AggOp::CountDistinct => Some(DataType::BigInt),
+                        AggOp::Min | AggOp::Max | AggOp::Sum | AggOp::SumDistinct => {
+                            col_types.get(src).cloned()
+                        }
+                        AggOp::Avg => None,
+                    };
+                    if let Some(dtype) = dtype {
+                        col_types.insert(*dest, dtype);
+                    }
+                }
+            }
+            PhysicalRelExpr::Rename { src_to_dest, .. } => {
+                for (src, dest) in src_to_dest {
+                    if let Some(dtype) = col_types.get(src).cloned() {
+                        col_types.insert(*dest, dtype);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Infers the parameter types of a predicate, which must be a boolean.
+    pub fn infer_predicate<P: Plan>(
+        &mut self,
+        predicate: &Expression<P>,
+        col_types: &HashMap<ColumnId, DataType>,
+    ) {
+        if let Expression::Param { idx } = predicate {
+            self.set(*idx, Some(DataType::Bool));
+        }
+        self.infer_expr(predicate, col_types);
+    }
+
+    /// Infers the parameter types of an expression.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - Expression using the parameters.
+    /// * `col_types` - Types of the columns referenced by the expression.
+    pub fn infer_expr<P: Plan>(
+        &mut self,
+        expr: &Expression<P>,
+        col_types: &HashMap<ColumnId, DataType>,
+    ) {
+        match expr {
+            Expression::Param { idx } => self.set(*idx, None),
+            Expression::Binary { op, left, right } => {
+                self.infer_expr(left, col_types);
+                self.infer_expr(right, col_types);
+                let (left_type, right_type) = match op {
+                    BinaryOp::And | BinaryOp::Or => (Some(DataType::Bool), Some(DataType::Bool)),
+                    // A number of days added to or subtracted from a date
+                    BinaryOp::Add | BinaryOp::Sub => {
+                        let day_count = |dtype: Option<DataType>| match dtype {
+                            Some(DataType::Date) => Some(DataType::BigInt),
+                            dtype => dtype,
+                        };
+                        (
+                            day_count(self.expr_type(right, col_types)),
+                            day_count(self.expr_type(left, col_types)),
+                        )
+                    }
+                    _ => (
+                        self.expr_type(right, col_types),
+                        self.expr_type(left, col_types),
+                    ),
+                };
+                if let Expression::Param { idx } = left.as_ref() {
+                    self.set(*idx, left_type);
+                }
+                if let Expression::Param { idx } = right.as_ref() {
+                    self.set(*idx, right_type);
+                }
+            }
+            Expression::Unary { op, expr } => {
+                self.infer_expr(expr, col_types);
+                if let (UnaryOp::Not, Expression::Param { idx }) = (op, expr.as_ref()) {
+                    self.set(*idx, Some(DataType::Bool));
+                }
+            }
+            Expression::InList { expr, list, .. } => {
+                self.infer_expr(expr, col_types);
+                for e in list {
+                    self.infer_expr(e, col_types);
+                }
+                if let Expression::Param { idx } = expr.as_ref() {
+                    let dtype = list.iter().find_map(|e| self.expr_type(e, col_types));
+                    self.set(*idx, dtype);
+                }
+                let dtype = self.expr_type(expr, col_types);
+                for e in list {
+                    if let Expression::Param { idx } = e {
+                        self.set(*idx, dtype.clone());
+                    }
+                }
+            }
+            // CAST($1 AS t) takes a value of type t
+            Expression::Cast { expr, dtype } => {
+                self.infer_expr(expr, col_types);
+                if let Expression::Param { idx } = expr.as_ref() {
+                    self.set(*idx, Some(dtype.clone()));
+                }
+            }
+            Expression::Function { id, args } => {
+                for arg in args {
+                    self.infer_expr(arg, col_types);
+                }
+                let arg_types = args
+                    .iter()
+                    .map(|e| self.expr_type(e, col_types))
+                    .collect::<Vec<_>>();
+                let function = get_function(*id);
+                for (i, arg) in args.iter().enumerate() {
+                    if let Expression::Param { idx } = arg {
+                        self.set(*idx, function.param_type(i, &arg_types));
+                    }
+                }
+            }
+            Expression::Case {
+                expr,
+                whens,
+                else_expr,
+            } => {
+                self.infer_expr(expr, col_types);
+                for (when, then) in whens {
+                    self.infer_expr(when, col_types);
+                    self.infer_expr(then, col_types);
+                    // Compared to the operand, which is `true` in a searched CASE
+                    if let Expression::Param { idx } = when {
+                        self.set(*idx, self.expr_type(expr, col_types));
+                    }
+                }
+                self.infer_expr(else_expr, col_types);
+            }
+            Expression::ColRef { .. } | Expression::Field { .. } | Expression::Subquery { .. } => {}
+        }
+    }
+
+    /// Type of the values of an expression, if known.
+    fn expr_type<P: Plan>(
+        &self,
+        expr: &Expression<P>,
+        col_types: &HashMap<ColumnId, DataType>,
+    ) -> Option<DataType> {
+        match expr {
+            Expression::ColRef { id } => col_types.get(id).cloned(),
+            Expression::Field { val } => match val {
+                Field::Null => None,
+                val => Some(val.into()),
+            },
+            Expression::Binary { op, left, right } => match op {
+                BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => op
+                    .arithmetic_type(
+                        self.expr_type(left, col_types),
+                        self.expr_type(right, col_types),
+                    ),
+                _ => Some(DataType::Bool),
+            },
+            Expression::Unary {
+                op: UnaryOp::Neg,
+                expr,
+            } => self.expr_type(expr, col_types),
+            Expression::Unary { .. } | Expression::InList { .. } => Some(DataType::Bool),
+            Expression::Case {
+                whens, else_expr, ..
+            } => whens
+                .iter()
+                .find_map(|(_, then)| self.expr_type(then, col_types))
+                .or_else(|| self.expr_type(else_expr, col_types)),
+            Expression::Function { id, args } => {
+                let arg_types = args
+                    .iter()
+                    .map(|e| self.expr_type(e, col_types))
+                    .collect::<Vec<_>>();
+                get_function(*id).return_type(&arg_types)
+            }
+            Expression::Cast { dtype, .. } => Some(dtype.clone()),
+            Expression::Param { idx } => self.get(*idx).cloned(),
+            Expression::Subquery { .. } => None,
+        }
+    }
+
+    /// Checks the values bound to the parameters against their types, converting them to
+    /// the parameter types when the conversion is lossless.
+    pub fn check(&self, values: Vec<Field>) -> Result<Vec<Field>, CrustyError> {
+        if values.len() != self.types.len() {
+            return Err(c_err(
+                format!(
+                    "Expected {} parameters, got {}",
+                    self.types.len(),
+                    values.len()
+                )
+                .as_str(),
+            ));
+        }
+        values
+            .into_iter()
+            .zip(self.types.iter())
+            .enumerate()
+            .map(|(i, (value, dtype))| match dtype {
+                Some(dtype) => {
+                    let value = coerce_field(value, dtype);
+                    if value == Field::Null || is_valid_type(dtype, &value) {
+                        Ok(value)
+                    } else {
+                        Err(c_err(
+                            format!(
+                                "Parameter ${} expects a value of type {}, got {}",
+                                i + 1,
+                                dtype,
+                                value
+                            )
+                            .as_str(),
+                        ))
+                    }
+                }
+                None => Ok(value),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::logical_expr::prelude::LogicalRelExpr;
+
+    type Expr = Expression<LogicalRelExpr>;
+
+    fn param(idx: usize) -> Expr {
+        Expression::Param { idx }
+    }
+
+    #[test]
+    fn test_infer_expr() {
+        let col_types = [(0, DataType::BigInt), (1, DataType::String)]
+            .into_iter()
+            .collect();
+        let mut types = ParamTypes::default();
+        // @0 > $1 AND @1 IN ($3, 'a') AND $4
+        let expr = Expression::binary(
+            BinaryOp::And,
+            Expression::binary(
+                BinaryOp::And,
+                Expression::binary(BinaryOp::Gt, Expr::col_ref(0), param(0)),
+                Expression::in_list(Expr::col_ref(1), vec![param(2)], false),
+            ),
+            param(3),
+        );
+        types.infer_predicate(&expr, &col_types);
+        assert_eq!(types.len(), 4);
+        assert_eq!(types.get(0), Some(&DataType::BigInt));
+        assert_eq!(types.get(1), None);
+        assert_eq!(types.get(2), Some(&DataType::String));
+        assert_eq!(types.get(3), Some(&DataType::Bool));
+
+        // Declared types are kept
+        let mut types = ParamTypes::new(vec![DataType::String]);
+        types.infer_expr(
+            &Expression::binary(BinaryOp::Eq, param(0), Expr::int(1)),
+            &col_types,
+        );
+        assert_eq!(types.get(0), Some(&DataType::String));
+    }
+
+    #[test]
+    fn test_check() {
+        let mut types = ParamTypes::new(vec![DataType::BigInt]);
+        types.set(1, None);
+        let values = types
+            .check(vec![Field::Int(1), Field::String("a".to_string())])
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![Field::BigInt(1), Field::String("a".to_string())]
+        );
+        assert!(types.check(vec![Field::Null, Field::Null]).is_ok());
+        assert!(types
+            .check(vec![Field::String("1".to_string()), Field::Null])
+            .is_err());
+        assert!(types.check(vec![Field::BigInt(1)]).is_err());
+    }
+}
@@ -1,20 +1,33 @@
 use crate::{
     opiterator::{
-        Aggregate, CrossJoin, Filter, HashEqJoin, NestedLoopJoin, OpIterator, Project, SeqScan,
+        Aggregate, Apply, ApplyFn, CrossJoin, Distinct, EnforceSingleRow, Filter, HashEqJoin,
+        HashSetOp, Instrumented, Limit, NestedLoopJoin, OpIterator, OperatorMetrics,
+        OperatorMetricsRef, Project, RecursiveUnion, SemiJoin, SeqScan, Sort, TupleIterator,
+        UnionAll, WorkTableRef, WorkTableScan,
     },
     Managers,
 };
 use common::{
     catalog::{get_column_index_from_temp_col_id, CatalogRef},
     error::c_err,
-    ids::{ColumnId, LogicalTimeStamp, TransactionId},
-    logical_expr::prelude::{Expression, JoinType},
+    ids::{ColumnId, ContainerId, LogicalTimeStamp, TransactionId},
+    logical_expr::prelude::{Expression, JoinType, SetOpType},
     physical_expr::physical_rel_expr::PhysicalRelExpr,
-    query::bytecode_expr::{ByteCodeExpr, ByteCodes},
+    query::bytecode_expr::{like_prefix, ByteCodeExpr, ByteCodes},
     traits::plan::Plan,
-    BinaryOp, CrustyError, TableSchema,
+    BinaryOp, CrustyError, Field, TableSchema, Tuple, UnaryOp,
 };
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+thread_local! {
+    /// Work tables of the recursive unions being converted, with their schema. A
+    /// `WorkTableScan` is only valid within the recursive side of its RecursiveUnion,
+    /// which registers the work table while that side is converted.
+    static WORK_TABLES: RefCell<HashMap<usize, (WorkTableRef, TableSchema)>> =
+        RefCell::new(HashMap::new());
+}
 
 /// Convert a physical expression to a bytecode expression.
 /// This function may take in `col_id_to_idx` (mapping from the unique column ID to the
@@ -69,6 +82,28 @@ fn convert_expr_to_bytecode_inner<P: Plan>(
             bytecode_expr.add_code(i);
         }
         Expression::Binary { op, left, right } => {
+            // Patterns like 'abc%' only compare the prefix
+            // x LIKE 'abc%' Bytecode will be [x]['abc'][StartsWith]
+            if let (
+                BinaryOp::Like | BinaryOp::NotLike,
+                Expression::Field {
+                    val: Field::String(pattern),
+                },
+            ) = (op, right.as_ref())
+            {
+                if let Some(prefix) = like_prefix(pattern) {
+                    convert_expr_to_bytecode_inner(left, bytecode_expr)?;
+                    let i = bytecode_expr.add_literal(Field::String(prefix));
+                    bytecode_expr.add_code(ByteCodes::PushLit as usize);
+                    bytecode_expr.add_code(i);
+                    if *op == BinaryOp::Like {
+                        bytecode_expr.add_code(ByteCodes::StartsWith as usize);
+                    } else {
+                        bytecode_expr.add_code(ByteCodes::NotStartsWith as usize);
+                    }
+                    return Ok(());
+                }
+            }
             // (a+b)-(c+d) Bytecode will be [a][b][+][c][d][+][-]
             // i, Stack
             // 0, [a]
@@ -85,6 +120,7 @@ fn convert_expr_to_bytecode_inner<P: Plan>(
                 BinaryOp::Sub => bytecode_expr.add_code(ByteCodes::Sub as usize),
                 BinaryOp::Mul => bytecode_expr.add_code(ByteCodes::Mul as usize),
                 BinaryOp::Div => bytecode_expr.add_code(ByteCodes::Div as usize),
+                BinaryOp::Mod => bytecode_expr.add_code(ByteCodes::Mod as usize),
                 BinaryOp::Eq => bytecode_expr.add_code(ByteCodes::Eq as usize),
                 BinaryOp::Neq => bytecode_expr.add_code(ByteCodes::Neq as usize),
                 BinaryOp::Gt => bytecode_expr.add_code(ByteCodes::Gt as usize),
@@ -93,13 +129,97 @@ fn convert_expr_to_bytecode_inner<P: Plan>(
                 BinaryOp::Le => bytecode_expr.add_code(ByteCodes::Lte as usize),
                 BinaryOp::And => bytecode_expr.add_code(ByteCodes::And as usize),
                 BinaryOp::Or => bytecode_expr.add_code(ByteCodes::Or as usize),
+                BinaryOp::Like => bytecode_expr.add_code(ByteCodes::Like as usize),
+                BinaryOp::ILike => bytecode_expr.add_code(ByteCodes::ILike as usize),
+                BinaryOp::NotLike => bytecode_expr.add_code(ByteCodes::NotLike as usize),
+                BinaryOp::NotILike => bytecode_expr.add_code(ByteCodes::NotILike as usize),
+                BinaryOp::IsDistinctFrom => {
+                    bytecode_expr.add_code(ByteCodes::IsDistinctFrom as usize)
+                }
+                BinaryOp::IsNotDistinctFrom => {
+                    bytecode_expr.add_code(ByteCodes::IsNotDistinctFrom as usize)
+                }
+            }
+        }
+        Expression::Unary { op, expr } => {
+            // x IS NULL Bytecode will be [x][IsNull]
+            convert_expr_to_bytecode_inner(expr, bytecode_expr)?;
+            match op {
+                UnaryOp::IsNull => bytecode_expr.add_code(ByteCodes::IsNull as usize),
+                UnaryOp::IsNotNull => bytecode_expr.add_code(ByteCodes::IsNotNull as usize),
+                UnaryOp::Not => bytecode_expr.add_code(ByteCodes::Not as usize),
+                UnaryOp::Neg => bytecode_expr.add_code(ByteCodes::Neg as usize),
             }
         }
         Expression::ColRef { id: i } => {
             bytecode_expr.add_code(ByteCodes::PushField as usize);
             bytecode_expr.add_code(*i);
         }
-        // TODO: Currently does not support `Case` and `Subquery` physical expressions
+        Expression::InList {
+            expr,
+            list,
+            negated,
+        } => {
+            // x IN (a, b) Bytecode will be [x][a][b][In][2]
+            convert_expr_to_bytecode_inner(expr, bytecode_expr)?;
+            for e in list {
+                convert_expr_to_bytecode_inner(e, bytecode_expr)?;
+            }
+            if *negated {
+                bytecode_expr.add_code(ByteCodes::NotIn as usize);
+            } else {
+                bytecode_expr.add_code(ByteCodes::In as usize);
+            }
+            bytecode_expr.add_code(list.len());
+        }
+        Expression::Case {
+            expr,
+            whens,
+            else_expr,
+        } => {
+            // CASE x WHEN a THEN b ELSE c END Bytecode will be
+            // [x][a][Eq][JumpIfNotTrue L][b][Jump END] L: [c] END:
+            // A searched CASE has `true` as operand: its conditions are evaluated as is
+            let searched = expr.is_searched_case_operand();
+            let mut end_jumps = Vec::with_capacity(whens.len());
+            for (when, then) in whens {
+                if !searched {
+                    convert_expr_to_bytecode_inner(expr, bytecode_expr)?;
+                }
+                convert_expr_to_bytecode_inner(when, bytecode_expr)?;
+                if !searched {
+                    bytecode_expr.add_code(ByteCodes::Eq as usize);
+                }
+                let next_when = bytecode_expr.add_jump(ByteCodes::JumpIfNotTrue);
+                convert_expr_to_bytecode_inner(then, bytecode_expr)?;
+                end_jumps.push(bytecode_expr.add_jump(ByteCodes::Jump));
+                bytecode_expr.patch_jump(next_when);
+            }
+            convert_expr_to_bytecode_inner(else_expr, bytecode_expr)?;
+            for pos in end_jumps {
+                bytecode_expr.patch_jump(pos);
+            }
+        }
+        Expression::Function { id, args } => {
+            // f(a, b) Bytecode will be [a][b][CallFn][f][2]
+            for arg in args {
+                convert_expr_to_bytecode_inner(arg, bytecode_expr)?;
+            }
+            bytecode_expr.add_code(ByteCodes::CallFn as usize);
+            bytecode_expr.add_code(*id);
+            bytecode_expr.add_code(args.len());
+        }
+        Expression::Cast { expr, dtype } => {
+            // Bytecode will be [expr][Cast][type]
+            convert_expr_to_bytecode_inner(expr, bytecode_expr)?;
+            bytecode_expr.add_cast(dtype);
+        }
+        Expression::Param { idx } => {
+            return Err(c_err(
+                format!("Parameter ${} is not bound", idx + 1).as_str(),
+            ))
+        }
+        // TODO: Currently does not support `Subquery` physical expressions
         _ => return Err(c_err("Unsupported expression")),
     }
     Ok(())
@@ -130,12 +250,185 @@ pub fn physical_plan_to_op_iterator(
     timestamp: LogicalTimeStamp,
 ) -> Result<Box<dyn OpIterator>, CrustyError> {
     let (result, _) =
-        physical_plan_to_op_iterator_helper(managers, catalog, physical_plan, tid, timestamp);
+        physical_plan_to_op_iterator_helper(managers, catalog, physical_plan, tid, timestamp, None);
     result
 }
 
+/// Runtime metrics of the operators built for a physical plan by
+/// `physical_plan_to_instrumented_op_iterator`, by plan node.
+#[derive(Default)]
+pub struct PlanMetrics {
+    /// Metrics by address of the plan node the operator was built for.
+    operators: RefCell<HashMap<usize, OperatorMetricsRef>>,
+}
+
+impl PlanMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Metrics of the operator built for `plan`, shared if the node is converted again.
+    fn register(&self, plan: &PhysicalRelExpr) -> OperatorMetricsRef {
+        self.operators
+            .borrow_mut()
+            .entry(plan as *const PhysicalRelExpr as usize)
+            .or_default()
+            .clone()
+    }
+
+    /// Metrics of the operator built for `plan`, which must be a node of the converted
+    /// plan (not a copy of it). None if no operator was built for the node.
+    pub fn get(&self, plan: &PhysicalRelExpr) -> Option<OperatorMetrics> {
+        self.operators
+            .borrow()
+            .get(&(plan as *const PhysicalRelExpr as usize))
+            .map(|metrics| metrics.lock().unwrap().clone())
+    }
+}
+
+/// Convert a physical plan to an opiterator whose operators record their runtime
+/// metrics in `metrics`, as used by EXPLAIN ANALYZE.
+///
+/// # Arguments
+///
+/// * `managers` - Managers struct (saved in ServerState)
+///
+/// * `catalog` - Shared ownership of the catalog
+///
+/// * `physical_plan` - Root of the physical plan tree
+///
+/// * `tid` - Transaction ID
+///
+/// * `timestamp` - Logical timestamp
+///
+/// * `metrics` - Metrics of the operators, by node of `physical_plan`
+///
+/// # Returns
+///
+/// * `Result<Box<dyn OpIterator>, CrustyError>` - The converted root opiterator
+pub fn physical_plan_to_instrumented_op_iterator(
+    managers: &'static Managers,
+    catalog: &CatalogRef,
+    physical_plan: &PhysicalRelExpr,
+    tid: TransactionId,
+    timestamp: LogicalTimeStamp,
+    metrics: &PlanMetrics,
+) -> Result<Box<dyn OpIterator>, CrustyError> {
+    let (result, _) = physical_plan_to_op_iterator_helper(
+        managers,
+        catalog,
+        physical_plan,
+        tid,
+        timestamp,
+        Some(metrics),
+    );
+    result
+}
+
+/// Build a sequential scan over all the columns of the base table `cid`, optionally
+/// filtered by `predicate`. Unlike the scans built for a physical plan, the produced
+/// tuples keep their `value_id`, so this can feed mutation operators such as `Delete`.
+///
+/// # Arguments
+///
+/// * `managers` - Managers struct (saved in ServerState)
+///
+/// * `catalog` - Shared ownership of the catalog
+///
+/// * `cid` - Container id of the table to scan
+///
+/// * `predicate` - Optional predicate whose column references are offsets in the
+///   table schema (see `Translator::bind_table`)
+///
+/// * `tid` - Transaction ID
+///
+/// # Returns
+///
+/// * `Result<Box<dyn OpIterator>, CrustyError>` - The scan (and filter) opiterator
+pub fn table_scan_to_op_iterator<P: Plan>(
+    managers: &'static Managers,
+    catalog: &CatalogRef,
+    cid: &ContainerId,
+    predicate: Option<Expression<P>>,
+    tid: TransactionId,
+) -> Result<Box<dyn OpIterator>, CrustyError> {
+    let schema = catalog
+        .get_table_schema(*cid)
+        .ok_or_else(|| c_err(&format!("Table with id {} not found", cid)))?;
+    let scan = Box::new(SeqScan::new(managers, &schema, cid, tid, None, None));
+    match predicate {
+        Some(predicate) => {
+            let predicate = convert_expr_to_bytecode(predicate, None)?;
+            Ok(Box::new(Filter::new(predicate, schema, scan)))
+        }
+        None => Ok(scan),
+    }
+}
+
+/// Bind the columns of an outer tuple referenced by a correlated plan, replacing
+/// them with the values of the tuple (NULL if no tuple is given).
+///
+/// # Arguments
+///
+/// * `plan` - Correlated plan, i.e. the func of a FlatMap
+///
+/// * `bound` - Referenced column IDs with their index in the outer tuple
+///
+/// * `tuple` - Outer tuple
+fn bind_columns(
+    plan: &PhysicalRelExpr,
+    bound: &[(ColumnId, ColumnId)],
+    tuple: Option<&Tuple>,
+) -> PhysicalRelExpr {
+    let values = bound
+        .iter()
+        .map(|(id, idx)| {
+            let val = tuple
+                .and_then(|t| t.get_field(*idx))
+                .cloned()
+                .unwrap_or(Field::Null);
+            (*id, Expression::Field { val })
+        })
+        .collect();
+    plan.clone().replace_variables_with_exprs(&values)
+}
+
 /// Helper function called by `physical_plan_to_op_iterator` to recursively convert the
-/// physical plan to an opiterator.
+/// physical plan to an opiterator, wrapping each operator in `Instrumented` if `metrics`
+/// is set.
+fn physical_plan_to_op_iterator_helper(
+    managers: &'static Managers,
+    catalog: &CatalogRef,
+    physical_plan: &PhysicalRelExpr,
+    tid: TransactionId,
+    timestamp: LogicalTimeStamp,
+    metrics: Option<&PlanMetrics>,
+) -> (
+    Result<Box<dyn OpIterator>, CrustyError>,
+    HashMap<ColumnId, ColumnId>,
+) {
+    let (result, col_id_to_idx) = physical_plan_node_to_op_iterator(
+        managers,
+        catalog,
+        physical_plan,
+        tid,
+        timestamp,
+        metrics,
+    );
+    match metrics {
+        Some(metrics) => {
+            let result = result.map(|iter| {
+                Box::new(Instrumented::new(iter, metrics.register(physical_plan)))
+                    as Box<dyn OpIterator>
+            });
+            (result, col_id_to_idx)
+        }
+        None => (result, col_id_to_idx),
+    }
+}
+
+/// Convert the root of the physical plan to an opiterator, converting its inputs with
+/// `physical_plan_to_op_iterator_helper`.
 ///
 /// # Arguments
 ///
@@ -149,17 +442,20 @@ pub fn physical_plan_to_op_iterator(
 ///
 /// * `timestamp` - Logical timestamp
 ///
+/// * `metrics` - Metrics of the operators, if they are instrumented
+///
 /// # Returns
 ///
 /// * `Result<(Box<dyn OpIterator>, HashMap<ColumnId, ColumnId>), CrustyError>` -
 ///   The converted opiterator and a mapping from the unique column ID to the
 ///   index of the column in the schema
-fn physical_plan_to_op_iterator_helper(
+fn physical_plan_node_to_op_iterator(
     managers: &'static Managers,
     catalog: &CatalogRef,
     physical_plan: &PhysicalRelExpr,
     tid: TransactionId,
     _timestamp: LogicalTimeStamp,
+    metrics: Option<&PlanMetrics>,
 ) -> (
     Result<Box<dyn OpIterator>, CrustyError>,
     HashMap<ColumnId, ColumnId>,
@@ -202,8 +498,9 @@ fn physical_plan_to_op_iterator_helper(
         }
 
         PhysicalRelExpr::Project { src, cols, .. } => {
-            let (src_iter, col_id_to_idx) =
-                physical_plan_to_op_iterator_helper(managers, catalog, src, tid, _timestamp);
+            let (src_iter, col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, src, tid, _timestamp, metrics,
+            );
             let input_schema = src_iter.as_ref().unwrap().get_schema();
 
             let indexes = cols
@@ -240,8 +537,9 @@ fn physical_plan_to_op_iterator_helper(
         PhysicalRelExpr::Rename {
             src, src_to_dest, ..
         } => {
-            let (src_iter, col_id_to_idx) =
-                physical_plan_to_op_iterator_helper(managers, catalog, src, tid, _timestamp);
+            let (src_iter, col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, src, tid, _timestamp, metrics,
+            );
             let new_col_id_to_index = col_id_to_idx
                 .iter()
                 .map(|(old_id, offset)| {
@@ -255,8 +553,9 @@ fn physical_plan_to_op_iterator_helper(
         PhysicalRelExpr::Select {
             src, predicates, ..
         } => {
-            let (src_iter, col_id_to_idx) =
-                physical_plan_to_op_iterator_helper(managers, catalog, src, tid, _timestamp);
+            let (src_iter, col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, src, tid, _timestamp, metrics,
+            );
 
             let mut bytecode_exprs = Vec::new();
             for pred in predicates {
@@ -281,10 +580,12 @@ fn physical_plan_to_op_iterator_helper(
             predicates,
             ..
         } => {
-            let (left_iter, left_col_id_to_idx) =
-                physical_plan_to_op_iterator_helper(managers, catalog, left, tid, _timestamp);
-            let (right_iter, right_col_id_to_idx) =
-                physical_plan_to_op_iterator_helper(managers, catalog, right, tid, _timestamp);
+            let (left_iter, left_col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, left, tid, _timestamp, metrics,
+            );
+            let (right_iter, right_col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, right, tid, _timestamp, metrics,
+            );
 
             let left_schema = left_iter.as_ref().unwrap().get_schema();
             let right_schema = right_iter.as_ref().unwrap().get_schema();
@@ -322,6 +623,58 @@ fn physical_plan_to_op_iterator_helper(
             (Ok(child_filter), new_col_id_to_idx)
         }
 
+        PhysicalRelExpr::NestedLoopJoin {
+            join_type: join_type @ (JoinType::LeftSemi | JoinType::LeftAnti),
+            left,
+            right,
+            predicates,
+            ..
+        } => {
+            let (left_iter, left_col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, left, tid, _timestamp, metrics,
+            );
+            let (right_iter, right_col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, right, tid, _timestamp, metrics,
+            );
+
+            // The predicate is `left_key = right_key` where only the right key refers
+            // to the right side (the left key may be a constant)
+            let (left_key, right_key) = match &predicates[..] {
+                [Expression::Binary {
+                    op: BinaryOp::Eq,
+                    left: l,
+                    right: r,
+                }] => {
+                    if r.intersect_with(right) {
+                        (l, r)
+                    } else {
+                        (r, l)
+                    }
+                }
+                _ => return (Err(err), HashMap::new()),
+            };
+
+            let (left_iter, right_iter) = match (left_iter, right_iter) {
+                (Ok(l), Ok(r)) => (l, r),
+                (Err(e), _) | (_, Err(e)) => return (Err(e), HashMap::new()),
+            };
+            let keys = (|| {
+                Ok((
+                    convert_expr_to_bytecode(*left_key.clone(), Some(&left_col_id_to_idx))?,
+                    convert_expr_to_bytecode(*right_key.clone(), Some(&right_col_id_to_idx))?,
+                ))
+            })();
+            let (left_key, right_key) = match keys {
+                Ok(keys) => keys,
+                Err(e) => return (Err(e), HashMap::new()),
+            };
+            let schema = left_iter.get_schema().clone();
+            let join = Box::new(SemiJoin::new(
+                *join_type, schema, left_key, right_key, left_iter, right_iter,
+            ));
+            (Ok(join), left_col_id_to_idx)
+        }
+
         PhysicalRelExpr::NestedLoopJoin {
             join_type,
             left,
@@ -330,10 +683,12 @@ fn physical_plan_to_op_iterator_helper(
             ..
         } => {
             debug_assert_eq!(join_type, &JoinType::Inner);
-            let (left_iter, left_col_id_to_idx) =
-                physical_plan_to_op_iterator_helper(managers, catalog, left, tid, _timestamp);
-            let (right_iter, right_col_id_to_idx) =
-                physical_plan_to_op_iterator_helper(managers, catalog, right, tid, _timestamp);
+            let (left_iter, left_col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, left, tid, _timestamp, metrics,
+            );
+            let (right_iter, right_col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, right, tid, _timestamp, metrics,
+            );
 
             let left_schema = left_iter.as_ref().unwrap().get_schema();
             let right_schema = right_iter.as_ref().unwrap().get_schema();
@@ -382,10 +737,12 @@ fn physical_plan_to_op_iterator_helper(
             ..
         } => {
             debug_assert_eq!(join_type, &JoinType::Inner);
-            let (left_iter, left_col_id_to_idx) =
-                physical_plan_to_op_iterator_helper(managers, catalog, left, tid, _timestamp);
-            let (right_iter, right_col_id_to_idx) =
-                physical_plan_to_op_iterator_helper(managers, catalog, right, tid, _timestamp);
+            let (left_iter, left_col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, left, tid, _timestamp, metrics,
+            );
+            let (right_iter, right_col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, right, tid, _timestamp, metrics,
+            );
 
             let left_schema = left_iter.as_ref().unwrap().get_schema();
             let right_schema = right_iter.as_ref().unwrap().get_schema();
@@ -432,8 +789,9 @@ fn physical_plan_to_op_iterator_helper(
             aggrs,
             ..
         } => {
-            let (src_iter, col_id_to_idx) =
-                physical_plan_to_op_iterator_helper(managers, catalog, src, tid, _timestamp);
+            let (src_iter, col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, src, tid, _timestamp, metrics,
+            );
             let in_schema = src_iter.as_ref().unwrap().get_schema();
 
             let mut out_schema_att = Vec::new();
@@ -491,9 +849,221 @@ fn physical_plan_to_op_iterator_helper(
             (Ok(Box::new(agg_iter)), new_col_id_to_idx)
         }
 
+        PhysicalRelExpr::Sort { src, cols, .. } => {
+            let (src_iter, col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, src, tid, _timestamp, metrics,
+            );
+            let schema = src_iter.as_ref().unwrap().get_schema().clone();
+
+            let fields = cols
+                .iter()
+                .map(|(id, asc, nulls_first)| {
+                    convert_expr_to_bytecode(
+                        Expression::<PhysicalRelExpr>::ColRef { id: *id },
+                        Some(&col_id_to_idx),
+                    )
+                    .map(|expr| (expr, *asc, *nulls_first))
+                })
+                .collect::<Result<Vec<_>, CrustyError>>()
+                .unwrap();
+
+            let sort_iter = Sort::new(managers, fields, schema, src_iter.unwrap());
+            (Ok(Box::new(sort_iter)), col_id_to_idx)
+        }
+
+        PhysicalRelExpr::Limit {
+            src, limit, offset, ..
+        } => {
+            let (src_iter, col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, src, tid, _timestamp, metrics,
+            );
+            let schema = src_iter.as_ref().unwrap().get_schema().clone();
+
+            let limit_iter = Limit::new(*limit, *offset, schema, src_iter.unwrap());
+            (Ok(Box::new(limit_iter)), col_id_to_idx)
+        }
+
+        PhysicalRelExpr::Distinct { src, .. } => {
+            let (src_iter, col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, src, tid, _timestamp, metrics,
+            );
+            let schema = src_iter.as_ref().unwrap().get_schema().clone();
+
+            let distinct_iter = Distinct::new(schema, src_iter.unwrap());
+            (Ok(Box::new(distinct_iter)), col_id_to_idx)
+        }
+
+        PhysicalRelExpr::EnforceSingleRow { src, .. } => {
+            let (src_iter, col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, src, tid, _timestamp, metrics,
+            );
+            let src_iter = match src_iter {
+                Ok(iter) => iter,
+                Err(e) => return (Err(e), HashMap::new()),
+            };
+            let schema = src_iter.get_schema().clone();
+
+            let single_row_iter = EnforceSingleRow::new(schema, src_iter);
+            (Ok(Box::new(single_row_iter)), col_id_to_idx)
+        }
+
+        PhysicalRelExpr::FlatMap { input, func, .. } => {
+            let (input_iter, input_col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, input, tid, _timestamp, metrics,
+            );
+            let input_iter = match input_iter {
+                Ok(iter) => iter,
+                Err(e) => return (Err(e), HashMap::new()),
+            };
+            let input_schema = input_iter.get_schema().clone();
+
+            // Columns of the input referenced by func, with their index in the input tuples
+            let bound: Vec<(ColumnId, ColumnId)> = func
+                .free()
+                .into_iter()
+                .filter_map(|id| input_col_id_to_idx.get(&id).map(|idx| (id, *idx)))
+                .collect();
+
+            // Build func once with NULL bindings to get its schema and column mapping
+            // func is rebuilt for each input tuple, so its operators are not instrumented
+            let (func_iter, func_col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers,
+                catalog,
+                &bind_columns(func, &bound, None),
+                tid,
+                _timestamp,
+                None,
+            );
+            let func_schema = match func_iter {
+                Ok(iter) => iter.get_schema().clone(),
+                Err(e) => return (Err(e), HashMap::new()),
+            };
+            let schema = input_schema.merge(&func_schema);
+            let mut col_id_to_idx = input_col_id_to_idx;
+            for (id, idx) in func_col_id_to_idx {
+                col_id_to_idx.insert(id, idx + input_schema.size());
+            }
+
+            let catalog = catalog.clone();
+            let func = func.as_ref().clone();
+            let apply_fn: ApplyFn = Box::new(move |tuple| {
+                physical_plan_to_op_iterator_helper(
+                    managers,
+                    &catalog,
+                    &bind_columns(&func, &bound, Some(tuple)),
+                    tid,
+                    _timestamp,
+                    None,
+                )
+                .0
+            });
+            let apply_iter = Apply::new(schema, input_iter, apply_fn);
+            (Ok(Box::new(apply_iter)), col_id_to_idx)
+        }
+
+        PhysicalRelExpr::UnionAll { left, right, .. } => {
+            let (left_iter, left_col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, left, tid, _timestamp, metrics,
+            );
+            let (right_iter, _) = physical_plan_to_op_iterator_helper(
+                managers, catalog, right, tid, _timestamp, metrics,
+            );
+            let (left_iter, right_iter) = match (left_iter, right_iter) {
+                (Ok(l), Ok(r)) => (l, r),
+                (Err(e), _) | (_, Err(e)) => return (Err(e), HashMap::new()),
+            };
+            let schema = left_iter.get_schema().clone();
+
+            let union_iter = UnionAll::new(schema, left_iter, right_iter);
+            (Ok(Box::new(union_iter)), left_col_id_to_idx)
+        }
+
+        PhysicalRelExpr::HashSetOp {
+            set_op,
+            left,
+            right,
+            ..
+        } => {
+            let (left_iter, left_col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, left, tid, _timestamp, metrics,
+            );
+            let (right_iter, _) = physical_plan_to_op_iterator_helper(
+                managers, catalog, right, tid, _timestamp, metrics,
+            );
+            let (left_iter, right_iter) = match (left_iter, right_iter) {
+                (Ok(l), Ok(r)) => (l, r),
+                (Err(e), _) | (_, Err(e)) => return (Err(e), HashMap::new()),
+            };
+            let schema = left_iter.get_schema().clone();
+
+            let set_op_iter = HashSetOp::new(*set_op, schema, left_iter, right_iter);
+            (Ok(Box::new(set_op_iter)), left_col_id_to_idx)
+        }
+
+        PhysicalRelExpr::RecursiveUnion {
+            set_op,
+            base,
+            recursive,
+            work_table,
+            ..
+        } => {
+            let (base_iter, base_col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, base, tid, _timestamp, metrics,
+            );
+            let base_iter = match base_iter {
+                Ok(iter) => iter,
+                Err(e) => return (Err(e), HashMap::new()),
+            };
+            let schema = base_iter.get_schema().clone();
+
+            let table: WorkTableRef = Arc::new(RwLock::new(Vec::new()));
+            WORK_TABLES.with(|tables| {
+                tables
+                    .borrow_mut()
+                    .insert(*work_table, (table.clone(), schema.clone()))
+            });
+            let (recursive_iter, _) = physical_plan_to_op_iterator_helper(
+                managers, catalog, recursive, tid, _timestamp, metrics,
+            );
+            WORK_TABLES.with(|tables| tables.borrow_mut().remove(work_table));
+            let recursive_iter = match recursive_iter {
+                Ok(iter) => iter,
+                Err(e) => return (Err(e), HashMap::new()),
+            };
+
+            let distinct = *set_op != SetOpType::UnionAll;
+            let union_iter =
+                RecursiveUnion::new(distinct, schema, base_iter, recursive_iter, table);
+            (Ok(Box::new(union_iter)), base_col_id_to_idx)
+        }
+
+        PhysicalRelExpr::WorkTableScan { id, cols, .. } => {
+            match WORK_TABLES.with(|tables| tables.borrow().get(id).cloned()) {
+                Some((table, schema)) => {
+                    let col_id_to_idx = cols
+                        .iter()
+                        .enumerate()
+                        .map(|(i, id)| (*id, i as ColumnId))
+                        .collect();
+                    (
+                        Ok(Box::new(WorkTableScan::new(schema, table))),
+                        col_id_to_idx,
+                    )
+                }
+                None => (Err(err), HashMap::new()),
+            }
+        }
+
+        PhysicalRelExpr::SingleRow { .. } => {
+            let schema = TableSchema::new(Vec::new());
+            let iter = TupleIterator::new(vec![Tuple::new(Vec::new())], schema);
+            (Ok(Box::new(iter)), HashMap::new())
+        }
+
         PhysicalRelExpr::Map { input, exprs, .. } => {
-            let (src_iter, col_id_to_idx) =
-                physical_plan_to_op_iterator_helper(managers, catalog, input, tid, _timestamp);
+            let (src_iter, col_id_to_idx) = physical_plan_to_op_iterator_helper(
+                managers, catalog, input, tid, _timestamp, metrics,
+            );
             let in_schema = src_iter.as_ref().unwrap().get_schema();
 
             // Projecting all the columns
@@ -1,27 +1,34 @@
 // Reference: https://github.com/rotaki/decorrelator
 
 use std::{
-    collections::HashMap,
+    borrow::Cow,
+    collections::{HashMap, HashSet},
     sync::{Arc, RwLock},
 };
 
 use common::{
-    catalog::get_column_index_from_temp_col_id, query::origin_expr::OriginExpression,
+    catalog::get_column_index_from_temp_col_id,
+    query::bytecode_expr::{normalize_like_escape, Cast},
+    query::functions::{get_function, lookup_function, DateUnit},
+    query::origin_expr::OriginExpression,
     query::rules::RulesRef,
 };
 use common::{
     catalog::CatalogRef,
     datatypes::{default_decimal_precision, default_decimal_scale},
-    ids::ColumnId,
-    logical_expr::prelude::{Expression, JoinType},
+    ids::{ColumnId, ContainerId},
+    logical_expr::prelude::{Expression, JoinType, SetOpType},
     physical::col_id_generator::ColIdGeneratorRef,
+    table::ViewInfo,
     traits::plan::Plan,
-    AggOp, BinaryOp,
+    AggOp, Attribute, BinaryOp, UnaryOp,
 };
 use common::{logical_expr::prelude::LogicalRelExpr, Field};
 use common::{CrustyError, DataType};
 use sqlparser::ast::{self, ExactNumberInfo};
 
+use crate::mutator::convert_value;
+
 /// Retrieve the name from the command parser object.
 ///
 /// # Argument
@@ -66,17 +73,58 @@ pub fn get_attr(dtype: &ast::DataType) -> Result<DataType, CrustyError> {
     }
 }
 
+/// Retrieve the target type of `CAST(x AS type)`, which accepts more type names than
+/// table columns do.
+fn get_cast_type(dtype: &ast::DataType) -> Result<DataType, CrustyError> {
+    match dtype {
+        ast::DataType::SmallInt(_) => Ok(DataType::SmallInt),
+        ast::DataType::BigInt(_) => Ok(DataType::BigInt),
+        ast::DataType::Bool | ast::DataType::Boolean => Ok(DataType::Bool),
+        ast::DataType::Text | ast::DataType::String(_) => Ok(DataType::String),
+        ast::DataType::Numeric(info) | ast::DataType::Dec(info) => {
+            get_attr(&ast::DataType::Decimal(*info))
+        }
+        _ => get_attr(dtype),
+    }
+}
+
 pub type EnvironmentRef = Arc<Environment>;
 
+/// Name and arguments of a call to a function of the registry written with a syntax of
+/// its own. Arguments such as the unit of `EXTRACT(YEAR FROM d)` are made up.
+type SpecialFunctionCall<'a> = (&'static str, Vec<Cow<'a, sqlparser::ast::Expr>>);
+
+/// A common table expression defined by a WITH clause.
+#[derive(Debug, Clone)]
+struct CommonTableExpr {
+    /// Plan of the CTE. Each reference to the CTE renames its output columns.
+    plan: LogicalRelExpr,
+    /// Names of the output columns, in the order of `plan.att_in_order()`
+    columns: Vec<Vec<String>>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Environment {
     outer: Option<EnvironmentRef>,
     columns: Arc<RwLock<HashMap<String, ColumnId>>>,
 
+    /// Common table expressions visible in the current scope
+    ctes: Arc<RwLock<HashMap<String, CommonTableExpr>>>,
+
+    /// Views being expanded, used to reject views that reference themselves
+    views: Vec<String>,
+
     /// The opposite of `rename`: maps the uniquely generated column id to the
     /// original column index and container id
     id_to_origin: Arc<RwLock<HashMap<ColumnId, OriginExpression>>>,
 
+    /// Types of the columns whose type is known, shared by all the scopes of a query
+    col_types: Arc<RwLock<HashMap<ColumnId, DataType>>>,
+
+    /// Types of the parameters of a prepared statement, declared or inferred from the
+    /// values they are compared with. Shared by all the scopes of a query.
+    param_types: Arc<RwLock<HashMap<usize, DataType>>>,
+
     /// Catalog: used to find where the column is coming from
     catalog: CatalogRef,
 }
@@ -86,20 +134,42 @@ impl Environment {
         Environment {
             outer: None,
             columns: Arc::new(RwLock::new(HashMap::new())),
+            ctes: Arc::new(RwLock::new(HashMap::new())),
+            views: Vec::new(),
             id_to_origin: Arc::new(RwLock::new(HashMap::new())),
+            col_types: Arc::new(RwLock::new(HashMap::new())),
+            param_types: Arc::new(RwLock::new(HashMap::new())),
             catalog,
         }
     }
 
     fn new_with_outer(outer: EnvironmentRef, catalog: CatalogRef) -> Environment {
         Environment {
+            views: outer.views.clone(),
+            col_types: outer.col_types.clone(),
+            param_types: outer.param_types.clone(),
             outer: Some(outer),
             columns: Arc::new(RwLock::new(HashMap::new())),
+            ctes: Arc::new(RwLock::new(HashMap::new())),
             id_to_origin: Arc::new(RwLock::new(HashMap::new())),
             catalog,
         }
     }
 
+    /// Scope of the query of the view `view_name`. Nothing from the current scope is
+    /// visible in it.
+    fn new_view_scope(&self, view_name: &str) -> Environment {
+        let mut env = Environment::new(self.catalog.clone());
+        env.views = self.views.clone();
+        env.col_types = self.col_types.clone();
+        env.views.push(view_name.to_string());
+        env
+    }
+
+    fn is_expanding_view(&self, view_name: &str) -> bool {
+        self.views.iter().any(|name| name == view_name)
+    }
+
     fn get(&self, name: &str) -> Option<usize> {
         if let Some(index) = self.columns.read().unwrap().get(name) {
             return Some(*index);
@@ -135,10 +205,48 @@ impl Environment {
             .insert(name.to_string(), index);
     }
 
+    fn get_cte(&self, name: &str) -> Option<CommonTableExpr> {
+        if let Some(cte) = self.ctes.read().unwrap().get(name) {
+            return Some(cte.clone());
+        }
+
+        if let Some(outer) = &self.outer {
+            return outer.get_cte(name);
+        }
+
+        None
+    }
+
+    fn set_cte(&self, name: &str, cte: CommonTableExpr) {
+        self.ctes.write().unwrap().insert(name.to_string(), cte);
+    }
+
     fn add_to_origin_map(&self, col_id: ColumnId, origin: OriginExpression) {
         self.id_to_origin.write().unwrap().insert(col_id, origin);
     }
 
+    fn set_column_type(&self, col_id: ColumnId, dtype: DataType) {
+        self.col_types.write().unwrap().insert(col_id, dtype);
+    }
+
+    /// Type of the values of a column, None if it is not known before execution.
+    fn get_column_type(&self, col_id: ColumnId) -> Option<DataType> {
+        self.col_types.read().unwrap().get(&col_id).cloned()
+    }
+
+    /// Sets the type of the parameter `idx`, unless it is already known.
+    fn set_param_type(&self, idx: usize, dtype: DataType) {
+        self.param_types
+            .write()
+            .unwrap()
+            .entry(idx)
+            .or_insert(dtype);
+    }
+
+    fn get_param_type(&self, idx: usize) -> Option<DataType> {
+        self.param_types.read().unwrap().get(&idx).cloned()
+    }
+
     fn get_names(&self, col_id: usize) -> Vec<String> {
         let mut names = Vec::new();
         for (name, index) in self.columns.read().unwrap().iter() {
@@ -149,6 +257,20 @@ impl Environment {
         names
     }
 
+    /// Name of a column for display: a qualified name if there is one (e.g. `t1.a`),
+    /// else the name given in the query.
+    pub fn get_column_name(&self, col_id: ColumnId) -> Option<String> {
+        let mut names = self.get_names(col_id);
+        if names.is_empty() {
+            return self
+                .outer
+                .as_ref()
+                .and_then(|outer| outer.get_column_name(col_id));
+        }
+        names.sort_by_key(|name| (!name.contains('.'), name.clone()));
+        names.into_iter().next()
+    }
+
     /// Express OriginExpression in terms of BaseCidAndIndex (Convert DerivedColRef
     /// to BaseCidAndIndex)
     pub fn get_origin(&self, expr: &OriginExpression) -> OriginExpression {
@@ -183,12 +305,18 @@ impl Environment {
                     }
                 }
             }
-            OriginExpression::Field { .. } => expr.clone(),
+            OriginExpression::Field { .. }
+            | OriginExpression::Subquery
+            | OriginExpression::Param { .. } => expr.clone(),
             OriginExpression::Binary { op, left, right } => OriginExpression::Binary {
                 op: *op,
                 left: Box::new(self.get_origin(left)),
                 right: Box::new(self.get_origin(right)),
             },
+            OriginExpression::Unary { op, expr } => OriginExpression::Unary {
+                op: *op,
+                expr: Box::new(self.get_origin(expr)),
+            },
             OriginExpression::Case {
                 expr,
                 whens,
@@ -201,6 +329,23 @@ impl Environment {
                     .collect(),
                 else_expr: Box::new(self.get_origin(else_expr)),
             },
+            OriginExpression::InList {
+                expr,
+                list,
+                negated,
+            } => OriginExpression::InList {
+                expr: Box::new(self.get_origin(expr)),
+                list: list.iter().map(|e| self.get_origin(e)).collect(),
+                negated: *negated,
+            },
+            OriginExpression::Function { id, args } => OriginExpression::Function {
+                id: *id,
+                args: args.iter().map(|e| self.get_origin(e)).collect(),
+            },
+            OriginExpression::Cast { expr, dtype } => OriginExpression::Cast {
+                expr: Box::new(self.get_origin(expr)),
+                dtype: dtype.clone(),
+            },
         }
     }
 }
@@ -290,6 +435,14 @@ impl Translator {
         }
     }
 
+    /// Declare the types of the parameters of a prepared statement, e.g. of
+    /// `PREPARE q (INT, TEXT) AS ...`. `$1` has the first type.
+    pub fn declare_params(&self, types: &[DataType]) {
+        for (idx, dtype) in types.iter().enumerate() {
+            self.env.set_param_type(idx, dtype.clone());
+        }
+    }
+
     pub fn from_sql(
         sql: &sqlparser::ast::Query,
         catalog: &CatalogRef,
@@ -300,45 +453,471 @@ impl Translator {
         translator.process_query(sql)
     }
 
+    /// Translate the query of a view. Returns its plan and the names of each output
+    /// column. Fails if the query is invalid, references the view itself, or does not
+    /// have as many columns as the column list of the view.
+    pub fn translate_view(
+        view: &ViewInfo,
+        catalog: &CatalogRef,
+        enabled_rules: &RulesRef,
+        col_id_gen: &ColIdGeneratorRef,
+    ) -> Result<(LogicalRelExpr, Vec<Vec<String>>), TranslatorError> {
+        let translator = Translator::new(catalog, enabled_rules, col_id_gen);
+        translator.process_view(view)
+    }
+
+    /// Bind the columns of a single base table to their offsets in the table schema.
+    /// After this, `process_table_expr` translates expressions over that table (e.g. the
+    /// WHERE clause of a DELETE) into expressions whose column references are offsets,
+    /// so they can be evaluated directly on the stored tuples without building a plan.
+    ///
+    /// Returns the container id of the table.
+    pub fn bind_table(&mut self, table_name: &str) -> Result<ContainerId, TranslatorError> {
+        let cid = self
+            .catalog_ref
+            .get_table_id_if_exists(table_name)
+            .ok_or(translation_err!(TableNotFound, "{}", table_name))?;
+        let table = self.catalog_ref.get_table(cid).unwrap();
+        for (i, att) in table.schema.attributes().enumerate() {
+            self.env.set(att.name(), i);
+            self.env.set(&format!("{}.{}", table_name, att.name()), i);
+            self.env.set_column_type(i, att.dtype.clone());
+        }
+        Ok(cid)
+    }
+
+    /// Translate an expression over the table bound by `bind_table`.
+    pub fn process_table_expr(
+        &self,
+        expr: &sqlparser::ast::Expr,
+    ) -> Result<Expression<LogicalRelExpr>, TranslatorError> {
+        let expr = self.process_expr(expr, Some(0))?;
+        if expr.has_subquery() {
+            return Err(translation_err!(
+                UnsupportedSQL,
+                "Subqueries are not supported in this context"
+            ));
+        }
+        Ok(expr)
+    }
+
     pub fn process_query(
         &mut self,
         query: &sqlparser::ast::Query,
     ) -> Result<Query, TranslatorError> {
-        let select = match query.body.as_ref() {
-            sqlparser::ast::SetExpr::Select(select) => select,
+        if let Some(with) = &query.with {
+            self.process_with(with)?;
+        }
+        let plan = match query.body.as_ref() {
+            sqlparser::ast::SetExpr::Select(select) => {
+                self.process_select(select, &query.order_by, &query.limit, &query.offset)?
+            }
+            body => {
+                let mut plan = self.process_set_expr(body)?;
+                if !query.order_by.is_empty() {
+                    let cols = plan.att_in_order();
+                    plan = self.process_order_by(plan, &query.order_by, &cols)?;
+                    if plan.att().len() != cols.len() {
+                        // Columns added for the sort keys must not show up in the result
+                        plan =
+                            plan.project(true, &self.enabled_rules, &self.col_id_gen, cols, false);
+                    }
+                }
+                Self::process_limit(plan, &query.limit, &query.offset)?
+            }
+        };
+
+        Ok(Query {
+            env: self.env.clone(),
+            plan,
+        })
+    }
+
+    /// Register the common table expressions of a WITH clause in the environment.
+    /// Each CTE can reference the ones defined before it. With RECURSIVE, a CTE whose
+    /// query is a UNION [ALL] of a base query and a query referencing the CTE itself
+    /// becomes a recursive union over a work table.
+    fn process_with(&mut self, with: &sqlparser::ast::With) -> Result<(), TranslatorError> {
+        let mut names = HashSet::new();
+        for cte in &with.cte_tables {
+            let name = cte.alias.name.value.clone();
+            if !is_valid_alias(&name) {
+                return Err(translation_err!(InvalidSQL, "Invalid CTE name: {}", name));
+            }
+            if !names.insert(name.clone()) {
+                return Err(translation_err!(
+                    InvalidSQL,
+                    "CTE name {} specified more than once",
+                    name
+                ));
+            }
+            let recursive_parts = match cte.query.body.as_ref() {
+                sqlparser::ast::SetExpr::SetOperation {
+                    op: sqlparser::ast::SetOperator::Union,
+                    set_quantifier,
+                    left,
+                    right,
+                } if with.recursive => Some((set_quantifier, left, right)),
+                _ => None,
+            };
+            let (plan, columns) = match recursive_parts {
+                Some((set_quantifier, base, recursive)) => self.process_recursive_cte(
+                    &name,
+                    &cte.alias.columns,
+                    set_quantifier,
+                    base,
+                    recursive,
+                )?,
+                None => {
+                    let mut translator = Translator::new_with_outer(
+                        &self.catalog_ref,
+                        &self.enabled_rules,
+                        &self.col_id_gen,
+                        &self.env,
+                    );
+                    let plan = translator.process_query(&cte.query)?.plan;
+                    let columns = Self::cte_columns(&name, &cte.alias.columns, &plan, &translator)?;
+                    (plan, columns)
+                }
+            };
+            self.env.set_cte(&name, CommonTableExpr { plan, columns });
+        }
+        Ok(())
+    }
+
+    /// Translate `base UNION [ALL] recursive` of a recursive CTE. While the recursive
+    /// part is translated, the name of the CTE refers to the work table holding the
+    /// rows of the previous iteration.
+    fn process_recursive_cte(
+        &mut self,
+        name: &str,
+        alias_columns: &[sqlparser::ast::Ident],
+        set_quantifier: &sqlparser::ast::SetQuantifier,
+        base: &sqlparser::ast::SetExpr,
+        recursive: &sqlparser::ast::SetExpr,
+    ) -> Result<(LogicalRelExpr, Vec<Vec<String>>), TranslatorError> {
+        let set_op = match set_quantifier {
+            sqlparser::ast::SetQuantifier::All => SetOpType::UnionAll,
+            sqlparser::ast::SetQuantifier::Distinct | sqlparser::ast::SetQuantifier::None => {
+                SetOpType::Union
+            }
             _ => {
                 return Err(translation_err!(
                     UnsupportedSQL,
-                    "Only SELECT queries are supported"
+                    "Unsupported set quantifier: {}",
+                    set_quantifier
+                ))
+            }
+        };
+        let mut base_translator = Translator::new_with_outer(
+            &self.catalog_ref,
+            &self.enabled_rules,
+            &self.col_id_gen,
+            &self.env,
+        );
+        let base_plan = base_translator.process_set_expr(base)?;
+        let columns = Self::cte_columns(name, alias_columns, &base_plan, &base_translator)?;
+
+        // The work table has the columns of the base
+        let work_table = self.col_id_gen.next();
+        let base_types: Vec<Option<DataType>> = base_plan
+            .att_in_order()
+            .iter()
+            .map(|col| self.env.get_column_type(*col))
+            .collect();
+        let work_table_cols: Vec<ColumnId> = base_types
+            .iter()
+            .map(|dtype| {
+                let col = self.col_id_gen.next();
+                if let Some(dtype) = dtype {
+                    self.env.set_column_type(col, dtype.clone());
+                }
+                col
+            })
+            .collect();
+        let work_table_env = Arc::new(Environment::new_with_outer(
+            self.env.clone(),
+            self.catalog_ref.clone(),
+        ));
+        work_table_env.set_cte(
+            name,
+            CommonTableExpr {
+                plan: LogicalRelExpr::work_table(work_table, work_table_cols.clone()),
+                columns: columns.clone(),
+            },
+        );
+        let mut recursive_translator = Translator::new_with_outer(
+            &self.catalog_ref,
+            &self.enabled_rules,
+            &self.col_id_gen,
+            &work_table_env,
+        );
+        let recursive_plan = recursive_translator.process_set_expr(recursive)?;
+        if recursive_plan.att().len() != work_table_cols.len() {
+            return Err(translation_err!(
+                InvalidSQL,
+                "Both sides of recursive CTE {} must have the same number of columns ({} vs {})",
+                name,
+                work_table_cols.len(),
+                recursive_plan.att().len()
+            ));
+        }
+        // The rows of the recursive part are fed back into the work table, so they
+        // take the types of the base
+        self.set_op_types(
+            format!("recursive CTE {}", name),
+            &base_plan,
+            &recursive_plan,
+        )?;
+        let recursive_plan = self.coerce_columns(recursive_plan, &base_types)?;
+
+        let plan = if recursive_plan.has_work_table(work_table) {
+            base_plan.recursive_union(set_op, recursive_plan, work_table)
+        } else {
+            // Not actually recursive
+            base_plan.set_op(set_op, recursive_plan)
+        };
+        Ok((plan, columns))
+    }
+
+    /// Translate the query of a view. The view is translated in its own scope, so it
+    /// can only reference tables and other views.
+    fn process_view(
+        &self,
+        view: &ViewInfo,
+    ) -> Result<(LogicalRelExpr, Vec<Vec<String>>), TranslatorError> {
+        if self.env.is_expanding_view(&view.name) {
+            return Err(translation_err!(
+                InvalidSQL,
+                "View {} references itself",
+                view.name
+            ));
+        }
+        let dialect = sqlparser::dialect::GenericDialect {};
+        let query = match sqlparser::parser::Parser::parse_sql(&dialect, &view.sql)
+            .map_err(|e| translation_err!(InvalidSQL, "View {}: {}", view.name, e))?
+            .pop()
+        {
+            Some(sqlparser::ast::Statement::Query(query)) => query,
+            _ => {
+                return Err(translation_err!(
+                    InvalidSQL,
+                    "View {} is not defined by a query",
+                    view.name
+                ))
+            }
+        };
+
+        let mut translator = Translator {
+            catalog_ref: self.catalog_ref.clone(),
+            enabled_rules: self.enabled_rules.clone(),
+            col_id_gen: self.col_id_gen.clone(),
+            env: Arc::new(self.env.new_view_scope(&view.name)),
+        };
+        // The tables of a view can be dropped after it is created
+        let plan = match translator.process_query(&query) {
+            Ok(query) => query.plan,
+            Err(TranslatorError::TableNotFound(table)) => {
+                return Err(translation_err!(
+                    TableNotFound,
+                    "{} (referenced by view {})",
+                    table,
+                    view.name
+                ))
+            }
+            Err(e) => return Err(e),
+        };
+        let alias_columns: Vec<sqlparser::ast::Ident> = view
+            .columns
+            .iter()
+            .map(sqlparser::ast::Ident::new)
+            .collect();
+        let columns = Self::cte_columns(&view.name, &alias_columns, &plan, &translator)?;
+        Ok((plan, columns))
+    }
+
+    /// Add a CTE or view referenced in a FROM clause to the current scope, making its
+    /// columns available by name, qualified by the alias if any or by `table_name`.
+    fn add_derived_table(
+        &self,
+        plan: LogicalRelExpr,
+        columns: &[Vec<String>],
+        table_name: String,
+        alias: &Option<sqlparser::ast::TableAlias>,
+    ) -> Result<LogicalRelExpr, TranslatorError> {
+        let qualifier = match alias {
+            Some(alias) if is_valid_alias(&alias.name.value) => alias.name.value.clone(),
+            Some(alias) => {
+                return Err(translation_err!(
+                    InvalidSQL,
+                    "Invalid alias name: {}",
+                    alias.name.value
                 ))
             }
+            None => table_name,
         };
+        let cols = plan.att_in_order();
+        let (plan, new_col_ids) = plan.rename(&self.enabled_rules, &self.col_id_gen);
+        for (col, names) in cols.iter().zip(columns) {
+            let new_col_id = new_col_ids[col];
+            if let Some(dtype) = self.env.get_column_type(*col) {
+                self.env.set_column_type(new_col_id, dtype);
+            }
+            for col_name in names {
+                self.env.set(col_name, new_col_id);
+                self.env
+                    .set(&format!("{}.{}", qualifier, col_name), new_col_id);
+            }
+        }
+        Ok(plan)
+    }
+
+    /// Names of the output columns of a CTE or view: the given column list if any,
+    /// otherwise the names given by the query.
+    fn cte_columns(
+        name: &str,
+        alias_columns: &[sqlparser::ast::Ident],
+        plan: &LogicalRelExpr,
+        translator: &Translator,
+    ) -> Result<Vec<Vec<String>>, TranslatorError> {
+        let cols = plan.att_in_order();
+        if alias_columns.is_empty() {
+            return Ok(cols
+                .iter()
+                .map(|col| {
+                    let mut names = translator.env.get_names(*col);
+                    // Qualified names refer to the tables of the CTE's query
+                    names.retain(|name| !name.contains('.'));
+                    names
+                })
+                .collect());
+        }
+        if alias_columns.len() != cols.len() {
+            return Err(translation_err!(
+                InvalidSQL,
+                "{} has {} columns but {} column names were given",
+                name,
+                cols.len(),
+                alias_columns.len()
+            ));
+        }
+        Ok(alias_columns
+            .iter()
+            .map(|ident| vec![ident.value.clone()])
+            .collect())
+    }
 
+    fn process_select(
+        &mut self,
+        select: &sqlparser::ast::Select,
+        order_by: &[sqlparser::ast::OrderByExpr],
+        limit: &Option<sqlparser::ast::Expr>,
+        offset: &Option<sqlparser::ast::Offset>,
+    ) -> Result<LogicalRelExpr, TranslatorError> {
         let plan = self.process_from(&select.from)?;
         let plan = self.process_where(plan, &select.selection)?;
-        let plan = self.process_projection(
+        self.process_projection(
             plan,
             &select.projection,
             &select.from,
-            &query.order_by,
-            &query.limit,
+            order_by,
+            limit,
+            offset,
             &select.group_by,
             &select.having,
             &select.distinct,
-        )?;
+        )
+    }
 
-        Ok(Query {
-            env: self.env.clone(),
-            plan,
-        })
+    /// Translate an operand of a set operation (or a whole chain of them).
+    /// Each operand is translated in its own scope. Only the output names of the
+    /// leftmost operand are visible afterwards, since they name the result columns.
+    fn process_set_expr(
+        &mut self,
+        set_expr: &sqlparser::ast::SetExpr,
+    ) -> Result<LogicalRelExpr, TranslatorError> {
+        match set_expr {
+            sqlparser::ast::SetExpr::Select(select) => {
+                self.process_select(select, &[], &None, &None)
+            }
+            sqlparser::ast::SetExpr::Query(query) => Ok(self.process_query(query)?.plan),
+            sqlparser::ast::SetExpr::SetOperation {
+                op,
+                set_quantifier,
+                left,
+                right,
+            } => {
+                let all = match set_quantifier {
+                    sqlparser::ast::SetQuantifier::All => true,
+                    sqlparser::ast::SetQuantifier::Distinct
+                    | sqlparser::ast::SetQuantifier::None => false,
+                    _ => {
+                        return Err(translation_err!(
+                            UnsupportedSQL,
+                            "Unsupported set quantifier: {}",
+                            set_quantifier
+                        ))
+                    }
+                };
+                let set_op = match (op, all) {
+                    (sqlparser::ast::SetOperator::Union, false) => SetOpType::Union,
+                    (sqlparser::ast::SetOperator::Union, true) => SetOpType::UnionAll,
+                    (sqlparser::ast::SetOperator::Intersect, false) => SetOpType::Intersect,
+                    (sqlparser::ast::SetOperator::Intersect, true) => SetOpType::IntersectAll,
+                    (sqlparser::ast::SetOperator::Except, false) => SetOpType::Except,
+                    (sqlparser::ast::SetOperator::Except, true) => SetOpType::ExceptAll,
+                };
+
+                let mut left_translator = Translator::new_with_outer(
+                    &self.catalog_ref,
+                    &self.enabled_rules,
+                    &self.col_id_gen,
+                    &self.env,
+                );
+                let left_plan = left_translator.process_set_expr(left)?;
+                let mut right_translator = Translator::new_with_outer(
+                    &self.catalog_ref,
+                    &self.enabled_rules,
+                    &self.col_id_gen,
+                    &self.env,
+                );
+                let right_plan = right_translator.process_set_expr(right)?;
+
+                let left_cols = left_plan.att_in_order();
+                let right_cols = right_plan.att_in_order();
+                if left_cols.len() != right_cols.len() {
+                    return Err(translation_err!(
+                        InvalidSQL,
+                        "Each {} query must have the same number of columns ({} vs {})",
+                        op,
+                        left_cols.len(),
+                        right_cols.len()
+                    ));
+                }
+                let types = self.set_op_types(op, &left_plan, &right_plan)?;
+                let left_plan = self.coerce_columns(left_plan, &types)?;
+                let right_plan = self.coerce_columns(right_plan, &types)?;
+                for (i, new_i) in left_cols.into_iter().zip(left_plan.att_in_order()) {
+                    for name in left_translator.env.get_names(i) {
+                        self.env.set(&name, new_i);
+                    }
+                }
+                Ok(left_plan.set_op(set_op, right_plan))
+            }
+            _ => Err(translation_err!(
+                UnsupportedSQL,
+                "Only SELECT queries and set operations are supported"
+            )),
+        }
     }
 
     fn process_from(
         &mut self,
         from: &[sqlparser::ast::TableWithJoins],
     ) -> Result<LogicalRelExpr, TranslatorError> {
+        // A SELECT without FROM computes its select list once
         if from.is_empty() {
-            return Err(translation_err!(InvalidSQL, "FROM clause is empty"));
+            return Ok(LogicalRelExpr::single_row());
         }
 
         let mut join_exprs = Vec::with_capacity(from.len());
@@ -443,8 +1022,16 @@ impl Translator {
                 // If name exists in the catalog, then add the columns to the environment
                 // Otherwise return an error
                 let table_name = get_table_name(name);
-                if let Some(cid) = self.catalog_ref.get_table_id_if_exists(&table_name) {
+                if let Some(cte) = self.env.get_cte(&table_name) {
+                    let plan = self.add_derived_table(cte.plan, &cte.columns, table_name, alias)?;
+                    Ok((plan, false))
+                } else if let Some(view) = self.catalog_ref.get_view(&table_name) {
+                    let (plan, columns) = self.process_view(&view)?;
+                    let plan = self.add_derived_table(plan, &columns, table_name, alias)?;
+                    Ok((plan, false))
+                } else if let Some(cid) = self.catalog_ref.get_table_id_if_exists(&table_name) {
                     let cols = self.catalog_ref.get_cols(&table_name);
+                    let schema = self.catalog_ref.get_table_schema(cid).unwrap();
                     let plan = LogicalRelExpr::scan(
                         cid,
                         table_name.clone(),
@@ -461,12 +1048,14 @@ impl Translator {
                         self.env.set(&col_name, new_col_id);
                         self.env
                             .set(&format!("{}.{}", table_name, col_name), new_col_id);
+                        let index = get_column_index_from_temp_col_id(i); // Remember that `i` here is cid * MAX_COLUMNS + offset
                         self.env.add_to_origin_map(
                             new_col_id,
-                            OriginExpression::BaseCidAndIndex {
-                                cid,
-                                index: get_column_index_from_temp_col_id(i), // Remember that `i` here is cid * MAX_COLUMNS + offset
-                            },
+                            OriginExpression::BaseCidAndIndex { cid, index },
+                        );
+                        self.env.set_column_type(
+                            new_col_id,
+                            schema.get_attribute(index).unwrap().dtype.clone(),
                         );
 
                         // If there is an alias, set the alias in the current environment
@@ -530,88 +1119,161 @@ impl Translator {
 
     fn process_where(
         &mut self,
-        plan: LogicalRelExpr,
+        mut plan: LogicalRelExpr,
         where_clause: &Option<sqlparser::ast::Expr>,
     ) -> Result<LogicalRelExpr, TranslatorError> {
         if let Some(expr) = where_clause {
-            match self.process_expr(expr, Some(0)) {
-                Ok(expr) => {
-                    match expr {
-                        Expression::Subquery { expr } => {
-                            if expr.att().len() != 1 {
-                                panic!("Subquery in WHERE clause returns more than one column")
-                            }
-                            // Add map first
-                            let col_id = self.col_id_gen.next();
-                            let subquery_expr = Expression::subquery(*expr);
-                            let plan = plan.map(
-                                true,
-                                &self.enabled_rules,
-                                &self.col_id_gen,
-                                [(col_id, subquery_expr.clone())],
-                            );
-                            self.env.add_to_origin_map(col_id, subquery_expr.into());
-                            // Add select
-                            Ok(plan.select(
-                                true,
-                                &self.enabled_rules,
-                                &self.col_id_gen,
-                                vec![Expression::col_ref(col_id)],
-                            ))
-                        }
-                        _ => Ok(plan.select(
-                            true,
-                            &self.enabled_rules,
-                            &self.col_id_gen,
-                            vec![expr],
-                        )),
-                    }
+            // IN subqueries become semi/anti joins. They are applied after the other
+            // conditions, which can then be evaluated before the join.
+            let (in_subqueries, predicates): (Vec<_>, Vec<_>) = split_conjunction(expr)
+                .into_iter()
+                .partition(|e| matches!(e, sqlparser::ast::Expr::InSubquery { .. }));
+            for predicate in predicates {
+                plan = self.process_predicate(plan, predicate)?;
+            }
+            for in_subquery in in_subqueries {
+                if let sqlparser::ast::Expr::InSubquery {
+                    expr,
+                    subquery,
+                    negated,
+                } = in_subquery
+                {
+                    plan = self.process_in_subquery(plan, expr, subquery, *negated)?;
                 }
-                Err(TranslatorError::ColumnNotFound(_)) => {
-                    // Search globally.
-                    let expr = self.process_expr(expr, None)?;
+            }
+        }
+        Ok(plan)
+    }
+
+    fn process_predicate(
+        &mut self,
+        plan: LogicalRelExpr,
+        expr: &sqlparser::ast::Expr,
+    ) -> Result<LogicalRelExpr, TranslatorError> {
+        match self.process_expr(expr, Some(0)) {
+            Ok(expr) => {
+                if expr.has_subquery() {
+                    // Add map first so that the subqueries are hoisted
                     let col_id = self.col_id_gen.next();
-                    self.env.add_to_origin_map(col_id, expr.clone().into());
-                    Ok(plan
-                        .map(
-                            true,
-                            &self.enabled_rules,
-                            &self.col_id_gen,
-                            [(col_id, expr)],
-                        )
-                        .select(
-                            true,
-                            &self.enabled_rules,
-                            &self.col_id_gen,
-                            vec![Expression::col_ref(col_id)],
-                        ))
+                    let plan = plan.map(
+                        true,
+                        &self.enabled_rules,
+                        &self.col_id_gen,
+                        [(col_id, expr.clone())],
+                    );
+                    self.add_column(col_id, &expr);
+                    // Add select
+                    Ok(plan.select(
+                        true,
+                        &self.enabled_rules,
+                        &self.col_id_gen,
+                        vec![Expression::col_ref(col_id)],
+                    ))
+                } else {
+                    Ok(plan.select(true, &self.enabled_rules, &self.col_id_gen, vec![expr]))
                 }
-                Err(e) => Err(e),
             }
-        } else {
-            Ok(plan)
+            Err(TranslatorError::ColumnNotFound(_)) => {
+                // Search globally.
+                let expr = self.process_expr(expr, None)?;
+                let col_id = self.col_id_gen.next();
+                self.add_column(col_id, &expr);
+                Ok(plan
+                    .map(
+                        true,
+                        &self.enabled_rules,
+                        &self.col_id_gen,
+                        [(col_id, expr)],
+                    )
+                    .select(
+                        true,
+                        &self.enabled_rules,
+                        &self.col_id_gen,
+                        vec![Expression::col_ref(col_id)],
+                    ))
+            }
+            Err(e) => Err(e),
         }
     }
 
-    #[allow(clippy::too_many_arguments)]
-    fn process_projection(
+    /// Translate `expr [NOT] IN (subquery)` into a semi-join (anti-join) of the plan
+    /// with the subquery on `expr = <subquery column>`.
+    fn process_in_subquery(
         &mut self,
-        mut plan: LogicalRelExpr,
-        projection: &Vec<sqlparser::ast::SelectItem>,
-        _from: &[sqlparser::ast::TableWithJoins],
-        _order_by: &[sqlparser::ast::OrderByExpr],
-        _limit: &Option<sqlparser::ast::Expr>,
-        group_by: &sqlparser::ast::GroupByExpr,
-        having: &Option<sqlparser::ast::Expr>,
-        _distinct: &Option<sqlparser::ast::Distinct>,
+        plan: LogicalRelExpr,
+        expr: &sqlparser::ast::Expr,
+        subquery: &sqlparser::ast::Query,
+        negated: bool,
     ) -> Result<LogicalRelExpr, TranslatorError> {
-        let mut projected_cols = Vec::new();
-        let mut aggregations = Vec::new();
-        let mut maps = Vec::new();
+        let expr = self.process_expr(expr, None)?;
+        if expr.has_subquery() {
+            return Err(translation_err!(
+                UnsupportedSQL,
+                "Subqueries are not supported on the left side of IN"
+            ));
+        }
+        let mut translator = Translator::new_with_outer(
+            &self.catalog_ref,
+            &self.enabled_rules,
+            &self.col_id_gen,
+            &self.env,
+        );
+        let subquery = translator.process_query(subquery)?.plan;
+        let cols = subquery.att_in_order();
+        if cols.len() != 1 {
+            return Err(translation_err!(
+                InvalidSQL,
+                "Subquery in IN returns {} columns instead of 1",
+                cols.len()
+            ));
+        }
+        if !subquery.free().is_empty() {
+            return Err(translation_err!(
+                UnsupportedSQL,
+                "Correlated IN subqueries are not supported"
+            ));
+        }
+        let join_type = if negated {
+            JoinType::LeftAnti
+        } else {
+            JoinType::LeftSemi
+        };
+        Ok(plan.join(
+            true,
+            &self.enabled_rules,
+            &self.col_id_gen,
+            join_type,
+            subquery,
+            vec![self.binary(BinaryOp::Eq, expr, Expression::col_ref(cols[0]))?],
+        ))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn process_projection(
+        &mut self,
+        mut plan: LogicalRelExpr,
+        projection: &Vec<sqlparser::ast::SelectItem>,
+        from: &[sqlparser::ast::TableWithJoins],
+        order_by: &[sqlparser::ast::OrderByExpr],
+        limit: &Option<sqlparser::ast::Expr>,
+        offset: &Option<sqlparser::ast::Offset>,
+        group_by: &sqlparser::ast::GroupByExpr,
+        having: &Option<sqlparser::ast::Expr>,
+        distinct: &Option<sqlparser::ast::Distinct>,
+    ) -> Result<LogicalRelExpr, TranslatorError> {
+        let mut projected_cols = Vec::new();
+        let mut aggregations = Vec::new();
+        let mut maps = Vec::new();
         let mut is_wildcard = false;
         for item in projection {
             match item {
                 sqlparser::ast::SelectItem::Wildcard(_) => {
+                    if from.is_empty() {
+                        return Err(translation_err!(
+                            InvalidSQL,
+                            "SELECT * with no tables specified is not valid"
+                        ));
+                    }
                     is_wildcard = true;
                     break;
                 }
@@ -630,7 +1292,7 @@ impl Translator {
                                         &self.col_id_gen,
                                         [(col_id, expr.clone())],
                                     );
-                                    self.env.add_to_origin_map(col_id, expr.into());
+                                    self.add_column(col_id, &expr);
                                     col_id
                                 };
                                 projected_cols.push(col_id);
@@ -640,7 +1302,7 @@ impl Translator {
                                 let expr = self.process_expr(expr, None)?;
                                 // Add a map to the plan
                                 let col_id = self.col_id_gen.next();
-                                self.env.add_to_origin_map(col_id, expr.clone().into());
+                                self.add_column(col_id, &expr);
                                 plan = plan.map(
                                     true,
                                     &self.enabled_rules,
@@ -664,7 +1326,7 @@ impl Translator {
                         // Level3: |map col_id3 + col_id4 + 4 to col_id5
 
                         let mut aggs = Vec::new();
-                        let res = self.process_aggregation_arguments(plan, expr, &mut aggs);
+                        let res = self.process_aggregation_arguments(plan, expr, &mut aggs)?;
                         plan = res.0;
                         let expr = res.1;
                         let col_id = if let Expression::ColRef { id } = expr {
@@ -672,7 +1334,7 @@ impl Translator {
                         } else {
                             // create a new col_id for the expression
                             let col_id = self.col_id_gen.next();
-                            self.env.add_to_origin_map(col_id, expr.clone().into());
+                            self.add_column(col_id, &expr);
                             maps.push((col_id, expr));
                             col_id
                         };
@@ -689,7 +1351,7 @@ impl Translator {
                                     id
                                 } else {
                                     let col_id = self.col_id_gen.next();
-                                    self.env.add_to_origin_map(col_id, expr.clone().into());
+                                    self.add_column(col_id, &expr);
                                     plan = plan.map(
                                         true,
                                         &self.enabled_rules,
@@ -703,7 +1365,7 @@ impl Translator {
                                 // Search globally.
                                 let expr = self.process_expr(expr, None)?;
                                 let col_id = self.col_id_gen.next();
-                                self.env.add_to_origin_map(col_id, expr.clone().into());
+                                self.add_column(col_id, &expr);
                                 plan = plan.map(
                                     true,
                                     &self.enabled_rules,
@@ -729,7 +1391,7 @@ impl Translator {
                         // Level3: |map col_id3 + col_id4 + 4 to col_id5
 
                         let mut aggs = Vec::new();
-                        let res = self.process_aggregation_arguments(plan, expr, &mut aggs);
+                        let res = self.process_aggregation_arguments(plan, expr, &mut aggs)?;
                         plan = res.0;
                         let expr = res.1;
                         let col_id = if let Expression::ColRef { id } = expr {
@@ -737,7 +1399,7 @@ impl Translator {
                         } else {
                             // create a new col_id for the expression
                             let col_id = self.col_id_gen.next();
-                            self.env.add_to_origin_map(col_id, expr.clone().into());
+                            self.add_column(col_id, &expr);
                             maps.push((col_id, expr));
                             col_id
                         };
@@ -783,7 +1445,7 @@ impl Translator {
                         } else {
                             // create a new col_id for the expression
                             let col_id = self.col_id_gen.next();
-                            self.env.add_to_origin_map(col_id, expr.clone().into());
+                            self.add_column(col_id, &expr);
                             plan = plan.map(
                                 true,
                                 &self.enabled_rules,
@@ -801,6 +1463,37 @@ impl Translator {
             plan = self.process_where(plan, having)?;
         }
         plan = plan.map(true, &self.enabled_rules, &self.col_id_gen, maps); // This map corresponds to the Level3 in the comment above
+        if !order_by.is_empty() {
+            if is_wildcard {
+                // Columns added for the sort keys must not show up in the result
+                projected_cols = plan.att_in_order();
+                is_wildcard = false;
+            }
+            plan = self.process_order_by(plan, order_by, &projected_cols)?;
+            // Rows that are equal on the select list would have several sort keys
+            if let (
+                Some(sqlparser::ast::Distinct::Distinct),
+                LogicalRelExpr::OrderBy { cols, .. },
+            ) = (distinct, &plan)
+            {
+                for ((col_id, _, _), order_by_expr) in cols.iter().zip(order_by) {
+                    let in_select_list = projected_cols.contains(col_id)
+                        || projection.iter().any(|item| match item {
+                            sqlparser::ast::SelectItem::UnnamedExpr(expr)
+                            | sqlparser::ast::SelectItem::ExprWithAlias { expr, .. } => {
+                                *expr == order_by_expr.expr
+                            }
+                            _ => false,
+                        });
+                    if !in_select_list {
+                        return Err(translation_err!(
+                            InvalidSQL,
+                            "for SELECT DISTINCT, ORDER BY expressions must appear in select list"
+                        ));
+                    }
+                }
+            }
+        }
         plan = plan.project(
             true,
             &self.enabled_rules,
@@ -808,7 +1501,109 @@ impl Translator {
             projected_cols,
             is_wildcard,
         );
-        Ok(plan)
+        match distinct {
+            Some(sqlparser::ast::Distinct::Distinct) => plan = plan.distinct(),
+            Some(sqlparser::ast::Distinct::On(_)) => {
+                return Err(translation_err!(
+                    UnsupportedSQL,
+                    "DISTINCT ON is not supported"
+                ))
+            }
+            None => {}
+        }
+        Self::process_limit(plan, limit, offset)
+    }
+
+    fn process_limit(
+        plan: LogicalRelExpr,
+        limit: &Option<sqlparser::ast::Expr>,
+        offset: &Option<sqlparser::ast::Offset>,
+    ) -> Result<LogicalRelExpr, TranslatorError> {
+        if limit.is_none() && offset.is_none() {
+            return Ok(plan);
+        }
+        let limit = match limit {
+            Some(expr) => Some(Self::process_row_count(expr, "LIMIT")?),
+            None => None,
+        };
+        let offset = match offset {
+            Some(offset) => Self::process_row_count(&offset.value, "OFFSET")?,
+            None => 0,
+        };
+        Ok(plan.limit(limit, offset))
+    }
+
+    /// Row count of a LIMIT or OFFSET clause. Only non-negative integer literals are supported.
+    fn process_row_count(
+        expr: &sqlparser::ast::Expr,
+        clause: &str,
+    ) -> Result<usize, TranslatorError> {
+        match expr {
+            sqlparser::ast::Expr::Value(sqlparser::ast::Value::Number(n, _)) => n
+                .parse::<usize>()
+                .map_err(|_| translation_err!(InvalidSQL, "Invalid {} row count: {}", clause, n)),
+            _ => Err(translation_err!(
+                UnsupportedSQL,
+                "{} only supports integer literals",
+                clause
+            )),
+        }
+    }
+
+    /// Sort the plan by the ORDER BY expressions. A sort key can be a column
+    /// (projected or not), an alias from the select list, a position in the
+    /// select list (e.g. ORDER BY 1) or an expression over the columns.
+    /// Without NULLS FIRST/LAST, NULLs are sorted as if larger than any value.
+    fn process_order_by(
+        &mut self,
+        mut plan: LogicalRelExpr,
+        order_by: &[sqlparser::ast::OrderByExpr],
+        projected_cols: &[ColumnId],
+    ) -> Result<LogicalRelExpr, TranslatorError> {
+        let mut cols = Vec::with_capacity(order_by.len());
+        for order_by_expr in order_by {
+            let col_id = match &order_by_expr.expr {
+                sqlparser::ast::Expr::Value(sqlparser::ast::Value::Number(n, _)) => {
+                    let position = n.parse::<usize>().map_err(|_| {
+                        translation_err!(InvalidSQL, "Invalid ORDER BY position: {}", n)
+                    })?;
+                    if position == 0 || position > projected_cols.len() {
+                        return Err(translation_err!(
+                            InvalidSQL,
+                            "ORDER BY position {} is not in select list",
+                            position
+                        ));
+                    }
+                    projected_cols[position - 1]
+                }
+                expr => {
+                    let expr = match self.process_expr(expr, Some(0)) {
+                        Ok(expr) => expr,
+                        // Search globally.
+                        Err(TranslatorError::ColumnNotFound(_)) => self.process_expr(expr, None)?,
+                        Err(e) => return Err(e),
+                    };
+                    if let Expression::ColRef { id } = expr {
+                        id
+                    } else {
+                        // create a new col_id for the expression
+                        let col_id = self.col_id_gen.next();
+                        self.add_column(col_id, &expr);
+                        plan = plan.map(
+                            true,
+                            &self.enabled_rules,
+                            &self.col_id_gen,
+                            [(col_id, expr)],
+                        );
+                        col_id
+                    }
+                }
+            };
+            let asc = order_by_expr.asc.unwrap_or(true);
+            let nulls_first = order_by_expr.nulls_first.unwrap_or(!asc);
+            cols.push((col_id, asc, nulls_first));
+        }
+        Ok(plan.order_by(cols))
     }
 
     // DFS until we find an aggregation function
@@ -823,7 +1618,7 @@ impl Translator {
         mut plan: LogicalRelExpr,
         expr: &sqlparser::ast::Expr,
         aggs: &mut Vec<(usize, (usize, AggOp))>,
-    ) -> (LogicalRelExpr, Expression<LogicalRelExpr>) {
+    ) -> Result<(LogicalRelExpr, Expression<LogicalRelExpr>), TranslatorError> {
         match expr {
             sqlparser::ast::Expr::Identifier(_) | sqlparser::ast::Expr::CompoundIdentifier(_) => {
                 unreachable!(
@@ -831,12 +1626,17 @@ impl Translator {
                 )
             }
             sqlparser::ast::Expr::Value(_) | sqlparser::ast::Expr::TypedString { .. } => {
-                let expr = self.process_expr(expr, Some(0)).unwrap();
-                (plan, expr)
+                let expr = self.process_expr(expr, Some(0))?;
+                Ok((plan, expr))
             }
             sqlparser::ast::Expr::BinaryOp { left, op, right } => {
-                let (plan, left) = self.process_aggregation_arguments(plan, left, aggs);
-                let (plan, right) = self.process_aggregation_arguments(plan, right, aggs);
+                if let Some(call) = Self::special_function_call(expr) {
+                    let (name, args) = call?;
+                    let args = args.iter().map(|arg| arg.as_ref()).collect();
+                    return self.process_scalar_call_arguments(plan, name, args, aggs);
+                }
+                let (plan, left) = self.process_aggregation_arguments(plan, left, aggs)?;
+                let (plan, right) = self.process_aggregation_arguments(plan, right, aggs)?;
                 let bin_op = match op {
                     sqlparser::ast::BinaryOperator::And => BinaryOp::And,
                     sqlparser::ast::BinaryOperator::Or => BinaryOp::Or,
@@ -844,6 +1644,7 @@ impl Translator {
                     sqlparser::ast::BinaryOperator::Minus => BinaryOp::Sub,
                     sqlparser::ast::BinaryOperator::Multiply => BinaryOp::Mul,
                     sqlparser::ast::BinaryOperator::Divide => BinaryOp::Div,
+                    sqlparser::ast::BinaryOperator::Modulo => BinaryOp::Mod,
                     sqlparser::ast::BinaryOperator::Eq => BinaryOp::Eq,
                     sqlparser::ast::BinaryOperator::NotEq => BinaryOp::Neq,
                     sqlparser::ast::BinaryOperator::Lt => BinaryOp::Lt,
@@ -852,7 +1653,62 @@ impl Translator {
                     sqlparser::ast::BinaryOperator::GtEq => BinaryOp::Ge,
                     _ => unimplemented!("Unsupported binary operator: {:?}", op),
                 };
-                (plan, Expression::binary(bin_op, left, right))
+                Ok((plan, self.binary(bin_op, left, right)?))
+            }
+            sqlparser::ast::Expr::UnaryOp { op, expr } => {
+                let (plan, expr) = self.process_aggregation_arguments(plan, expr, aggs)?;
+                let expr = match op {
+                    sqlparser::ast::UnaryOperator::Plus => expr,
+                    sqlparser::ast::UnaryOperator::Minus => self.unary(UnaryOp::Neg, expr)?,
+                    sqlparser::ast::UnaryOperator::Not => self.unary(UnaryOp::Not, expr)?,
+                    _ => {
+                        return Err(translation_err!(
+                            UnsupportedSQL,
+                            "Unsupported unary operator: {:?}",
+                            op
+                        ));
+                    }
+                };
+                Ok((plan, expr))
+            }
+            sqlparser::ast::Expr::Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+            } => {
+                let (mut plan, expr) = match operand {
+                    Some(operand) => self.process_aggregation_arguments(plan, operand, aggs)?,
+                    None => (
+                        plan,
+                        Expression::Field {
+                            val: Field::Bool(true),
+                        },
+                    ),
+                };
+                let mut whens = Vec::with_capacity(conditions.len());
+                for (when, then) in conditions.iter().zip(results) {
+                    let (p, when) = self.process_aggregation_arguments(plan, when, aggs)?;
+                    let (p, then) = self.process_aggregation_arguments(p, then, aggs)?;
+                    plan = p;
+                    whens.push((when, then));
+                }
+                let (plan, else_expr) = match else_result {
+                    Some(else_result) => {
+                        self.process_aggregation_arguments(plan, else_result, aggs)?
+                    }
+                    None => (plan, Expression::Field { val: Field::Null }),
+                };
+                Ok((plan, self.case(expr, whens, else_expr)?))
+            }
+            sqlparser::ast::Expr::Function(function) if !is_agg_function(function) => {
+                let args = self.function_args(function)?;
+                self.process_scalar_call_arguments(
+                    plan,
+                    &get_table_name(&function.name),
+                    args,
+                    aggs,
+                )
             }
             sqlparser::ast::Expr::Function(function) => {
                 let name = get_table_name(&function.name).to_uppercase();
@@ -864,6 +1720,19 @@ impl Translator {
                     "MAX" => AggOp::Max,
                     _ => unimplemented!("Unsupported aggregation function: {:?}", function),
                 };
+                // MIN and MAX are not affected by duplicates
+                let agg_op = match (function.distinct, agg_op) {
+                    (false, op) | (true, op @ (AggOp::Min | AggOp::Max)) => op,
+                    (true, AggOp::Count) => AggOp::CountDistinct,
+                    (true, AggOp::Sum) => AggOp::SumDistinct,
+                    (true, op) => {
+                        return Err(translation_err!(
+                            UnsupportedSQL,
+                            "Unsupported DISTINCT aggregation: {:?}",
+                            op
+                        ));
+                    }
+                };
                 if function.args.len() != 1 {
                     unimplemented!("Unsupported aggregation function: {:?}", function);
                 }
@@ -878,10 +1747,11 @@ impl Translator {
                         match self.process_expr(expr, Some(0)) {
                             Ok(expr) => {
                                 self.env.add_to_origin_map(agg_col_id, expr.clone().into());
+                                self.add_agg_column_type(agg_col_id, agg_op, &expr);
 
                                 if let Expression::ColRef { id } = expr {
                                     aggs.push((agg_col_id, (id, agg_op)));
-                                    (plan, Expression::col_ref(agg_col_id))
+                                    Ok((plan, Expression::col_ref(agg_col_id)))
                                 } else {
                                     plan = plan.map(
                                         true,
@@ -890,14 +1760,15 @@ impl Translator {
                                         [(agg_col_id, expr)],
                                     );
                                     aggs.push((agg_col_id, (agg_col_id, agg_op)));
-                                    (plan, Expression::col_ref(agg_col_id))
+                                    Ok((plan, Expression::col_ref(agg_col_id)))
                                 }
                             }
                             Err(TranslatorError::ColumnNotFound(_)) => {
                                 // Search globally.
-                                let expr = self.process_expr(expr, None).unwrap();
+                                let expr = self.process_expr(expr, None)?;
                                 let col_id = self.col_id_gen.next();
-                                self.env.add_to_origin_map(col_id, expr.clone().into());
+                                self.add_column(col_id, &expr);
+                                self.add_agg_column_type(agg_col_id, agg_op, &expr);
                                 plan = plan.map(
                                     true,
                                     &self.enabled_rules,
@@ -905,9 +1776,9 @@ impl Translator {
                                     [(col_id, expr)],
                                 );
                                 aggs.push((agg_col_id, (col_id, agg_op)));
-                                (plan, Expression::col_ref(agg_col_id))
+                                Ok((plan, Expression::col_ref(agg_col_id)))
                             }
-                            _ => unimplemented!("Unsupported expression: {:?}", expr),
+                            Err(e) => Err(e),
                         }
                     }
                     sqlparser::ast::FunctionArgExpr::QualifiedWildcard(_) => {
@@ -919,8 +1790,8 @@ impl Translator {
                         if matches!(agg_op, AggOp::Count) {
                             let col_id = self.col_id_gen.next();
                             let count_expr = Expression::int(1);
-                            self.env
-                                .add_to_origin_map(col_id, count_expr.clone().into());
+                            self.add_column(col_id, &count_expr);
+                            self.add_agg_column_type(agg_col_id, agg_op, &count_expr);
                             plan = plan.map(
                                 true,
                                 &self.enabled_rules,
@@ -928,18 +1799,711 @@ impl Translator {
                                 [(col_id, count_expr)],
                             );
                             aggs.push((agg_col_id, (col_id, agg_op)));
-                            (plan, Expression::col_ref(agg_col_id))
+                            Ok((plan, Expression::col_ref(agg_col_id)))
                         } else {
                             panic!("Wildcard is only supported for COUNT");
                         }
                     }
                 }
             }
-            sqlparser::ast::Expr::Nested(expr) => {
-                self.process_aggregation_arguments(plan, expr, aggs)
+            sqlparser::ast::Expr::Nested(expr) => {
+                self.process_aggregation_arguments(plan, expr, aggs)
+            }
+            sqlparser::ast::Expr::Cast {
+                expr,
+                data_type,
+                format: None,
+            } => {
+                let (plan, expr) = self.process_aggregation_arguments(plan, expr, aggs)?;
+                Ok((plan, self.cast(expr, data_type)?))
+            }
+            _ => match Self::special_function_call(expr) {
+                Some(call) => {
+                    let (name, args) = call?;
+                    let args = args.iter().map(|arg| arg.as_ref()).collect();
+                    self.process_scalar_call_arguments(plan, name, args, aggs)
+                }
+                None => unimplemented!("Unsupported expression: {:?}", expr),
+            },
+        }
+    }
+
+    /// Process the arguments of a scalar function applied to aggregates, e.g.
+    /// `ROUND(AVG(a), 2)`.
+    fn process_scalar_call_arguments(
+        &self,
+        mut plan: LogicalRelExpr,
+        name: &str,
+        args: Vec<&sqlparser::ast::Expr>,
+        aggs: &mut Vec<(usize, (usize, AggOp))>,
+    ) -> Result<(LogicalRelExpr, Expression<LogicalRelExpr>), TranslatorError> {
+        let mut exprs = Vec::with_capacity(args.len());
+        for arg in args {
+            let (p, expr) = self.process_aggregation_arguments(plan, arg, aggs)?;
+            plan = p;
+            exprs.push(expr);
+        }
+        let expr = self.process_function_call(name, exprs)?;
+        Ok((plan, expr))
+    }
+
+    /// Type of the values of an expression, if it is known before execution.
+    fn expr_type(&self, expr: &Expression<LogicalRelExpr>) -> Option<DataType> {
+        match expr {
+            Expression::Field { val } => Some(val.into()),
+            Expression::ColRef { id } => self.env.get_column_type(*id),
+            Expression::Binary {
+                op:
+                    op @ (BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod),
+                left,
+                right,
+            } => op.arithmetic_type(self.expr_type(left), self.expr_type(right)),
+            Expression::Unary {
+                op: UnaryOp::Neg,
+                expr,
+            } => self.expr_type(expr),
+            Expression::Binary { .. } | Expression::Unary { .. } | Expression::InList { .. } => {
+                Some(DataType::Bool)
+            }
+            Expression::Function { id, args } => {
+                let arg_types = args.iter().map(|e| self.expr_type(e)).collect::<Vec<_>>();
+                get_function(*id).return_type(&arg_types)
+            }
+            Expression::Cast { dtype, .. } => Some(dtype.clone()),
+            Expression::Case {
+                whens, else_expr, ..
+            } => {
+                let results = whens.iter().map(|(_, then)| then);
+                self.common_type("CASE", results.chain([else_expr.as_ref()]))
+                    .ok()
+                    .flatten()
+            }
+            // The single column of a scalar subquery
+            Expression::Subquery { expr } => expr
+                .att_in_order()
+                .first()
+                .and_then(|col| self.env.get_column_type(*col)),
+            Expression::Param { idx } => self.env.get_param_type(*idx),
+        }
+    }
+
+    /// Type of the values of an expression. Only the type of an expression over
+    /// parameters of a prepared statement may be unknown.
+    fn known_type(
+        &self,
+        expr: &Expression<LogicalRelExpr>,
+    ) -> Result<Option<DataType>, TranslatorError> {
+        match self.expr_type(expr) {
+            None if !expr.has_param() => Err(translation_err!(
+                InvalidSQL,
+                "Cannot determine the type of {}",
+                expr.pretty_string().trim_end()
+            )),
+            dtype => Ok(dtype),
+        }
+    }
+
+    /// Record a column computed by `expr`, so that later expressions over the column
+    /// know where it comes from and its type.
+    fn add_column(&self, col_id: ColumnId, expr: &Expression<LogicalRelExpr>) {
+        self.env.add_to_origin_map(col_id, expr.clone().into());
+        if let Some(dtype) = self.expr_type(expr) {
+            self.env.set_column_type(col_id, dtype);
+        }
+    }
+
+    /// Record the type of the column `agg_col_id` holding the result of `agg_op` over
+    /// the values of `arg`.
+    fn add_agg_column_type(
+        &self,
+        agg_col_id: ColumnId,
+        agg_op: AggOp,
+        arg: &Expression<LogicalRelExpr>,
+    ) {
+        let arg_attr = Attribute::new(String::new(), self.expr_type(arg).unwrap_or(DataType::Null));
+        let dtype = agg_op.to_attr(&arg_attr).dtype;
+        if dtype != DataType::Null {
+            self.env.set_column_type(agg_col_id, dtype);
+        }
+    }
+
+    /// Common types of the columns of the two sides of a set operation. None for a
+    /// column whose type is unknown on both sides.
+    fn set_op_types(
+        &self,
+        op: impl std::fmt::Display,
+        left_plan: &LogicalRelExpr,
+        right_plan: &LogicalRelExpr,
+    ) -> Result<Vec<Option<DataType>>, TranslatorError> {
+        let left_cols = left_plan.att_in_order();
+        let right_cols = right_plan.att_in_order();
+        let mut types = Vec::with_capacity(left_cols.len());
+        for (i, (l, r)) in left_cols.iter().zip(right_cols.iter()).enumerate() {
+            let dtype = match (self.env.get_column_type(*l), self.env.get_column_type(*r)) {
+                (Some(l_dtype), Some(r_dtype)) => {
+                    Some(l_dtype.common_type(&r_dtype).ok_or_else(|| {
+                        translation_err!(
+                            InvalidSQL,
+                            "Column {} of {} has type {} on the left and {} on the right",
+                            i + 1,
+                            op,
+                            l_dtype,
+                            r_dtype
+                        )
+                    })?)
+                }
+                (dtype, None) | (None, dtype) => dtype,
+            };
+            types.push(dtype);
+        }
+        Ok(types)
+    }
+
+    /// Convert the output columns of `plan` to `types`. A column whose values
+    /// don't have the representation of its type is replaced by a cast of it.
+    fn coerce_columns(
+        &self,
+        plan: LogicalRelExpr,
+        types: &[Option<DataType>],
+    ) -> Result<LogicalRelExpr, TranslatorError> {
+        let cols = plan.att_in_order();
+        let mut exprs = Vec::new();
+        let mut new_cols = Vec::with_capacity(cols.len());
+        for (col, dtype) in cols.iter().zip(types) {
+            let expr = match dtype {
+                Some(dtype) => self.coerce(Expression::col_ref(*col), dtype)?,
+                None => Expression::col_ref(*col),
+            };
+            match expr {
+                Expression::ColRef { id } => new_cols.push(id),
+                expr => {
+                    let col_id = self.col_id_gen.next();
+                    self.add_column(col_id, &expr);
+                    exprs.push((col_id, expr));
+                    new_cols.push(col_id);
+                }
+            }
+        }
+        if exprs.is_empty() {
+            return Ok(plan);
+        }
+        Ok(plan
+            .map(true, &self.enabled_rules, &self.col_id_gen, exprs)
+            .project(true, &self.enabled_rules, &self.col_id_gen, new_cols, false))
+    }
+
+    /// The common type of the expressions whose type is known. `context` names the
+    /// construct in the error if two of the types don't match.
+    fn common_type<'e>(
+        &self,
+        context: &str,
+        exprs: impl IntoIterator<Item = &'e Expression<LogicalRelExpr>>,
+    ) -> Result<Option<DataType>, TranslatorError> {
+        let mut common: Option<DataType> = None;
+        for expr in exprs {
+            let Some(dtype) = self.known_type(expr)? else {
+                continue;
+            };
+            common = match common {
+                Some(c) => Some(c.common_type(&dtype).ok_or_else(|| {
+                    translation_err!(
+                        InvalidSQL,
+                        "{} types {} and {} cannot be matched",
+                        context,
+                        c,
+                        dtype
+                    )
+                })?),
+                None => Some(dtype),
+            };
+        }
+        Ok(common)
+    }
+
+    /// Convert an expression implicitly to `dtype`. Literals are converted right away
+    /// and no cast is added if the values already have the representation of `dtype`.
+    fn coerce(
+        &self,
+        expr: Expression<LogicalRelExpr>,
+        dtype: &DataType,
+    ) -> Result<Expression<LogicalRelExpr>, TranslatorError> {
+        match (self.expr_type(&expr), expr) {
+            (_, Expression::Field { val }) => val
+                .cast(dtype)
+                .map(|val| Expression::Field { val })
+                .map_err(|e| translation_err!(InvalidSQL, "{}", e)),
+            (None | Some(DataType::Null), expr) => Ok(expr),
+            (Some(from), expr) => match (&from, dtype) {
+                (DataType::Decimal(_, s1), DataType::Decimal(_, s2)) if s1 == s2 => Ok(expr),
+                _ if from == *dtype => Ok(expr),
+                _ => Ok(Expression::cast(expr, dtype.clone())),
+            },
+        }
+    }
+
+    /// Translate `CAST(expr AS type)`, rejecting conversions that can never succeed.
+    fn cast(
+        &self,
+        expr: Expression<LogicalRelExpr>,
+        data_type: &ast::DataType,
+    ) -> Result<Expression<LogicalRelExpr>, TranslatorError> {
+        let dtype =
+            get_cast_type(data_type).map_err(|e| translation_err!(UnsupportedSQL, "{}", e))?;
+        match self.expr_type(&expr) {
+            Some(from) if !from.can_cast_to(&dtype) => Err(translation_err!(
+                InvalidSQL,
+                "Cannot cast {} to {}",
+                from,
+                dtype
+            )),
+            _ => match expr {
+                Expression::Field { val } => val
+                    .cast(&dtype)
+                    .map(|val| Expression::Field { val })
+                    .map_err(|e| translation_err!(InvalidSQL, "{}", e)),
+                expr => Ok(Expression::cast(expr, dtype)),
+            },
+        }
+    }
+
+    /// Build a binary expression after checking the types of its operands. The
+    /// operands of comparisons are converted to their common type, and a string
+    /// literal compared with a value of another type is read as a value of that type,
+    /// e.g. in `d > '2024-01-01'`.
+    fn binary(
+        &self,
+        op: BinaryOp,
+        left: Expression<LogicalRelExpr>,
+        right: Expression<LogicalRelExpr>,
+    ) -> Result<Expression<LogicalRelExpr>, TranslatorError> {
+        let name =
+            |t: &Option<DataType>| t.as_ref().map_or("unknown".to_string(), |t| t.to_string());
+        let (lt, rt) = (self.expr_type(&left), self.expr_type(&right));
+        match op {
+            BinaryOp::And | BinaryOp::Or => {
+                if let Some(t) = [&lt, &rt]
+                    .into_iter()
+                    .flatten()
+                    .find(|t| !matches!(t, DataType::Bool | DataType::Null))
+                {
+                    return Err(translation_err!(
+                        InvalidSQL,
+                        "Operands of AND and OR must be bool, not {}",
+                        t
+                    ));
+                }
+                Ok(Expression::binary(op, left, right))
+            }
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => {
+                let is_number = |t: &Option<DataType>| {
+                    t.as_ref()
+                        .is_none_or(|t| t.is_numeric() || *t == DataType::Null)
+                };
+                let is_int = |t: &Option<DataType>| {
+                    matches!(
+                        t,
+                        None | Some(
+                            DataType::BigInt | DataType::Int | DataType::SmallInt | DataType::Null
+                        )
+                    )
+                };
+                let valid = match (&lt, &rt) {
+                    (Some(DataType::Date), Some(DataType::Date)) => op == BinaryOp::Sub,
+                    (Some(DataType::Date), r) => {
+                        matches!(op, BinaryOp::Add | BinaryOp::Sub) && is_int(r)
+                    }
+                    (l, Some(DataType::Date)) => op == BinaryOp::Add && is_int(l),
+                    (l, r) => is_number(l) && is_number(r),
+                };
+                if !valid {
+                    return Err(translation_err!(
+                        InvalidSQL,
+                        "Operator {} cannot be applied to {} and {}",
+                        op,
+                        name(&lt),
+                        name(&rt)
+                    ));
+                }
+                // The arithmetic operators work on bigints and decimals
+                let widen = |expr: Expression<LogicalRelExpr>| match self.expr_type(&expr) {
+                    Some(DataType::Int | DataType::SmallInt) => {
+                        self.coerce(expr, &DataType::BigInt)
+                    }
+                    _ => Ok(expr),
+                };
+                Ok(Expression::binary(op, widen(left)?, widen(right)?))
+            }
+            BinaryOp::Eq
+            | BinaryOp::Neq
+            | BinaryOp::Lt
+            | BinaryOp::Gt
+            | BinaryOp::Le
+            | BinaryOp::Ge
+            | BinaryOp::IsDistinctFrom
+            | BinaryOp::IsNotDistinctFrom => {
+                let (lt, rt) = match (
+                    &left,
+                    &right,
+                    self.known_type(&left)?,
+                    self.known_type(&right)?,
+                ) {
+                    // A parameter of unknown type takes the type of the value it is
+                    // compared with
+                    (Expression::Param { idx }, _, None, Some(t))
+                    | (_, Expression::Param { idx }, Some(t), None)
+                        if t != DataType::Null =>
+                    {
+                        self.env.set_param_type(*idx, t.clone());
+                        (Some(t.clone()), Some(t))
+                    }
+                    (_, _, lt, rt) => (lt, rt),
+                };
+                let common = match (&left, &right, &lt, &rt) {
+                    (
+                        Expression::Field {
+                            val: Field::String(_),
+                        },
+                        _,
+                        _,
+                        Some(t),
+                    )
+                    | (
+                        _,
+                        Expression::Field {
+                            val: Field::String(_),
+                        },
+                        Some(t),
+                        _,
+                    ) if !t.is_string() && *t != DataType::Null => Some(t.clone()),
+                    (_, _, Some(l), Some(r)) => Some(l.common_type(r).ok_or_else(|| {
+                        translation_err!(InvalidSQL, "Cannot compare {} with {}", l, r)
+                    })?),
+                    // An expression over parameters of unknown types is compared as is
+                    _ => None,
+                };
+                match common {
+                    Some(common) => Ok(Expression::binary(
+                        op,
+                        self.coerce(left, &common)?,
+                        self.coerce(right, &common)?,
+                    )),
+                    None => Ok(Expression::binary(op, left, right)),
+                }
+            }
+            BinaryOp::Like | BinaryOp::ILike | BinaryOp::NotLike | BinaryOp::NotILike => {
+                // Other types must be cast to a string explicitly
+                let is_string = |t: &Option<DataType>| {
+                    t.as_ref()
+                        .is_none_or(|t| t.is_string() || *t == DataType::Null)
+                };
+                if !is_string(&lt) || !is_string(&rt) {
+                    return Err(translation_err!(
+                        InvalidSQL,
+                        "Operator {} cannot be applied to {} and {}",
+                        op,
+                        name(&lt),
+                        name(&rt)
+                    ));
+                }
+                for expr in [&left, &right] {
+                    if let Expression::Param { idx } = expr {
+                        self.env.set_param_type(*idx, DataType::String);
+                    }
+                }
+                Ok(Expression::binary(op, left, right))
+            }
+        }
+    }
+
+    /// Build a unary expression after checking the type of its operand.
+    fn unary(
+        &self,
+        op: UnaryOp,
+        expr: Expression<LogicalRelExpr>,
+    ) -> Result<Expression<LogicalRelExpr>, TranslatorError> {
+        match (op, self.expr_type(&expr)) {
+            (_, None | Some(DataType::Null)) | (UnaryOp::IsNull | UnaryOp::IsNotNull, _) => {
+                Ok(Expression::unary(op, expr))
+            }
+            (UnaryOp::Not, Some(DataType::Bool)) => Ok(Expression::unary(op, expr)),
+            (UnaryOp::Neg, Some(DataType::Int | DataType::SmallInt)) => {
+                Ok(Expression::unary(op, self.coerce(expr, &DataType::BigInt)?))
+            }
+            (UnaryOp::Neg, Some(t)) if t.is_numeric() => Ok(Expression::unary(op, expr)),
+            (op, Some(t)) => Err(translation_err!(
+                InvalidSQL,
+                "Operator {}cannot be applied to {}",
+                op,
+                t
+            )),
+        }
+    }
+
+    /// Build a CASE expression, converting the values compared with the operand and
+    /// the results to their common types.
+    fn case(
+        &self,
+        expr: Expression<LogicalRelExpr>,
+        whens: Vec<(Expression<LogicalRelExpr>, Expression<LogicalRelExpr>)>,
+        else_expr: Expression<LogicalRelExpr>,
+    ) -> Result<Expression<LogicalRelExpr>, TranslatorError> {
+        let operand_type = self.common_type(
+            "CASE",
+            [&expr]
+                .into_iter()
+                .chain(whens.iter().map(|(when, _)| when)),
+        )?;
+        let result_type = self.common_type(
+            "CASE",
+            whens.iter().map(|(_, then)| then).chain([&else_expr]),
+        )?;
+        let coerce = |expr, dtype: &Option<DataType>| match dtype {
+            Some(dtype) => self.coerce(expr, dtype),
+            None => Ok(expr),
+        };
+        let whens = whens
+            .into_iter()
+            .map(|(when, then)| Ok((coerce(when, &operand_type)?, coerce(then, &result_type)?)))
+            .collect::<Result<Vec<_>, TranslatorError>>()?;
+        Ok(Expression::case(
+            coerce(expr, &operand_type)?,
+            whens,
+            coerce(else_expr, &result_type)?,
+        ))
+    }
+
+    /// Resolve a scalar function against the function registry and check the types
+    /// of its arguments. The arguments of functions such as COALESCE are converted
+    /// to their common type.
+    fn process_function_call(
+        &self,
+        name: &str,
+        args: Vec<Expression<LogicalRelExpr>>,
+    ) -> Result<Expression<LogicalRelExpr>, TranslatorError> {
+        let id = lookup_function(name)
+            .ok_or_else(|| translation_err!(UnsupportedSQL, "Unsupported function: {}", name))?;
+        let arg_types = args
+            .iter()
+            .map(|e| self.known_type(e))
+            .collect::<Result<Vec<_>, _>>()?;
+        let literals = args
+            .iter()
+            .map(|e| match e {
+                Expression::Field { val } => Some(val),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let function = get_function(id);
+        function
+            .check_args(&arg_types)
+            .and_then(|_| function.check_literal_args(&literals))
+            .map_err(|e| translation_err!(InvalidSQL, "{}", e))?;
+        let args = match function.return_type(&arg_types) {
+            Some(dtype) if function.unifies_args() => args
+                .into_iter()
+                .map(|arg| self.coerce(arg, &dtype))
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => args,
+        };
+        Ok(Expression::function(id, args))
+    }
+
+    fn process_special_function_call(
+        &self,
+        call: Result<SpecialFunctionCall<'_>, TranslatorError>,
+        distance: Option<usize>,
+    ) -> Result<Expression<LogicalRelExpr>, TranslatorError> {
+        let (name, args) = call?;
+        let args = args
+            .iter()
+            .map(|arg| self.process_expr(arg, distance))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.process_function_call(name, args)
+    }
+
+    /// Arguments of a function call written with parentheses, e.g. `UPPER(a)`.
+    fn function_args<'a>(
+        &self,
+        function: &'a sqlparser::ast::Function,
+    ) -> Result<Vec<&'a sqlparser::ast::Expr>, TranslatorError> {
+        let name = get_table_name(&function.name).to_uppercase();
+        if function.distinct || function.filter.is_some() || function.over.is_some() {
+            return Err(translation_err!(
+                UnsupportedSQL,
+                "Unsupported function call: {}",
+                function
+            ));
+        }
+        function
+            .args
+            .iter()
+            .map(|arg| match arg {
+                sqlparser::ast::FunctionArg::Unnamed(sqlparser::ast::FunctionArgExpr::Expr(
+                    expr,
+                )) => Ok(expr),
+                _ => Err(translation_err!(
+                    UnsupportedSQL,
+                    "Unsupported argument of {}: {}",
+                    name,
+                    arg
+                )),
+            })
+            .collect()
+    }
+
+    /// Name and arguments of the scalar functions with a syntax of their own, e.g.
+    /// `SUBSTRING(a FROM 1 FOR 2)` or `d + INTERVAL '1 day'`. None for any other
+    /// expression.
+    fn special_function_call(
+        call: &sqlparser::ast::Expr,
+    ) -> Option<Result<SpecialFunctionCall<'_>, TranslatorError>> {
+        use sqlparser::ast::{BinaryOperator, DateTimeField, Expr, TrimWhereField, Value};
+        let call = match call {
+            Expr::Substring {
+                expr,
+                substring_from,
+                substring_for,
+                ..
+            } => {
+                let mut args = vec![Cow::Borrowed(expr.as_ref())];
+                match (substring_from, substring_for) {
+                    (Some(from), _) => args.push(Cow::Borrowed(from)),
+                    (None, Some(_)) => {
+                        return Some(Err(translation_err!(
+                            UnsupportedSQL,
+                            "SUBSTRING requires a start position"
+                        )))
+                    }
+                    (None, None) => {}
+                }
+                args.extend(substring_for.as_deref().map(Cow::Borrowed));
+                ("SUBSTRING", args)
+            }
+            Expr::Trim {
+                expr,
+                trim_where,
+                trim_what,
+                trim_characters,
+            } => {
+                let name = match trim_where {
+                    Some(TrimWhereField::Leading) => "LTRIM",
+                    Some(TrimWhereField::Trailing) => "RTRIM",
+                    Some(TrimWhereField::Both) | None => "TRIM",
+                };
+                let mut args = vec![Cow::Borrowed(expr.as_ref())];
+                args.extend(trim_what.as_deref().map(Cow::Borrowed));
+                args.extend(trim_characters.iter().flatten().map(Cow::Borrowed));
+                (name, args)
+            }
+            Expr::Position { expr, r#in } => (
+                "POSITION",
+                vec![Cow::Borrowed(expr.as_ref()), Cow::Borrowed(r#in.as_ref())],
+            ),
+            Expr::Ceil { expr, field } | Expr::Floor { expr, field }
+                if *field == DateTimeField::NoDateTime =>
+            {
+                let name = match call {
+                    Expr::Ceil { .. } => "CEIL",
+                    _ => "FLOOR",
+                };
+                (name, vec![Cow::Borrowed(expr.as_ref())])
+            }
+            Expr::Extract { field, expr } => {
+                let unit = match field {
+                    DateTimeField::Millennium | DateTimeField::Millenium => "millennium",
+                    DateTimeField::Century => "century",
+                    DateTimeField::Decade => "decade",
+                    DateTimeField::Year => "year",
+                    DateTimeField::Isoyear => "isoyear",
+                    DateTimeField::Quarter => "quarter",
+                    DateTimeField::Month => "month",
+                    DateTimeField::Week | DateTimeField::IsoWeek => "week",
+                    DateTimeField::Day => "day",
+                    DateTimeField::Dow | DateTimeField::DayOfWeek => "dow",
+                    DateTimeField::Isodow => "isodow",
+                    DateTimeField::Doy | DateTimeField::DayOfYear => "doy",
+                    DateTimeField::Epoch => "epoch",
+                    _ => {
+                        return Some(Err(translation_err!(
+                            UnsupportedSQL,
+                            "Unsupported EXTRACT field: {}",
+                            field
+                        )))
+                    }
+                };
+                let unit = Expr::Value(Value::SingleQuotedString(unit.to_string()));
+                (
+                    "DATE_PART",
+                    vec![Cow::Owned(unit), Cow::Borrowed(expr.as_ref())],
+                )
+            }
+            // `d + INTERVAL '1 month'`, `INTERVAL '1 month' + d` and `d - INTERVAL '1 month'`
+            Expr::BinaryOp {
+                left,
+                op: op @ (BinaryOperator::Plus | BinaryOperator::Minus),
+                right,
+            } => {
+                let (date, interval, negated) = match (left.as_ref(), right.as_ref()) {
+                    (date, Expr::Interval(interval)) => {
+                        (date, interval, *op == BinaryOperator::Minus)
+                    }
+                    (Expr::Interval(interval), date) if *op == BinaryOperator::Plus => {
+                        (date, interval, false)
+                    }
+                    _ => return None,
+                };
+                let (n, unit) = match parse_interval(interval) {
+                    Ok(interval) => interval,
+                    Err(e) => return Some(Err(e)),
+                };
+                let n = if negated { -n } else { n };
+                let n = Expr::Value(Value::Number(n.to_string(), false));
+                let unit = Expr::Value(Value::SingleQuotedString(unit.to_string()));
+                (
+                    "DATE_ADD",
+                    vec![Cow::Borrowed(date), Cow::Owned(n), Cow::Owned(unit)],
+                )
+            }
+            _ => return None,
+        };
+        Some(Ok(call))
+    }
+
+    /// Translate `expr [NOT] [I]LIKE pattern [ESCAPE escape_char]`. A pattern with an
+    /// ESCAPE character is rewritten to use `\`, the escape character of LIKE patterns.
+    fn process_like(
+        &self,
+        op: BinaryOp,
+        expr: &sqlparser::ast::Expr,
+        pattern: &sqlparser::ast::Expr,
+        escape_char: Option<char>,
+        distance: Option<usize>,
+    ) -> Result<Expression<LogicalRelExpr>, TranslatorError> {
+        let expr = self.process_expr(expr, distance)?;
+        let pattern = match (self.process_expr(pattern, distance)?, escape_char) {
+            (pattern, None) => pattern,
+            (
+                Expression::Field {
+                    val: Field::String(pattern),
+                },
+                Some(escape),
+            ) => Expression::Field {
+                val: Field::String(normalize_like_escape(&pattern, escape)),
+            },
+            (_, Some(_)) => {
+                return Err(translation_err!(
+                    UnsupportedSQL,
+                    "ESCAPE is only supported with a constant pattern"
+                ))
             }
-            _ => unimplemented!("Unsupported expression: {:?}", expr),
+        };
+        if expr.has_subquery() || pattern.has_subquery() {
+            return Err(translation_err!(
+                UnsupportedSQL,
+                "Subqueries are not supported in LIKE"
+            ));
         }
+        self.binary(op, expr, pattern)
     }
 
     fn process_expr(
@@ -997,6 +2561,9 @@ impl Translator {
             }
             sqlparser::ast::Expr::BinaryOp { left, op, right } => {
                 use sqlparser::ast::BinaryOperator::*;
+                if let Some(call) = Self::special_function_call(expr) {
+                    return self.process_special_function_call(call, distance);
+                }
                 let left = self.process_expr(left, distance)?;
                 let right = self.process_expr(right, distance)?;
                 let bin_op = match op {
@@ -1006,6 +2573,7 @@ impl Translator {
                     Minus => BinaryOp::Sub,
                     Multiply => BinaryOp::Mul,
                     Divide => BinaryOp::Div,
+                    Modulo => BinaryOp::Mod,
                     Eq => BinaryOp::Eq,
                     NotEq => BinaryOp::Neq,
                     Lt => BinaryOp::Lt,
@@ -1020,10 +2588,12 @@ impl Translator {
                         ));
                     }
                 };
-                Ok(Expression::binary(bin_op, left, right))
+                self.binary(bin_op, left, right)
             }
             sqlparser::ast::Expr::Value(value) => match value {
-                sqlparser::ast::Value::Number(num, _) => Ok(Expression::int(num.parse().unwrap())),
+                sqlparser::ast::Value::Number(..) => convert_value(value)
+                    .map(|val| Expression::Field { val })
+                    .map_err(|_| translation_err!(InvalidSQL, "Invalid number: {}", value)),
                 sqlparser::ast::Value::SingleQuotedString(s)
                 | sqlparser::ast::Value::DoubleQuotedString(s) => Ok(Expression::Field {
                     val: Field::String(s.clone()),
@@ -1032,6 +2602,12 @@ impl Translator {
                     val: Field::Bool(*b),
                 }),
                 sqlparser::ast::Value::Null => Ok(Expression::Field { val: Field::Null }),
+                sqlparser::ast::Value::Placeholder(name) => {
+                    match name.strip_prefix('$').and_then(|n| n.parse::<usize>().ok()) {
+                        Some(n) if n > 0 => Ok(Expression::Param { idx: n - 1 }),
+                        _ => Err(translation_err!(InvalidSQL, "Invalid parameter: {}", name)),
+                    }
+                }
                 _ => Err(translation_err!(
                     UnsupportedSQL,
                     "Unsupported value: {:?}",
@@ -1050,8 +2626,7 @@ impl Translator {
                 // Add count(*) to the subquery
                 let col_id1 = translator.col_id_gen.next();
                 let col1_expr = Expression::int(1);
-                self.env
-                    .add_to_origin_map(col_id1, col1_expr.clone().into());
+                self.add_column(col_id1, &col1_expr);
                 plan = plan.map(
                     true,
                     &translator.enabled_rules,
@@ -1061,6 +2636,7 @@ impl Translator {
                 let col_id2 = translator.col_id_gen.next();
                 // TODO: Did not add col_id2 to the origin map
                 plan = plan.aggregate(vec![], vec![(col_id2, (col_id1, AggOp::Count))]);
+                self.env.set_column_type(col_id2, DataType::BigInt);
                 // Add count(*) > 0  to the subquery
                 let exists_expr = if *negated {
                     Expression::binary(
@@ -1076,8 +2652,7 @@ impl Translator {
                     )
                 };
                 let col_id3 = self.col_id_gen.next();
-                self.env
-                    .add_to_origin_map(col_id3, exists_expr.clone().into());
+                self.add_column(col_id3, &exists_expr);
                 plan = plan.map(
                     true,
                     &translator.enabled_rules,
@@ -1126,12 +2701,214 @@ impl Translator {
                 };
                 Ok(expr)
             }
-            sqlparser::ast::Expr::Nested(expr) => self.process_expr(expr, distance),
-            _ => Err(translation_err!(
+            sqlparser::ast::Expr::InList {
+                expr,
+                list,
+                negated,
+            } => {
+                let expr = self.process_expr(expr, distance)?;
+                let list = list
+                    .iter()
+                    .map(|e| self.process_expr(e, distance))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if expr.has_subquery() || list.iter().any(|e| e.has_subquery()) {
+                    return Err(translation_err!(
+                        UnsupportedSQL,
+                        "Subqueries are not supported in IN lists"
+                    ));
+                }
+                let dtype = self.common_type("IN", [&expr].into_iter().chain(&list))?;
+                let (expr, list) = match dtype {
+                    Some(dtype) => (
+                        self.coerce(expr, &dtype)?,
+                        list.into_iter()
+                            .map(|e| self.coerce(e, &dtype))
+                            .collect::<Result<Vec<_>, _>>()?,
+                    ),
+                    None => (expr, list),
+                };
+                Ok(Expression::in_list(expr, list, *negated))
+            }
+            sqlparser::ast::Expr::IsNull(expr) => Ok(Expression::unary(
+                UnaryOp::IsNull,
+                self.process_expr(expr, distance)?,
+            )),
+            sqlparser::ast::Expr::IsNotNull(expr) => Ok(Expression::unary(
+                UnaryOp::IsNotNull,
+                self.process_expr(expr, distance)?,
+            )),
+            sqlparser::ast::Expr::UnaryOp { op, expr } => {
+                use sqlparser::ast::UnaryOperator::*;
+                let expr = self.process_expr(expr, distance)?;
+                match (op, expr) {
+                    (Plus, expr) => Ok(expr),
+                    // Fold the sign of a literal, e.g. -1
+                    (Minus, Expression::Field { val }) => Ok(Expression::Field {
+                        val: (-val).map_err(|e| {
+                            translation_err!(InvalidSQL, "Invalid negation: {:?}", e)
+                        })?,
+                    }),
+                    (Minus, expr) => self.unary(UnaryOp::Neg, expr),
+                    (Not, expr) => self.unary(UnaryOp::Not, expr),
+                    _ => Err(translation_err!(
+                        UnsupportedSQL,
+                        "Unsupported unary operator: {:?}",
+                        op
+                    )),
+                }
+            }
+            // x BETWEEN a AND b is x >= a AND x <= b
+            sqlparser::ast::Expr::Between {
+                expr,
+                negated,
+                low,
+                high,
+            } => {
+                let expr = self.process_expr(expr, distance)?;
+                let low = self.process_expr(low, distance)?;
+                let high = self.process_expr(high, distance)?;
+                if expr.has_subquery() || low.has_subquery() || high.has_subquery() {
+                    return Err(translation_err!(
+                        UnsupportedSQL,
+                        "Subqueries are not supported in BETWEEN"
+                    ));
+                }
+                if *negated {
+                    Ok(Expression::binary(
+                        BinaryOp::Or,
+                        self.binary(BinaryOp::Lt, expr.clone(), low)?,
+                        self.binary(BinaryOp::Gt, expr, high)?,
+                    ))
+                } else {
+                    Ok(Expression::binary(
+                        BinaryOp::And,
+                        self.binary(BinaryOp::Ge, expr.clone(), low)?,
+                        self.binary(BinaryOp::Le, expr, high)?,
+                    ))
+                }
+            }
+            sqlparser::ast::Expr::Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+            } => {
+                let expr = match operand {
+                    Some(operand) => self.process_expr(operand, distance)?,
+                    None => Expression::Field {
+                        val: Field::Bool(true),
+                    },
+                };
+                let whens = conditions
+                    .iter()
+                    .zip(results)
+                    .map(|(when, then)| {
+                        Ok((
+                            self.process_expr(when, distance)?,
+                            self.process_expr(then, distance)?,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, TranslatorError>>()?;
+                let else_expr = match else_result {
+                    Some(else_result) => self.process_expr(else_result, distance)?,
+                    None => Expression::Field { val: Field::Null },
+                };
+                if expr.has_subquery()
+                    || else_expr.has_subquery()
+                    || whens
+                        .iter()
+                        .any(|(when, then)| when.has_subquery() || then.has_subquery())
+                {
+                    return Err(translation_err!(
+                        UnsupportedSQL,
+                        "Subqueries are not supported in CASE"
+                    ));
+                }
+                self.case(expr, whens, else_expr)
+            }
+            sqlparser::ast::Expr::IsDistinctFrom(left, right) => self.binary(
+                BinaryOp::IsDistinctFrom,
+                self.process_expr(left, distance)?,
+                self.process_expr(right, distance)?,
+            ),
+            sqlparser::ast::Expr::IsNotDistinctFrom(left, right) => self.binary(
+                BinaryOp::IsNotDistinctFrom,
+                self.process_expr(left, distance)?,
+                self.process_expr(right, distance)?,
+            ),
+            sqlparser::ast::Expr::Cast {
+                expr,
+                data_type,
+                format: None,
+            } => {
+                let expr = self.process_expr(expr, distance)?;
+                self.cast(expr, data_type)
+            }
+            sqlparser::ast::Expr::Like {
+                negated,
+                expr,
+                pattern,
+                escape_char,
+            } => {
+                let op = if *negated {
+                    BinaryOp::NotLike
+                } else {
+                    BinaryOp::Like
+                };
+                self.process_like(op, expr, pattern, *escape_char, distance)
+            }
+            sqlparser::ast::Expr::ILike {
+                negated,
+                expr,
+                pattern,
+                escape_char,
+            } => {
+                let op = if *negated {
+                    BinaryOp::NotILike
+                } else {
+                    BinaryOp::ILike
+                };
+                self.process_like(op, expr, pattern, *escape_char, distance)
+            }
+            sqlparser::ast::Expr::InSubquery { .. } => Err(translation_err!(
                 UnsupportedSQL,
-                "Unsupported expression: {:?}",
-                expr
+                "IN subqueries are only supported as conditions of the WHERE clause"
             )),
+            sqlparser::ast::Expr::Subquery(query) => {
+                let mut translator = Translator::new_with_outer(
+                    &self.catalog_ref,
+                    &self.enabled_rules,
+                    &self.col_id_gen,
+                    &self.env,
+                );
+                let subquery = translator.process_query(query)?.plan;
+                let cols = subquery.att();
+                if cols.len() != 1 {
+                    return Err(translation_err!(
+                        InvalidSQL,
+                        "Scalar subquery returns {} columns instead of 1",
+                        cols.len()
+                    ));
+                }
+                Ok(Expression::subquery(subquery))
+            }
+            sqlparser::ast::Expr::Function(function) => {
+                let args = self
+                    .function_args(function)?
+                    .into_iter()
+                    .map(|arg| self.process_expr(arg, distance))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.process_function_call(&get_table_name(&function.name), args)
+            }
+            sqlparser::ast::Expr::Nested(expr) => self.process_expr(expr, distance),
+            _ => match Self::special_function_call(expr) {
+                Some(call) => self.process_special_function_call(call, distance),
+                None => Err(translation_err!(
+                    UnsupportedSQL,
+                    "Unsupported expression: {:?}",
+                    expr
+                )),
+            },
         }
     }
 }
@@ -1145,10 +2922,78 @@ fn get_table_name(name: &sqlparser::ast::ObjectName) -> String {
         .join(".")
 }
 
+/// Split an expression into its top-level conjuncts (`a AND (b AND c)` gives `a`, `b`, `c`).
+fn split_conjunction(expr: &sqlparser::ast::Expr) -> Vec<&sqlparser::ast::Expr> {
+    match expr {
+        sqlparser::ast::Expr::BinaryOp {
+            left,
+            op: sqlparser::ast::BinaryOperator::And,
+            right,
+        } => {
+            let mut conjuncts = split_conjunction(left);
+            conjuncts.extend(split_conjunction(right));
+            conjuncts
+        }
+        sqlparser::ast::Expr::Nested(expr) => match expr.as_ref() {
+            sqlparser::ast::Expr::BinaryOp {
+                op: sqlparser::ast::BinaryOperator::And,
+                ..
+            } => split_conjunction(expr),
+            _ => vec![expr],
+        },
+        _ => vec![expr],
+    }
+}
+
 fn is_valid_alias(alias: &str) -> bool {
     alias.chars().all(|c| c.is_alphanumeric() || c == '_')
 }
 
+/// Number and unit of a single-unit interval: `INTERVAL '3 days'` or `INTERVAL '3' DAY`.
+fn parse_interval(
+    interval: &sqlparser::ast::Interval,
+) -> Result<(i64, &'static str), TranslatorError> {
+    use sqlparser::ast::{Expr, Value};
+    let unsupported = || translation_err!(UnsupportedSQL, "Unsupported interval: {}", interval);
+    let value = match interval.value.as_ref() {
+        Expr::Value(Value::SingleQuotedString(s) | Value::Number(s, _)) => s.as_str(),
+        _ => return Err(unsupported()),
+    };
+    if interval.last_field.is_some() || interval.fractional_seconds_precision.is_some() {
+        return Err(unsupported());
+    }
+    let (n, unit) = match interval.leading_field {
+        Some(field) => (value.trim(), field.to_string()),
+        None => {
+            let mut parts = value.split_whitespace();
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(n), Some(unit), None) => (n, unit.to_string()),
+                _ => return Err(unsupported()),
+            }
+        }
+    };
+    let n = n.parse::<i64>().map_err(|_| unsupported())?;
+    let unit = match DateUnit::parse(&unit) {
+        Some(DateUnit::Day) => "day",
+        Some(DateUnit::Week) => "week",
+        Some(DateUnit::Month) => "month",
+        Some(DateUnit::Quarter) => "quarter",
+        Some(DateUnit::Year) => "year",
+        Some(DateUnit::Decade) => "decade",
+        Some(DateUnit::Century) => "century",
+        Some(DateUnit::Millennium) => "millennium",
+        _ => return Err(unsupported()),
+    };
+    Ok((n, unit))
+}
+
+fn is_agg_function(function: &sqlparser::ast::Function) -> bool {
+    matches!(
+        get_table_name(&function.name).to_uppercase().as_str(),
+        "COUNT" | "SUM" | "AVG" | "MIN" | "MAX"
+    )
+}
+
 fn has_agg(expr: &sqlparser::ast::Expr) -> bool {
     use sqlparser::ast::Expr::*;
     match expr {
@@ -1158,11 +3003,70 @@ fn has_agg(expr: &sqlparser::ast::Expr) -> bool {
         TypedString { .. } => false,
 
         BinaryOp { left, op: _, right } => has_agg(left) || has_agg(right),
-        Function(function) => matches!(
-            get_table_name(&function.name).to_uppercase().as_str(),
-            "COUNT" | "SUM" | "AVG" | "MIN" | "MAX"
-        ),
+        IsNull(expr) | IsNotNull(expr) => has_agg(expr),
+        UnaryOp { expr, .. } => has_agg(expr),
+        InList { expr, list, .. } => has_agg(expr) || list.iter().any(has_agg),
+        // Aggregates of the subquery belong to the subquery
+        InSubquery { expr, .. } => has_agg(expr),
+        Case {
+            operand,
+            conditions,
+            results,
+            else_result,
+        } => {
+            operand.as_deref().is_some_and(has_agg)
+                || conditions.iter().any(has_agg)
+                || results.iter().any(has_agg)
+                || else_result.as_deref().is_some_and(has_agg)
+        }
+        Between {
+            expr, low, high, ..
+        } => has_agg(expr) || has_agg(low) || has_agg(high),
+        IsDistinctFrom(left, right) | IsNotDistinctFrom(left, right) => {
+            has_agg(left) || has_agg(right)
+        }
+        Like { expr, pattern, .. } | ILike { expr, pattern, .. } => {
+            has_agg(expr) || has_agg(pattern)
+        }
+        Function(function) => {
+            is_agg_function(function)
+                || function.args.iter().any(|arg| {
+                    matches!(
+                        arg,
+                        sqlparser::ast::FunctionArg::Unnamed(sqlparser::ast::FunctionArgExpr::Expr(
+                            expr
+                        )) if has_agg(expr)
+                    )
+                })
+        }
+        Substring {
+            expr,
+            substring_from,
+            substring_for,
+            ..
+        } => {
+            has_agg(expr)
+                || substring_from.as_deref().is_some_and(has_agg)
+                || substring_for.as_deref().is_some_and(has_agg)
+        }
+        Trim {
+            expr,
+            trim_what,
+            trim_characters,
+            ..
+        } => {
+            has_agg(expr)
+                || trim_what.as_deref().is_some_and(has_agg)
+                || trim_characters.iter().flatten().any(has_agg)
+        }
+        Position { expr, r#in } => has_agg(expr) || has_agg(r#in),
+        Ceil { expr, .. } | Floor { expr, .. } | Extract { expr, .. } | Cast { expr, .. } => {
+            has_agg(expr)
+        }
+        Interval(interval) => has_agg(&interval.value),
         Nested(expr) => has_agg(expr),
+        // Aggregates of a subquery belong to the subquery
+        Subquery(_) | Exists { .. } => false,
         _ => unimplemented!("Unsupported expression: {:?}", expr),
     }
 }
@@ -1172,11 +3076,14 @@ mod tests {
     use std::sync::Arc;
 
     use common::{
-        catalog::Catalog, physical::col_id_generator::ColIdGenerator, query::rules::Rules,
-        table::TableInfo, DataType, TableSchema,
+        catalog::Catalog,
+        physical::col_id_generator::ColIdGenerator,
+        query::rules::Rules,
+        table::{TableInfo, ViewInfo},
+        DataType, TableSchema,
     };
 
-    use super::Translator;
+    use super::{Translator, TranslatorError};
 
     fn get_test_catalog() -> Arc<Catalog> {
         let catalog = Catalog::new();
@@ -1204,6 +3111,17 @@ mod tests {
         let t3_table = TableInfo::new(t3_cid, t3_table_name, t3_schema);
         catalog.add_table(t3_table);
 
+        let views = [
+            ("v1", vec!["x", "y"], "SELECT a, b FROM t1 WHERE a > 1"),
+            ("v2", vec![], "SELECT x AS z FROM v1"),
+            ("v3", vec![], "SELECT a FROM v3"),
+            ("v4", vec![], "SELECT g FROM t4"),
+        ];
+        for (name, columns, sql) in views {
+            let columns = columns.into_iter().map(String::from).collect();
+            catalog.add_view(ViewInfo::new(name.to_string(), columns, sql.to_string()));
+        }
+
         catalog
     }
 
@@ -1391,6 +3309,307 @@ mod tests {
         println!("{}", get_plan(sql));
     }
 
+    #[test]
+    fn parse_order_by() {
+        let sql = "SELECT a, b AS x FROM t1 ORDER BY x DESC, 1, p + q NULLS FIRST";
+        let plan = get_plan(sql);
+        println!("{}", plan);
+        assert!(plan.contains("order_by"));
+    }
+
+    #[test]
+    fn parse_order_by_wildcard() {
+        let sql = "SELECT * FROM t1 ORDER BY a + b";
+        println!("{}", get_plan(sql));
+    }
+
+    #[test]
+    fn parse_select_distinct() {
+        let sql = "SELECT DISTINCT a, b FROM t1 ORDER BY a LIMIT 1";
+        let plan = get_plan(sql);
+        println!("{}", plan);
+        assert!(plan.contains("-> distinct"));
+    }
+
+    #[test]
+    fn parse_select_distinct_order_by() {
+        let sql = "SELECT DISTINCT a AS x, b + 1 FROM t1 ORDER BY x, t1.a, b + 1, 2";
+        println!("{}", get_plan(sql));
+        let query = parse_sql("SELECT DISTINCT a FROM t1 ORDER BY b");
+        let err = get_translator().process_query(&query).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("ORDER BY expressions must appear in select list"));
+    }
+
+    #[test]
+    fn parse_distinct_aggregate() {
+        let sql =
+            "SELECT a, COUNT(DISTINCT b), SUM(DISTINCT p), MAX(DISTINCT q) FROM t1 GROUP BY a";
+        let plan = get_plan(sql);
+        println!("{}", plan);
+        assert!(plan.contains("CountDistinct"));
+        assert!(plan.contains("SumDistinct"));
+
+        let query = parse_sql("SELECT a, AVG(DISTINCT b) FROM t1 GROUP BY a");
+        let mut translator = get_translator();
+        assert!(matches!(
+            translator.process_query(&query),
+            Err(TranslatorError::UnsupportedSQL(_))
+        ));
+    }
+
+    #[test]
+    fn parse_limit_offset() {
+        let sql = "SELECT a FROM t1 ORDER BY a LIMIT 10 OFFSET 5";
+        let plan = get_plan(sql);
+        println!("{}", plan);
+        assert!(plan.starts_with("-> limit(Some(10), offset: 5)"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_limit_non_literal() {
+        let sql = "SELECT a FROM t1 LIMIT a";
+        println!("{}", get_plan(sql));
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_order_by_invalid_position() {
+        let sql = "SELECT a, b FROM t1 ORDER BY 3";
+        println!("{}", get_plan(sql));
+    }
+
+    #[test]
+    fn parse_union() {
+        let sql = "SELECT a FROM t1 UNION SELECT c FROM t2";
+        let plan = get_plan(sql);
+        println!("{}", plan);
+        assert!(plan.starts_with("-> union\n"));
+    }
+
+    #[test]
+    fn parse_set_operations_with_order_by() {
+        let sql = "SELECT a, b FROM t1 EXCEPT ALL SELECT c, d FROM t2 \
+                   INTERSECT SELECT e, f FROM t3 ORDER BY a DESC LIMIT 3";
+        let plan = get_plan(sql);
+        println!("{}", plan);
+        assert!(plan.starts_with("-> limit(Some(3), offset: 0)"));
+        assert!(plan.contains("-> except_all"));
+        assert!(plan.contains("-> intersect"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_union_arity_mismatch() {
+        let sql = "SELECT a, b FROM t1 UNION SELECT c FROM t2";
+        println!("{}", get_plan(sql));
+    }
+
+    #[test]
+    fn parse_union_type_mismatch() {
+        let plan = get_plan("SELECT a, b FROM t1 UNION SELECT c + 1, NULL FROM t2");
+        assert!(plan.starts_with("-> union\n"));
+
+        for sql in [
+            "SELECT a FROM t1 UNION ALL SELECT 'x' FROM t2",
+            "SELECT a FROM t1 EXCEPT SELECT CAST(c AS VARCHAR) FROM (SELECT c FROM t2) s",
+        ] {
+            let query = parse_sql(sql);
+            let mut translator = get_translator();
+            assert!(
+                matches!(
+                    translator.process_query(&query),
+                    Err(TranslatorError::InvalidSQL(_))
+                ),
+                "{}",
+                sql
+            );
+        }
+    }
+
+    #[test]
+    fn parse_union_common_type() {
+        // The integers of the left side are converted to decimals
+        let sql = "SELECT a FROM t1 UNION SELECT CAST(c AS DECIMAL(10, 2)) FROM t2";
+        let plan = get_plan(sql);
+        println!("{}", plan);
+        assert!(plan.starts_with("-> union\n"));
+        assert!(plan.contains(" as decimal(21,2))"));
+    }
+
+    #[test]
+    fn parse_in_list() {
+        let sql = "SELECT a FROM t1 WHERE b IN (1, 2, 3) AND p NOT IN (4)";
+        let plan = get_plan(sql);
+        println!("{}", plan);
+        assert!(plan.contains(" in (1, 2, 3)"));
+        assert!(plan.contains(" not in (4)"));
+    }
+
+    #[test]
+    fn parse_in_subquery() {
+        let sql = "SELECT a FROM t1 WHERE b = 1 AND a IN (SELECT c FROM t2) AND a NOT IN (SELECT e FROM t3)";
+        let plan = get_plan(sql);
+        println!("{}", plan);
+        assert!(plan.contains("-> left_semi_join("));
+        assert!(plan.contains("-> left_anti_join("));
+    }
+
+    #[test]
+    fn parse_in_subquery_common_type() {
+        let sql = "SELECT a FROM t1 WHERE a IN (SELECT CAST(c AS DECIMAL(10, 2)) FROM t2)";
+        let plan = get_plan(sql);
+        println!("{}", plan);
+        assert!(plan.contains("left_semi_join(cast("));
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_in_subquery_multiple_columns() {
+        let sql = "SELECT a FROM t1 WHERE a IN (SELECT c, d FROM t2)";
+        println!("{}", get_plan(sql));
+    }
+
+    #[test]
+    fn parse_scalar_subquery() {
+        let sql = "SELECT a FROM t1 WHERE b > (SELECT c FROM t2 WHERE d = 1)";
+        let plan = get_plan(sql);
+        println!("{}", plan);
+        assert!(plan.contains("-> enforce_single_row"));
+        assert!(!plan.contains("flatmap"));
+    }
+
+    #[test]
+    fn parse_correlated_scalar_subquery() {
+        let sql = "SELECT a, (SELECT c FROM t2 WHERE d = t1.b) FROM t1";
+        let plan = get_plan(sql);
+        println!("{}", plan);
+        assert!(plan.contains("-> enforce_single_row"));
+        assert!(plan.contains("flatmap"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_scalar_subquery_multiple_columns() {
+        let sql = "SELECT a FROM t1 WHERE b > (SELECT c, d FROM t2)";
+        println!("{}", get_plan(sql));
+    }
+
+    #[test]
+    fn parse_view() {
+        let sql = "SELECT v1.x, y, c FROM v1, t2 WHERE x = c";
+        println!("{}", get_plan(sql));
+    }
+
+    #[test]
+    fn parse_view_alias() {
+        let sql = "SELECT w.x FROM v1 AS w, v1 WHERE w.x = v1.y";
+        println!("{}", get_plan(sql));
+    }
+
+    #[test]
+    fn parse_view_of_view() {
+        let sql = "SELECT z FROM v2";
+        println!("{}", get_plan(sql));
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_view_hides_query_columns() {
+        // v1 renames a to x
+        let sql = "SELECT a FROM v1";
+        println!("{}", get_plan(sql));
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_view_references_itself() {
+        let sql = "SELECT a FROM v3";
+        println!("{}", get_plan(sql));
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_view_missing_table() {
+        let sql = "SELECT g FROM v4";
+        println!("{}", get_plan(sql));
+    }
+
+    #[test]
+    fn parse_cte() {
+        let sql = "WITH x AS (SELECT a, b AS y FROM t1 WHERE a > 1), z AS (SELECT y FROM x) \
+                   SELECT x.a, z.y FROM x, z";
+        println!("{}", get_plan(sql));
+    }
+
+    #[test]
+    fn parse_cte_column_names() {
+        let sql = "WITH x(m, n) AS (SELECT a, b FROM t1) SELECT m, x.n FROM x";
+        println!("{}", get_plan(sql));
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_cte_column_names_mismatch() {
+        let sql = "WITH x(m) AS (SELECT a, b FROM t1) SELECT m FROM x";
+        println!("{}", get_plan(sql));
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_cte_not_recursive() {
+        let sql = "WITH x AS (SELECT a FROM t1 UNION ALL SELECT a + 1 FROM x) SELECT a FROM x";
+        println!("{}", get_plan(sql));
+    }
+
+    #[test]
+    fn parse_recursive_cte() {
+        let sql =
+            "WITH RECURSIVE r(n) AS (SELECT a FROM t1 UNION ALL SELECT n + 1 FROM r WHERE n < 5) \
+                   SELECT n FROM r";
+        let plan = get_plan(sql);
+        println!("{}", plan);
+        assert!(plan.contains("recursive_union_all"));
+        assert!(plan.contains("work_table"));
+    }
+
+    #[test]
+    fn parse_recursive_cte_hierarchy() {
+        // Descendants of t2 rows, with d as the parent of c
+        let sql = "WITH RECURSIVE sub(id) AS (SELECT c FROM t2 WHERE c = 1 \
+                   UNION SELECT t2.c FROM sub, t2 WHERE t2.d = sub.id) SELECT id FROM sub";
+        let plan = get_plan(sql);
+        println!("{}", plan);
+        assert!(plan.contains("recursive_union"));
+        assert!(plan.contains("work_table"));
+    }
+
+    #[test]
+    fn parse_recursive_cte_without_from() {
+        let sql = "WITH RECURSIVE r(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM r WHERE n < 5) \
+                   SELECT * FROM r";
+        let plan = get_plan(sql);
+        println!("{}", plan);
+        assert!(plan.contains("single_row"));
+        assert!(plan.contains("recursive_union_all"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_select_wildcard_without_from() {
+        let sql = "SELECT *";
+        println!("{}", get_plan(sql));
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_cte_duplicate_name() {
+        let sql = "WITH x AS (SELECT a FROM t1), x AS (SELECT c FROM t2) SELECT a FROM x";
+        println!("{}", get_plan(sql));
+    }
+
     // #[test]
     // fn parse_subquery_where() {
     //     let sql = "SELECT a FROM t1 WHERE exists (SELECT * FROM t2 WHERE c = a)";
@@ -53,6 +53,8 @@ impl ContainerSamples {
     pub fn add_sample(&mut self, tuple: Tuple, value_id: ValueId, idx: Option<usize>) {
         match idx {
             Some(i) => {
+                // The replaced sample is no longer tracked
+                self.id_to_sample.retain(|_, j| *j != i);
                 self.samples[i] = tuple;
                 self.id_to_sample.insert(value_id, i);
             }
@@ -63,11 +65,50 @@ impl ContainerSamples {
         }
     }
 
+    /// remove_sample removes the sample tracked under value_id (if any) and
+    /// returns it. The last sample is moved into the freed position so the
+    /// samples stay contiguous.
+    pub fn remove_sample(&mut self, value_id: &ValueId) -> Option<Tuple> {
+        let idx = self.id_to_sample.remove(value_id)?;
+        let last = self.samples.len() - 1;
+        let tuple = self.samples.swap_remove(idx);
+        if idx != last {
+            if let Some(moved) = self.id_to_sample.values_mut().find(|j| **j == last) {
+                *moved = idx;
+            }
+        }
+        Some(tuple)
+    }
+
+    /// update_sample replaces the sample tracked under old_value_id (if any)
+    /// with the updated tuple, now tracked under value_id. Returns whether a
+    /// sample was replaced.
+    pub fn update_sample(
+        &mut self,
+        tuple: Tuple,
+        value_id: ValueId,
+        old_value_id: &ValueId,
+    ) -> bool {
+        match self.id_to_sample.remove(old_value_id) {
+            Some(i) => {
+                self.samples[i] = tuple;
+                self.id_to_sample.insert(value_id, i);
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Increment the record count
     pub fn increment_record_count(&mut self) {
         self.record_count += 1;
     }
 
+    /// Decrement the record count
+    pub fn decrement_record_count(&mut self) {
+        self.record_count = self.record_count.saturating_sub(1);
+    }
+
     /// Get PerAttrStats
     pub fn get_per_attr_stats(&self) -> &Vec<PerAttrStats> {
         &self.per_attr_stats
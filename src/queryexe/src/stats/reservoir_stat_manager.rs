@@ -9,7 +9,7 @@ use common::ids::StateInfo;
 use common::logical_expr::prelude::Expression;
 use common::physical::config::ServerConfig;
 use common::physical_expr::physical_rel_expr::PhysicalRelExpr;
-use common::query::bytecode_expr::ByteCodeExpr;
+use common::query::bytecode_expr::{like_selectivity, ByteCodeExpr};
 use common::testutil::get_rng;
 use common::{
     ids::ContainerId, traits::stat_manager_trait::StatManagerTrait,
@@ -152,17 +152,47 @@ impl StatManagerTrait for ReservoirStatManager {
         Ok(())
     }
 
-    fn deleted_record(&self, _value_id: &ValueId) -> Result<(), CrustyError> {
-        todo!()
+    fn deregister_table(&self, c_id: ContainerId) -> Result<(), CrustyError> {
+        let mut samples = self.samples.write().unwrap();
+        let mut states = self.states.write().unwrap();
+        if samples.remove(&c_id).is_none() {
+            return Err(CrustyError::CrustyError(
+                "Container not found/registered".to_string(),
+            ));
+        }
+        states.remove(&c_id);
+        Ok(())
+    }
+
+    fn deleted_record(&self, value_id: &ValueId) -> Result<(), CrustyError> {
+        let mut samples = self.samples.write().unwrap();
+        let container_samples =
+            samples
+                .get_mut(&value_id.container_id)
+                .ok_or(CrustyError::CrustyError(
+                    "Container not found/registered".to_string(),
+                ))?;
+        container_samples.remove_sample(value_id);
+        container_samples.decrement_record_count();
+        Ok(())
     }
 
     fn updated_record(
         &self,
-        _tuple: &Tuple,
-        _value_id: &ValueId,
-        _old_value_id: Option<&ValueId>,
+        tuple: &Tuple,
+        value_id: &ValueId,
+        old_value_id: Option<&ValueId>,
     ) -> Result<(), CrustyError> {
-        todo!()
+        let mut samples = self.samples.write().unwrap();
+        let container_samples =
+            samples
+                .get_mut(&value_id.container_id)
+                .ok_or(CrustyError::CrustyError(
+                    "Container not found/registered".to_string(),
+                ))?;
+        // The record count is unchanged, only a sampled record needs refreshing
+        container_samples.update_sample(tuple.clone(), *value_id, old_value_id.unwrap_or(value_id));
+        Ok(())
     }
 
     fn new_record(&self, tuple: &Tuple, value_id: ValueId) -> Result<(), CrustyError> {
@@ -200,6 +230,8 @@ impl StatManagerTrait for ReservoirStatManager {
         c_id: ContainerId,
         predicate: &[Expression<PhysicalRelExpr>],
     ) -> Result<(usize, f64), CrustyError> {
+        let like_sel = like_predicate_selectivity(predicate);
+        let range_bounds = range_predicate_bounds(predicate);
         let predicate = predicate
             .iter()
             .map(|p| convert_expr_to_bytecode(p.clone(), None))
@@ -217,12 +249,25 @@ impl StatManagerTrait for ReservoirStatManager {
         let matching_count = container_samples
             .samples
             .iter()
-            .filter(|tuple| predicate.iter().all(|p| p.eval(tuple) == Field::Bool(true)))
+            .filter(|tuple| {
+                predicate
+                    .iter()
+                    .all(|p| p.eval(tuple).is_ok_and(|v| v == Field::Bool(true)))
+            })
             .count();
 
         let sample_size = container_samples.samples.len();
 
-        let selectivity = matching_count as f64 / sample_size as f64;
+        let range_sel = range_predicate_selectivity(&range_bounds, &container_samples.samples);
+        let selectivity = match (like_sel, range_sel) {
+            // LIKE patterns matched by no sample are rare rather than absent: estimate
+            // their selectivity from the pattern, below what the sample can measure
+            (Some(like_sel), _) if matching_count == 0 => like_sel.min(1.0 / sample_size as f64),
+            // Likewise, ranges falling between the sampled values are estimated from the
+            // fraction of the sampled value range they cover
+            (_, Some(range_sel)) if matching_count == 0 => range_sel.min(1.0 / sample_size as f64),
+            _ => matching_count as f64 / sample_size as f64,
+        };
         let count = (selectivity * container_samples.record_count as f64) as usize;
         Ok((count, selectivity))
     }
@@ -267,9 +312,13 @@ impl StatManagerTrait for ReservoirStatManager {
                     .iter()
                     .zip(left_exprs.iter().zip(right_exprs.iter()))
                     .all(|(op, (left_expr, right_expr))| {
-                        let left_field = left_expr.eval(left_tuple);
-                        let right_field = right_expr.eval(right_tuple);
-                        compare_fields(*op, &left_field, &right_field)
+                        // Samples the expressions fail on are not counted as matches
+                        match (left_expr.eval(left_tuple), right_expr.eval(right_tuple)) {
+                            (Ok(left_field), Ok(right_field)) => {
+                                compare_fields(*op, &left_field, &right_field)
+                            }
+                            _ => false,
+                        }
                     })
                 {
                     matching_count += 1;
@@ -345,6 +394,126 @@ impl ReservoirStatManager {
     }
 }
 
+/// Selectivity of the LIKE predicates with a constant pattern estimated from their
+/// patterns, if the predicate has any.
+fn like_predicate_selectivity(predicate: &[Expression<PhysicalRelExpr>]) -> Option<f64> {
+    let sels = predicate
+        .iter()
+        .filter_map(|p| match p {
+            Expression::Binary { op, right, .. } => match (op, right.as_ref()) {
+                (
+                    BinaryOp::Like | BinaryOp::ILike,
+                    Expression::Field {
+                        val: Field::String(pattern),
+                    },
+                ) => Some(like_selectivity(pattern)),
+                (
+                    BinaryOp::NotLike | BinaryOp::NotILike,
+                    Expression::Field {
+                        val: Field::String(pattern),
+                    },
+                ) => Some(1.0 - like_selectivity(pattern)),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if sels.is_empty() {
+        None
+    } else {
+        Some(sels.into_iter().product())
+    }
+}
+
+/// Numeric value of a field, used to interpolate ranges.
+fn numeric_value(field: &Field) -> Option<f64> {
+    match field {
+        Field::BigInt(v) => Some(*v as f64),
+        Field::Int(v) => Some(*v as f64),
+        Field::SmallInt(v) => Some(*v as f64),
+        Field::Decimal(v, s) => Some(*v as f64 / 10f64.powi(*s as i32)),
+        _ => None,
+    }
+}
+
+/// Bounds `[low, high]` of the columns restricted from both sides by the conjunctions
+/// of the predicate, e.g. by `a BETWEEN 1 AND 5`, which is `a >= 1 AND a <= 5`.
+fn range_predicate_bounds(predicate: &[Expression<PhysicalRelExpr>]) -> Vec<(usize, f64, f64)> {
+    fn collect(
+        expr: &Expression<PhysicalRelExpr>,
+        bounds: &mut HashMap<usize, (Option<f64>, Option<f64>)>,
+    ) {
+        if let Expression::Binary { op, left, right } = expr {
+            match (op, left.as_ref(), right.as_ref()) {
+                (BinaryOp::And, left, right) => {
+                    collect(left, bounds);
+                    collect(right, bounds);
+                }
+                (
+                    BinaryOp::Ge | BinaryOp::Gt,
+                    Expression::ColRef { id },
+                    Expression::Field { val },
+                ) => {
+                    if let Some(v) = numeric_value(val) {
+                        let low = &mut bounds.entry(*id).or_default().0;
+                        *low = Some(low.map_or(v, |low| low.max(v)));
+                    }
+                }
+                (
+                    BinaryOp::Le | BinaryOp::Lt,
+                    Expression::ColRef { id },
+                    Expression::Field { val },
+                ) => {
+                    if let Some(v) = numeric_value(val) {
+                        let high = &mut bounds.entry(*id).or_default().1;
+                        *high = Some(high.map_or(v, |high| high.min(v)));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    let mut bounds = HashMap::new();
+    for p in predicate {
+        collect(p, &mut bounds);
+    }
+    bounds
+        .into_iter()
+        .filter_map(|(id, bounds)| match bounds {
+            (Some(low), Some(high)) => Some((id, low, high)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Selectivity of the ranges estimated as the fraction of the range of sampled values
+/// they cover, if there are any ranges.
+fn range_predicate_selectivity(bounds: &[(usize, f64, f64)], samples: &[Tuple]) -> Option<f64> {
+    if bounds.is_empty() {
+        return None;
+    }
+    let sel = bounds
+        .iter()
+        .map(|&(id, low, high)| {
+            let values = samples
+                .iter()
+                .filter_map(|t| t.get_field(id).and_then(numeric_value));
+            let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+                (min.min(v), max.max(v))
+            });
+            let overlap = high.min(max) - low.max(min);
+            if low > high || overlap < 0.0 {
+                0.0
+            } else if max > min {
+                overlap / (max - min)
+            } else {
+                1.0
+            }
+        })
+        .product();
+    Some(sel)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -418,6 +587,129 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_deleted_record() {
+        let stat_manager = gen_test_stat_manager();
+        let c_id = 1;
+        let tuple_count = 10;
+        let mut rng = get_rng();
+        let (table, tuples) = gen_test_table_and_tuples(&mut rng, c_id, tuple_count);
+        stat_manager.register_table(c_id, table.schema).unwrap();
+        let value_ids = (0..tuple_count)
+            .map(|i| {
+                let mut value_id = ValueId::new(c_id);
+                value_id.slot_id = Some(i as u16);
+                value_id
+            })
+            .collect::<Vec<_>>();
+        for (tuple, value_id) in tuples.iter().zip(value_ids.iter()) {
+            stat_manager.new_record(tuple, *value_id).unwrap();
+        }
+
+        stat_manager.deleted_record(&value_ids[2]).unwrap();
+        assert_eq!(stat_manager.get_container_record_count(c_id), Ok(9));
+        {
+            let samples = stat_manager.samples.read().unwrap();
+            let container_samples = samples.get(&c_id).unwrap();
+            assert_eq!(container_samples.samples.len(), 9);
+            assert!(!container_samples.id_to_sample.contains_key(&value_ids[2]));
+            // The last sample was moved into the freed slot
+            let idx = container_samples.id_to_sample[&value_ids[9]];
+            assert_eq!(container_samples.samples[idx], tuples[9]);
+        }
+
+        let err_expected = stat_manager.deleted_record(&ValueId::new(2));
+        assert_eq!(
+            err_expected,
+            Err(CrustyError::CrustyError(
+                "Container not found/registered".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_deregister_table() {
+        let stat_manager = gen_test_stat_manager();
+        let c_id = 1;
+        let mut rng = get_rng();
+        let (table, tuples) = gen_test_table_and_tuples(&mut rng, c_id, 10);
+        stat_manager
+            .register_table(c_id, table.schema.clone())
+            .unwrap();
+        for (i, tuple) in tuples.iter().enumerate() {
+            let mut value_id = ValueId::new(c_id);
+            value_id.slot_id = Some(i as u16);
+            stat_manager.new_record(tuple, value_id).unwrap();
+        }
+
+        stat_manager.deregister_table(c_id).unwrap();
+        assert!(!stat_manager.samples.read().unwrap().contains_key(&c_id));
+        assert!(!stat_manager.states.read().unwrap().contains_key(&c_id));
+        assert_eq!(
+            stat_manager.deregister_table(c_id),
+            Err(CrustyError::CrustyError(
+                "Container not found/registered".to_string()
+            ))
+        );
+
+        // Registering again starts from scratch
+        stat_manager.register_table(c_id, table.schema).unwrap();
+        assert_eq!(stat_manager.get_container_record_count(c_id), Ok(0));
+    }
+
+    #[test]
+    fn test_updated_record() {
+        let stat_manager = gen_test_stat_manager();
+        let c_id = 1;
+        let tuple_count = 10;
+        let mut rng = get_rng();
+        let (table, tuples) = gen_test_table_and_tuples(&mut rng, c_id, tuple_count);
+        stat_manager.register_table(c_id, table.schema).unwrap();
+        let value_ids = (0..tuple_count)
+            .map(|i| {
+                let mut value_id = ValueId::new(c_id);
+                value_id.slot_id = Some(i as u16);
+                value_id
+            })
+            .collect::<Vec<_>>();
+        for (tuple, value_id) in tuples.iter().zip(value_ids.iter()) {
+            stat_manager.new_record(tuple, *value_id).unwrap();
+        }
+
+        // Update in place
+        stat_manager
+            .updated_record(&tuples[0], &value_ids[3], None)
+            .unwrap();
+        // Update that moves the record
+        let mut moved_id = ValueId::new(c_id);
+        moved_id.slot_id = Some(tuple_count as u16);
+        stat_manager
+            .updated_record(&tuples[1], &moved_id, Some(&value_ids[5]))
+            .unwrap();
+        assert_eq!(
+            stat_manager.get_container_record_count(c_id),
+            Ok(tuple_count as usize)
+        );
+        {
+            let samples = stat_manager.samples.read().unwrap();
+            let container_samples = samples.get(&c_id).unwrap();
+            assert_eq!(container_samples.samples.len(), tuple_count as usize);
+            let idx = container_samples.id_to_sample[&value_ids[3]];
+            assert_eq!(container_samples.samples[idx], tuples[0]);
+            assert!(!container_samples.id_to_sample.contains_key(&value_ids[5]));
+            let idx = container_samples.id_to_sample[&moved_id];
+            assert_eq!(container_samples.samples[idx], tuples[1]);
+        }
+
+        let err_expected = stat_manager.updated_record(&tuples[0], &ValueId::new(2), None);
+        assert_eq!(
+            err_expected,
+            Err(CrustyError::CrustyError(
+                "Container not found/registered".to_string()
+            ))
+        );
+    }
+
     #[test]
     fn test_estimated_record_count_simple() {
         let stat_manager = gen_test_stat_manager();
@@ -526,6 +818,98 @@ mod tests {
         assert!(estimated_count <= tuple_count.try_into().unwrap());
     }
 
+    #[test]
+    fn test_estimated_like_selectivity() {
+        let stat_manager = gen_test_stat_manager();
+        let c_id = 1;
+        let value_id = ValueId::new(c_id);
+        let tuple_count = 1000;
+        let mut rng = get_rng();
+        let (table, tuples) = gen_test_table_and_tuples(&mut rng, c_id, tuple_count);
+        stat_manager.register_table(c_id, table.schema).unwrap();
+        for tuple in &tuples {
+            stat_manager.new_record(tuple, value_id).unwrap();
+        }
+        let like = |pattern: &str| Expression::<PhysicalRelExpr>::Binary {
+            op: BinaryOp::Like,
+            left: Box::new(Expression::<PhysicalRelExpr>::ColRef { id: 5 }), // Referring to "sa1"
+            right: Box::new(Expression::<PhysicalRelExpr>::Field {
+                val: Field::String(pattern.to_string()),
+            }),
+        };
+
+        // Every string matches
+        let (estimated_count, est_sel) = stat_manager
+            .estimate_count_and_sel(c_id, &[like("%")])
+            .unwrap();
+        assert_eq!(estimated_count, tuple_count as usize);
+        assert_eq!(est_sel, 1.0);
+
+        // A pattern matched by no sample is still given a small selectivity
+        let (_, est_sel) = stat_manager
+            .estimate_count_and_sel(c_id, &[like("#no#match#%")])
+            .unwrap();
+        assert!(est_sel > 0.0);
+        assert!(est_sel <= 1.0 / tuple_count as f64);
+    }
+
+    #[test]
+    fn test_estimated_range_selectivity() {
+        let stat_manager = gen_test_stat_manager();
+        let c_id = 1;
+        let value_id = ValueId::new(c_id);
+        let tuple_count = 1000;
+        let mut rng = get_rng();
+        let (table, tuples) = gen_test_table_and_tuples(&mut rng, c_id, tuple_count);
+        stat_manager.register_table(c_id, table.schema).unwrap();
+        for tuple in &tuples {
+            stat_manager.new_record(tuple, value_id).unwrap();
+        }
+        // col BETWEEN low AND high
+        let between = |col: usize, low: i64, high: i64| {
+            let bound = |op, val| Expression::<PhysicalRelExpr>::Binary {
+                op,
+                left: Box::new(Expression::<PhysicalRelExpr>::ColRef { id: col }),
+                right: Box::new(Expression::<PhysicalRelExpr>::Field {
+                    val: Field::BigInt(val),
+                }),
+            };
+            Expression::<PhysicalRelExpr>::Binary {
+                op: BinaryOp::And,
+                left: Box::new(bound(BinaryOp::Ge, low)),
+                right: Box::new(bound(BinaryOp::Le, high)),
+            }
+        };
+
+        // ia1 should be uniformly distributed 0-9
+        let (_, est_sel) = stat_manager
+            .estimate_count_and_sel(c_id, &[between(1, 2, 6)])
+            .unwrap();
+        assert!((0.4..=0.6).contains(&est_sel));
+
+        // A range of ia4 values (0-9999) matched by no record is still given a small
+        // selectivity
+        let ia4 = tuples
+            .iter()
+            .map(|t| t.get_field(4).unwrap().clone())
+            .collect::<Vec<_>>();
+        let low = (5000..9999)
+            .find(|v| !ia4.contains(&Field::BigInt(*v)) && !ia4.contains(&Field::BigInt(v + 1)))
+            .unwrap();
+        let (_, est_sel) = stat_manager
+            .estimate_count_and_sel(c_id, &[between(4, low, low + 1)])
+            .unwrap();
+        assert!(est_sel > 0.0);
+        assert!(est_sel <= 1.0 / tuple_count as f64);
+
+        // An empty range
+        let (estimated_count, est_sel) = stat_manager
+            .estimate_count_and_sel(c_id, &[between(1, 6, 2)])
+            .unwrap();
+        assert_eq!(estimated_count, 0);
+        assert_eq!(est_sel, 0.0);
+    }
+
     #[test]
     fn test_estimate_join_selectivity() {
         let stat_manager = gen_test_stat_manager();
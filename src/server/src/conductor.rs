@@ -1,25 +1,41 @@
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 
 use crate::database_state::DatabaseState;
 
+use crate::prepared::{
+    DeletePlan, InsertPlan, PreparedStatement, PreparedStatements, StatementPlan, UpdatePlan,
+};
 use crate::sql_parser::{ParserResponse, SQLParser};
 use crate::Executor;
 
 use common::error::c_err;
-use common::ids::TransactionId;
+use common::ids::{ContainerId, TransactionId, ValueId};
 use common::util::data_reader::CsvReader;
 
 use common::physical_expr::physical_rel_expr::PhysicalRelExpr;
+use common::query::expr::Expression;
 use common::query::rules::Rules;
-use common::{CrustyError, QueryResult};
+use common::{Attribute, CrustyError, DataType, Field, QueryResult, TableSchema, Tuple};
 
-use queryexe::query::planner::physical_plan_to_op_iterator;
-use queryexe::query::translate_and_validate::{get_name, Query};
+use queryexe::mutator::convert_literal;
+use queryexe::opiterator::{Delete, TupleIterator, Update};
+use queryexe::query::explain::{ExplainNode, Explainer};
+use queryexe::query::params::ParamTypes;
+use queryexe::query::planner::{
+    convert_expr_to_bytecode, physical_plan_to_instrumented_op_iterator,
+    physical_plan_to_op_iterator, table_scan_to_op_iterator, PlanMetrics,
+};
+use queryexe::query::translate_and_validate::{get_attr, get_name, Query};
 use queryexe::query::Translator;
 use queryexe::Managers;
-use sqlparser::ast::{SetExpr, Statement};
+use sqlparser::ast::{
+    self, AnalyzeFormat, Expr, Ident, ObjectType, SetExpr, Statement, TableFactor, TableWithJoins,
+    Value,
+};
 use std::fs::OpenOptions;
+use std::time::{Duration, Instant};
 
 use txn_manager::transactions::Transaction;
 
@@ -33,12 +49,36 @@ type ConductorCostModel = DummyCostModel;
 //type Optimizer = CascadesOptimizer<ConductorCostModel>;
 type Optimizer = MockOptimizer<ConductorCostModel>;
 
+/// Work done by a statement of a transaction block, undone if the block rolls back.
+///
+/// Transaction blocks are implemented by the conductor alone, with this undo log, and not
+/// by the transaction manager, which is a stub. The statements of a block run in the
+/// active transaction like any other statement. Hence a block is atomic but not isolated:
+/// other sessions see its changes before it commits, and a block interrupted by a server
+/// shutdown is not rolled back on restart.
+enum Undo {
+    /// Records inserted into and deleted from a table.
+    Changes {
+        table_id: ContainerId,
+        inserted: Vec<Tuple>,
+        deleted: Vec<Tuple>,
+    },
+    /// A table created in the block.
+    CreateTable(String),
+    /// A view, materialized or not, created in the block.
+    CreateView(String),
+}
+
 /// Conductor runs query to the database
 pub struct Conductor {
     pub parser: SQLParser,
     pub optimizer: Optimizer,
     pub executor: Executor,
     pub active_txn: Transaction,
+    /// Undo log of the open transaction block (BEGIN ... COMMIT), if any.
+    undo_log: Option<Vec<Undo>>,
+    /// Statements prepared by the session running the conductor.
+    pub prepared: PreparedStatements,
 }
 
 impl Conductor {
@@ -51,6 +91,8 @@ impl Conductor {
             optimizer,
             executor,
             active_txn: Transaction::new(),
+            undo_log: None,
+            prepared: PreparedStatements::default(),
         };
         Ok(conductor)
     }
@@ -68,15 +110,26 @@ impl Conductor {
             optimizer,
             executor,
             active_txn: Transaction::new_from_tid(tid),
+            undo_log: None,
+            prepared: PreparedStatements::default(),
         };
         Ok(conductor)
     }
 
+    /// Sets the prepared statements used by PREPARE, EXECUTE and DEALLOCATE, shared by
+    /// the conductors of a session.
+    pub fn with_prepared_statements(mut self, prepared: PreparedStatements) -> Self {
+        self.prepared = prepared;
+        self
+    }
+
+    /// Runs the statements of a SQL string in order, stopping at the first error, and
+    /// returns one result per statement. All the statements run in the active transaction.
     pub fn run_sql_from_string(
         &mut self,
         sql: String,
         db_state: &'static DatabaseState,
-    ) -> Result<QueryResult, CrustyError> {
+    ) -> Result<Vec<QueryResult>, CrustyError> {
         debug!("Parsing SQL: {:?}", &sql);
         match SQLParser::parse_sql(sql) {
             ParserResponse::SQL(ast) => self.run_sql(ast, db_state),
@@ -88,6 +141,19 @@ impl Conductor {
         }
     }
 
+    /// Runs a SQL string holding a single statement and returns its result.
+    pub fn run_statement_from_string(
+        &mut self,
+        sql: String,
+        db_state: &'static DatabaseState,
+    ) -> Result<QueryResult, CrustyError> {
+        let mut results = self.run_sql_from_string(sql, db_state)?;
+        if results.len() != 1 {
+            return Err(c_err("Expected a single statement"));
+        }
+        Ok(results.pop().unwrap())
+    }
+
     pub fn to_logical_plan(
         &self,
         sql: &str,
@@ -134,6 +200,7 @@ impl Conductor {
         physical_plan: PhysicalRelExpr,
         db_state: &'static DatabaseState,
     ) -> Result<QueryResult, CrustyError> {
+        self.refresh_matviews_read_by(&physical_plan, db_state)?;
         let op_iterator = physical_plan_to_op_iterator(
             db_state.managers,
             &db_state.catalog,
@@ -148,6 +215,17 @@ impl Conductor {
         self.executor.execute()
     }
 
+    /// Brings the materialized views read by the plan up to date with their tables.
+    fn refresh_matviews_read_by(
+        &self,
+        physical_plan: &PhysicalRelExpr,
+        db_state: &'static DatabaseState,
+    ) -> Result<(), CrustyError> {
+        let mut tables = Vec::new();
+        physical_plan.get_tables_involved(&mut tables);
+        db_state.refresh_matviews(&tables, self.active_txn.tid()?)
+    }
+
     pub fn run_opiterator(
         &mut self,
         op_iterator: Box<dyn queryexe::opiterator::OpIterator>,
@@ -163,11 +241,71 @@ impl Conductor {
         &mut self,
         ast: Vec<Statement>,
         db_state: &'static DatabaseState,
-    ) -> Result<QueryResult, CrustyError> {
+    ) -> Result<Vec<QueryResult>, CrustyError> {
         if ast.is_empty() {
             return Err(c_err("Empty SQL command"));
         }
-        match ast.first().unwrap() {
+        let mut results = Vec::with_capacity(ast.len());
+        for (i, statement) in ast.iter().enumerate() {
+            match self.run_statement(statement, db_state) {
+                Ok(result) => results.push(result),
+                Err(e) => {
+                    let rolled_back = self.undo_log.is_some();
+                    self.rollback(db_state)?;
+                    if ast.len() == 1 {
+                        return Err(e);
+                    }
+                    let mut message = format!("Statement {} failed: {}", i + 1, e);
+                    if rolled_back {
+                        message.push_str(", transaction rolled back");
+                    }
+                    return Err(c_err(message.as_str()));
+                }
+            }
+        }
+        // A transaction block cannot outlive its script
+        if self.undo_log.is_some() {
+            self.rollback(db_state)?;
+            return Err(c_err(
+                "Transaction block not closed by COMMIT, transaction rolled back",
+            ));
+        }
+        Ok(results)
+    }
+
+    fn run_statement(
+        &mut self,
+        statement: &Statement,
+        db_state: &'static DatabaseState,
+    ) -> Result<QueryResult, CrustyError> {
+        match statement {
+            Statement::StartTransaction { .. } => {
+                if self.undo_log.is_some() {
+                    return Err(c_err("A transaction block is already open"));
+                }
+                self.undo_log = Some(Vec::new());
+                Ok(QueryResult::MessageOnly("Transaction started".to_string()))
+            }
+            Statement::Commit { .. } => {
+                if self.undo_log.take().is_none() {
+                    return Err(c_err("No transaction block is open"));
+                }
+                Ok(QueryResult::MessageOnly(
+                    "Transaction committed".to_string(),
+                ))
+            }
+            Statement::Rollback { savepoint, .. } => {
+                if savepoint.is_some() {
+                    return Err(c_err("Savepoints are not supported"));
+                }
+                if self.undo_log.is_none() {
+                    return Err(c_err("No transaction block is open"));
+                }
+                self.rollback(db_state)?;
+                Ok(QueryResult::MessageOnly(
+                    "Transaction rolled back".to_string(),
+                ))
+            }
             Statement::CreateTable {
                 name: table_name,
                 columns,
@@ -176,31 +314,64 @@ impl Conductor {
             } => {
                 debug!("Processing CREATE table: {:?}", table_name);
                 debug!("Columns: {:?}", columns);
-                db_state.create_table(&get_name(table_name)?, columns, constraints)
+                let table_name = get_name(table_name)?;
+                let result = db_state.create_table(&table_name, columns, constraints)?;
+                self.push_undo(Undo::CreateTable(table_name));
+                Ok(result)
             }
-            Statement::Query(qbox) => {
-                debug!("Processing SQL Query");
+            Statement::Query(_) | Statement::Delete { .. } | Statement::Update { .. } => {
+                let plan = self.plan_statement(statement, &[], db_state)?;
+                self.run_plan(plan, db_state)
+            }
+            Statement::Explain {
+                analyze,
+                statement,
+                format,
+                ..
+            } => {
+                debug!("Explaining: {:?}", statement);
+                let query = match statement.as_ref() {
+                    Statement::Query(query) => query,
+                    _ => return Err(c_err("Only queries can be explained")),
+                };
                 let enabled_rules = Arc::new(Rules::default());
                 let lp = Translator::from_sql(
-                    qbox,
+                    query,
                     &db_state.catalog,
                     &enabled_rules,
                     &db_state.col_id_gen,
                 )
-                .unwrap();
-
-                // println!("Optimize start time: {:?}", std::time::Instant::now());
-
-                // inside here, see if any parts of the plan already exist (use hash)
-                // we pass the optional query registrar to replace subplans (TODO)
+                .map_err(|e| c_err(format!("{}", e).as_str()))?;
                 let pp = self
                     .optimizer
                     .optimize(&lp, Some(&db_state.query_registrar));
+                let mut explainer = Explainer::new(
+                    &db_state.catalog,
+                    lp.get_environment(),
+                    db_state.managers.stats,
+                );
+                if !*analyze {
+                    let plan = explainer.explain(&pp);
+                    return explain_result(&plan, format.as_ref(), None);
+                }
 
-                // TESTING - optimizer above will return subset stub for now if it exists
-                //   so that we can see what running a physical subplan looks like
-
-                self.run_physical_plan(pp, db_state)
+                // Run the query with instrumented operators, dropping its result
+                self.refresh_matviews_read_by(&pp, db_state)?;
+                let metrics = PlanMetrics::new();
+                let op_iterator = physical_plan_to_instrumented_op_iterator(
+                    db_state.managers,
+                    &db_state.catalog,
+                    &pp,
+                    self.active_txn.tid()?,
+                    db_state.get_current_time(),
+                    &metrics,
+                )?;
+                self.executor.configure_query(op_iterator);
+                let start = Instant::now();
+                self.executor.execute()?;
+                let execution_time = start.elapsed();
+                let plan = explainer.with_metrics(&metrics).explain(&pp);
+                explain_result(&plan, format.as_ref(), Some(execution_time))
             }
             Statement::Insert {
                 table_name,
@@ -217,33 +388,153 @@ impl Conductor {
                 } else {
                     return Err(c_err("No source for insert"));
                 };
-                match source.body.as_ref() {
-                    SetExpr::Values(values) if columns.is_empty() => {
-                        // identify the table id and schema of the table via catalog
-                        let table_name = get_name(table_name)?;
-                        let table_id = db_state.catalog.get_table_id(&table_name);
-                        let table_schema = db_state.catalog.get_table_schema(table_id).unwrap();
-                        let count = self.executor.import_tuples(
-                            values,
-                            &table_name,
+                // identify the table id and schema of the table via catalog
+                let table_name = get_name(table_name)?;
+                let table_id = db_state
+                    .catalog
+                    .get_table_id_if_exists(&table_name)
+                    .ok_or_else(|| {
+                        c_err(format!("Table {} does not exist", table_name).as_str())
+                    })?;
+                db_state.check_not_matview(table_id, &table_name)?;
+                let table_schema = db_state.catalog.get_table_schema(table_id).unwrap();
+                let col_order = if columns.is_empty() {
+                    None
+                } else {
+                    Some(get_column_order(columns, &table_schema, &table_name)?)
+                };
+                let tid = self.active_txn.tid()?;
+                let inserted = match source.body.as_ref() {
+                    SetExpr::Values(values) => self.executor.import_tuples(
+                        values,
+                        &table_name,
+                        &table_id,
+                        &table_schema,
+                        col_order,
+                        tid,
+                    )?,
+                    _ => {
+                        // The source is read fully before writing so that a query reading
+                        // the target table never sees the inserted records.
+                        let enabled_rules = Arc::new(Rules::default());
+                        let lp = Translator::from_sql(
+                            source,
+                            &db_state.catalog,
+                            &enabled_rules,
+                            &db_state.col_id_gen,
+                        )
+                        .map_err(|e| c_err(format!("{}", e).as_str()))?;
+                        let pp = self
+                            .optimizer
+                            .optimize(&lp, Some(&db_state.query_registrar));
+                        let tuples = match self.run_physical_plan(pp, db_state)? {
+                            QueryResult::Select { result, .. } => result,
+                            _ => return Err(c_err("Insert source did not produce tuples")),
+                        };
+                        self.executor.insert_tuples(
+                            tuples,
                             &table_id,
                             &table_schema,
-                            self.active_txn.tid()?,
-                        )?;
-                        let qr = QueryResult::new_insert_result(count, table_name);
-                        Ok(qr)
+                            col_order,
+                            tid,
+                        )?
+                    }
+                };
+                self.log_changes(db_state, table_id, &inserted, &[]);
+                Ok(QueryResult::new_insert_result(inserted.len(), table_name))
+            }
+            Statement::Drop {
+                object_type,
+                if_exists,
+                names,
+                ..
+            } => {
+                debug!("Dropping {}: {:?}", object_type, names);
+                self.check_no_transaction_block("DROP")?;
+                let mut messages = Vec::new();
+                for name in names {
+                    let name = get_name(name)?;
+                    let result = match object_type {
+                        ObjectType::Table => db_state.drop_table(&name, *if_exists)?,
+                        ObjectType::View => db_state.drop_view(&name, *if_exists)?,
+                        _ => {
+                            return Err(c_err(
+                                format!("DROP {} is not supported", object_type).as_str(),
+                            ))
+                        }
+                    };
+                    if let QueryResult::MessageOnly(message) = result {
+                        messages.push(message);
                     }
-                    SetExpr::Values(_) => {
-                        Err(c_err(
-                            "Inserts with columns specified are not currently supported. Must supply values for the entire table",
-                        ))
+                }
+                Ok(QueryResult::MessageOnly(messages.join("\n")))
+            }
+            Statement::CreateView {
+                or_replace,
+                materialized,
+                name,
+                columns,
+                query,
+                ..
+            } => {
+                debug!("Processing CREATE view: {:?}", name);
+                let name = get_name(name)?;
+                let columns = columns.iter().map(|col| col.value.clone()).collect();
+                let result = if *materialized {
+                    if *or_replace {
+                        return Err(c_err(
+                            "CREATE OR REPLACE MATERIALIZED VIEW is not supported",
+                        ));
                     }
-                    _ => {
-                        Err(c_err(
-                            "Inserts via query not currently supported. Must supply values",
-                        ))
+                    let tid = self.active_txn.tid()?;
+                    db_state.create_matview(&name, columns, query, tid)?
+                } else {
+                    if *or_replace {
+                        self.check_no_transaction_block("CREATE OR REPLACE VIEW")?;
                     }
+                    db_state.create_view(&name, columns, query, *or_replace)?
+                };
+                self.push_undo(Undo::CreateView(name));
+                Ok(result)
+            }
+            Statement::Truncate {
+                table_name,
+                partitions,
+                ..
+            } => {
+                debug!("Truncating table: {}", table_name);
+                if partitions.is_some() {
+                    return Err(c_err("TRUNCATE with partitions is not supported"));
                 }
+                self.check_no_transaction_block("TRUNCATE")?;
+                let tid = self.active_txn.tid()?;
+                db_state.truncate_table(&get_name(table_name)?, tid)
+            }
+            Statement::Prepare {
+                name,
+                data_types,
+                statement,
+            } => self.prepare(&name.value, data_types, statement, db_state),
+            Statement::Execute { name, parameters } => {
+                self.execute_prepared(&name.value, parameters, db_state)
+            }
+            Statement::Deallocate { name, .. } => {
+                let mut prepared = self.prepared.write().unwrap();
+                if name.quote_style.is_none() && name.value.eq_ignore_ascii_case("all") {
+                    prepared.clear();
+                    return Ok(QueryResult::MessageOnly(
+                        "Deallocated all prepared statements".to_string(),
+                    ));
+                }
+                if prepared.remove(&name.value).is_none() {
+                    return Err(c_err(
+                        format!("Prepared statement {} does not exist", name.value).as_str(),
+                    ));
+                }
+                Ok(QueryResult::MessageOnly(format!(
+                    "Deallocated prepared statement {}",
+                    name.value
+                )))
             }
             _ => {
                 unimplemented!()
@@ -251,6 +542,466 @@ impl Conductor {
         }
     }
 
+    /// Parses and plans a query, INSERT ... VALUES, DELETE or UPDATE statement, whose
+    /// expressions may hold parameters of the types `param_types`, if declared.
+    fn plan_statement(
+        &self,
+        statement: &Statement,
+        param_types: &[DataType],
+        db_state: &'static DatabaseState,
+    ) -> Result<StatementPlan, CrustyError> {
+        let enabled_rules = Arc::new(Rules::default());
+        match statement {
+            Statement::Query(qbox) => {
+                debug!("Processing SQL Query");
+                let mut translator =
+                    Translator::new(&db_state.catalog, &enabled_rules, &db_state.col_id_gen);
+                translator.declare_params(param_types);
+                let lp = translator
+                    .process_query(qbox)
+                    .map_err(|e| c_err(format!("{}", e).as_str()))?;
+
+                // println!("Optimize start time: {:?}", std::time::Instant::now());
+
+                // inside here, see if any parts of the plan already exist (use hash)
+                // we pass the optional query registrar to replace subplans (TODO)
+                let pp = self
+                    .optimizer
+                    .optimize(&lp, Some(&db_state.query_registrar));
+
+                // TESTING - optimizer above will return subset stub for now if it exists
+                //   so that we can see what running a physical subplan looks like
+
+                Ok(StatementPlan::Query(pp))
+            }
+            Statement::Insert {
+                table_name,
+                columns,
+                source,
+                ..
+            } => {
+                let values = match source.as_ref().map(|source| source.body.as_ref()) {
+                    Some(SetExpr::Values(values)) => values,
+                    _ => return Err(c_err("Only INSERT ... VALUES can be prepared")),
+                };
+                let table_name = get_name(table_name)?;
+                let table_id = db_state
+                    .catalog
+                    .get_table_id_if_exists(&table_name)
+                    .ok_or_else(|| {
+                        c_err(format!("Table {} does not exist", table_name).as_str())
+                    })?;
+                let table_schema = db_state.catalog.get_table_schema(table_id).unwrap();
+                let col_order = if columns.is_empty() {
+                    None
+                } else {
+                    Some(get_column_order(columns, &table_schema, &table_name)?)
+                };
+                db_state.check_not_matview(table_id, &table_name)?;
+                let translator =
+                    Translator::new(&db_state.catalog, &enabled_rules, &db_state.col_id_gen);
+                translator.declare_params(param_types);
+                let mut rows = Vec::with_capacity(values.rows.len());
+                for row in &values.rows {
+                    let mut exprs = Vec::with_capacity(row.len());
+                    for value in row {
+                        let expr = match value {
+                            Expr::Value(Value::Placeholder(_)) => translator
+                                .process_table_expr(value)
+                                .map_err(|e| c_err(format!("{}", e).as_str()))?,
+                            _ => match convert_literal(value) {
+                                Some(val) => Expression::Field {
+                                    val: val.map_err(|e| c_err(format!("{:?}", e).as_str()))?,
+                                },
+                                None => return Err(c_err("Only values supported in insert")),
+                            },
+                        };
+                        exprs.push(expr);
+                    }
+                    rows.push(exprs);
+                }
+                Ok(StatementPlan::Insert(InsertPlan {
+                    table_name,
+                    table_id,
+                    table_schema,
+                    col_order,
+                    rows,
+                }))
+            }
+            Statement::Delete {
+                from,
+                using,
+                selection,
+                returning,
+                order_by,
+                limit,
+                ..
+            } => {
+                debug!("Deleting from: {:?} selection: {:?}", from, selection);
+                if from.len() != 1
+                    || using.is_some()
+                    || returning.is_some()
+                    || !order_by.is_empty()
+                    || limit.is_some()
+                {
+                    return Err(c_err(
+                        "Only DELETE FROM <table> [WHERE <predicate>] is supported",
+                    ));
+                }
+                let table_name = get_target_table_name(&from[0])?;
+                let mut translator =
+                    Translator::new(&db_state.catalog, &enabled_rules, &db_state.col_id_gen);
+                translator.declare_params(param_types);
+                let table_id = translator
+                    .bind_table(&table_name)
+                    .map_err(|e| c_err(format!("{}", e).as_str()))?;
+                db_state.check_not_matview(table_id, &table_name)?;
+                let table_schema = db_state.catalog.get_table_schema(table_id).unwrap();
+                let predicate = selection
+                    .as_ref()
+                    .map(|expr| translator.process_table_expr(expr))
+                    .transpose()
+                    .map_err(|e| c_err(format!("{}", e).as_str()))?;
+                Ok(StatementPlan::Delete(DeletePlan {
+                    table_name,
+                    table_id,
+                    table_schema,
+                    predicate,
+                }))
+            }
+            Statement::Update {
+                table,
+                assignments,
+                from,
+                selection,
+                returning,
+            } => {
+                debug!(
+                    "Updating: {:?} assignments: {:?} selection: {:?}",
+                    table, assignments, selection
+                );
+                if from.is_some() || returning.is_some() {
+                    return Err(c_err(
+                        "Only UPDATE <table> SET <assignments> [WHERE <predicate>] is supported",
+                    ));
+                }
+                let table_name = get_target_table_name(table)?;
+                let mut translator =
+                    Translator::new(&db_state.catalog, &enabled_rules, &db_state.col_id_gen);
+                translator.declare_params(param_types);
+                let table_id = translator
+                    .bind_table(&table_name)
+                    .map_err(|e| c_err(format!("{}", e).as_str()))?;
+                db_state.check_not_matview(table_id, &table_name)?;
+                let table_schema = db_state.catalog.get_table_schema(table_id).unwrap();
+                let mut update_exprs: Vec<(usize, _)> = Vec::new();
+                for assignment in assignments {
+                    let col_name = match assignment.id.last() {
+                        Some(ident) => &ident.value,
+                        None => return Err(c_err("Missing column in assignment")),
+                    };
+                    let idx = table_schema.get_field_index(col_name).ok_or_else(|| {
+                        c_err(format!("Column {} not found in {}", col_name, table_name).as_str())
+                    })?;
+                    if update_exprs.iter().any(|(i, _)| *i == idx) {
+                        return Err(c_err(
+                            format!("Column {} assigned more than once", col_name).as_str(),
+                        ));
+                    }
+                    let expr = translator
+                        .process_table_expr(&assignment.value)
+                        .map_err(|e| c_err(format!("{}", e).as_str()))?;
+                    update_exprs.push((idx, expr));
+                }
+                let predicate = selection
+                    .as_ref()
+                    .map(|expr| translator.process_table_expr(expr))
+                    .transpose()
+                    .map_err(|e| c_err(format!("{}", e).as_str()))?;
+                Ok(StatementPlan::Update(UpdatePlan {
+                    table_name,
+                    table_id,
+                    table_schema,
+                    assignments: update_exprs,
+                    predicate,
+                }))
+            }
+            _ => Err(c_err(
+                "Only queries, INSERT ... VALUES, DELETE and UPDATE can be prepared",
+            )),
+        }
+    }
+
+    /// Runs a statement plan whose parameters are bound.
+    fn run_plan(
+        &mut self,
+        plan: StatementPlan,
+        db_state: &'static DatabaseState,
+    ) -> Result<QueryResult, CrustyError> {
+        let tid = self.active_txn.tid()?;
+        match plan {
+            StatementPlan::Query(pp) => self.run_physical_plan(pp, db_state),
+            StatementPlan::Insert(InsertPlan {
+                table_name,
+                table_id,
+                table_schema,
+                col_order,
+                rows,
+            }) => {
+                let mut tuples = Vec::with_capacity(rows.len());
+                for row in rows {
+                    let mut fields = Vec::with_capacity(row.len());
+                    for expr in row {
+                        match expr {
+                            Expression::Field { val } => fields.push(val),
+                            _ => return Err(c_err("Parameters of INSERT are not bound")),
+                        }
+                    }
+                    tuples.push(Tuple::new(fields));
+                }
+                let inserted = self.executor.insert_tuples(
+                    tuples,
+                    &table_id,
+                    &table_schema,
+                    col_order,
+                    tid,
+                )?;
+                self.log_changes(db_state, table_id, &inserted, &[]);
+                Ok(QueryResult::new_insert_result(inserted.len(), table_name))
+            }
+            StatementPlan::Delete(DeletePlan {
+                table_name,
+                table_id,
+                predicate,
+                ..
+            }) => {
+                let scan = table_scan_to_op_iterator(
+                    db_state.managers,
+                    &db_state.catalog,
+                    &table_id,
+                    predicate,
+                    tid,
+                )?;
+                self.executor
+                    .configure_query(Box::new(Delete::new(db_state.managers, tid, scan)));
+                let deleted = match self.executor.execute()? {
+                    QueryResult::Select { result, .. } => result,
+                    _ => return Err(c_err("Delete did not produce tuples")),
+                };
+                self.log_changes(db_state, table_id, &[], &deleted);
+                Ok(QueryResult::new_delete_result(deleted.len(), table_name))
+            }
+            StatementPlan::Update(UpdatePlan {
+                table_name,
+                table_id,
+                assignments,
+                predicate,
+                ..
+            }) => {
+                let mut update_exprs = Vec::with_capacity(assignments.len());
+                for (idx, expr) in assignments {
+                    update_exprs.push((idx, convert_expr_to_bytecode(expr, None)?));
+                }
+                // The old values of the updated records are needed by materialized views
+                // and to roll back the update
+                let old_tuples =
+                    if db_state.has_dependent_matviews(table_id) || self.undo_log.is_some() {
+                        let scan = table_scan_to_op_iterator(
+                            db_state.managers,
+                            &db_state.catalog,
+                            &table_id,
+                            predicate.clone(),
+                            tid,
+                        )?;
+                        self.executor.configure_query(scan);
+                        match self.executor.execute()? {
+                            QueryResult::Select { result, .. } => result,
+                            _ => return Err(c_err("Update did not produce tuples")),
+                        }
+                    } else {
+                        Vec::new()
+                    };
+                let scan = table_scan_to_op_iterator(
+                    db_state.managers,
+                    &db_state.catalog,
+                    &table_id,
+                    predicate,
+                    tid,
+                )?;
+                self.executor.configure_query(Box::new(Update::new(
+                    db_state.managers,
+                    &table_id,
+                    tid,
+                    update_exprs,
+                    scan,
+                )));
+                let mut new_tuples = Vec::new();
+                let res = self.executor.execute_into(&mut new_tuples);
+                // Records updated before a failure stay changed, so they are logged too.
+                // Both scans return the records in the same order.
+                let updated = new_tuples.len().min(old_tuples.len());
+                self.log_changes(db_state, table_id, &new_tuples, &old_tuples[..updated]);
+                res?;
+                Ok(QueryResult::new_update_result(new_tuples.len(), table_name))
+            }
+        }
+    }
+
+    /// PREPARE <name> [(<types>)] AS <statement>: plans the statement and stores it in
+    /// the prepared statements of the session.
+    fn prepare(
+        &mut self,
+        name: &str,
+        data_types: &[ast::DataType],
+        statement: &Statement,
+        db_state: &'static DatabaseState,
+    ) -> Result<QueryResult, CrustyError> {
+        if self.prepared.read().unwrap().contains_key(name) {
+            return Err(c_err(
+                format!("Prepared statement {} already exists", name).as_str(),
+            ));
+        }
+        let declared = data_types
+            .iter()
+            .map(get_attr)
+            .collect::<Result<Vec<_>, _>>()?;
+        let plan = self.plan_statement(statement, &declared, db_state)?;
+        let mut param_types = ParamTypes::new(declared);
+        plan.infer_param_types(&mut param_types, &db_state.catalog);
+        let tables = plan
+            .tables_involved()
+            .into_iter()
+            .filter_map(|table_id| {
+                let schema = db_state.catalog.get_table_schema(table_id)?;
+                Some((table_id, schema))
+            })
+            .collect();
+        self.prepared.write().unwrap().insert(
+            name.to_string(),
+            PreparedStatement {
+                plan,
+                param_types,
+                tables,
+            },
+        );
+        Ok(QueryResult::MessageOnly(format!(
+            "Prepared statement {}",
+            name
+        )))
+    }
+
+    /// EXECUTE <name> [(<values>)]: runs a prepared statement with the given parameter values.
+    fn execute_prepared(
+        &mut self,
+        name: &str,
+        parameters: &[Expr],
+        db_state: &'static DatabaseState,
+    ) -> Result<QueryResult, CrustyError> {
+        let mut values = Vec::with_capacity(parameters.len());
+        for parameter in parameters {
+            values.push(get_param_value(parameter, db_state)?);
+        }
+        let plan = {
+            let prepared = self.prepared.read().unwrap();
+            let statement = prepared.get(name).ok_or_else(|| {
+                c_err(format!("Prepared statement {} does not exist", name).as_str())
+            })?;
+            statement.check_tables(name, &db_state.catalog)?;
+            let values = statement.param_types.check(values)?;
+            statement.plan.clone().bind_params(&values)
+        };
+        self.run_plan(plan, db_state)
+    }
+
+    /// Logs the changes of a statement for materialized views and, in a transaction block,
+    /// for rolling back.
+    fn log_changes(
+        &mut self,
+        db_state: &'static DatabaseState,
+        table_id: ContainerId,
+        inserted: &[Tuple],
+        deleted: &[Tuple],
+    ) {
+        db_state.log_changes(table_id, inserted, deleted);
+        self.push_undo(Undo::Changes {
+            table_id,
+            inserted: inserted.to_vec(),
+            deleted: deleted.to_vec(),
+        });
+    }
+
+    fn push_undo(&mut self, undo: Undo) {
+        if let Some(undo_log) = self.undo_log.as_mut() {
+            undo_log.push(undo);
+        }
+    }
+
+    /// Errors for statements that cannot be undone if run in a transaction block.
+    fn check_no_transaction_block(&self, statement: &str) -> Result<(), CrustyError> {
+        if self.undo_log.is_some() {
+            return Err(c_err(
+                format!("{} cannot run inside a transaction block", statement).as_str(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Rolls back the open transaction block, if any, undoing its statements in reverse order.
+    fn rollback(&mut self, db_state: &'static DatabaseState) -> Result<(), CrustyError> {
+        let undo_log = match self.undo_log.take() {
+            Some(undo_log) => undo_log,
+            None => return Ok(()),
+        };
+        let tid = self.active_txn.tid()?;
+        // Restored records get new value ids, which earlier changes must refer to
+        let mut moved: HashMap<ValueId, ValueId> = HashMap::new();
+        for undo in undo_log.into_iter().rev() {
+            match undo {
+                Undo::Changes {
+                    table_id,
+                    mut inserted,
+                    deleted,
+                } => {
+                    let table_schema = db_state.catalog.get_table_schema(table_id).unwrap();
+                    for tuple in inserted.iter_mut() {
+                        if let Some(id) = tuple.value_id.and_then(|id| moved.get(&id)) {
+                            tuple.value_id = Some(*id);
+                        }
+                    }
+                    if !inserted.is_empty() {
+                        let records = TupleIterator::new(inserted.clone(), table_schema.clone());
+                        self.executor.configure_query(Box::new(Delete::new(
+                            db_state.managers,
+                            tid,
+                            Box::new(records),
+                        )));
+                        self.executor.execute()?;
+                    }
+                    let deleted_ids: Vec<_> = deleted.iter().map(|t| t.value_id).collect();
+                    let restored = if deleted.is_empty() {
+                        Vec::new()
+                    } else {
+                        self.executor
+                            .insert_tuples(deleted, &table_id, &table_schema, None, tid)?
+                    };
+                    for (old_id, tuple) in deleted_ids.into_iter().zip(restored.iter()) {
+                        if let (Some(old_id), Some(new_id)) = (old_id, tuple.value_id) {
+                            moved.insert(old_id, new_id);
+                        }
+                    }
+                    db_state.log_changes(table_id, &restored, &inserted);
+                }
+                Undo::CreateTable(name) => {
+                    db_state.drop_table(&name, true)?;
+                }
+                Undo::CreateView(name) => {
+                    db_state.drop_view(&name, true)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn import_csv(
         &mut self,
         table_name: &str,
@@ -258,6 +1009,16 @@ impl Conductor {
         db_state: &'static DatabaseState,
     ) -> Result<QueryResult, CrustyError> {
         let table_id = db_state.catalog.get_table_id(table_name);
+        db_state.check_not_matview(table_id, table_name)?;
+        if db_state.has_dependent_matviews(table_id) {
+            return Err(c_err(
+                format!(
+                    "Cannot import into {}, it is used by a materialized view",
+                    table_name
+                )
+                .as_str(),
+            ));
+        }
         let table_schema = db_state.catalog.get_table_schema(table_id).unwrap();
         let file = OpenOptions::new().read(true).open(file_path).unwrap();
         let mut csv_reader = CsvReader::new(file, &table_schema, b',', false).unwrap();
@@ -272,6 +1033,100 @@ impl Conductor {
     }
 }
 
+/// Result of an EXPLAIN: a single `QUERY PLAN` column, with one row per line of the
+/// text plan, or a single row holding the JSON plan. With EXPLAIN ANALYZE, the
+/// execution time of the query is added after the plan.
+fn explain_result(
+    plan: &ExplainNode,
+    format: Option<&AnalyzeFormat>,
+    execution_time: Option<Duration>,
+) -> Result<QueryResult, CrustyError> {
+    let execution_ms = execution_time.map(|time| time.as_secs_f64() * 1000.0);
+    let lines = match format {
+        None | Some(AnalyzeFormat::TEXT) => {
+            let mut lines: Vec<String> = plan
+                .to_text()
+                .lines()
+                .map(|line| line.to_string())
+                .collect();
+            if let Some(execution_ms) = execution_ms {
+                lines.push(format!("Execution time: {:.3}ms", execution_ms));
+            }
+            lines
+        }
+        Some(AnalyzeFormat::JSON) => {
+            let mut json = plan.to_json();
+            if let Some(execution_ms) = execution_ms {
+                json["execution_ms"] = serde_json::json!(execution_ms);
+            }
+            vec![serde_json::to_string_pretty(&json)
+                .map_err(|e| c_err(format!("{}", e).as_str()))?]
+        }
+        Some(format) => {
+            return Err(c_err(
+                format!("EXPLAIN format {} is not supported", format).as_str(),
+            ))
+        }
+    };
+    let schema = TableSchema::new(vec![Attribute::new(
+        "QUERY PLAN".to_string(),
+        DataType::String,
+    )]);
+    let tuples = lines
+        .into_iter()
+        .map(|line| Tuple::new(vec![Field::String(line)]))
+        .collect();
+    Ok(QueryResult::new_select_result(&schema, tuples, None))
+}
+
+/// Maps the columns of an INSERT column list to their index in the table schema.
+fn get_column_order(
+    columns: &[Ident],
+    schema: &TableSchema,
+    table_name: &str,
+) -> Result<Vec<usize>, CrustyError> {
+    let mut col_order = Vec::with_capacity(columns.len());
+    for column in columns {
+        let idx = schema.get_field_index(&column.value).ok_or_else(|| {
+            c_err(format!("Column {} not found in {}", column.value, table_name).as_str())
+        })?;
+        if col_order.contains(&idx) {
+            return Err(c_err(
+                format!("Column {} specified more than once", column.value).as_str(),
+            ));
+        }
+        col_order.push(idx);
+    }
+    Ok(col_order)
+}
+
+/// Value of a parameter of EXECUTE, which must be a constant.
+fn get_param_value(expr: &Expr, db_state: &'static DatabaseState) -> Result<Field, CrustyError> {
+    // Numbers are converted like inserted values, so that decimals are supported
+    if let Expr::Value(Value::Number(..)) | Expr::UnaryOp { .. } = expr {
+        if let Some(val) = convert_literal(expr) {
+            return val.map_err(|e| c_err(format!("{:?}", e).as_str()));
+        }
+    }
+    let enabled_rules = Arc::new(Rules::default());
+    let translator = Translator::new(&db_state.catalog, &enabled_rules, &db_state.col_id_gen);
+    match translator.process_table_expr(expr) {
+        Ok(Expression::Field { val }) => Ok(val),
+        Ok(_) => Err(c_err(
+            format!("Parameter {} of EXECUTE is not a constant", expr).as_str(),
+        )),
+        Err(e) => Err(c_err(format!("{}", e).as_str())),
+    }
+}
+
+/// Retrieve the name of the single base table targeted by a DELETE or UPDATE.
+fn get_target_table_name(table: &TableWithJoins) -> Result<String, CrustyError> {
+    match &table.relation {
+        TableFactor::Table { name, .. } if table.joins.is_empty() => get_name(name),
+        _ => Err(c_err("Only a single base table can be modified")),
+    }
+}
+
 // pub struct Conductor {
 //     pub parser: SQLParser,
 //     pub optimizer: Optimizer,
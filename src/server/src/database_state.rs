@@ -9,22 +9,37 @@ use std::sync::{Arc, RwLock};
 
 use crate::{StorageManager, StorageTrait};
 use common::catalog::{Catalog, CatalogRef};
-use common::ids::{AtomicTimeStamp, StateMeta};
+use common::ids::{AtomicTimeStamp, StateMeta, StateType, ValueId};
 use common::physical::col_id_generator::{ColIdGenerator, ColIdGeneratorRef};
 use common::physical_expr::physical_rel_expr::PhysicalRelExpr;
 use common::query::query_registrar::QueryStateRegistrar;
-use common::table::TableInfo;
+use common::query::rules::Rules;
+use common::table::{TableInfo, ViewInfo};
 use common::traits::stat_manager_trait::StatManagerTrait;
 use common::traits::state_tracker_trait::StateTrackerTrait;
 use common::{prelude::*, QUERY_CACHES_DIR_NAME};
 use common::{Attribute, QueryResult};
+use queryexe::mutator;
 use queryexe::query::get_attr;
+use queryexe::query::matview::{zset_add, zset_merge, DeltaPlan, ZSet};
+use queryexe::query::planner::table_scan_to_op_iterator;
+use queryexe::query::Translator;
 use queryexe::Managers;
-use sqlparser::ast::ColumnDef;
 use sqlparser::ast::TableConstraint;
+use sqlparser::ast::{ColumnDef, ColumnOption, Expr};
 
+use crate::prepared::PreparedStatements;
 use crate::sql_parser::{ParserResponse, SQLParser};
 
+/// Materialized view maintained incrementally.
+struct MatView {
+    /// Definition of the view, persisted to rebuild the view on load.
+    definition: ViewInfo,
+    plan: DeltaPlan,
+    /// Value ids of the stored rows of the view, by row.
+    rows: HashMap<Vec<Field>, Vec<ValueId>>,
+}
+
 #[derive(Serialize)]
 pub struct DatabaseState {
     pub id: u64,
@@ -47,7 +62,20 @@ pub struct DatabaseState {
     // TODO: query registrar state should be persisted (work around physical plan stuff)
     pub query_registrar: QueryStateRegistrar,
 
+    #[serde(skip)]
+    // Maintenance state of the materialized views, rebuilt on load from their definitions
+    matviews: RwLock<HashMap<ContainerId, MatView>>,
+
+    #[serde(skip)]
+    // Changes to the tables used by materialized views, with the time of the change,
+    // until every materialized view using the table is refreshed
+    delta_log: RwLock<HashMap<ContainerId, Vec<(LogicalTimeStamp, ZSet)>>>,
+
     client_tids: RwLock<HashMap<u64, TransactionId>>,
+
+    #[serde(skip)]
+    // Statements prepared by each client, kept until the client disconnects
+    client_prepared: RwLock<HashMap<u64, PreparedStatements>>,
 }
 
 /// This is exclusively for loading in serialized data back to DatabaseState. DO NOT USE FOR ANYTHING ELSE
@@ -58,6 +86,9 @@ pub struct SerializedDatabaseState {
     pub catalog: CatalogRef,
     pub col_id_gen: ColIdGeneratorRef,
     pub container_vec: HashMap<ContainerId, StateMeta>,
+    /// Definitions of the materialized views, by container.
+    #[serde(default)]
+    pub matviews: HashMap<ContainerId, ViewInfo>,
 }
 
 #[allow(dead_code)]
@@ -71,12 +102,20 @@ impl DatabaseState {
     pub fn get_serializable_db_state(&self) -> SerializedDatabaseState {
         let r = self.container_vec.read().unwrap();
         let container_vec = r.clone();
+        let matviews = self
+            .matviews
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(table_id, matview)| (*table_id, matview.definition.clone()))
+            .collect();
         SerializedDatabaseState {
             id: self.id,
             name: self.name.clone(),
             catalog: self.catalog.clone(),
             col_id_gen: self.col_id_gen.clone(),
             container_vec,
+            matviews,
         }
     }
 
@@ -111,7 +150,10 @@ impl DatabaseState {
             container_vec: Arc::new(RwLock::new(HashMap::new())),
             atomic_time: common::ids::AtomicTimeStamp::new(0),
             query_registrar: QueryStateRegistrar::new(),
+            matviews: RwLock::new(HashMap::new()),
+            delta_log: RwLock::new(HashMap::new()),
             client_tids: RwLock::new(HashMap::new()),
+            client_prepared: RwLock::new(HashMap::new()),
         };
         Ok(db_state)
     }
@@ -130,8 +172,12 @@ impl DatabaseState {
             container_vec: Arc::new(RwLock::new(partial_db_state_info.container_vec)),
             atomic_time: common::ids::AtomicTimeStamp::new(0), // I thihk it's fine to reset this?
             client_tids: RwLock::new(HashMap::new()),
+            client_prepared: RwLock::new(HashMap::new()),
             query_registrar: QueryStateRegistrar::default(), // TODO: persist query_registrar state and inherit from partial
+            matviews: RwLock::new(HashMap::new()),
+            delta_log: RwLock::new(HashMap::new()),
         };
+        db_state.rebuild_matviews(partial_db_state_info.matviews)?;
         Ok(db_state)
     }
 
@@ -175,6 +221,12 @@ impl DatabaseState {
     ) -> Result<QueryResult, CrustyError> {
         // Constraints aren't implemented yet
 
+        if self.catalog.get_view(table_name).is_some() {
+            return Err(CrustyError::CrustyError(format!(
+                "View {} already exists",
+                table_name
+            )));
+        }
         let table_id = self.catalog.get_table_id(table_name);
         let pks = match SQLParser::get_pks(columns, constraints) {
             Ok(pks) => pks,
@@ -184,16 +236,51 @@ impl DatabaseState {
 
         let mut attributes: Vec<Attribute> = Vec::new();
         for col in columns {
+            let dtype = get_attr(&col.data_type)?;
+            let mut not_null = false;
+            let mut unique = false;
+            let mut default = None;
+            for column_option in &col.options {
+                match &column_option.option {
+                    ColumnOption::NotNull => not_null = true,
+                    ColumnOption::Unique { .. } => unique = true,
+                    ColumnOption::Default(Expr::Value(value)) => {
+                        let field = mutator::convert_value(value).map_err(|e| {
+                            CrustyError::CrustyError(format!(
+                                "Invalid default for column {}: {:?}",
+                                col.name, e
+                            ))
+                        })?;
+                        if field != Field::Null && !mutator::is_valid_type(&dtype, &field) {
+                            return Err(CrustyError::CrustyError(format!(
+                                "Default {} does not match type {} of column {}",
+                                field, dtype, col.name
+                            )));
+                        }
+                        default = Some(field);
+                    }
+                    ColumnOption::Default(_) => {
+                        return Err(CrustyError::CrustyError(format!(
+                            "Only literal defaults are supported (column {})",
+                            col.name
+                        )));
+                    }
+                    _ => {}
+                }
+            }
             let constraint = if pks.contains(&col.name) {
                 common::Constraint::PrimaryKey
+            } else if unique && not_null {
+                common::Constraint::UniqueNotNull
+            } else if unique {
+                common::Constraint::Unique
+            } else if not_null {
+                common::Constraint::NotNull
             } else {
                 common::Constraint::None
             };
-            let attr = Attribute {
-                name: col.name.value.clone().to_string(),
-                dtype: get_attr(&col.data_type)?,
-                constraint,
-            };
+            let attr = Attribute::new_with_constraint(col.name.value.clone(), dtype, constraint)
+                .with_default(default);
             attributes.push(attr);
         }
         let schema = TableSchema::new(attributes);
@@ -221,6 +308,625 @@ impl DatabaseState {
         Ok(qr)
     }
 
+    /// Drops a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table_name` - Name of the table to drop.
+    /// * `if_exists` - Do not fail if the table does not exist.
+    pub fn drop_table(
+        &self,
+        table_name: &str,
+        if_exists: bool,
+    ) -> Result<QueryResult, CrustyError> {
+        let table_id = match self.catalog.get_table_id_if_exists(table_name) {
+            Some(table_id) if self.catalog.is_valid_table(table_id) => table_id,
+            _ if if_exists => {
+                return Ok(QueryResult::MessageOnly(format!(
+                    "Table {} does not exist, skipping",
+                    table_name
+                )));
+            }
+            _ => {
+                return Err(CrustyError::CrustyError(format!(
+                    "Table {} does not exist",
+                    table_name
+                )));
+            }
+        };
+        if self.is_matview(table_id) {
+            return Err(CrustyError::CrustyError(format!(
+                "{} is a materialized view, use DROP VIEW",
+                table_name
+            )));
+        }
+        self.check_no_dependent_matviews(table_id, table_name)?;
+        self.query_registrar.purge_queries_with_table(table_id)?;
+        self.managers.sm.remove_container(table_id)?;
+        self.catalog.remove_table(table_id);
+        self.managers.stats.deregister_table(table_id)?;
+        self.container_vec.write().unwrap().remove(&table_id);
+
+        Ok(QueryResult::MessageOnly(format!(
+            "Table {} dropped",
+            table_name
+        )))
+    }
+
+    /// Creates a view. The query is validated, then stored as SQL text and expanded
+    /// wherever the view is referenced.
+    ///
+    /// # Arguments
+    ///
+    /// * `view_name` - Name of the new view.
+    /// * `columns` - Column names of the view, empty to use the names from the query.
+    /// * `query` - Query defining the view.
+    /// * `or_replace` - Replace the view if it already exists.
+    pub fn create_view(
+        &self,
+        view_name: &str,
+        columns: Vec<String>,
+        query: &sqlparser::ast::Query,
+        or_replace: bool,
+    ) -> Result<QueryResult, CrustyError> {
+        if self.catalog.is_valid_table_from_string(view_name) {
+            return Err(CrustyError::CrustyError(format!(
+                "Table {} already exists",
+                view_name
+            )));
+        }
+        if !or_replace && self.catalog.get_view(view_name).is_some() {
+            return Err(CrustyError::CrustyError(format!(
+                "View {} already exists",
+                view_name
+            )));
+        }
+        let view_info = ViewInfo::new(view_name.to_string(), columns, query.to_string());
+        let enabled_rules = Arc::new(Rules::default());
+        Translator::translate_view(&view_info, &self.catalog, &enabled_rules, &self.col_id_gen)
+            .map_err(|e| CrustyError::CrustyError(format!("{}", e)))?;
+        self.catalog.add_view(view_info);
+
+        Ok(QueryResult::MessageOnly(format!(
+            "View {} created",
+            view_name
+        )))
+    }
+
+    /// Drops a view.
+    ///
+    /// # Arguments
+    ///
+    /// * `view_name` - Name of the view to drop.
+    /// * `if_exists` - Do not fail if the view does not exist.
+    pub fn drop_view(&self, view_name: &str, if_exists: bool) -> Result<QueryResult, CrustyError> {
+        if let Some(table_id) = self.catalog.get_table_id_if_exists(view_name) {
+            if self.is_matview(table_id) {
+                return self.drop_matview(table_id, view_name);
+            }
+        }
+        match self.catalog.remove_view(view_name) {
+            Some(_) => Ok(QueryResult::MessageOnly(format!(
+                "View {} dropped",
+                view_name
+            ))),
+            None if if_exists => Ok(QueryResult::MessageOnly(format!(
+                "View {} does not exist, skipping",
+                view_name
+            ))),
+            None => Err(CrustyError::CrustyError(format!(
+                "View {} does not exist",
+                view_name
+            ))),
+        }
+    }
+
+    /// Creates a materialized view, stored in its own container. The view is then
+    /// maintained incrementally: the changes to its tables are logged, and propagated
+    /// to the view when it is read.
+    ///
+    /// # Arguments
+    ///
+    /// * `view_name` - Name of the new view.
+    /// * `columns` - Column names of the view, empty to use the names from the query.
+    /// * `query` - Query defining the view.
+    /// * `tid` - Transaction reading the tables to fill the view.
+    pub fn create_matview(
+        &self,
+        view_name: &str,
+        columns: Vec<String>,
+        query: &sqlparser::ast::Query,
+        tid: TransactionId,
+    ) -> Result<QueryResult, CrustyError> {
+        if self.catalog.is_valid_table_from_string(view_name)
+            || self.catalog.get_view(view_name).is_some()
+        {
+            return Err(CrustyError::CrustyError(format!(
+                "{} already exists",
+                view_name
+            )));
+        }
+        let view_info = ViewInfo::new(view_name.to_string(), columns, query.to_string());
+        let enabled_rules = Arc::new(Rules::default());
+        let (plan, names) =
+            Translator::translate_view(&view_info, &self.catalog, &enabled_rules, &self.col_id_gen)
+                .map_err(|e| CrustyError::CrustyError(format!("{}", e)))?;
+        let mut plan = DeltaPlan::new(&plan, &self.catalog)?;
+
+        // Each column of the view needs a unique name to be stored as a table
+        let item_names = get_select_item_names(query);
+        let mut attributes: Vec<Attribute> = Vec::new();
+        for (i, attr) in plan.schema().attributes().enumerate() {
+            let name = match (
+                item_names.as_ref().and_then(|n| n[i].clone()),
+                &names[i][..],
+            ) {
+                _ if !view_info.columns.is_empty() => view_info.columns[i].clone(),
+                (Some(name), _) => name,
+                (None, [name]) => name.clone(),
+                _ => {
+                    return Err(CrustyError::CrustyError(format!(
+                        "Column {} of materialized view {} needs a name",
+                        i + 1,
+                        view_name
+                    )))
+                }
+            };
+            if attributes.iter().any(|a| a.name == name) {
+                return Err(CrustyError::CrustyError(format!(
+                    "Column {} appears more than once in materialized view {}",
+                    name, view_name
+                )));
+            }
+            attributes.push(Attribute::new(name, attr.dtype.clone()));
+        }
+        let schema = TableSchema::new(attributes);
+        let dependencies = plan.dependencies().to_vec();
+
+        // The content of the tables is the first change to the view
+        let last_update = self.get_current_time();
+        let mut deltas = HashMap::new();
+        for dependency in &dependencies {
+            let mut delta = ZSet::new();
+            for tuple in self.scan_table(*dependency, tid)? {
+                zset_add(&mut delta, tuple.field_vals, 1);
+            }
+            deltas.insert(*dependency, delta);
+        }
+        let delta = plan.apply(&deltas)?;
+
+        let table_id = self.catalog.get_table_id(view_name);
+        self.managers.sm.create_container(
+            table_id,
+            Some(view_name.to_string()),
+            StateType::MatView,
+            None,
+        )?;
+        self.catalog.add_table(TableInfo::new(
+            table_id,
+            view_name.to_string(),
+            schema.clone(),
+        ));
+        self.managers.stats.register_table(table_id, schema)?;
+        let mut matview = MatView {
+            definition: view_info,
+            plan,
+            rows: HashMap::new(),
+        };
+        self.apply_matview_delta(table_id, &mut matview, delta, tid)?;
+        self.matviews.write().unwrap().insert(table_id, matview);
+        self.container_vec.write().unwrap().insert(
+            table_id,
+            StateMeta {
+                state_type: StateType::MatView,
+                id: table_id,
+                name: Some(view_name.to_string()),
+                last_update: Some(last_update),
+                dependencies: Some(dependencies),
+            },
+        );
+
+        Ok(QueryResult::MessageOnly(format!(
+            "Materialized view {} created",
+            view_name
+        )))
+    }
+
+    /// Rebuilds the maintenance state of the materialized views of a loaded database.
+    /// The changes logged before the shutdown are lost, so each view is brought up to
+    /// date by comparing its stored rows with the result of its query.
+    ///
+    /// # Arguments
+    ///
+    /// * `definitions` - Definitions of the materialized views, by container.
+    fn rebuild_matviews(
+        &self,
+        mut definitions: HashMap<ContainerId, ViewInfo>,
+    ) -> Result<(), CrustyError> {
+        let tid = TransactionId::new();
+        let mut table_ids: Vec<ContainerId> = definitions.keys().cloned().collect();
+        table_ids.sort();
+        for table_id in table_ids {
+            self.rebuild_matview(table_id, &mut definitions, tid)?;
+        }
+        self.prune_delta_log();
+        Ok(())
+    }
+
+    /// Rebuilds a materialized view, after the ones it reads. Does nothing if the view
+    /// is already rebuilt.
+    fn rebuild_matview(
+        &self,
+        table_id: ContainerId,
+        definitions: &mut HashMap<ContainerId, ViewInfo>,
+        tid: TransactionId,
+    ) -> Result<(), CrustyError> {
+        let definition = match definitions.remove(&table_id) {
+            Some(definition) => definition,
+            None => return Ok(()),
+        };
+        let enabled_rules = Arc::new(Rules::default());
+        let (plan, _) = Translator::translate_view(
+            &definition,
+            &self.catalog,
+            &enabled_rules,
+            &self.col_id_gen,
+        )
+        .map_err(|e| CrustyError::CrustyError(format!("{}", e)))?;
+        let mut plan = DeltaPlan::new(&plan, &self.catalog)?;
+        let mut deltas = HashMap::new();
+        for dependency in plan.dependencies().to_vec() {
+            self.rebuild_matview(dependency, definitions, tid)?;
+            let mut delta = ZSet::new();
+            for tuple in self.scan_table(dependency, tid)? {
+                zset_add(&mut delta, tuple.field_vals, 1);
+            }
+            deltas.insert(dependency, delta);
+        }
+        // The difference between the result of the query and the stored rows
+        let mut delta = plan.apply(&deltas)?;
+        let mut rows: HashMap<Vec<Field>, Vec<ValueId>> = HashMap::new();
+        for tuple in self.scan_table(table_id, tid)? {
+            zset_add(&mut delta, tuple.field_vals.clone(), -1);
+            if let Some(id) = tuple.value_id {
+                rows.entry(tuple.field_vals).or_default().push(id);
+            }
+        }
+        let mut matview = MatView {
+            definition,
+            plan,
+            rows,
+        };
+        self.apply_matview_delta(table_id, &mut matview, delta, tid)?;
+        self.matviews.write().unwrap().insert(table_id, matview);
+        // The logical time restarts on load
+        if let Some(meta) = self.container_vec.write().unwrap().get_mut(&table_id) {
+            meta.last_update = Some(self.get_current_time());
+        }
+        Ok(())
+    }
+
+    /// Drops a materialized view.
+    fn drop_matview(
+        &self,
+        table_id: ContainerId,
+        view_name: &str,
+    ) -> Result<QueryResult, CrustyError> {
+        self.check_no_dependent_matviews(table_id, view_name)?;
+        self.query_registrar.purge_queries_with_table(table_id)?;
+        self.managers.sm.remove_container(table_id)?;
+        self.catalog.remove_table(table_id);
+        self.managers.stats.deregister_table(table_id)?;
+        self.container_vec.write().unwrap().remove(&table_id);
+        self.matviews.write().unwrap().remove(&table_id);
+        self.delta_log.write().unwrap().remove(&table_id);
+        self.prune_delta_log();
+
+        Ok(QueryResult::MessageOnly(format!(
+            "Materialized view {} dropped",
+            view_name
+        )))
+    }
+
+    /// Returns true if the container stores a materialized view.
+    pub fn is_matview(&self, table_id: ContainerId) -> bool {
+        self.container_vec
+            .read()
+            .unwrap()
+            .get(&table_id)
+            .is_some_and(|meta| meta.state_type == StateType::MatView)
+    }
+
+    /// Fails if the container stores a materialized view, which cannot be modified directly.
+    pub fn check_not_matview(&self, table_id: ContainerId, name: &str) -> Result<(), CrustyError> {
+        if self.is_matview(table_id) {
+            return Err(CrustyError::CrustyError(format!(
+                "Cannot modify materialized view {}",
+                name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Names of the materialized views reading the table.
+    fn dependent_matviews(&self, table_id: ContainerId) -> Vec<String> {
+        self.container_vec
+            .read()
+            .unwrap()
+            .values()
+            .filter(|meta| {
+                meta.state_type == StateType::MatView
+                    && meta
+                        .dependencies
+                        .as_ref()
+                        .is_some_and(|deps| deps.contains(&table_id))
+            })
+            .map(|meta| meta.name.clone().unwrap_or_default())
+            .collect()
+    }
+
+    /// Returns true if a materialized view reads the table, in which case the changes
+    /// to the table must be logged with `log_changes`.
+    pub fn has_dependent_matviews(&self, table_id: ContainerId) -> bool {
+        !self.dependent_matviews(table_id).is_empty()
+    }
+
+    fn check_no_dependent_matviews(
+        &self,
+        table_id: ContainerId,
+        name: &str,
+    ) -> Result<(), CrustyError> {
+        match self.dependent_matviews(table_id).first() {
+            Some(view_name) => Err(CrustyError::CrustyError(format!(
+                "{} is used by materialized view {}",
+                name, view_name
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Records the changes made to a table by a statement, at a new logical time.
+    /// The changes are kept only if a materialized view reads the table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table_id` - Changed table.
+    /// * `inserted` - Inserted tuples.
+    /// * `deleted` - Deleted tuples.
+    pub fn log_changes(&self, table_id: ContainerId, inserted: &[Tuple], deleted: &[Tuple]) {
+        let mut delta = ZSet::new();
+        for tuple in inserted {
+            zset_add(&mut delta, tuple.field_vals.clone(), 1);
+        }
+        for tuple in deleted {
+            zset_add(&mut delta, tuple.field_vals.clone(), -1);
+        }
+        self.log_delta(table_id, delta);
+    }
+
+    fn log_delta(&self, table_id: ContainerId, delta: ZSet) {
+        if delta.is_empty() || !self.has_dependent_matviews(table_id) {
+            self.atomic_time
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            return;
+        }
+        // The time is taken under the lock: a refresh reading the time under the lock
+        // sees every change logged up to that time
+        let mut delta_log = self.delta_log.write().unwrap();
+        let ts = self
+            .atomic_time
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+            + 1;
+        delta_log.entry(table_id).or_default().push((ts, delta));
+    }
+
+    /// Brings the materialized views among the given containers up to date.
+    ///
+    /// # Arguments
+    ///
+    /// * `table_ids` - Containers read by a query.
+    /// * `tid` - Transaction of the query.
+    pub fn refresh_matviews(
+        &self,
+        table_ids: &[ContainerId],
+        tid: TransactionId,
+    ) -> Result<(), CrustyError> {
+        for table_id in table_ids {
+            if self.is_matview(*table_id) {
+                self.refresh_matview(*table_id, tid)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Propagates the changes logged since the last update of the materialized view.
+    fn refresh_matview(
+        &self,
+        table_id: ContainerId,
+        tid: TransactionId,
+    ) -> Result<(), CrustyError> {
+        let (dependencies, last_update) = match self.container_vec.read().unwrap().get(&table_id) {
+            Some(meta) => (
+                meta.dependencies.clone().unwrap_or_default(),
+                meta.last_update.unwrap_or_default(),
+            ),
+            None => return Ok(()),
+        };
+        // A materialized view reading another one sees its changes once it is refreshed
+        for dependency in &dependencies {
+            if self.is_matview(*dependency) {
+                self.refresh_matview(*dependency, tid)?;
+            }
+        }
+        let mut deltas = HashMap::new();
+        let now = {
+            let delta_log = self.delta_log.read().unwrap();
+            let now = self.get_current_time();
+            if now == last_update {
+                return Ok(());
+            }
+            for dependency in &dependencies {
+                let mut delta = ZSet::new();
+                for (ts, changes) in delta_log.get(dependency).into_iter().flatten() {
+                    if *ts > last_update && *ts <= now {
+                        zset_merge(&mut delta, changes);
+                    }
+                }
+                deltas.insert(*dependency, delta);
+            }
+            now
+        };
+        {
+            let mut matviews = self.matviews.write().unwrap();
+            let matview = matviews.get_mut(&table_id).ok_or_else(|| {
+                CrustyError::CrustyError(format!(
+                    "Materialized view {} cannot be maintained",
+                    table_id
+                ))
+            })?;
+            let delta = matview.plan.apply(&deltas)?;
+            self.apply_matview_delta(table_id, matview, delta, tid)?;
+        }
+        if let Some(meta) = self.container_vec.write().unwrap().get_mut(&table_id) {
+            meta.last_update = Some(now);
+        }
+        self.prune_delta_log();
+        Ok(())
+    }
+
+    /// Writes the changes to the rows of a materialized view to its container.
+    fn apply_matview_delta(
+        &self,
+        table_id: ContainerId,
+        matview: &mut MatView,
+        delta: ZSet,
+        tid: TransactionId,
+    ) -> Result<(), CrustyError> {
+        for (row, weight) in &delta {
+            let tuple = Tuple::new(row.clone());
+            if *weight > 0 {
+                for _ in 0..*weight {
+                    let id = self
+                        .managers
+                        .sm
+                        .insert_value(table_id, tuple.to_bytes(), tid);
+                    self.managers.stats.new_record(&tuple, id)?;
+                    matview.rows.entry(row.clone()).or_default().push(id);
+                }
+            } else {
+                let ids = matview.rows.entry(row.clone()).or_default();
+                for _ in 0..-*weight {
+                    let id = ids.pop().ok_or_else(|| {
+                        CrustyError::CrustyError(format!(
+                            "Row {:?} not found in materialized view {}",
+                            row, table_id
+                        ))
+                    })?;
+                    self.managers.sm.delete_value(id, tid)?;
+                    self.managers.stats.deleted_record(&id)?;
+                }
+                if ids.is_empty() {
+                    matview.rows.remove(row);
+                }
+            }
+        }
+        self.managers.stats.set_ts(table_id, tid.id());
+        // Materialized views reading this one
+        self.log_delta(table_id, delta);
+        Ok(())
+    }
+
+    /// Removes the logged changes already propagated to every materialized view.
+    fn prune_delta_log(&self) {
+        let container_vec = self.container_vec.read().unwrap();
+        let mut delta_log = self.delta_log.write().unwrap();
+        delta_log.retain(|table_id, entries| {
+            let oldest_update = container_vec
+                .values()
+                .filter(|meta| {
+                    meta.dependencies
+                        .as_ref()
+                        .is_some_and(|deps| deps.contains(table_id))
+                })
+                .map(|meta| meta.last_update.unwrap_or_default())
+                .min();
+            match oldest_update {
+                Some(oldest_update) => {
+                    entries.retain(|(ts, _)| *ts > oldest_update);
+                    !entries.is_empty()
+                }
+                None => false,
+            }
+        });
+    }
+
+    /// Reads every tuple of a table.
+    fn scan_table(
+        &self,
+        table_id: ContainerId,
+        tid: TransactionId,
+    ) -> Result<Vec<Tuple>, CrustyError> {
+        let mut scan = table_scan_to_op_iterator::<PhysicalRelExpr>(
+            self.managers,
+            &self.catalog,
+            &table_id,
+            None,
+            tid,
+        )?;
+        let mut tuples = Vec::new();
+        scan.configure(false);
+        scan.open()?;
+        while let Some(tuple) = scan.next()? {
+            tuples.push(tuple);
+        }
+        scan.close()?;
+        Ok(tuples)
+    }
+
+    /// Removes all records from a table, keeping the table itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `table_name` - Name of the table to truncate.
+    /// * `tid` - Transaction truncating the table.
+    pub fn truncate_table(
+        &self,
+        table_name: &str,
+        tid: TransactionId,
+    ) -> Result<QueryResult, CrustyError> {
+        let table_id = self
+            .catalog
+            .get_table_id_if_exists(table_name)
+            .filter(|table_id| self.catalog.is_valid_table(*table_id))
+            .ok_or_else(|| {
+                CrustyError::CrustyError(format!("Table {} does not exist", table_name))
+            })?;
+        self.check_not_matview(table_id, table_name)?;
+        let schema = self.catalog.get_table_schema(table_id).unwrap();
+        let deleted = if self.has_dependent_matviews(table_id) {
+            self.scan_table(table_id, tid)?
+        } else {
+            Vec::new()
+        };
+        self.query_registrar.purge_queries_with_table(table_id)?;
+        self.managers.sm.remove_container(table_id)?;
+        self.managers.sm.create_container(
+            table_id,
+            Some(table_name.to_string()),
+            common::ids::StateType::BaseTable,
+            None,
+        )?;
+        self.managers.stats.deregister_table(table_id)?;
+        self.managers.stats.register_table(table_id, schema)?;
+        self.managers.stats.set_ts(table_id, tid.id());
+        self.log_changes(table_id, &[], &deleted);
+
+        Ok(QueryResult::MessageOnly(format!(
+            "Table {} truncated",
+            table_name
+        )))
+    }
+
     pub fn reset(&self) -> Result<(), CrustyError> {
         self.query_registrar.reset()?;
         // get rid of persisted query registrar info and reset
@@ -391,4 +1097,40 @@ impl DatabaseState {
         let map = self.client_tids.read().unwrap();
         map.get(&client_id).copied()
     }
+
+    // Statements prepared by the client, created on first use
+    pub fn get_prepared_statements(&self, client_id: u64) -> PreparedStatements {
+        let mut map = self.client_prepared.write().unwrap();
+        map.entry(client_id).or_default().clone()
+    }
+
+    // Drop the statements prepared by the client (e.g., when it disconnects)
+    pub fn remove_prepared_statements(&self, client_id: u64) {
+        let mut map = self.client_prepared.write().unwrap();
+        map.remove(&client_id);
+    }
+}
+
+/// Names of the columns of a query given by its SELECT list: the alias of an
+/// expression or the name of a column. None if the query is not a simple SELECT or
+/// has a wildcard.
+fn get_select_item_names(query: &sqlparser::ast::Query) -> Option<Vec<Option<String>>> {
+    use sqlparser::ast::{SelectItem, SetExpr};
+    let select = match query.body.as_ref() {
+        SetExpr::Select(select) => select,
+        _ => return None,
+    };
+    select
+        .projection
+        .iter()
+        .map(|item| match item {
+            SelectItem::ExprWithAlias { alias, .. } => Some(Some(alias.value.clone())),
+            SelectItem::UnnamedExpr(Expr::Identifier(ident)) => Some(Some(ident.value.clone())),
+            SelectItem::UnnamedExpr(Expr::CompoundIdentifier(idents)) => {
+                Some(idents.last().map(|ident| ident.value.clone()))
+            }
+            SelectItem::UnnamedExpr(_) => Some(None),
+            SelectItem::Wildcard(_) | SelectItem::QualifiedWildcard(..) => None,
+        })
+        .collect()
 }
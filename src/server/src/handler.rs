@@ -154,10 +154,11 @@ pub fn run_database_command(
     match database_command {
         DBCommand::ExecuteSQL => {
             let sql = command_args.first().expect("SQL not provided").to_string();
-            let mut conductor = Conductor::new_from_tid(db.managers, tid)?;
-            let qr = if let Some(query_result) = db.query_result_from_sql(&sql)? {
+            let mut conductor = Conductor::new_from_tid(db.managers, tid)?
+                .with_prepared_statements(db.get_prepared_statements(client_id));
+            let mut results = if let Some(query_result) = db.query_result_from_sql(&sql)? {
                 info!("Fetched registered query result");
-                query_result
+                vec![query_result]
             } else {
                 conductor.run_sql_from_string(sql, db)?
             };
@@ -167,7 +168,12 @@ pub fn run_database_command(
             // ask Kathir ab this
             let _new_tid = db.assign_new_tid(client_id);
 
-            Ok((false, Response::QueryResult(qr)))
+            let response = if results.len() == 1 {
+                Response::QueryResult(results.pop().unwrap())
+            } else {
+                Response::QueryResults(results)
+            };
+            Ok((false, response))
         }
         DBCommand::ShowTables => {
             let tables = db.get_table_names()?;
@@ -219,7 +225,7 @@ pub fn run_database_command(
                         ),
                     ));
                 }
-                None => conductor.run_sql_from_string(query.clone(), db)?,
+                None => conductor.run_statement_from_string(query.clone(), db)?,
             };
 
             let lp = conductor.to_logical_plan(query, db)?;
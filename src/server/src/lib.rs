@@ -7,6 +7,7 @@ mod conductor;
 mod daemon;
 mod database_state;
 mod handler;
+mod prepared;
 mod server;
 mod server_state;
 mod sql_parser;
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use common::catalog::CatalogRef;
+use common::ids::ContainerId;
+use common::logical_expr::prelude::LogicalRelExpr;
+use common::physical_expr::physical_rel_expr::PhysicalRelExpr;
+use common::query::expr::Expression;
+use common::{CrustyError, DataType, Field, TableSchema};
+use queryexe::query::params::ParamTypes;
+
+/// Statements prepared by a session, by name.
+pub type PreparedStatements = Arc<RwLock<HashMap<String, PreparedStatement>>>;
+
+/// Statement prepared by PREPARE: parsed and planned once, then run by EXECUTE with
+/// the values of its parameters.
+pub struct PreparedStatement {
+    pub plan: StatementPlan,
+    pub param_types: ParamTypes,
+    /// Tables read or changed by the plan, with their schema when the statement was
+    /// prepared. The plan is only valid while they are unchanged.
+    pub tables: Vec<(ContainerId, TableSchema)>,
+}
+
+impl PreparedStatement {
+    /// Returns an error if a table used by the plan was dropped or recreated since the
+    /// statement was prepared.
+    pub fn check_tables(&self, name: &str, catalog: &CatalogRef) -> Result<(), CrustyError> {
+        for (table_id, schema) in &self.tables {
+            if catalog.get_table_schema(*table_id).as_ref() != Some(schema) {
+                return Err(CrustyError::CrustyError(format!(
+                    "A table used by prepared statement {} was dropped or changed, prepare it again",
+                    name
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Plan of a statement whose expressions may hold parameters.
+#[derive(Clone)]
+pub enum StatementPlan {
+    Query(PhysicalRelExpr),
+    Insert(InsertPlan),
+    Delete(DeletePlan),
+    Update(UpdatePlan),
+}
+
+impl StatementPlan {
+    /// Replace the parameters of the plan with the given values.
+    pub fn bind_params(self, params: &[Field]) -> StatementPlan {
+        let bind = |expr: Expression<LogicalRelExpr>| expr.bind_params(params);
+        match self {
+            StatementPlan::Query(plan) => StatementPlan::Query(plan.bind_params(params)),
+            StatementPlan::Insert(plan) => StatementPlan::Insert(InsertPlan {
+                rows: plan
+                    .rows
+                    .into_iter()
+                    .map(|row| row.into_iter().map(bind).collect())
+                    .collect(),
+                ..plan
+            }),
+            StatementPlan::Delete(plan) => StatementPlan::Delete(DeletePlan {
+                predicate: plan.predicate.map(bind),
+                ..plan
+            }),
+            StatementPlan::Update(plan) => StatementPlan::Update(UpdatePlan {
+                assignments: plan
+                    .assignments
+                    .into_iter()
+                    .map(|(idx, expr)| (idx, bind(expr)))
+                    .collect(),
+                predicate: plan.predicate.map(bind),
+                ..plan
+            }),
+        }
+    }
+
+    /// Tables read or changed by the plan.
+    pub fn tables_involved(&self) -> Vec<ContainerId> {
+        match self {
+            StatementPlan::Query(plan) => {
+                let mut tables = Vec::new();
+                plan.get_tables_involved(&mut tables);
+                tables.sort();
+                tables.dedup();
+                tables
+            }
+            StatementPlan::Insert(InsertPlan { table_id, .. })
+            | StatementPlan::Delete(DeletePlan { table_id, .. })
+            | StatementPlan::Update(UpdatePlan { table_id, .. }) => vec![*table_id],
+        }
+    }
+
+    /// Infers the types of the parameters used by the plan.
+    pub fn infer_param_types(&self, param_types: &mut ParamTypes, catalog: &CatalogRef) {
+        match self {
+            StatementPlan::Query(plan) => param_types.infer_plan(plan, catalog),
+            StatementPlan::Insert(plan) => {
+                for row in &plan.rows {
+                    for (i, expr) in row.iter().enumerate() {
+                        if let Expression::Param { idx } = expr {
+                            let col = plan.col_order.as_ref().map_or(i, |order| order[i]);
+                            let dtype = plan
+                                .table_schema
+                                .get_attribute(col)
+                                .map(|a| a.dtype.clone());
+                            param_types.set(*idx, dtype);
+                        }
+                    }
+                }
+            }
+            StatementPlan::Delete(plan) => {
+                if let Some(predicate) = &plan.predicate {
+                    param_types.infer_predicate(predicate, &column_types(&plan.table_schema));
+                }
+            }
+            StatementPlan::Update(plan) => {
+                let col_types = column_types(&plan.table_schema);
+                for (idx, expr) in &plan.assignments {
+                    if let Expression::Param { idx: param } = expr {
+                        param_types.set(*param, col_types.get(idx).cloned());
+                    }
+                    param_types.infer_expr(expr, &col_types);
+                }
+                if let Some(predicate) = &plan.predicate {
+                    param_types.infer_predicate(predicate, &col_types);
+                }
+            }
+        }
+    }
+}
+
+/// Types of the columns of a table, by column index. Expressions over a single table
+/// refer to its columns by index.
+fn column_types(schema: &TableSchema) -> HashMap<usize, DataType> {
+    schema
+        .attributes()
+        .enumerate()
+        .map(|(i, attr)| (i, attr.dtype.clone()))
+        .collect()
+}
+
+/// INSERT INTO <table> VALUES ...
+#[derive(Clone)]
+pub struct InsertPlan {
+    pub table_name: String,
+    pub table_id: ContainerId,
+    pub table_schema: TableSchema,
+    /// Schema index of each inserted value. None if the rows cover every column in order.
+    pub col_order: Option<Vec<usize>>,
+    /// Inserted rows, made of literals and parameters.
+    pub rows: Vec<Vec<Expression<LogicalRelExpr>>>,
+}
+
+/// DELETE FROM <table> [WHERE <predicate>]
+#[derive(Clone)]
+pub struct DeletePlan {
+    pub table_name: String,
+    pub table_id: ContainerId,
+    pub table_schema: TableSchema,
+    pub predicate: Option<Expression<LogicalRelExpr>>,
+}
+
+/// UPDATE <table> SET <assignments> [WHERE <predicate>]
+#[derive(Clone)]
+pub struct UpdatePlan {
+    pub table_name: String,
+    pub table_id: ContainerId,
+    pub table_schema: TableSchema,
+    /// Schema index of each assigned column and its new value.
+    pub assignments: Vec<(usize, Expression<LogicalRelExpr>)>,
+    pub predicate: Option<Expression<LogicalRelExpr>>,
+}
@@ -374,6 +374,11 @@ impl QueryEngine {
     }
 
     pub fn run_sql(&mut self, sql: &str) -> Result<QueryResult, CrustyError> {
+        self.conductor
+            .run_statement_from_string(sql.to_string(), self.database_state)
+    }
+
+    pub fn run_script(&mut self, sql: &str) -> Result<Vec<QueryResult>, CrustyError> {
         self.conductor
             .run_sql_from_string(sql.to_string(), self.database_state)
     }
@@ -454,6 +459,7 @@ mod test {
 
     mod query_engine {
         use super::*;
+        use common::{Field, Tuple};
 
         #[test]
         fn test_run_sql() {
@@ -499,5 +505,1733 @@ mod test {
             };
             assert_eq!(t.len(), 5);
         }
+
+        #[test]
+        fn test_delete() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let sql = "CREATE TABLE foo (id INT PRIMARY KEY, name VARCHAR(10));";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO foo VALUES (1, 'a'), (2, 'b'), (3, 'c'), (4, 'd'), (5, 'e'), (6, 'f'), (7, 'g'), (8, 'h'), (9, 'i'), (10, 'j');";
+            query_engine.run_sql(sql).unwrap();
+            // Delete with some predicate
+            let sql = "DELETE FROM foo WHERE id > 5 AND name <> 'g';";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(result, QueryResult::new_delete_result(4, "foo".to_string()));
+            let sql = "SELECT * FROM foo;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(result.get_tuples().unwrap().len(), 6);
+            // Delete everything left
+            let sql = "DELETE FROM foo;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(result, QueryResult::new_delete_result(6, "foo".to_string()));
+            let sql = "SELECT * FROM foo;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert!(result.get_tuples().unwrap().is_empty());
+        }
+
+        #[test]
+        fn test_update() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let sql = "CREATE TABLE foo (id INT PRIMARY KEY, val INT, name VARCHAR(10));";
+            query_engine.run_sql(sql).unwrap();
+            let sql =
+                "INSERT INTO foo VALUES (1, 10, 'a'), (2, 20, 'b'), (3, 30, 'c'), (4, 40, 'd');";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "UPDATE foo SET val = val + id, name = 'x' WHERE id > 2;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(result, QueryResult::new_update_result(2, "foo".to_string()));
+            let sql = "SELECT * FROM foo WHERE name = 'x';";
+            let result = query_engine.run_sql(sql).unwrap();
+            let mut vals = result
+                .get_tuples()
+                .unwrap()
+                .iter()
+                .map(|t| t.get_field(1).unwrap().clone())
+                .collect::<Vec<_>>();
+            vals.sort();
+            assert_eq!(vals, vec![Field::BigInt(33), Field::BigInt(44)]);
+            // Every record is updated exactly once
+            let sql = "UPDATE foo SET val = val * 2;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(result, QueryResult::new_update_result(4, "foo".to_string()));
+            let sql = "SELECT * FROM foo WHERE val = 20 OR val = 88;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(result.get_tuples().unwrap().len(), 2);
+            // Type mismatch is rejected
+            let sql = "UPDATE foo SET val = 'y';";
+            assert!(query_engine.run_sql(sql).is_err());
+
+            // New values are stored with the type of their column
+            let sql = "CREATE TABLE bar (id INT PRIMARY KEY, d DECIMAL(10, 2), e DECIMAL(10, 3));";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO bar VALUES (1, 1.5, 2.125);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "UPDATE bar SET d = 5;";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "SELECT d, d + 1 FROM bar;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(
+                result.get_tuples().unwrap()[0].field_vals,
+                vec![Field::Decimal(500, 2), Field::Decimal(600, 2)]
+            );
+            let sql = "UPDATE bar SET d = e;";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "SELECT d FROM bar;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(
+                result.get_tuples().unwrap()[0].field_vals,
+                vec![Field::Decimal(213, 2)]
+            );
+        }
+
+        #[test]
+        fn test_insert_with_columns() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let sql = "CREATE TABLE foo (id INT PRIMARY KEY, val INT DEFAULT 7, name VARCHAR(10) NOT NULL, note VARCHAR(10));";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO foo (name, id) VALUES ('a', 1), ('b', 2);";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(result, QueryResult::new_insert_result(2, "foo".to_string()));
+            let sql = "SELECT * FROM foo WHERE id = 2;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(
+                result.get_tuples().unwrap()[0].field_vals,
+                vec![
+                    Field::BigInt(2),
+                    Field::BigInt(7),
+                    Field::String("b".to_string()),
+                    Field::Null
+                ]
+            );
+            // Omitting a NOT NULL column without a default is rejected
+            let sql = "INSERT INTO foo (id, val) VALUES (3, 3);";
+            assert!(query_engine.run_sql(sql).is_err());
+            // Unknown and repeated columns are rejected
+            let sql = "INSERT INTO foo (id, bar) VALUES (3, 3);";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "INSERT INTO foo (id, id) VALUES (3, 3);";
+            assert!(query_engine.run_sql(sql).is_err());
+            // Arity must match the column list
+            let sql = "INSERT INTO foo (id, name) VALUES (3);";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "SELECT * FROM foo;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(result.get_tuples().unwrap().len(), 2);
+        }
+
+        #[test]
+        fn test_insert_select() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let sql = "CREATE TABLE foo (id INT PRIMARY KEY, val INT);";
+            query_engine.run_sql(sql).unwrap();
+            let sql =
+                "CREATE TABLE bar (id INT PRIMARY KEY, amount DECIMAL(10, 2), note VARCHAR(10));";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO foo VALUES (1, 10), (2, 20), (3, 30);";
+            query_engine.run_sql(sql).unwrap();
+
+            // Self insert reads the source before writing
+            let sql = "INSERT INTO foo SELECT id + 3, val FROM foo;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(result, QueryResult::new_insert_result(3, "foo".to_string()));
+            let sql = "SELECT * FROM foo;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(result.get_tuples().unwrap().len(), 6);
+
+            // Column list and coercion to the target types
+            let sql = "INSERT INTO bar (amount, id) SELECT val, id FROM foo WHERE id > 4;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(result, QueryResult::new_insert_result(2, "bar".to_string()));
+            let sql = "SELECT * FROM bar WHERE id = 5;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(
+                result.get_tuples().unwrap()[0].field_vals,
+                vec![Field::BigInt(5), Field::Decimal(2000, 2), Field::Null]
+            );
+
+            // Width mismatch is rejected
+            let sql = "INSERT INTO bar SELECT id, val FROM foo;";
+            assert!(query_engine.run_sql(sql).is_err());
+
+            // Values out of the range of the target type are rejected, not wrapped
+            let sql = "INSERT INTO foo VALUES (7, 9000000000000000000);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO bar (id, amount) SELECT id, val FROM foo WHERE id = 7;";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "SELECT * FROM bar WHERE id = 7;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert!(result.get_tuples().unwrap().is_empty());
+        }
+
+        #[test]
+        fn test_order_by() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let sql = "CREATE TABLE foo (id INT PRIMARY KEY, val INT);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO foo (id) VALUES (4), (1);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO foo VALUES (3, 10), (2, 20), (5, 10);";
+            query_engine.run_sql(sql).unwrap();
+
+            let ids = |result: QueryResult| -> Vec<Field> {
+                result
+                    .get_tuples()
+                    .unwrap()
+                    .iter()
+                    .map(|t| t.get_field(0).unwrap().clone())
+                    .collect()
+            };
+            let expected =
+                |v: &[i64]| -> Vec<Field> { v.iter().map(|i| Field::BigInt(*i)).collect() };
+
+            let sql = "SELECT id FROM foo ORDER BY id;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(ids(result), expected(&[1, 2, 3, 4, 5]));
+            let sql = "SELECT id AS x FROM foo ORDER BY x DESC;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(ids(result), expected(&[5, 4, 3, 2, 1]));
+
+            // NULLs sort last ascending and first descending unless specified
+            let sql = "SELECT id FROM foo ORDER BY val, id;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(ids(result), expected(&[3, 5, 2, 1, 4]));
+            let sql = "SELECT id FROM foo ORDER BY val DESC, id;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(ids(result), expected(&[1, 4, 2, 3, 5]));
+            let sql = "SELECT id, val FROM foo ORDER BY 2 NULLS FIRST, 1 DESC;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(ids(result), expected(&[4, 1, 5, 3, 2]));
+
+            // Expressions and columns that are not projected
+            let sql = "SELECT * FROM foo ORDER BY 0 - id;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(result.get_tuples().unwrap()[0].field_vals.len(), 2);
+            assert_eq!(ids(result), expected(&[5, 4, 3, 2, 1]));
+        }
+
+        #[test]
+        fn test_limit_offset() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let sql = "CREATE TABLE foo (id INT PRIMARY KEY, val INT);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO foo VALUES (3, 30), (1, 10), (5, 50), (2, 20), (4, 40);";
+            query_engine.run_sql(sql).unwrap();
+
+            let ids = |result: QueryResult| -> Vec<Field> {
+                result
+                    .get_tuples()
+                    .unwrap()
+                    .iter()
+                    .map(|t| t.get_field(0).unwrap().clone())
+                    .collect()
+            };
+            let expected =
+                |v: &[i64]| -> Vec<Field> { v.iter().map(|i| Field::BigInt(*i)).collect() };
+
+            let sql = "SELECT id FROM foo ORDER BY id LIMIT 2;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(ids(result), expected(&[1, 2]));
+            let sql = "SELECT id FROM foo ORDER BY id DESC LIMIT 2 OFFSET 1;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(ids(result), expected(&[4, 3]));
+            let sql = "SELECT id FROM foo ORDER BY id OFFSET 3;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(ids(result), expected(&[4, 5]));
+            let sql = "SELECT * FROM foo WHERE val > 20 LIMIT 0;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert!(result.get_tuples().unwrap().is_empty());
+            let sql = "SELECT * FROM foo LIMIT 10 OFFSET 10;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert!(result.get_tuples().unwrap().is_empty());
+            let sql = "SELECT * FROM foo LIMIT 10;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(result.get_tuples().unwrap().len(), 5);
+        }
+
+        #[test]
+        fn test_select_distinct() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let sql = "CREATE TABLE foo (id INT PRIMARY KEY, a INT, b INT);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO foo VALUES (1, 1, 10), (2, 2, 10), (3, 1, 10), (4, 1, 20);";
+            query_engine.run_sql(sql).unwrap();
+
+            let sql = "SELECT DISTINCT a FROM foo ORDER BY a;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(
+                *result.get_tuples().unwrap(),
+                vec![
+                    Tuple::new(vec![Field::BigInt(1)]),
+                    Tuple::new(vec![Field::BigInt(2)])
+                ]
+            );
+            let sql = "SELECT DISTINCT a, b FROM foo;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(result.get_tuples().unwrap().len(), 3);
+            let sql = "SELECT DISTINCT b FROM foo ORDER BY b DESC LIMIT 1;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(
+                *result.get_tuples().unwrap(),
+                vec![Tuple::new(vec![Field::BigInt(20)])]
+            );
+            let sql = "SELECT DISTINCT * FROM foo;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(result.get_tuples().unwrap().len(), 4);
+            // The sort keys must be in the select list
+            let sql = "SELECT DISTINCT a + 1 AS c FROM foo ORDER BY a + 1 DESC;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(
+                *result.get_tuples().unwrap(),
+                vec![
+                    Tuple::new(vec![Field::BigInt(3)]),
+                    Tuple::new(vec![Field::BigInt(2)])
+                ]
+            );
+            let sql = "SELECT DISTINCT a FROM foo ORDER BY b;";
+            assert!(query_engine.run_sql(sql).is_err());
+        }
+
+        #[test]
+        fn test_set_operations() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let sql = "CREATE TABLE foo (id INT PRIMARY KEY, val VARCHAR(10));";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "CREATE TABLE bar (id INT PRIMARY KEY, val VARCHAR(10));";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO foo VALUES (1, 'a'), (2, 'b'), (3, 'c');";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO bar VALUES (2, 'b'), (3, 'x'), (4, 'd');";
+            query_engine.run_sql(sql).unwrap();
+            let ids = |sql: &str, query_engine: &mut QueryEngine| {
+                let result = query_engine.run_sql(sql).unwrap();
+                let mut ids = result
+                    .get_tuples()
+                    .unwrap()
+                    .iter()
+                    .map(|t| t.get_field(0).unwrap().clone())
+                    .collect::<Vec<_>>();
+                ids.sort();
+                ids
+            };
+
+            let sql = "SELECT id FROM foo UNION SELECT id FROM bar;";
+            assert_eq!(
+                ids(sql, &mut query_engine),
+                (1..=4).map(Field::BigInt).collect::<Vec<_>>()
+            );
+            let sql = "SELECT id FROM foo UNION ALL SELECT id FROM bar;";
+            assert_eq!(ids(sql, &mut query_engine).len(), 6);
+            let sql = "SELECT id FROM foo INTERSECT SELECT id FROM bar;";
+            assert_eq!(
+                ids(sql, &mut query_engine),
+                vec![Field::BigInt(2), Field::BigInt(3)]
+            );
+            let sql = "SELECT id, val FROM foo EXCEPT SELECT id, val FROM bar;";
+            assert_eq!(
+                ids(sql, &mut query_engine),
+                vec![Field::BigInt(1), Field::BigInt(3)]
+            );
+
+            // ORDER BY and LIMIT apply to the result of the set operation
+            let sql =
+                "SELECT val, id FROM foo UNION SELECT val, id FROM bar ORDER BY val DESC LIMIT 2;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(
+                *result.get_tuples().unwrap(),
+                vec![
+                    Tuple::new(vec![Field::String("x".to_string()), Field::BigInt(3)]),
+                    Tuple::new(vec![Field::String("d".to_string()), Field::BigInt(4)])
+                ]
+            );
+
+            // Both sides must be union-compatible
+            let sql = "SELECT id FROM foo UNION SELECT val FROM bar;";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "SELECT id FROM foo UNION SELECT id, val FROM bar;";
+            assert!(query_engine.run_sql(sql).is_err());
+        }
+
+        #[test]
+        fn test_in_predicates() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let sql = "CREATE TABLE foo (id INT PRIMARY KEY, a INT);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "CREATE TABLE bar (id INT PRIMARY KEY, b INT);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO foo VALUES (1, 1), (2, 2), (3, 3), (4, NULL);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO bar VALUES (1, 2), (2, 3), (3, NULL);";
+            query_engine.run_sql(sql).unwrap();
+            let ids = |sql: &str, query_engine: &mut QueryEngine| {
+                let result = query_engine.run_sql(sql).unwrap();
+                let mut ids = result
+                    .get_tuples()
+                    .unwrap()
+                    .iter()
+                    .map(|t| t.get_field(0).unwrap().clone())
+                    .collect::<Vec<_>>();
+                ids.sort();
+                ids
+            };
+            let fields = |ids: &[i64]| ids.iter().map(|i| Field::BigInt(*i)).collect::<Vec<_>>();
+
+            let sql = "SELECT id FROM foo WHERE a IN (1, 3, 5);";
+            assert_eq!(ids(sql, &mut query_engine), fields(&[1, 3]));
+            let sql = "SELECT id FROM foo WHERE a NOT IN (1, 3);";
+            assert_eq!(ids(sql, &mut query_engine), fields(&[2]));
+            // NOT IN with a NULL in the list is never true
+            let sql = "SELECT id FROM foo WHERE a NOT IN (1, NULL);";
+            assert_eq!(ids(sql, &mut query_engine), fields(&[]));
+
+            let sql = "SELECT id FROM foo WHERE a IN (SELECT b FROM bar);";
+            assert_eq!(ids(sql, &mut query_engine), fields(&[2, 3]));
+            let sql = "SELECT id FROM foo WHERE id > 2 AND a IN (SELECT b FROM bar);";
+            assert_eq!(ids(sql, &mut query_engine), fields(&[3]));
+            let sql = "SELECT id FROM foo WHERE a NOT IN (SELECT b FROM bar WHERE id < 3);";
+            assert_eq!(ids(sql, &mut query_engine), fields(&[1]));
+            // The subquery returns a NULL, so NOT IN is never true
+            let sql = "SELECT id FROM foo WHERE a NOT IN (SELECT b FROM bar);";
+            assert_eq!(ids(sql, &mut query_engine), fields(&[]));
+            // NOT IN an empty subquery is true, even for NULL
+            let sql = "SELECT id FROM foo WHERE a NOT IN (SELECT b FROM bar WHERE id > 10);";
+            assert_eq!(ids(sql, &mut query_engine), fields(&[1, 2, 3, 4]));
+
+            let sql = "SELECT id FROM foo WHERE a IN (SELECT id, b FROM bar);";
+            assert!(query_engine.run_sql(sql).is_err());
+
+            // IN in the SELECT list
+            let sql = "SELECT id, a IN (1, 2) FROM foo;";
+            let result = query_engine.run_sql(sql).unwrap();
+            let mut rows = result
+                .get_tuples()
+                .unwrap()
+                .iter()
+                .map(|t| t.field_vals.clone())
+                .collect::<Vec<_>>();
+            rows.sort();
+            assert_eq!(
+                rows,
+                vec![
+                    vec![Field::BigInt(1), Field::Bool(true)],
+                    vec![Field::BigInt(2), Field::Bool(true)],
+                    vec![Field::BigInt(3), Field::Bool(false)],
+                    vec![Field::BigInt(4), Field::Null],
+                ]
+            );
+            // IN subqueries are only supported in WHERE, but are rejected without panicking
+            let sql = "SELECT id, a IN (SELECT b FROM bar) FROM foo;";
+            assert!(query_engine.run_sql(sql).is_err());
+        }
+
+        #[test]
+        fn test_scalar_subqueries() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let sql = "CREATE TABLE foo (id INT PRIMARY KEY, a INT);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "CREATE TABLE bar (id INT PRIMARY KEY, b INT);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO foo VALUES (1, 1), (2, 2), (3, 3), (4, 4);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO bar VALUES (1, 2), (2, 3), (3, NULL);";
+            query_engine.run_sql(sql).unwrap();
+            let rows = |sql: &str, query_engine: &mut QueryEngine| {
+                let result = query_engine.run_sql(sql).unwrap();
+                let mut rows = result
+                    .get_tuples()
+                    .unwrap()
+                    .iter()
+                    .map(|t| t.field_vals.clone())
+                    .collect::<Vec<_>>();
+                rows.sort();
+                rows
+            };
+
+            let sql = "SELECT id FROM foo WHERE a > (SELECT b FROM bar WHERE id = 1);";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![vec![Field::BigInt(3)], vec![Field::BigInt(4)]]
+            );
+            let sql = "SELECT id, (SELECT b FROM bar WHERE id = 2) FROM foo WHERE id = 1;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![vec![Field::BigInt(1), Field::BigInt(3)]]
+            );
+            // An empty subquery gives NULL
+            let sql = "SELECT id, (SELECT b FROM bar WHERE id > 10) FROM foo WHERE id = 1;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![vec![Field::BigInt(1), Field::Null]]
+            );
+            // Correlated subquery, evaluated for each row of foo
+            let sql = "SELECT id, (SELECT b FROM bar WHERE bar.id = foo.id) FROM foo;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![
+                    vec![Field::BigInt(1), Field::BigInt(2)],
+                    vec![Field::BigInt(2), Field::BigInt(3)],
+                    vec![Field::BigInt(3), Field::Null],
+                    vec![Field::BigInt(4), Field::Null],
+                ]
+            );
+
+            // More than one row is a runtime error
+            let sql = "SELECT id FROM foo WHERE a > (SELECT b FROM bar);";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "SELECT id FROM foo WHERE a > (SELECT id, b FROM bar WHERE id = 1);";
+            assert!(query_engine.run_sql(sql).is_err());
+        }
+
+        #[test]
+        fn test_views() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let sql = "CREATE TABLE foo (id INT PRIMARY KEY, a INT);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO foo VALUES (1, 10), (2, 20), (3, 30);";
+            query_engine.run_sql(sql).unwrap();
+            let rows = |sql: &str, query_engine: &mut QueryEngine| {
+                let result = query_engine.run_sql(sql).unwrap();
+                let mut rows = result
+                    .get_tuples()
+                    .unwrap()
+                    .iter()
+                    .map(|t| t.field_vals.clone())
+                    .collect::<Vec<_>>();
+                rows.sort();
+                rows
+            };
+
+            let sql = "CREATE VIEW big (k, v) AS SELECT id, a FROM foo WHERE a > 10;";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "SELECT k, big.v FROM big;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![
+                    vec![Field::BigInt(2), Field::BigInt(20)],
+                    vec![Field::BigInt(3), Field::BigInt(30)],
+                ]
+            );
+            // Views see the current content of their tables
+            let sql = "INSERT INTO foo VALUES (4, 40);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "CREATE VIEW bigger AS SELECT k FROM big WHERE v > 20;";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "SELECT k FROM bigger;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![vec![Field::BigInt(3)], vec![Field::BigInt(4)]]
+            );
+
+            // Names are shared between tables and views
+            let sql = "CREATE VIEW big AS SELECT id FROM foo;";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "CREATE VIEW foo AS SELECT id FROM foo;";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "CREATE TABLE big (id INT PRIMARY KEY);";
+            assert!(query_engine.run_sql(sql).is_err());
+            // Invalid definitions are rejected
+            let sql = "CREATE VIEW bad (x, y) AS SELECT id FROM foo;";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "CREATE OR REPLACE VIEW big AS SELECT k FROM bigger;";
+            assert!(query_engine.run_sql(sql).is_err());
+
+            let sql = "CREATE OR REPLACE VIEW big (k, v) AS SELECT id, a FROM foo WHERE a < 20;";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "SELECT k FROM big;";
+            assert_eq!(rows(sql, &mut query_engine), vec![vec![Field::BigInt(1)]]);
+
+            // Views on a dropped table can no longer be queried
+            let sql = "DROP TABLE foo;";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "SELECT k FROM bigger;";
+            assert!(query_engine.run_sql(sql).is_err());
+
+            let sql = "DROP VIEW bigger, big;";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "SELECT k FROM big;";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "DROP VIEW big;";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "DROP VIEW IF EXISTS big;";
+            query_engine.run_sql(sql).unwrap();
+        }
+
+        #[test]
+        fn test_explain() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let sql = "CREATE TABLE foo (id INT PRIMARY KEY, a INT);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO foo VALUES (1, 10), (2, 20), (3, 30), (4, 40);";
+            query_engine.run_sql(sql).unwrap();
+            let plan = |sql: &str, query_engine: &mut QueryEngine| {
+                let result = query_engine.run_sql(sql).unwrap();
+                result
+                    .get_tuples()
+                    .unwrap()
+                    .iter()
+                    .map(|t| match t.get_field(0).unwrap() {
+                        Field::String(line) => line.clone(),
+                        field => panic!("Unexpected field {:?}", field),
+                    })
+                    .collect::<Vec<_>>()
+            };
+            let sql = "EXPLAIN SELECT id, a + 1 AS b FROM foo WHERE a > 20 LIMIT 1;";
+            assert_eq!(
+                plan(sql, &mut query_engine),
+                vec![
+                    "-> limit(1, offset 0)  (rows=1)",
+                    "  -> project(foo.id, b)  (rows=2)",
+                    "    -> map(b <- foo.a + 1)  (rows=2)",
+                    "      -> project(foo.id, foo.a)  (rows=2)",
+                    "        -> select(foo.a > 20)  (rows=2)",
+                    "          -> rename(foo.id <- foo.id, foo.a <- foo.a)  (rows=4)",
+                    "            -> scan(foo, [foo.id, foo.a])  (rows=4)",
+                ]
+            );
+            let sql = "EXPLAIN (FORMAT JSON) SELECT id FROM foo WHERE a < 30;";
+            let lines = plan(sql, &mut query_engine);
+            assert_eq!(lines.len(), 1);
+            let json: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+            assert_eq!(json["operator"], "project");
+            assert_eq!(json["details"], "foo.id");
+            let select = &json["children"][0];
+            assert_eq!(select["operator"], "select");
+            assert_eq!(select["details"], "foo.a < 30");
+            assert_eq!(select["estimated_rows"], 2);
+            assert_eq!(select["children"][0]["children"][0]["operator"], "scan");
+
+            let sql = "EXPLAIN (FORMAT GRAPHVIZ) SELECT id FROM foo;";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "EXPLAIN INSERT INTO foo VALUES (5, 50);";
+            assert!(query_engine.run_sql(sql).is_err());
+        }
+
+        #[test]
+        fn test_explain_analyze() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let sql = "CREATE TABLE foo (id INT PRIMARY KEY, a INT);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO foo VALUES (1, 10), (2, 20), (3, 30), (4, 40);";
+            query_engine.run_sql(sql).unwrap();
+            let plan = |sql: &str, query_engine: &mut QueryEngine| {
+                let result = query_engine.run_sql(sql).unwrap();
+                result
+                    .get_tuples()
+                    .unwrap()
+                    .iter()
+                    .map(|t| match t.get_field(0).unwrap() {
+                        Field::String(line) => line.clone(),
+                        field => panic!("Unexpected field {:?}", field),
+                    })
+                    .collect::<Vec<_>>()
+            };
+            let sql = "EXPLAIN ANALYZE SELECT id FROM foo WHERE a > 15 ORDER BY id;";
+            let lines = plan(sql, &mut query_engine);
+            assert_eq!(lines.len(), 6);
+            assert!(lines[1].starts_with(
+                "  -> sort(foo.id asc)  (rows=3)  (actual rows=3 loops=1 next_calls=4 "
+            ));
+            assert!(lines[1].ends_with(" peak_state=3)"));
+            assert!(lines[2]
+                .starts_with("    -> select(foo.a > 15)  (rows=3)  (actual rows=3 loops=1 "));
+            assert!(lines[4]
+                .starts_with("        -> scan(foo, [foo.id, foo.a])  (rows=4)  (actual rows=4 "));
+            assert!(lines[5].starts_with("Execution time: "));
+
+            let sql = "EXPLAIN (ANALYZE, FORMAT JSON) SELECT DISTINCT a FROM foo, foo AS f2;";
+            let lines = plan(sql, &mut query_engine);
+            let json: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+            assert!(json["execution_ms"].is_number());
+            assert_eq!(json["operator"], "distinct");
+            assert_eq!(json["actual"]["rows"], 4);
+            assert_eq!(json["actual"]["peak_state_size"], 4);
+            let join = &json["children"][0]["children"][0];
+            assert_eq!(join["operator"], "cross_join");
+            assert_eq!(join["estimated_rows"], 16);
+            assert_eq!(join["actual"]["rows"], 16);
+            assert_eq!(join["actual"]["next_calls"], 17);
+
+            // The query is run, but its result is not returned
+            let sql = "EXPLAIN ANALYZE SELECT id FROM foo;";
+            let lines = plan(sql, &mut query_engine);
+            assert_eq!(lines.len(), 3);
+            assert!(lines[1].starts_with("  -> scan(foo, [foo.id])  (rows=4)  (actual rows=4 "));
+        }
+
+        #[test]
+        fn test_scripts() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let rows = |sql: &str, query_engine: &mut QueryEngine| {
+                let result = query_engine.run_sql(sql).unwrap();
+                let mut rows = result
+                    .get_tuples()
+                    .unwrap()
+                    .iter()
+                    .map(|t| t.field_vals.clone())
+                    .collect::<Vec<_>>();
+                rows.sort();
+                rows
+            };
+            let ints = |rows: &[&[i64]]| {
+                rows.iter()
+                    .map(|row| row.iter().map(|v| Field::BigInt(*v)).collect::<Vec<_>>())
+                    .collect::<Vec<_>>()
+            };
+
+            // One result per statement
+            let sql = "CREATE TABLE foo (id INT PRIMARY KEY, a INT);
+                INSERT INTO foo VALUES (1, 10), (2, 20);
+                SELECT id FROM foo WHERE a > 15;";
+            let results = query_engine.run_script(sql).unwrap();
+            assert_eq!(results.len(), 3);
+            assert_eq!(results[2].get_tuples().unwrap().len(), 1);
+            let sql = "CREATE MATERIALIZED VIEW big AS SELECT id FROM foo WHERE a > 15;";
+            query_engine.run_sql(sql).unwrap();
+            assert!(query_engine
+                .run_sql("SELECT id FROM foo; SELECT a FROM foo;")
+                .is_err());
+
+            // Without a transaction block, the statements before an error are kept
+            let sql = "INSERT INTO foo VALUES (3, 30);
+                INSERT INTO missing VALUES (1);
+                INSERT INTO foo VALUES (4, 40);";
+            let err = query_engine.run_script(sql).unwrap_err();
+            assert!(err.to_string().contains("Statement 2 failed"));
+            let expected = ints(&[&[1, 10], &[2, 20], &[3, 30]]);
+            assert_eq!(rows("SELECT id, a FROM foo;", &mut query_engine), expected);
+
+            // A failing transaction block is rolled back
+            let sql = "BEGIN;
+                INSERT INTO foo VALUES (4, 40);
+                UPDATE foo SET a = 5 WHERE id > 1;
+                DELETE FROM foo WHERE id = 1;
+                CREATE TABLE bar (b INT PRIMARY KEY);
+                INSERT INTO bar VALUES (1);
+                INSERT INTO missing VALUES (1);
+                COMMIT;";
+            let err = query_engine.run_script(sql).unwrap_err();
+            assert!(err.to_string().contains("Statement 7 failed"));
+            assert!(err.to_string().contains("rolled back"));
+            assert_eq!(rows("SELECT id, a FROM foo;", &mut query_engine), expected);
+            assert!(query_engine.run_sql("SELECT b FROM bar;").is_err());
+            assert_eq!(
+                rows("SELECT id FROM big;", &mut query_engine),
+                ints(&[&[2], &[3]])
+            );
+
+            // As are blocks ending with ROLLBACK or not closed
+            let sql = "BEGIN; DELETE FROM foo; ROLLBACK; SELECT id FROM foo;";
+            let results = query_engine.run_script(sql).unwrap();
+            assert_eq!(results[3].get_tuples().unwrap().len(), 3);
+            let sql = "BEGIN; DELETE FROM foo;";
+            assert!(query_engine.run_script(sql).is_err());
+            assert_eq!(rows("SELECT id, a FROM foo;", &mut query_engine), expected);
+
+            // A committed block is kept
+            let sql = "START TRANSACTION;
+                INSERT INTO foo VALUES (4, 40);
+                UPDATE foo SET a = 5 WHERE id = 2;
+                COMMIT;";
+            assert_eq!(query_engine.run_script(sql).unwrap().len(), 4);
+            assert_eq!(
+                rows("SELECT id, a FROM foo;", &mut query_engine),
+                ints(&[&[1, 10], &[2, 5], &[3, 30], &[4, 40]])
+            );
+            assert_eq!(
+                rows("SELECT id FROM big;", &mut query_engine),
+                ints(&[&[3], &[4]])
+            );
+
+            // Statements that cannot be undone are rejected in a block
+            let sql = "BEGIN; TRUNCATE TABLE foo; COMMIT;";
+            assert!(query_engine.run_script(sql).is_err());
+            let sql = "COMMIT;";
+            assert!(query_engine.run_script(sql).is_err());
+            assert_eq!(rows("SELECT id FROM foo;", &mut query_engine).len(), 4);
+        }
+
+        #[test]
+        fn test_null_logic() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let ids = |sql: &str, query_engine: &mut QueryEngine| {
+                let result = query_engine.run_sql(sql).unwrap();
+                let mut ids = result
+                    .get_tuples()
+                    .unwrap()
+                    .iter()
+                    .map(|t| t.get_field(0).unwrap().clone())
+                    .collect::<Vec<_>>();
+                ids.sort();
+                ids.into_iter()
+                    .map(|f| match f {
+                        Field::BigInt(i) => i,
+                        f => panic!("Unexpected field {:?}", f),
+                    })
+                    .collect::<Vec<_>>()
+            };
+            query_engine
+                .run_sql("CREATE TABLE foo (id INT PRIMARY KEY, a INT, b INT);")
+                .unwrap();
+            query_engine
+                .run_sql(
+                    "INSERT INTO foo VALUES (1, 1, 1), (2, 2, NULL), (3, NULL, NULL), (4, NULL, 4);",
+                )
+                .unwrap();
+
+            // Comparisons with NULL are unknown and select nothing
+            let sql = "SELECT id FROM foo WHERE a = b;";
+            assert_eq!(ids(sql, &mut query_engine), vec![1]);
+            let sql = "SELECT id FROM foo WHERE a <> b;";
+            assert!(ids(sql, &mut query_engine).is_empty());
+            let sql = "SELECT id FROM foo WHERE a + 1 > 1;";
+            assert_eq!(ids(sql, &mut query_engine), vec![1, 2]);
+
+            // AND and OR follow the SQL truth tables
+            let sql = "SELECT id FROM foo WHERE a = 2 OR b = 4;";
+            assert_eq!(ids(sql, &mut query_engine), vec![2, 4]);
+            let sql = "SELECT id FROM foo WHERE a = 1 AND b = 1;";
+            assert_eq!(ids(sql, &mut query_engine), vec![1]);
+            let sql = "SELECT id FROM foo WHERE (a = 1 OR b = 4) AND id > 0;";
+            assert_eq!(ids(sql, &mut query_engine), vec![1, 4]);
+
+            // IS [NOT] NULL
+            let sql = "SELECT id FROM foo WHERE a IS NULL;";
+            assert_eq!(ids(sql, &mut query_engine), vec![3, 4]);
+            let sql = "SELECT id FROM foo WHERE b IS NOT NULL AND a IS NULL;";
+            assert_eq!(ids(sql, &mut query_engine), vec![4]);
+
+            // IS [NOT] DISTINCT FROM treats NULL as a value
+            let sql = "SELECT id FROM foo WHERE a IS NOT DISTINCT FROM b;";
+            assert_eq!(ids(sql, &mut query_engine), vec![1, 3]);
+            let sql = "SELECT id FROM foo WHERE a IS DISTINCT FROM b;";
+            assert_eq!(ids(sql, &mut query_engine), vec![2, 4]);
+
+            // Predicates of DELETE and UPDATE
+            query_engine
+                .run_sql("UPDATE foo SET b = 0 WHERE b IS NULL;")
+                .unwrap();
+            let sql = "SELECT id FROM foo WHERE b = 0;";
+            assert_eq!(ids(sql, &mut query_engine), vec![2, 3]);
+            query_engine
+                .run_sql("DELETE FROM foo WHERE a > 1;")
+                .unwrap();
+            let sql = "SELECT id FROM foo;";
+            assert_eq!(ids(sql, &mut query_engine), vec![1, 3, 4]);
+        }
+
+        #[test]
+        fn test_unary_mod_between() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let ids = |sql: &str, query_engine: &mut QueryEngine| {
+                let result = query_engine.run_sql(sql).unwrap();
+                let mut ids = result
+                    .get_tuples()
+                    .unwrap()
+                    .iter()
+                    .map(|t| t.get_field(0).unwrap().clone())
+                    .collect::<Vec<_>>();
+                ids.sort();
+                ids.into_iter()
+                    .map(|f| match f {
+                        Field::BigInt(i) => i,
+                        f => panic!("Unexpected field {:?}", f),
+                    })
+                    .collect::<Vec<_>>()
+            };
+            query_engine
+                .run_sql("CREATE TABLE foo (id INT PRIMARY KEY, a INT);")
+                .unwrap();
+            query_engine
+                .run_sql("INSERT INTO foo VALUES (1, 1), (2, 5), (3, -4), (4, NULL), (5, 7);")
+                .unwrap();
+
+            // NOT of an unknown condition is unknown
+            let sql = "SELECT id FROM foo WHERE NOT a > 1;";
+            assert_eq!(ids(sql, &mut query_engine), vec![1, 3]);
+            let sql = "SELECT id FROM foo WHERE NOT (a > 1 OR id = 1);";
+            assert_eq!(ids(sql, &mut query_engine), vec![3]);
+
+            // Unary minus and modulo
+            let sql = "SELECT id FROM foo WHERE -a > 0;";
+            assert_eq!(ids(sql, &mut query_engine), vec![3]);
+            let sql = "SELECT id FROM foo WHERE a % 3 = 1;";
+            assert_eq!(ids(sql, &mut query_engine), vec![1, 5]);
+            let sql = "SELECT id FROM foo WHERE a % 3 = -1;";
+            assert_eq!(ids(sql, &mut query_engine), vec![3]);
+            let result = query_engine
+                .run_sql("SELECT -a, a % 3 FROM foo WHERE id = 2;")
+                .unwrap();
+            assert_eq!(
+                result.get_tuples().unwrap()[0].field_vals,
+                vec![Field::BigInt(-5), Field::BigInt(2)]
+            );
+            // Division by zero fails the query
+            let sql = "SELECT a % 0 FROM foo WHERE id = 2;";
+            assert!(query_engine.run_sql(sql).is_err());
+
+            // [NOT] BETWEEN
+            let sql = "SELECT id FROM foo WHERE a BETWEEN 1 AND 5;";
+            assert_eq!(ids(sql, &mut query_engine), vec![1, 2]);
+            let sql = "SELECT id FROM foo WHERE a BETWEEN -5 AND -1;";
+            assert_eq!(ids(sql, &mut query_engine), vec![3]);
+            let sql = "SELECT id FROM foo WHERE a NOT BETWEEN 1 AND 5;";
+            assert_eq!(ids(sql, &mut query_engine), vec![3, 5]);
+            let sql = "SELECT id FROM foo WHERE a BETWEEN 5 AND 1;";
+            assert!(ids(sql, &mut query_engine).is_empty());
+        }
+
+        #[test]
+        fn test_case() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let rows = |sql: &str, query_engine: &mut QueryEngine| {
+                let result = query_engine.run_sql(sql).unwrap();
+                let mut rows = result
+                    .get_tuples()
+                    .unwrap()
+                    .iter()
+                    .map(|t| t.field_vals.clone())
+                    .collect::<Vec<_>>();
+                rows.sort();
+                rows
+            };
+            let s = |s: &str| Field::String(s.to_string());
+            query_engine
+                .run_sql("CREATE TABLE foo (id INT PRIMARY KEY, a INT);")
+                .unwrap();
+            query_engine
+                .run_sql("INSERT INTO foo VALUES (1, 1), (2, 5), (3, NULL), (4, 2);")
+                .unwrap();
+
+            // Searched CASE: an unknown condition is not true
+            let sql = "SELECT id, CASE WHEN a > 2 THEN 'big' WHEN a > 0 THEN 'small' ELSE 'none' END FROM foo;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![
+                    vec![Field::BigInt(1), s("small")],
+                    vec![Field::BigInt(2), s("big")],
+                    vec![Field::BigInt(3), s("none")],
+                    vec![Field::BigInt(4), s("small")],
+                ]
+            );
+
+            // Simple CASE without ELSE is NULL when nothing matches
+            let sql = "SELECT id, CASE a WHEN 1 THEN a * 10 WHEN 2 THEN a * 100 END FROM foo;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![
+                    vec![Field::BigInt(1), Field::BigInt(10)],
+                    vec![Field::BigInt(2), Field::Null],
+                    vec![Field::BigInt(3), Field::Null],
+                    vec![Field::BigInt(4), Field::BigInt(200)],
+                ]
+            );
+
+            // CASE in a filter
+            let sql = "SELECT id FROM foo WHERE CASE WHEN a IS NULL THEN id ELSE a END > 2;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![vec![Field::BigInt(2)], vec![Field::BigInt(3)]]
+            );
+            let sql = "SELECT id FROM foo WHERE CASE a % 2 WHEN 0 THEN true ELSE false END;";
+            assert_eq!(rows(sql, &mut query_engine), vec![vec![Field::BigInt(4)]]);
+
+            // CASE as an aggregate argument is evaluated by a map below the aggregate
+            let sql = "EXPLAIN SELECT SUM(CASE WHEN a > 1 THEN a ELSE 0 END) FROM foo;";
+            let plan = rows(sql, &mut query_engine);
+            assert!(plan.iter().any(|line| matches!(&line[0], Field::String(line)
+                if line.contains("map(") && line.contains("<- case when foo.a > 1 then foo.a else 0 end)"))));
+        }
+
+        #[test]
+        fn test_scalar_functions() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let rows = |sql: &str, query_engine: &mut QueryEngine| {
+                let result = query_engine.run_sql(sql).unwrap();
+                let mut rows = result
+                    .get_tuples()
+                    .unwrap()
+                    .iter()
+                    .map(|t| t.field_vals.clone())
+                    .collect::<Vec<_>>();
+                rows.sort();
+                rows
+            };
+            let s = |s: &str| Field::String(s.to_string());
+            query_engine
+                .run_sql("CREATE TABLE foo (id INT PRIMARY KEY, a INT, s VARCHAR(20));")
+                .unwrap();
+            query_engine
+                .run_sql(
+                    "INSERT INTO foo VALUES (1, -3, ' Hello '), (2, 9, 'world'), (3, NULL, NULL);",
+                )
+                .unwrap();
+
+            // String functions, including the special SQL syntax forms
+            let sql =
+                "SELECT id, UPPER(s), LENGTH(s), TRIM(s), SUBSTRING(s FROM 2 FOR 3) FROM foo;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![
+                    vec![
+                        Field::BigInt(1),
+                        s(" HELLO "),
+                        Field::BigInt(7),
+                        s("Hello"),
+                        s("Hel")
+                    ],
+                    vec![
+                        Field::BigInt(2),
+                        s("WORLD"),
+                        Field::BigInt(5),
+                        s("world"),
+                        s("orl")
+                    ],
+                    vec![
+                        Field::BigInt(3),
+                        Field::Null,
+                        Field::Null,
+                        Field::Null,
+                        Field::Null
+                    ],
+                ]
+            );
+            let sql = "SELECT CONCAT(s, '!', a), REPLACE(s, 'o', '0'), POSITION('o' IN s) FROM foo WHERE id > 1;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![
+                    vec![s("!"), Field::Null, Field::Null],
+                    vec![s("world!9"), s("w0rld"), Field::BigInt(2)],
+                ]
+            );
+
+            // Math functions
+            let sql = "SELECT ABS(a), POWER(a, 2), SQRT(a), ROUND(2.45, 1), FLOOR(-2.5), CEIL(a) FROM foo WHERE id = 2;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![vec![
+                    Field::BigInt(9),
+                    Field::Decimal(810000, 4),
+                    Field::Decimal(30000, 4),
+                    Field::Decimal(25, 1),
+                    Field::Decimal(-3, 0),
+                    Field::BigInt(9),
+                ]]
+            );
+            // Values outside the domain of a function fail the query
+            let sql = "SELECT SQRT(a) FROM foo WHERE id = 1;";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "SELECT POWER(a, 30) FROM foo WHERE id = 2;";
+            assert!(query_engine.run_sql(sql).is_err());
+
+            // NULL functions
+            let sql = "SELECT id, COALESCE(a, id * 10), NULLIF(a, 9) FROM foo;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![
+                    vec![Field::BigInt(1), Field::BigInt(-3), Field::BigInt(-3)],
+                    vec![Field::BigInt(2), Field::BigInt(9), Field::Null],
+                    vec![Field::BigInt(3), Field::BigInt(30), Field::Null],
+                ]
+            );
+
+            // Functions in a filter
+            let sql = "SELECT id FROM foo WHERE ABS(a) < 5 OR LOWER(s) = 'world';";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![vec![Field::BigInt(1)], vec![Field::BigInt(2)]]
+            );
+
+            // A function of an aggregate is evaluated by a map above the aggregate
+            let sql = "EXPLAIN SELECT ROUND(AVG(LENGTH(s)), 1) FROM foo;";
+            let plan = rows(sql, &mut query_engine);
+            assert!(plan
+                .iter()
+                .any(|line| matches!(&line[0], Field::String(line)
+                if line.contains("map(") && line.contains("<- LENGTH(foo.s))"))));
+            assert!(plan
+                .iter()
+                .any(|line| matches!(&line[0], Field::String(line)
+                if line.contains("map(") && line.contains("<- ROUND("))));
+
+            // Unknown functions and argument type errors are rejected
+            assert!(query_engine.run_sql("SELECT FOO(a) FROM foo;").is_err());
+            assert!(query_engine.run_sql("SELECT UPPER(a) FROM foo;").is_err());
+            assert!(query_engine.run_sql("SELECT ABS(s) FROM foo;").is_err());
+            assert!(query_engine
+                .run_sql("SELECT ROUND(a, 1, 2) FROM foo;")
+                .is_err());
+        }
+
+        #[test]
+        fn test_date_functions() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let rows = |sql: &str, query_engine: &mut QueryEngine| {
+                let result = query_engine.run_sql(sql).unwrap();
+                let mut rows = result
+                    .get_tuples()
+                    .unwrap()
+                    .iter()
+                    .map(|t| t.field_vals.clone())
+                    .collect::<Vec<_>>();
+                rows.sort();
+                rows
+            };
+            let s = |s: &str| Field::String(s.to_string());
+            query_engine
+                .run_sql("CREATE TABLE foo (id INT PRIMARY KEY, d DATE);")
+                .unwrap();
+            query_engine
+                .run_sql("INSERT INTO foo VALUES (1, DATE '2024-01-31'), (2, DATE '2024-05-15'), (3, NULL);")
+                .unwrap();
+            let date = common::datatypes::f_date;
+
+            // EXTRACT and DATE_TRUNC
+            let sql =
+                "SELECT id, EXTRACT(YEAR FROM d), EXTRACT(MONTH FROM d), EXTRACT(DOW FROM d), \
+                       DATE_TRUNC('month', d) FROM foo;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![
+                    vec![
+                        Field::BigInt(1),
+                        Field::BigInt(2024),
+                        Field::BigInt(1),
+                        Field::BigInt(3),
+                        date("2024-01-01")
+                    ],
+                    vec![
+                        Field::BigInt(2),
+                        Field::BigInt(2024),
+                        Field::BigInt(5),
+                        Field::BigInt(3),
+                        date("2024-05-01")
+                    ],
+                    vec![
+                        Field::BigInt(3),
+                        Field::Null,
+                        Field::Null,
+                        Field::Null,
+                        Field::Null
+                    ],
+                ]
+            );
+
+            // INTERVAL arithmetic and day counts
+            let sql = "SELECT d + INTERVAL '1 month', d - INTERVAL '2' DAY, d + 1, \
+                       d - DATE '2024-01-01' FROM foo WHERE id = 1;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![vec![
+                    date("2024-02-29"),
+                    date("2024-01-29"),
+                    date("2024-02-01"),
+                    Field::BigInt(30)
+                ]]
+            );
+            let sql = "SELECT id FROM foo WHERE d > DATE '2024-04-01' - INTERVAL '2 months';";
+            assert_eq!(rows(sql, &mut query_engine), vec![vec![Field::BigInt(2)]]);
+            let sql = "SELECT id FROM foo WHERE d < CURRENT_DATE AND d >= CURRENT_DATE - 100000;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![vec![Field::BigInt(1)], vec![Field::BigInt(2)]]
+            );
+
+            // Formatting
+            let sql = "SELECT TO_CHAR(d, 'Dy DD Mon YYYY'), TO_CHAR(d, 'YYYY-\"Q\"Q') FROM foo WHERE id = 2;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![vec![s("Wed 15 May 2024"), s("2024-Q2")]]
+            );
+
+            // Time-bucketed reports group by a truncated date
+            let sql = "EXPLAIN SELECT DATE_TRUNC('week', d), COUNT(*) FROM foo GROUP BY DATE_TRUNC('week', d);";
+            assert!(query_engine.run_sql(sql).is_ok());
+
+            // Unknown units and intervals are rejected
+            assert!(query_engine
+                .run_sql("SELECT DATE_TRUNC('fortnight', d) FROM foo;")
+                .is_err());
+            assert!(query_engine
+                .run_sql("SELECT d + INTERVAL '1 hour' FROM foo;")
+                .is_err());
+            assert!(query_engine
+                .run_sql("SELECT EXTRACT(HOUR FROM d) FROM foo;")
+                .is_err());
+            assert!(query_engine
+                .run_sql("SELECT EXTRACT(YEAR FROM id) FROM foo;")
+                .is_err());
+        }
+
+        #[test]
+        fn test_cast_and_coercion() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let rows = |sql: &str, query_engine: &mut QueryEngine| {
+                let result = query_engine.run_sql(sql).unwrap();
+                let mut rows = result
+                    .get_tuples()
+                    .unwrap()
+                    .iter()
+                    .map(|t| t.field_vals.clone())
+                    .collect::<Vec<_>>();
+                rows.sort();
+                rows
+            };
+            query_engine
+                .run_sql(
+                    "CREATE TABLE bar (id INT PRIMARY KEY, price DECIMAL(10, 2), d DATE, name VARCHAR(10));",
+                )
+                .unwrap();
+            // Strings are read as dates and integers as decimals
+            query_engine
+                .run_sql("INSERT INTO bar VALUES (1, 1.50, '2024-01-31', 'a'), (2, 3, DATE '2024-05-15', 'b');")
+                .unwrap();
+            let date = common::datatypes::f_date;
+
+            // Explicit casts
+            let sql =
+                "SELECT CAST(price AS INT), CAST(id AS DECIMAL(5, 1)), CAST(id AS VARCHAR(10)), \
+                       CAST('2024-02-01' AS DATE), CAST(d AS TEXT) FROM bar WHERE id = 1;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![vec![
+                    Field::BigInt(2),
+                    Field::Decimal(10, 1),
+                    Field::String("1".to_string()),
+                    date("2024-02-01"),
+                    Field::String("2024-01-31".to_string())
+                ]]
+            );
+
+            // Decimals compare with integers by value
+            let sql = "SELECT id FROM bar WHERE price > 2;";
+            assert_eq!(rows(sql, &mut query_engine), vec![vec![Field::BigInt(2)]]);
+            let sql = "SELECT id FROM bar WHERE price = 3 OR price = 1.5;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![vec![Field::BigInt(1)], vec![Field::BigInt(2)]]
+            );
+            let sql = "SELECT id FROM bar WHERE price IN (3, 4.5);";
+            assert_eq!(rows(sql, &mut query_engine), vec![vec![Field::BigInt(2)]]);
+
+            // A string literal compared with a date is a date
+            let sql = "SELECT id FROM bar WHERE d > '2024-02-01';";
+            assert_eq!(rows(sql, &mut query_engine), vec![vec![Field::BigInt(2)]]);
+            let sql = "SELECT id FROM bar WHERE d BETWEEN '2024-01-01' AND '2024-01-31';";
+            assert_eq!(rows(sql, &mut query_engine), vec![vec![Field::BigInt(1)]]);
+
+            // Mixed arithmetic and CASE results have a common type
+            let sql = "SELECT price + 1, id * 1.5, CASE WHEN id = 1 THEN 1 ELSE price END \
+                       FROM bar WHERE id = 1;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![vec![
+                    Field::Decimal(250, 2),
+                    common::datatypes::f_decimal(1.5),
+                    Field::Decimal(100, 2)
+                ]]
+            );
+
+            // Subqueries, NULLIF, COALESCE and set operations convert to a common type
+            let sql = "SELECT id FROM bar WHERE price > (SELECT id FROM bar WHERE id = 2);";
+            assert_eq!(rows(sql, &mut query_engine), vec![vec![Field::BigInt(2)]]);
+            let sql = "SELECT id FROM bar WHERE price IN (SELECT id + 1 FROM bar);";
+            assert_eq!(rows(sql, &mut query_engine), vec![vec![Field::BigInt(2)]]);
+            let sql = "SELECT id FROM bar WHERE id IN (SELECT price - 0.5 FROM bar);";
+            assert_eq!(rows(sql, &mut query_engine), vec![vec![Field::BigInt(1)]]);
+            let sql = "SELECT NULLIF(price, 3), COALESCE(id, 1.5) FROM bar;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![
+                    vec![Field::Decimal(150, 2), Field::Decimal(10000, 4)],
+                    vec![Field::Null, Field::Decimal(20000, 4)]
+                ]
+            );
+            let sql = "SELECT id FROM bar WHERE id = 1 UNION SELECT price FROM bar WHERE id = 1;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![vec![Field::Decimal(100, 2)], vec![Field::Decimal(150, 2)]]
+            );
+            let sql = "SELECT price FROM bar WHERE id = 1 \
+                       UNION ALL SELECT CAST(price AS DECIMAL(10, 3)) FROM bar WHERE id = 2;";
+            assert_eq!(
+                rows(sql, &mut query_engine),
+                vec![vec![Field::Decimal(1500, 3)], vec![Field::Decimal(3000, 3)]]
+            );
+
+            // Type errors are reported before execution
+            for sql in [
+                "SELECT id FROM bar WHERE d > 1;",
+                "SELECT name + 1 FROM bar;",
+                "SELECT NOT id FROM bar;",
+                "SELECT id FROM bar WHERE id AND name = 'a';",
+                "SELECT d + d FROM bar;",
+                "SELECT CASE WHEN id = 1 THEN d ELSE price END FROM bar;",
+                "SELECT CAST(d AS DECIMAL(5, 2)) FROM bar;",
+                "SELECT CAST('x' AS INT) FROM bar;",
+                "SELECT CAST(12345 AS DECIMAL(4, 2)) FROM bar;",
+            ] {
+                assert!(query_engine.run_sql(sql).is_err(), "{}", sql);
+            }
+            // Values that do not convert fail the query when it runs
+            let sql = "SELECT CAST(name AS INT) FROM bar;";
+            assert!(query_engine.run_sql(sql).is_err());
+        }
+
+        #[test]
+        fn test_like() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let ids = |sql: &str, query_engine: &mut QueryEngine| {
+                let result = query_engine.run_sql(sql).unwrap();
+                let mut ids = result
+                    .get_tuples()
+                    .unwrap()
+                    .iter()
+                    .map(|t| t.get_field(0).unwrap().clone())
+                    .collect::<Vec<_>>();
+                ids.sort();
+                ids.into_iter()
+                    .map(|f| match f {
+                        Field::BigInt(i) => i,
+                        f => panic!("Unexpected field {:?}", f),
+                    })
+                    .collect::<Vec<_>>()
+            };
+            query_engine
+                .run_sql("CREATE TABLE foo (id INT PRIMARY KEY, s VARCHAR(20));")
+                .unwrap();
+            query_engine
+                .run_sql(
+                    "INSERT INTO foo VALUES (1, 'apple'), (2, 'Apricot'), (3, 'banana'), \
+                     (4, '50% off'), (5, 'a_b'), (6, 'axb'), (7, NULL);",
+                )
+                .unwrap();
+
+            let sql = "SELECT id FROM foo WHERE s LIKE 'ap%';";
+            assert_eq!(ids(sql, &mut query_engine), vec![1]);
+            let sql = "SELECT id FROM foo WHERE s ILIKE 'ap%';";
+            assert_eq!(ids(sql, &mut query_engine), vec![1, 2]);
+            let sql = "SELECT id FROM foo WHERE s LIKE '%an%';";
+            assert_eq!(ids(sql, &mut query_engine), vec![3]);
+            let sql = "SELECT id FROM foo WHERE s LIKE 'a_b';";
+            assert_eq!(ids(sql, &mut query_engine), vec![5, 6]);
+            // NULL matches neither LIKE nor NOT LIKE
+            let sql = "SELECT id FROM foo WHERE s NOT LIKE 'a%';";
+            assert_eq!(ids(sql, &mut query_engine), vec![2, 3, 4]);
+            let sql = "SELECT id FROM foo WHERE s NOT ILIKE 'A%';";
+            assert_eq!(ids(sql, &mut query_engine), vec![3, 4]);
+
+            // Escaped wildcards
+            let sql = "SELECT id FROM foo WHERE s LIKE 'a\\_b';";
+            assert_eq!(ids(sql, &mut query_engine), vec![5]);
+            let sql = "SELECT id FROM foo WHERE s LIKE '50#% %' ESCAPE '#';";
+            assert_eq!(ids(sql, &mut query_engine), vec![4]);
+            let sql = "SELECT id FROM foo WHERE s LIKE 'a!_%' ESCAPE '!';";
+            assert_eq!(ids(sql, &mut query_engine), vec![5]);
+
+            // Other types are only matched once cast to a string
+            let sql = "SELECT id FROM foo WHERE id LIKE '1%';";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "SELECT id FROM foo WHERE s LIKE 5;";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "SELECT id FROM foo WHERE CAST(id AS VARCHAR(10)) LIKE '1%';";
+            assert_eq!(ids(sql, &mut query_engine), vec![1]);
+        }
+
+        #[test]
+        fn test_prepared_statements() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let rows = |sql: &str, query_engine: &mut QueryEngine| {
+                let result = query_engine.run_sql(sql).unwrap();
+                let mut rows = result
+                    .get_tuples()
+                    .unwrap()
+                    .iter()
+                    .map(|t| t.field_vals.clone())
+                    .collect::<Vec<_>>();
+                rows.sort();
+                rows
+            };
+            let ints = |rows: &[&[i64]]| {
+                rows.iter()
+                    .map(|row| row.iter().map(|v| Field::BigInt(*v)).collect::<Vec<_>>())
+                    .collect::<Vec<_>>()
+            };
+            query_engine
+                .run_sql("CREATE TABLE foo (id INT PRIMARY KEY, a INT, s VARCHAR(10));")
+                .unwrap();
+
+            // Inserts with parameters, numbered or not
+            query_engine
+                .run_sql("PREPARE ins AS INSERT INTO foo VALUES (?, ?, 'x');")
+                .unwrap();
+            for (id, a) in [(1, 10), (2, 20), (3, 30)] {
+                let sql = format!("EXECUTE ins({}, {});", id, a);
+                query_engine.run_sql(&sql).unwrap();
+            }
+            query_engine
+                .run_sql("PREPARE ins2 (INT) AS INSERT INTO foo (id, s) VALUES ($1, $2);")
+                .unwrap();
+            query_engine.run_sql("EXECUTE ins2(4, 'y');").unwrap();
+
+            // A query planned once and run with different values
+            let sql = "PREPARE q AS SELECT id FROM foo WHERE a > $1 AND s = $2;";
+            query_engine.run_sql(sql).unwrap();
+            assert_eq!(
+                rows("EXECUTE q(15, 'x');", &mut query_engine),
+                ints(&[&[2], &[3]])
+            );
+            assert_eq!(
+                rows("EXECUTE q(25, 'x');", &mut query_engine),
+                ints(&[&[3]])
+            );
+            assert!(rows("EXECUTE q(35, 'x');", &mut query_engine).is_empty());
+
+            // Columns are compared with parameters in the declared types
+            let sql = "PREPARE r (DECIMAL(10, 2)) AS SELECT id FROM foo WHERE a > $1;";
+            query_engine.run_sql(sql).unwrap();
+            assert_eq!(
+                rows("EXECUTE r(15.5);", &mut query_engine),
+                ints(&[&[2], &[3]])
+            );
+            assert_eq!(rows("EXECUTE r(20);", &mut query_engine), ints(&[&[3]]));
+
+            // Wrong parameter types and counts are rejected
+            let err = query_engine.run_sql("EXECUTE q('a', 'x');").unwrap_err();
+            assert!(err
+                .to_string()
+                .contains("Parameter $1 expects a value of type"));
+            assert!(query_engine.run_sql("EXECUTE q(15);").is_err());
+            assert!(query_engine.run_sql("EXECUTE ins2('a', 'y');").is_err());
+
+            // Updates and deletes
+            let sql = "PREPARE upd AS UPDATE foo SET a = $1 WHERE id = $2;";
+            query_engine.run_sql(sql).unwrap();
+            query_engine.run_sql("EXECUTE upd(5, 1);").unwrap();
+            query_engine
+                .run_sql("PREPARE del AS DELETE FROM foo WHERE id = ?;")
+                .unwrap();
+            query_engine.run_sql("EXECUTE del(3);").unwrap();
+            assert_eq!(
+                rows("SELECT id, a FROM foo WHERE id < 4;", &mut query_engine),
+                ints(&[&[1, 5], &[2, 20]])
+            );
+
+            // Names are unique until deallocated
+            assert!(query_engine
+                .run_sql("PREPARE q AS SELECT id FROM foo;")
+                .is_err());
+            query_engine.run_sql("DEALLOCATE q;").unwrap();
+            assert!(query_engine.run_sql("EXECUTE q(15, 'x');").is_err());
+            assert!(query_engine.run_sql("DEALLOCATE q;").is_err());
+            query_engine.run_sql("DEALLOCATE PREPARE ALL;").unwrap();
+            assert!(query_engine.run_sql("EXECUTE del(1);").is_err());
+
+            // Statements using a dropped or recreated table must be prepared again
+            query_engine
+                .run_sql("PREPARE q AS SELECT id FROM foo;")
+                .unwrap();
+            query_engine
+                .run_sql("PREPARE ins AS INSERT INTO foo VALUES (?, ?, 'x');")
+                .unwrap();
+            query_engine.run_sql("TRUNCATE TABLE foo;").unwrap();
+            assert!(rows("EXECUTE q;", &mut query_engine).is_empty());
+            query_engine.run_sql("DROP TABLE foo;").unwrap();
+            assert!(query_engine.run_sql("EXECUTE q;").is_err());
+            assert!(query_engine.run_sql("EXECUTE ins(1, 10);").is_err());
+            query_engine
+                .run_sql("CREATE TABLE foo (id INT PRIMARY KEY, b VARCHAR(10));")
+                .unwrap();
+            assert!(query_engine.run_sql("EXECUTE ins(1, 10);").is_err());
+            query_engine.run_sql("DEALLOCATE q;").unwrap();
+            query_engine
+                .run_sql("PREPARE q AS SELECT id FROM foo;")
+                .unwrap();
+            assert!(rows("EXECUTE q;", &mut query_engine).is_empty());
+        }
+
+        #[test]
+        fn test_materialized_views() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let sql = "CREATE TABLE foo (id INT PRIMARY KEY, a INT);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "CREATE TABLE bar (b INT PRIMARY KEY);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO foo VALUES (1, 10), (2, 20), (3, 20);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO bar VALUES (1);";
+            query_engine.run_sql(sql).unwrap();
+            let rows = |sql: &str, query_engine: &mut QueryEngine| {
+                let result = query_engine.run_sql(sql).unwrap();
+                let mut rows = result
+                    .get_tuples()
+                    .unwrap()
+                    .iter()
+                    .map(|t| t.field_vals.clone())
+                    .collect::<Vec<_>>();
+                rows.sort();
+                rows
+            };
+            let ints = |rows: &[&[i64]]| {
+                rows.iter()
+                    .map(|row| row.iter().map(|v| Field::BigInt(*v)).collect::<Vec<_>>())
+                    .collect::<Vec<_>>()
+            };
+
+            let sql =
+                "CREATE MATERIALIZED VIEW big AS SELECT id, a + 1 AS a1 FROM foo WHERE a > 10;";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "CREATE MATERIALIZED VIEW vals AS SELECT DISTINCT a FROM foo;";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "CREATE MATERIALIZED VIEW sums (a, n, total) AS SELECT a, COUNT(id), SUM(id) FROM foo GROUP BY a;";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "CREATE MATERIALIZED VIEW pairs AS SELECT id, b FROM foo, bar;";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "CREATE MATERIALIZED VIEW bigger AS SELECT id FROM big WHERE a1 > 21;";
+            query_engine.run_sql(sql).unwrap();
+            assert_eq!(
+                rows("SELECT id, a1 FROM big;", &mut query_engine),
+                ints(&[&[2, 21], &[3, 21]])
+            );
+            assert_eq!(
+                rows("SELECT a FROM vals;", &mut query_engine),
+                ints(&[&[10], &[20]])
+            );
+            assert_eq!(
+                rows("SELECT a, n, total FROM sums;", &mut query_engine),
+                ints(&[&[10, 1, 1], &[20, 2, 5]])
+            );
+            assert_eq!(
+                rows("SELECT id, b FROM pairs;", &mut query_engine),
+                ints(&[&[1, 1], &[2, 1], &[3, 1]])
+            );
+            assert!(rows("SELECT id FROM bigger;", &mut query_engine).is_empty());
+
+            // Changes to the tables are propagated when the views are read
+            let sql = "INSERT INTO foo VALUES (4, 30);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "DELETE FROM foo WHERE id = 2;";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "UPDATE foo SET a = 5 WHERE id = 1;";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO bar VALUES (2);";
+            query_engine.run_sql(sql).unwrap();
+            assert_eq!(
+                rows("SELECT id, a1 FROM big;", &mut query_engine),
+                ints(&[&[3, 21], &[4, 31]])
+            );
+            assert_eq!(
+                rows("SELECT a FROM vals;", &mut query_engine),
+                ints(&[&[5], &[20], &[30]])
+            );
+            assert_eq!(
+                rows("SELECT a, n, total FROM sums;", &mut query_engine),
+                ints(&[&[5, 1, 1], &[20, 1, 3], &[30, 1, 4]])
+            );
+            assert_eq!(
+                rows("SELECT id, b FROM pairs;", &mut query_engine),
+                ints(&[&[1, 1], &[1, 2], &[3, 1], &[3, 2], &[4, 1], &[4, 2]])
+            );
+            assert_eq!(
+                rows("SELECT id FROM bigger;", &mut query_engine),
+                ints(&[&[4]])
+            );
+            // The records updated before an UPDATE fails are propagated too
+            let sql = "UPDATE foo SET a = a + 1 + 0 % (a - 30);";
+            assert!(query_engine.run_sql(sql).is_err());
+            assert_eq!(
+                rows("SELECT id, a1 FROM big;", &mut query_engine),
+                rows("SELECT id, a + 1 FROM foo WHERE a > 10;", &mut query_engine)
+            );
+            let mut vals = rows("SELECT a FROM foo;", &mut query_engine);
+            vals.dedup();
+            assert_eq!(rows("SELECT a FROM vals;", &mut query_engine), vals);
+            assert!(vals.contains(&vec![Field::BigInt(21)]));
+            let sql = "TRUNCATE TABLE bar;";
+            query_engine.run_sql(sql).unwrap();
+            assert!(rows("SELECT id, b FROM pairs;", &mut query_engine).is_empty());
+
+            // The views are rebuilt when the database is loaded, including the changes
+            // not propagated before it was saved
+            let sql = "INSERT INTO foo VALUES (6, 60);";
+            query_engine.run_sql(sql).unwrap();
+            let path = base_dir.join("saved_db");
+            serde_json::to_writer(
+                fs::File::create(&path).unwrap(),
+                &query_engine.database_state.get_serializable_db_state(),
+            )
+            .unwrap();
+            let db_state = DatabaseState::load(path, query_engine.database_state.managers).unwrap();
+            query_engine.database_state = Box::leak(Box::new(db_state));
+            let sql = "INSERT INTO foo VALUES (7, 70);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO bar VALUES (3);";
+            query_engine.run_sql(sql).unwrap();
+            assert_eq!(
+                rows("SELECT id, a1 FROM big;", &mut query_engine),
+                rows("SELECT id, a + 1 FROM foo WHERE a > 10;", &mut query_engine)
+            );
+            assert_eq!(
+                rows("SELECT id FROM bigger;", &mut query_engine),
+                rows("SELECT id FROM foo WHERE a > 20;", &mut query_engine)
+            );
+            let mut vals = rows("SELECT a FROM foo;", &mut query_engine);
+            vals.dedup();
+            assert_eq!(rows("SELECT a FROM vals;", &mut query_engine), vals);
+            assert_eq!(
+                rows("SELECT id, b FROM pairs;", &mut query_engine),
+                rows("SELECT id, b FROM foo, bar;", &mut query_engine)
+            );
+            assert!(
+                rows("SELECT id FROM bigger;", &mut query_engine).contains(&vec![Field::BigInt(7)])
+            );
+            let sql = "TRUNCATE TABLE bar;";
+            query_engine.run_sql(sql).unwrap();
+
+            // Materialized views are only changed through their tables
+            let sql = "INSERT INTO big VALUES (5, 50);";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "DELETE FROM big;";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "UPDATE big SET a1 = 0;";
+            assert!(query_engine.run_sql(sql).is_err());
+            // Columns need distinct names
+            let sql = "CREATE MATERIALIZED VIEW bad AS SELECT id, id FROM foo;";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "CREATE MATERIALIZED VIEW bad AS SELECT a + 1 FROM foo;";
+            assert!(query_engine.run_sql(sql).is_err());
+            // Plans that cannot be maintained incrementally are rejected
+            let sql = "CREATE MATERIALIZED VIEW bad AS SELECT id FROM foo LIMIT 1;";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "CREATE MATERIALIZED VIEW big AS SELECT id FROM foo;";
+            assert!(query_engine.run_sql(sql).is_err());
+
+            // Tables and views used by a materialized view cannot be dropped
+            let sql = "DROP TABLE foo;";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "DROP VIEW big;";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "DROP TABLE big;";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "DROP VIEW bigger, big, vals, sums, pairs;";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "SELECT id FROM big;";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "DROP TABLE foo;";
+            query_engine.run_sql(sql).unwrap();
+        }
+
+        #[test]
+        fn test_common_table_expressions() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let sql = "CREATE TABLE nodes (id INT PRIMARY KEY, parent INT);";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO nodes VALUES (1, NULL), (2, 1), (3, 1), (4, 2), (5, 4), (6, 6);";
+            query_engine.run_sql(sql).unwrap();
+            let rows = |sql: &str, query_engine: &mut QueryEngine| {
+                let result = query_engine.run_sql(sql).unwrap();
+                let mut rows = result
+                    .get_tuples()
+                    .unwrap()
+                    .iter()
+                    .map(|t| t.field_vals.clone())
+                    .collect::<Vec<_>>();
+                rows.sort();
+                rows
+            };
+            let ints = |values: &[i64]| {
+                values
+                    .iter()
+                    .map(|v| vec![Field::BigInt(*v)])
+                    .collect::<Vec<_>>()
+            };
+
+            let sql = "WITH leaves AS (SELECT id FROM nodes WHERE id > 4) SELECT id FROM leaves;";
+            assert_eq!(rows(sql, &mut query_engine), ints(&[5, 6]));
+            // A CTE can reference the CTEs defined before it
+            let sql = "WITH x(n) AS (SELECT id FROM nodes WHERE id < 4), \
+                       y AS (SELECT n * 10 AS m FROM x WHERE n > 1) SELECT m FROM y;";
+            assert_eq!(rows(sql, &mut query_engine), ints(&[20, 30]));
+
+            let sql = "WITH RECURSIVE r(n) AS (SELECT id FROM nodes WHERE id = 1 \
+                       UNION ALL SELECT n + 1 FROM r WHERE n < 5) SELECT n FROM r;";
+            assert_eq!(rows(sql, &mut query_engine), ints(&[1, 2, 3, 4, 5]));
+            // UNION ends the recursion once no new rows are produced
+            let sql = "WITH RECURSIVE r(n) AS (SELECT id FROM nodes WHERE id > 4 \
+                       UNION SELECT n FROM r) SELECT n FROM r;";
+            assert_eq!(rows(sql, &mut query_engine), ints(&[5, 6]));
+            // A SELECT without FROM returns a single row
+            let sql =
+                "WITH RECURSIVE r(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM r WHERE n < 5) \
+                       SELECT * FROM r;";
+            assert_eq!(rows(sql, &mut query_engine), ints(&[1, 2, 3, 4, 5]));
+            let sql = "SELECT 1 + 2;";
+            assert_eq!(rows(sql, &mut query_engine), ints(&[3]));
+            let sql = "SELECT (SELECT id * 10) FROM nodes WHERE id < 3;";
+            assert_eq!(rows(sql, &mut query_engine), ints(&[10, 20]));
+
+            let sql = "WITH x AS (SELECT id FROM nodes), x AS (SELECT parent FROM nodes) \
+                       SELECT id FROM x;";
+            assert!(query_engine.run_sql(sql).is_err());
+        }
+
+        #[test]
+        fn test_drop_and_truncate() {
+            let base_dir = tempfile::tempdir().unwrap().into_path();
+            let mut query_engine = QueryEngine::new(&base_dir);
+            let sql = "CREATE TABLE foo (id INT PRIMARY KEY, name VARCHAR(10));";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "INSERT INTO foo VALUES (1, 'a'), (2, 'b'), (3, 'c');";
+            query_engine.run_sql(sql).unwrap();
+
+            let sql = "TRUNCATE TABLE foo;";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "SELECT * FROM foo;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert!(result.get_tuples().unwrap().is_empty());
+            let sql = "INSERT INTO foo VALUES (4, 'd');";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "SELECT * FROM foo;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert_eq!(result.get_tuples().unwrap().len(), 1);
+
+            let sql = "DROP TABLE foo;";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "SELECT * FROM foo;";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "DROP TABLE foo;";
+            assert!(query_engine.run_sql(sql).is_err());
+            let sql = "DROP TABLE IF EXISTS foo;";
+            query_engine.run_sql(sql).unwrap();
+
+            // The name can be reused
+            let sql = "CREATE TABLE foo (id INT PRIMARY KEY, name VARCHAR(10));";
+            query_engine.run_sql(sql).unwrap();
+            let sql = "SELECT * FROM foo;";
+            let result = query_engine.run_sql(sql).unwrap();
+            assert!(result.get_tuples().unwrap().is_empty());
+        }
     }
 }
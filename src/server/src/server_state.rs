@@ -218,7 +218,11 @@ impl ServerState {
 
     pub fn close_connection(&self, client_id: u64) {
         let mut active_connections = self.active_connections.write().unwrap();
-        active_connections.remove(&client_id);
+        if let Some(db_id) = active_connections.remove(&client_id) {
+            if let Some(db) = self.id_to_db.read().unwrap().get(&db_id) {
+                db.remove_prepared_statements(client_id);
+            }
+        }
     }
 
     fn get_db_id_from_name(&self, db_name: &str) -> Result<u64, CrustyError> {
@@ -27,7 +27,8 @@ impl SQLParser {
         SQLParser {}
     }
 
-    /// Validates sql string, first if it is sql itself, then if it has a primary key
+    /// Validates sql string, first if it is sql itself, then if its tables have a primary key.
+    /// The string may hold several statements separated by semicolons.
     pub fn parse_sql(sql: String) -> ParserResponse {
         // Allows for multiple checks and different errors for each fail
         let request = SQLParser::validate_sql(sql);
@@ -36,26 +37,21 @@ impl SQLParser {
             ParserResponse::SQL(ref ast) => {
                 if ast.is_empty() {
                     return ParserResponse::SQLConstraintError(String::from("No Statement"));
-                } else if ast.len() > 1 {
-                    return ParserResponse::SQLConstraintError(String::from(
-                        "Multiple Statements not supported",
-                    ));
                 }
 
-                let statement = ast.first().unwrap();
-                if let Statement::CreateTable {
-                    columns,
-                    constraints,
-                    ..
-                } = statement
-                {
-                    match SQLParser::get_pks(columns, constraints) {
-                        Ok(_) => request,
-                        Err(e) => e,
+                for statement in ast {
+                    if let Statement::CreateTable {
+                        columns,
+                        constraints,
+                        ..
+                    } = statement
+                    {
+                        if let Err(e) = SQLParser::get_pks(columns, constraints) {
+                            return e;
+                        }
                     }
-                } else {
-                    request
                 }
+                request
             }
             _ => unreachable!(),
         }
@@ -64,12 +60,188 @@ impl SQLParser {
     /// Returns Request::SQL if given string is valid sql, else returns Request::SQLError
     fn validate_sql(sql: String) -> ParserResponse {
         let dialect = sqlparser::dialect::GenericDialect {};
-        match Parser::parse_sql(&dialect, &sql) {
+        let mut statements = Vec::new();
+        for statement in SQLParser::split_statements(&sql) {
+            let statement =
+                SQLParser::number_placeholders(statement).unwrap_or_else(|| statement.to_string());
+            match SQLParser::rewrite_explain_options(&statement) {
+                Ok(Some(rewritten)) => statements.push(rewritten),
+                Ok(None) => statements.push(statement),
+                Err(e) => return ParserResponse::SQLError(e),
+            }
+        }
+        match Parser::parse_sql(&dialect, &statements.join(";")) {
             Ok(a) => ParserResponse::SQL(a),
             Err(e) => ParserResponse::SQLError(e),
         }
     }
 
+    /// Returns the characters of a SQL string, with their byte offset, that are not in a
+    /// quoted string, identifier or comment.
+    fn unquoted_chars(sql: &str) -> Vec<(usize, char)> {
+        let mut unquoted = Vec::new();
+        let mut quote = None;
+        let mut chars = sql.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match (quote, c) {
+                // A doubled quote inside a quoted string is an escaped quote, which is
+                // handled as closing and reopening the string.
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '\'' | '"' | '`') => quote = Some(c),
+                (None, '-') if matches!(chars.peek(), Some((_, '-'))) => {
+                    // Skip the comment up to the end of the line
+                    for (_, c) in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                }
+                (None, '/') if matches!(chars.peek(), Some((_, '*'))) => {
+                    // Skip the comment up to its end. Like the parser, comments nest.
+                    chars.next();
+                    let mut nested = 1;
+                    let mut last = ' ';
+                    for (_, c) in chars.by_ref() {
+                        match (last, c) {
+                            ('/', '*') => nested += 1,
+                            ('*', '/') => {
+                                nested -= 1;
+                                if nested == 0 {
+                                    break;
+                                }
+                            }
+                            _ => {}
+                        }
+                        last = c;
+                    }
+                }
+                (None, _) => unquoted.push((i, c)),
+            }
+        }
+        unquoted
+    }
+
+    /// Splits a SQL string at the semicolons that are not in a quoted string, identifier
+    /// or comment.
+    fn split_statements(sql: &str) -> Vec<&str> {
+        let mut statements = Vec::new();
+        let mut start = 0;
+        for (i, _) in SQLParser::unquoted_chars(sql)
+            .into_iter()
+            .filter(|(_, c)| *c == ';')
+        {
+            statements.push(&sql[start..i]);
+            start = i + 1;
+        }
+        statements.push(&sql[start..]);
+        statements
+    }
+
+    /// Numbers the `?` parameters of a statement, which become `$1`, `$2`, ... in order.
+    /// Returns None if the statement has no `?` parameter.
+    fn number_placeholders(sql: &str) -> Option<String> {
+        let positions: Vec<usize> = SQLParser::unquoted_chars(sql)
+            .into_iter()
+            .filter(|(_, c)| *c == '?')
+            .map(|(i, _)| i)
+            .collect();
+        if positions.is_empty() {
+            return None;
+        }
+        let mut numbered = String::with_capacity(sql.len() + positions.len());
+        let mut start = 0;
+        for (n, i) in positions.into_iter().enumerate() {
+            numbered.push_str(&sql[start..i]);
+            numbered.push_str(&format!("${}", n + 1));
+            start = i + 1;
+        }
+        numbered.push_str(&sql[start..]);
+        Some(numbered)
+    }
+
+    /// Rewrites the option list of `EXPLAIN (ANALYZE, FORMAT JSON) <statement>`, which the
+    /// parser does not support, to the keyword form `EXPLAIN ANALYZE FORMAT JSON <statement>`.
+    /// Returns None if the SQL has no option list.
+    fn rewrite_explain_options(sql: &str) -> Result<Option<String>, ParserError> {
+        let trimmed = sql.trim_start();
+        match trimmed.get(..7) {
+            Some(keyword) if keyword.eq_ignore_ascii_case("explain") => {}
+            _ => return Ok(None),
+        }
+        if !trimmed[7..].starts_with(|c: char| c.is_whitespace() || c == '(') {
+            return Ok(None);
+        }
+        let rest = trimmed[7..].trim_start();
+        if !rest.starts_with('(') {
+            return Ok(None);
+        }
+        let end = match rest.find(')') {
+            Some(end) => end,
+            None => return Ok(None),
+        };
+        let mut analyze = false;
+        let mut verbose = false;
+        let mut format = None;
+        for (i, option) in rest[1..end].split(',').enumerate() {
+            let words: Vec<String> = option
+                .split_whitespace()
+                .map(|w| w.to_uppercase())
+                .collect();
+            let (name, value) = match &words[..] {
+                [name] => (name.as_str(), None),
+                [name, value] => (name.as_str(), Some(value.as_str())),
+                // Not an option list, e.g. a parenthesized query
+                _ if i == 0 => return Ok(None),
+                _ => {
+                    return Err(ParserError::ParserError(format!(
+                        "Invalid EXPLAIN option: {}",
+                        option.trim()
+                    )))
+                }
+            };
+            let flag = match value {
+                None | Some("TRUE" | "ON" | "1") => true,
+                Some("FALSE" | "OFF" | "0") => false,
+                Some(value) if name == "FORMAT" => {
+                    format = Some(value.to_string());
+                    continue;
+                }
+                Some(value) => {
+                    return Err(ParserError::ParserError(format!(
+                        "Invalid value for EXPLAIN option {}: {}",
+                        name, value
+                    )))
+                }
+            };
+            match name {
+                "ANALYZE" => analyze = flag,
+                "VERBOSE" => verbose = flag,
+                _ if i == 0 => return Ok(None),
+                _ => {
+                    return Err(ParserError::ParserError(format!(
+                        "Unknown EXPLAIN option: {}",
+                        name
+                    )))
+                }
+            }
+        }
+        let mut rewritten = String::from("EXPLAIN");
+        if analyze {
+            rewritten.push_str(" ANALYZE");
+        }
+        if verbose {
+            rewritten.push_str(" VERBOSE");
+        }
+        if let Some(format) = format {
+            rewritten.push_str(" FORMAT ");
+            rewritten.push_str(&format);
+        }
+        rewritten.push(' ');
+        rewritten.push_str(&rest[end + 1..]);
+        Ok(Some(rewritten))
+    }
+
     /// Returns a vector of the Idents of tables that are primary keys if valid
     /// Returns an error (as request) if there is a problem
     ///
@@ -249,6 +421,91 @@ mod libtests {
     }
     */
 
+    #[test]
+    fn test_explain_options() {
+        let explain = |sql: &str| match SQLParser::parse_sql(sql.to_string()) {
+            ParserResponse::SQL(ast) => match ast.into_iter().next() {
+                Some(Statement::Explain {
+                    analyze,
+                    statement,
+                    format,
+                    ..
+                }) => Some((
+                    analyze,
+                    format.map(|f| f.to_string()),
+                    statement.to_string(),
+                )),
+                _ => None,
+            },
+            _ => None,
+        };
+        assert_eq!(
+            explain("EXPLAIN (FORMAT JSON) SELECT a FROM t"),
+            Some((
+                false,
+                Some("JSON".to_string()),
+                "SELECT a FROM t".to_string()
+            ))
+        );
+        assert_eq!(
+            explain("explain (analyze, format text) SELECT a FROM t"),
+            Some((
+                true,
+                Some("TEXT".to_string()),
+                "SELECT a FROM t".to_string()
+            ))
+        );
+        assert_eq!(
+            explain("EXPLAIN (ANALYZE false) SELECT a FROM t"),
+            Some((false, None, "SELECT a FROM t".to_string()))
+        );
+        assert_eq!(
+            explain("EXPLAIN (SELECT a FROM t)"),
+            Some((false, None, "(SELECT a FROM t)".to_string()))
+        );
+        assert_eq!(
+            explain("EXPLAIN (FORMAT JSON, COSTS) SELECT a FROM t"),
+            None
+        );
+        assert_eq!(explain("EXPLAIN (FORMAT XML) SELECT a FROM t"), None);
+    }
+
+    #[test]
+    fn test_multiple_statements() {
+        assert_eq!(
+            SQLParser::split_statements("SELECT ';' FROM t; -- a; b\nSELECT \"c;\" FROM t;"),
+            vec!["SELECT ';' FROM t", " -- a; b\nSELECT \"c;\" FROM t", ""]
+        );
+        assert_eq!(
+            SQLParser::split_statements("SELECT 1 /* a; /* b; */ c; */ FROM t; SELECT 2 /**/"),
+            vec!["SELECT 1 /* a; /* b; */ c; */ FROM t", " SELECT 2 /**/"]
+        );
+        let script = "CREATE TABLE t (a INT PRIMARY KEY);
+            INSERT INTO t VALUES (1);
+            EXPLAIN (FORMAT JSON) SELECT a FROM t;";
+        match SQLParser::parse_sql(script.to_string()) {
+            ParserResponse::SQL(ast) => {
+                assert_eq!(ast.len(), 3);
+                assert!(matches!(ast[2], Statement::Explain { .. }));
+            }
+            _ => panic!("Script should parse"),
+        }
+        let script = "SELECT a FROM t; CREATE TABLE u (a INT)";
+        assert!(matches!(
+            SQLParser::parse_sql(script.to_string()),
+            ParserResponse::SQLConstraintError(_)
+        ));
+    }
+
+    #[test]
+    fn test_number_placeholders() {
+        assert_eq!(
+            SQLParser::number_placeholders("SELECT a FROM t WHERE a = ? AND b = '?' AND c = ?"),
+            Some("SELECT a FROM t WHERE a = $1 AND b = '?' AND c = $2".to_string())
+        );
+        assert_eq!(SQLParser::number_placeholders("SELECT a FROM t"), None);
+    }
+
     #[test]
     fn test_get_pks() {
         // fail cases
@@ -204,6 +204,11 @@ impl StorageTrait for StorageManager {
             &container_id
         );
         containers.remove(&container_id).unwrap();
+        self.last_insert.write().unwrap().remove(&container_id);
+        self.container_names
+            .write()
+            .unwrap()
+            .retain(|_, c_id| *c_id != container_id);
         Ok(())
     }
 
@@ -391,9 +396,10 @@ impl Iterator for ValueIterator {
         while self.current <= self.max {
             match self.table_map.read().unwrap().get(&self.tracker) {
                 Some(res) => {
+                    let id = self.tracker;
                     self.tracker.slot_id = Some(self.tracker.slot_id.unwrap() + 1);
                     self.current += 1;
-                    return Some((res.clone(), self.tracker));
+                    return Some((res.clone(), id));
                 }
                 None => {
                     self.tracker.slot_id = Some(self.tracker.slot_id.unwrap() + 1);